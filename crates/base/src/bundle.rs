@@ -0,0 +1,89 @@
+use crate::context::context::{find_database_schemas, SjsContext};
+use schemajs_config::SchemeJsConfig;
+use schemajs_module_loader::bundle::ModuleBundle;
+use schemajs_module_loader::ts_module_loader::TypescriptModuleLoader;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Bundles the table modules of every database of the project at `config_path`,
+/// together with everything they import, into an eszip archive.
+pub async fn bundle_project(
+    config_path: PathBuf,
+    data_path: Option<PathBuf>,
+) -> anyhow::Result<Vec<u8>> {
+    let (folder_path, config_file) = SjsContext::resolve_config_path(&config_path)?;
+    let config = SchemeJsConfig::new(config_file)?;
+
+    let schemas = find_database_schemas(&config, &folder_path)?;
+
+    let import_map = SjsContext::load_import_map(&folder_path, &config.modules)?.map(Arc::new);
+    let remote_modules =
+        SjsContext::create_remote_modules(&folder_path, data_path, &config.modules)?.map(Arc::new);
+    let loader = TypescriptModuleLoader::new(import_map, remote_modules);
+
+    ModuleBundle::create(schemas, &loader).await
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bundle::bundle_project;
+    use crate::context::context::SjsContext;
+    use crate::runtime::{copy_test_project, SchemeJsRuntime};
+    use deno_core::serde_json::json;
+    use schemajs_helpers::create_helper_channel;
+    use schemajs_helpers::helper::{HelperCall, HelperDbContext};
+    use schemajs_module_loader::bundle::ModuleBundle;
+    use std::sync::Arc;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[tokio::test]
+    async fn test_bundle_round_trip() {
+        let folder = copy_test_project("./test_cases/default-db");
+
+        let bytes = bundle_project(folder.path().to_path_buf(), None)
+            .await
+            .unwrap();
+        let bundle = Arc::new(ModuleBundle::from_bytes(bytes).unwrap());
+
+        let databases = &bundle.manifest.databases;
+        assert_eq!(databases.len(), 1);
+        assert_eq!(databases[0].name, "public");
+        let mut tables: Vec<_> = databases[0]
+            .tables
+            .iter()
+            .map(|table| table.path_segments().unwrap().last().unwrap().to_string())
+            .collect();
+        tables.sort();
+        assert_eq!(tables, vec!["orders.ts", "products.ts", "users.ts"]);
+        for table in &databases[0].tables {
+            assert!(bundle.get(table).is_ok());
+        }
+
+        // The tables are only left in the bundle, which is all the runtime loads them from.
+        std::fs::remove_dir_all(folder.path().join("public/tables")).unwrap();
+
+        let (tx, _rx) = create_helper_channel(1);
+        let context = SjsContext::new(folder.path().to_path_buf(), None, tx)
+            .unwrap()
+            .with_bundle(bundle);
+        let mut rt = SchemeJsRuntime::new(Arc::new(context)).await.unwrap();
+
+        let (response, mut response_rx) = unbounded_channel();
+        rt.call_helper(HelperCall::CustomQuery {
+            identifier: "echo".to_string(),
+            req: json!({ "hello": "bundle" }),
+            db_ctx: HelperDbContext {
+                db: Some("public".to_string()),
+                table: Some("users".to_string()),
+            },
+            response,
+        })
+        .await;
+        assert_eq!(
+            response_rx.recv().await.unwrap(),
+            json!({ "hello": "bundle" })
+        );
+
+        rt.ctx.task_manager.read().stop_tasks();
+    }
+}
@@ -0,0 +1,310 @@
+use crate::context::context::SjsContext;
+use crate::runtime::SchemeJsRuntime;
+use schemajs_helpers::create_helper_channel;
+use schemajs_primitives::column::types::DataTypes;
+use schemajs_primitives::column::Column;
+use schemajs_primitives::table::Table;
+use serde_json::Value;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+static PRELUDE: &str = r#"// Generated by `schemajs codegen`. Do not edit by hand.
+
+export type FilterType = "=" | "!=" | ">" | ">=" | "<" | "<=";
+
+export type CustomQueryCaller = (db: string, table: string, query: string, args: unknown) => Promise<unknown>;
+
+const schemaJsQuery: CustomQueryCaller = (db, table, query, args) =>
+    (globalThis as any).SchemaJS.query(db, table, query, args);
+
+export interface TypedQueryBuilder<Row> {
+    readonly dbName: string;
+    readonly tableName: string;
+    where<K extends keyof Row & string>(key: K, filterType: FilterType, value: Row[K]): TypedQueryBuilder<Row>;
+    and(callback: (builder: TypedQueryBuilder<Row>) => void): TypedQueryBuilder<Row>;
+    or(callback: (builder: TypedQueryBuilder<Row>) => void): TypedQueryBuilder<Row>;
+    build(notFinal?: boolean): unknown;
+}
+
+const queryBuilder = <Row>(dbName: string, tableName: string): TypedQueryBuilder<Row> =>
+    new (globalThis as any).SchemaJS.QueryBuilder(dbName, tableName);
+"#;
+
+/// Loads the tables of the project at `config_path` through the runtime and generates their typings.
+pub async fn codegen_project(config_path: PathBuf) -> anyhow::Result<String> {
+    let (helper_tx, _helper_rx) = create_helper_channel(1);
+    let context = Arc::new(SjsContext::new(config_path, None, helper_tx)?);
+    let databases = SchemeJsRuntime::load_table_definitions(context).await?;
+
+    Ok(generate_typings(&databases))
+}
+
+/// Generates a TypeScript module with, for every table, an interface of its rows,
+/// typed wrappers of its custom queries and a `QueryBuilder` that only accepts its columns.
+pub fn generate_typings(databases: &[(String, Vec<Table>)]) -> String {
+    let mut out = String::from(PRELUDE);
+
+    for (db_name, tables) in databases {
+        let mut tables: Vec<&Table> = tables.iter().collect();
+        tables.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for table in tables {
+            write_table(&mut out, db_name, table);
+        }
+    }
+
+    out
+}
+
+fn write_table(out: &mut String, db_name: &str, table: &Table) {
+    let type_name = format!("{}{}", pascal_case(db_name), pascal_case(&table.name));
+    let fn_name = camel_case(&type_name);
+    let db = quote(db_name);
+    let tbl = quote(&table.name);
+
+    let mut columns: Vec<&Column> = table.columns.values().collect();
+    if !table.columns.contains_key("_uid") {
+        columns.push(Table::get_internal_uid());
+    }
+    columns.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "// {}.{}", db_name, table.name);
+    let _ = writeln!(out, "export interface {} {{", type_name);
+    for column in columns {
+        if let Some(comment) = &column.comment {
+            let _ = writeln!(out, "    /** {} */", comment.replace("*/", "*\\/"));
+        }
+        let _ = writeln!(
+            out,
+            "    {}{}: {};",
+            property_name(&column.name),
+            if column.required { "" } else { "?" },
+            data_type_to_ts(&column.data_type)
+        );
+    }
+    let _ = writeln!(out, "}}");
+
+    let mut queries: Vec<_> = table.queries.iter().collect();
+    queries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let _ = writeln!(out);
+    for query in &queries {
+        let query_type = format!("{}{}", type_name, pascal_case(&query.name));
+        let _ = writeln!(
+            out,
+            "export type {}Input = {};",
+            query_type,
+            json_schema_to_ts(query.input.as_ref())
+        );
+        let _ = writeln!(
+            out,
+            "export type {}Output = {};",
+            query_type,
+            json_schema_to_ts(query.output.as_ref())
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "export const {}Queries = (call: CustomQueryCaller = schemaJsQuery) => ({{",
+        fn_name
+    );
+    for query in &queries {
+        let query_type = format!("{}{}", type_name, pascal_case(&query.name));
+        let _ = writeln!(
+            out,
+            "    {}: (args: {}Input) => call({}, {}, {}, args) as Promise<{}Output>,",
+            property_name(&query.name),
+            query_type,
+            db,
+            tbl,
+            quote(&query.name),
+            query_type
+        );
+    }
+    let _ = writeln!(out, "}});");
+
+    let _ = writeln!(
+        out,
+        "export const {}Query = () => queryBuilder<{}>({}, {});",
+        fn_name, type_name, db, tbl
+    );
+}
+
+fn data_type_to_ts(data_type: &DataTypes) -> &'static str {
+    match data_type {
+        DataTypes::Null => "null",
+        DataTypes::Uuid | DataTypes::String => "string",
+        DataTypes::Boolean => "boolean",
+        DataTypes::Number => "number",
+    }
+}
+
+/// Converts the JSON Schema of a custom query to a TypeScript type, falling back to `unknown`
+/// for anything that has no direct equivalent.
+fn json_schema_to_ts(schema: Option<&Value>) -> String {
+    let schema = match schema.and_then(|schema| schema.as_object()) {
+        Some(schema) => schema,
+        None => return "unknown".to_string(),
+    };
+
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        return union(values.iter().map(|value| value.to_string()));
+    }
+
+    for key in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(schemas)) = schema.get(key) {
+            return union(schemas.iter().map(|schema| json_schema_to_ts(Some(schema))));
+        }
+    }
+
+    match schema.get("type") {
+        Some(Value::String(schema_type)) => json_type_to_ts(schema_type, schema),
+        Some(Value::Array(types)) => union(
+            types
+                .iter()
+                .filter_map(|schema_type| schema_type.as_str())
+                .map(|schema_type| json_type_to_ts(schema_type, schema)),
+        ),
+        _ => "unknown".to_string(),
+    }
+}
+
+fn json_type_to_ts(schema_type: &str, schema: &serde_json::Map<String, Value>) -> String {
+    match schema_type {
+        "string" => "string".to_string(),
+        "number" | "integer" => "number".to_string(),
+        "boolean" => "boolean".to_string(),
+        "null" => "null".to_string(),
+        "array" => format!("Array<{}>", json_schema_to_ts(schema.get("items"))),
+        "object" => {
+            let properties = match schema.get("properties").and_then(|p| p.as_object()) {
+                Some(properties) if !properties.is_empty() => properties,
+                _ => return "Record<string, unknown>".to_string(),
+            };
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(|required| required.as_array())
+                .map(|required| required.iter().filter_map(|key| key.as_str()).collect())
+                .unwrap_or_default();
+
+            let fields: Vec<String> = properties
+                .iter()
+                .map(|(key, property)| {
+                    format!(
+                        "{}{}: {}",
+                        property_name(key),
+                        if required.contains(&key.as_str()) {
+                            ""
+                        } else {
+                            "?"
+                        },
+                        json_schema_to_ts(Some(property))
+                    )
+                })
+                .collect();
+
+            format!("{{ {} }}", fields.join("; "))
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+fn union(types: impl Iterator<Item = String>) -> String {
+    let types: Vec<String> = types.collect();
+    if types.is_empty() {
+        "never".to_string()
+    } else {
+        types.join(" | ")
+    }
+}
+
+fn quote(value: &str) -> String {
+    Value::String(value.to_string()).to_string()
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn property_name(name: &str) -> String {
+    if is_identifier(name) {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn camel_case(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codegen::generate_typings;
+    use schemajs_primitives::column::types::DataTypes;
+    use schemajs_primitives::column::Column;
+    use schemajs_primitives::table::custom_query::CustomQuery;
+    use schemajs_primitives::table::Table;
+    use serde_json::json;
+
+    #[test]
+    fn test_generate_typings() {
+        let mut table = Table::new("users")
+            .add_column(Column::new("username", DataTypes::String).set_required(true))
+            .add_column(Column::new("age", DataTypes::Number))
+            .add_column(Column::new("enabled", DataTypes::Boolean));
+        table.queries.push(CustomQuery::new(
+            "findByName",
+            Some(json!({
+                "type": "object",
+                "properties": { "name": { "type": "string" } },
+                "required": ["name"]
+            })),
+            None,
+        ));
+
+        let typings = generate_typings(&[("public".to_string(), vec![table])]);
+
+        assert!(typings.contains("export interface PublicUsers {"));
+        assert!(typings.contains("    _uid: string;"));
+        assert!(typings.contains("    username: string;"));
+        assert!(typings.contains("    age?: number;"));
+        assert!(typings.contains("    enabled?: boolean;"));
+        assert!(typings.contains("export type PublicUsersFindByNameInput = { name: string };"));
+        assert!(typings.contains("export type PublicUsersFindByNameOutput = unknown;"));
+        assert!(typings.contains(
+            "    findByName: (args: PublicUsersFindByNameInput) => call(\"public\", \"users\", \"findByName\", args) as Promise<PublicUsersFindByNameOutput>,"
+        ));
+        assert!(typings.contains(
+            "export const publicUsersQuery = () => queryBuilder<PublicUsers>(\"public\", \"users\");"
+        ));
+    }
+}
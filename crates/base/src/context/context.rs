@@ -1,10 +1,23 @@
+use crate::hot_reload::SchemaChanges;
 use crate::manager::SchemeJsManager;
+use anyhow::anyhow;
+use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
+use import_map::ImportMap;
 use parking_lot::RwLock;
-use schemajs_config::SchemeJsConfig;
+use schemajs_config::{ModulesConfig, SchemeJsConfig};
 use schemajs_data::fdm::FileDescriptorManager;
+use schemajs_data::format::check_data_folder;
+use schemajs_dirs::{create_remote_modules_folder, get_base_path};
 use schemajs_engine::engine::SchemeJsEngine;
+use schemajs_engine::utils::fs::find_table_modules;
 use schemajs_helpers::helper::HelperCall;
 use schemajs_internal::manager::InternalManager;
+use schemajs_module_loader::bundle::ModuleBundle;
+use schemajs_module_loader::remote::{RemoteModuleOptions, RemoteModules};
+use schemajs_module_loader::ts_module_loader::TypescriptModuleLoader;
+use serde_json::json;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -21,6 +34,10 @@ pub struct SjsContext {
     pub config: Arc<SchemeJsConfig>,
     pub initialized: AtomicBool,
     pub fdm: Arc<FileDescriptorManager>,
+    pub schema_changes: SchemaChanges,
+    pub import_map: Option<Arc<ImportMap>>,
+    pub remote_modules: Option<Arc<RemoteModules>>,
+    pub bundle: Option<Arc<ModuleBundle>>,
     repl: AtomicBool,
 }
 
@@ -30,19 +47,7 @@ impl SjsContext {
         data_path: Option<PathBuf>,
         helper_tx: Sender<HelperCall>,
     ) -> anyhow::Result<Self> {
-        // Determine the base path by joining the current directory with the config path
-        let base_path = std::env::current_dir()?.join(&config_path);
-
-        // Determine the appropriate folder path and config file path
-        let (folder_path, config_file) = if base_path.is_dir() {
-            (base_path.clone(), base_path.join("SchemaJS.toml"))
-        } else {
-            let folder_path = base_path.parent().map_or_else(
-                || std::env::current_dir(),
-                |parent| Ok(parent.to_path_buf()),
-            )?;
-            (folder_path.clone(), base_path)
-        };
+        let (folder_path, config_file) = Self::resolve_config_path(&config_path)?;
 
         let config = Arc::new(SchemeJsConfig::new(config_file.clone())?);
         let file_descriptor_manager = Arc::new(FileDescriptorManager::new(
@@ -63,13 +68,27 @@ impl SjsContext {
             data_path
         };
 
+        // Shards panic when opened in an unknown format, so outdated data is reported first.
+        let dbs_path = get_base_path(data_path.clone()).join("dbs");
+        if dbs_path.exists() {
+            check_data_folder(&dbs_path)?;
+        }
+
         let mut engine = Arc::new(RwLock::new(SchemeJsEngine::new(
             data_path.clone(),
             config.clone(),
             helper_tx,
             file_descriptor_manager.clone(),
         )));
-        let mut internal_manager = Arc::new(InternalManager::new(engine.clone()));
+        let import_map = Self::load_import_map(&folder_path, &config.modules)?.map(Arc::new);
+        let remote_modules =
+            Self::create_remote_modules(&folder_path, data_path.clone(), &config.modules)?
+                .map(Arc::new);
+
+        let mut internal_manager = Arc::new(
+            InternalManager::new(engine.clone())
+                .map_err(|e| anyhow!("Invalid [auth.jwt] configuration: {}", e))?,
+        );
         let mut manager = Arc::new(RwLock::new(SchemeJsManager::new(engine.clone())));
 
         Ok(Self {
@@ -82,10 +101,104 @@ impl SjsContext {
             config,
             initialized: AtomicBool::new(false),
             fdm: file_descriptor_manager,
+            schema_changes: SchemaChanges::default(),
+            import_map,
+            remote_modules,
+            bundle: None,
             repl: AtomicBool::new(true),
         })
     }
 
+    /// Loads the table modules from `bundle` instead of the database folders.
+    pub fn with_bundle(mut self, bundle: Arc<ModuleBundle>) -> Self {
+        self.bundle = Some(bundle);
+        self
+    }
+
+    /// Returns the name and the table modules of every database, read from the bundle when there is one.
+    pub fn table_modules(&self) -> anyhow::Result<Vec<(String, Vec<ModuleSpecifier>)>> {
+        match &self.bundle {
+            Some(bundle) => Ok(bundle
+                .manifest
+                .databases
+                .iter()
+                .map(|db| (db.name.clone(), db.tables.clone()))
+                .collect()),
+            None => find_database_schemas(&self.config, &self.current_folder),
+        }
+    }
+
+    /// Returns the folder of the project and its config file.
+    pub(crate) fn resolve_config_path(config_path: &PathBuf) -> anyhow::Result<(PathBuf, PathBuf)> {
+        // Determine the base path by joining the current directory with the config path
+        let base_path = std::env::current_dir()?.join(config_path);
+
+        // Determine the appropriate folder path and config file path
+        if base_path.is_dir() {
+            Ok((base_path.clone(), base_path.join("SchemaJS.toml")))
+        } else {
+            let folder_path = base_path.parent().map_or_else(
+                || std::env::current_dir(),
+                |parent| Ok(parent.to_path_buf()),
+            )?;
+            Ok((folder_path, base_path))
+        }
+    }
+
+    pub fn module_loader(&self) -> TypescriptModuleLoader {
+        match &self.bundle {
+            Some(bundle) => TypescriptModuleLoader::from_bundle(bundle.clone()),
+            None => {
+                TypescriptModuleLoader::new(self.import_map.clone(), self.remote_modules.clone())
+            }
+        }
+    }
+
+    pub(crate) fn create_remote_modules(
+        folder_path: &PathBuf,
+        data_path: Option<PathBuf>,
+        modules: &ModulesConfig,
+    ) -> anyhow::Result<Option<RemoteModules>> {
+        if !modules.remote {
+            return Ok(None);
+        }
+
+        Ok(Some(RemoteModules::new(RemoteModuleOptions {
+            cache_dir: create_remote_modules_folder(data_path),
+            lockfile: modules.lock.then(|| folder_path.join("SchemaJS.lock")),
+            cached_only: modules.cached_only,
+            jsr_registry: Url::parse(&modules.jsr_registry)?,
+            npm_registry: Url::parse(&modules.npm_registry)?,
+            npm_cdn: Url::parse(&modules.npm_cdn)?,
+        })?))
+    }
+
+    /// Builds the import map of the table scripts from `[modules]`, with entries relative to the config folder.
+    pub(crate) fn load_import_map(
+        folder_path: &PathBuf,
+        modules: &ModulesConfig,
+    ) -> anyhow::Result<Option<ImportMap>> {
+        if modules.imports.is_empty() && modules.scopes.is_empty() {
+            return Ok(None);
+        }
+
+        let base_url = Url::from_directory_path(folder_path)
+            .map_err(|_| anyhow!("Invalid config folder {}", folder_path.display()))?;
+        let import_map = import_map::parse_from_value(
+            base_url,
+            json!({
+                "imports": modules.imports,
+                "scopes": modules.scopes,
+            }),
+        )?;
+
+        for diagnostic in import_map.diagnostics {
+            println!("[Import Map] {}", diagnostic);
+        }
+
+        Ok(Some(import_map.import_map))
+    }
+
     pub fn mark_loaded(&self) {
         self.initialized.store(true, Ordering::SeqCst);
     }
@@ -104,3 +217,25 @@ impl SjsContext {
         self.repl.load(Ordering::SeqCst)
     }
 }
+
+/// Finds the table modules of the databases in the workspace and of the default scheme,
+/// relative to the folder of the project.
+pub(crate) fn find_database_schemas(
+    config: &SchemeJsConfig,
+    folder_path: &PathBuf,
+) -> anyhow::Result<Vec<(String, Vec<ModuleSpecifier>)>> {
+    let mut databases = config.workspace.databases.clone();
+    databases.push(config.global.default_scheme.clone());
+    let mut evaluated_paths = HashSet::new();
+    let mut schemas = vec![];
+
+    for database_path in databases {
+        let path = folder_path.join(&database_path);
+
+        if evaluated_paths.insert(path.clone()) {
+            schemas.push(find_table_modules(&path)?);
+        }
+    }
+
+    Ok(schemas)
+}
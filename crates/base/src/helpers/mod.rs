@@ -1,13 +1,14 @@
+pub(crate) mod watchdog;
+
 use crate::context::context::SjsContext;
 use crate::pool::pool_provider::SjsPoolProvider;
 use crate::runtime::SchemeJsRuntime;
 use crate::thread::WORKER_RT;
 use r2d2::Pool;
 use schemajs_helpers::helper::HelperCall;
-use std::ops::DerefMut;
 use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
-use tokio::task::JoinHandle;
+use tokio::sync::Semaphore;
 
 pub struct HelpersManager {
     pub ctx: Arc<SjsContext>,
@@ -19,7 +20,7 @@ impl HelpersManager {
         rx: Receiver<HelperCall>,
         ctx: Arc<SjsContext>,
     ) -> HelpersManager {
-        let handler_thread = Self::init(sjs_runtime_pool, rx, ctx.clone());
+        Self::init(sjs_runtime_pool, rx, ctx.clone());
 
         Self { ctx }
     }
@@ -27,38 +28,61 @@ impl HelpersManager {
     pub fn init(
         sjs_runtime_pool: Arc<Pool<SjsPoolProvider>>,
         mut rx: Receiver<HelperCall>,
-        ctx: Arc<SjsContext>,
+        _ctx: Arc<SjsContext>,
     ) {
+        // Calls in flight are bounded by the runtimes the pool can hand out,
+        // the rest wait here instead of timing out on checkout.
+        let max_runtimes = Arc::new(Semaphore::new(sjs_runtime_pool.max_size() as usize));
+
         let rt = &WORKER_RT;
         rt.spawn_pinned(move || {
             tokio::task::spawn_local(async move {
                 while let Some(cmd) = rx.recv().await {
-                    let permit = SchemeJsRuntime::acquire().await;
-                    match SchemeJsRuntime::new(ctx.clone()).await {
-                        Ok(rt) => {
-                            let lock = rt.acquire_lock(); // TODO: Wait for lock
-                            match lock {
-                                Ok(_) => {
-                                    let mut runtime = scopeguard::guard(rt, |mut runtime| unsafe {
-                                        runtime.js_runtime.v8_isolate().enter();
-                                        runtime.release_lock();
-                                    });
-
-                                    runtime.call_helper(cmd).await;
+                    let permit = max_runtimes.clone().acquire_owned().await.unwrap();
+                    let pool = sjs_runtime_pool.clone();
 
-                                    unsafe {
-                                        runtime.js_runtime.v8_isolate().exit();
-                                    }
-
-                                    drop(permit);
-                                }
-                                Err(_) => {}
-                            }
-                        }
-                        Err(_) => {}
-                    }
+                    WORKER_RT.spawn_pinned(move || async move {
+                        Self::dispatch(pool, cmd).await;
+                        drop(permit);
+                    });
                 }
             })
         });
     }
+
+    async fn dispatch(sjs_runtime_pool: Arc<Pool<SjsPoolProvider>>, cmd: HelperCall) {
+        // Only one isolate can be entered per thread at a time.
+        let permit = SchemeJsRuntime::acquire().await;
+
+        // Checking out may have to create a runtime, which r2d2 does on its own threads.
+        let runtime = tokio::task::spawn_blocking(move || sjs_runtime_pool.get()).await;
+        let mut runtime = match runtime {
+            Ok(Ok(runtime)) => runtime,
+            _ => return,
+        };
+
+        if runtime.acquire_lock().is_err() {
+            return;
+        }
+
+        unsafe {
+            runtime.js_runtime.v8_isolate().enter();
+        }
+
+        runtime.reload_changed_tables().await;
+        runtime.call_helper(cmd).await;
+        let recycle = runtime.track_usage();
+        runtime.release_lock();
+
+        // A recycled runtime is dropped by the pool as soon as it is returned, on this thread.
+        // Its isolate stays entered so the isolate's own drop can exit it.
+        if !recycle {
+            unsafe {
+                runtime.js_runtime.v8_isolate().exit();
+            }
+        }
+
+        drop(runtime);
+        drop(permit);
+    }
 }
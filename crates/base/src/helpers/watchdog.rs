@@ -0,0 +1,159 @@
+use deno_core::v8;
+use schemajs_config::DatabaseConfig;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const HEAP_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Limits a single helper call runs under, taken from the `DatabaseConfig` of the database it belongs to.
+#[derive(Debug, Clone, Default)]
+pub struct HelperLimits {
+    pub timeout: Option<Duration>,
+    pub max_heap_growth: Option<usize>,
+    pub max_ops: u64,
+}
+
+impl From<&DatabaseConfig> for HelperLimits {
+    fn from(config: &DatabaseConfig) -> Self {
+        Self {
+            timeout: (config.custom_query_timeout > 0)
+                .then(|| Duration::from_secs(config.custom_query_timeout)),
+            max_heap_growth: (config.max_helper_heap_size > 0)
+                .then_some(config.max_helper_heap_size),
+            max_ops: config.max_helper_ops,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitExceeded {
+    Timeout,
+    Heap,
+}
+
+struct WatchState {
+    active: AtomicBool,
+    heap_threshold: usize,
+    timed_out: AtomicBool,
+    heap_exceeded: AtomicBool,
+}
+
+/// Watches a helper call from its own thread and terminates the isolate's execution
+/// once the call outlives its timeout or grows the heap past its limit.
+///
+/// The heap can only be inspected from the isolate's thread, so it is checked through interrupts.
+pub struct ExecutionWatchdog {
+    state: Arc<WatchState>,
+    stop_tx: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ExecutionWatchdog {
+    pub fn start(isolate: &mut v8::Isolate, limits: &HelperLimits) -> Self {
+        let heap_threshold = match limits.max_heap_growth {
+            Some(growth) => used_heap_size(isolate).saturating_add(growth),
+            None => usize::MAX,
+        };
+
+        let state = Arc::new(WatchState {
+            active: AtomicBool::new(true),
+            heap_threshold,
+            timed_out: AtomicBool::new(false),
+            heap_exceeded: AtomicBool::new(false),
+        });
+
+        let interval = match (limits.timeout, limits.max_heap_growth) {
+            (_, Some(_)) => HEAP_CHECK_INTERVAL,
+            (Some(timeout), None) => timeout,
+            (None, None) => {
+                return Self {
+                    state,
+                    stop_tx: None,
+                    thread: None,
+                }
+            }
+        };
+
+        let (stop_tx, stop_rx) = channel::<()>();
+        let handle = isolate.thread_safe_handle();
+        let thread_state = state.clone();
+        let timeout = limits.timeout;
+        let check_heap = limits.max_heap_growth.is_some();
+
+        let thread = std::thread::spawn(move || {
+            let started = Instant::now();
+
+            loop {
+                match stop_rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    _ => return,
+                }
+
+                if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+                    thread_state.timed_out.store(true, Ordering::SeqCst);
+                    handle.terminate_execution();
+                    return;
+                }
+
+                if check_heap {
+                    let data = Arc::into_raw(thread_state.clone()) as *mut c_void;
+                    if !handle.request_interrupt(check_heap_interrupt, data) {
+                        // The isolate is gone, the interrupt will never take its reference.
+                        unsafe { drop(Arc::from_raw(data as *const WatchState)) };
+                        return;
+                    }
+                }
+            }
+        });
+
+        Self {
+            state,
+            stop_tx: Some(stop_tx),
+            thread: Some(thread),
+        }
+    }
+
+    /// Stops watching the call and returns the limit it exceeded, if any.
+    /// A termination requested by the watchdog is cancelled so the isolate can run again.
+    pub fn stop(mut self, isolate: &mut v8::Isolate) -> Option<LimitExceeded> {
+        self.state.active.store(false, Ordering::SeqCst);
+        drop(self.stop_tx.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        let exceeded = if self.state.timed_out.load(Ordering::SeqCst) {
+            Some(LimitExceeded::Timeout)
+        } else if self.state.heap_exceeded.load(Ordering::SeqCst) {
+            Some(LimitExceeded::Heap)
+        } else {
+            None
+        };
+
+        if exceeded.is_some() {
+            isolate.cancel_terminate_execution();
+        }
+
+        exceeded
+    }
+}
+
+extern "C" fn check_heap_interrupt(isolate: &mut v8::Isolate, data: *mut c_void) {
+    let state = unsafe { Arc::from_raw(data as *const WatchState) };
+
+    // Interrupts still queued when the call ended must not affect the next one.
+    if state.active.load(Ordering::SeqCst) && used_heap_size(isolate) > state.heap_threshold {
+        state.heap_exceeded.store(true, Ordering::SeqCst);
+        isolate.terminate_execution();
+    }
+}
+
+fn used_heap_size(isolate: &mut v8::Isolate) -> usize {
+    let mut stats = v8::HeapStatistics::default();
+    isolate.get_heap_statistics(&mut stats);
+    stats.used_heap_size()
+}
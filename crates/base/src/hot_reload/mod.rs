@@ -0,0 +1,61 @@
+pub mod table_diff;
+pub mod watcher;
+
+use deno_core::ModuleSpecifier;
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// A table module to reload, since it or a local module it imports changed on disk after
+/// runtimes loaded it.
+#[derive(Debug)]
+pub struct SchemaChange {
+    pub generation: u64,
+    pub scheme: String,
+    pub specifier: ModuleSpecifier,
+    /// Whether the new definition was compatible and applied to the engine.
+    /// Decided by the first runtime that evaluates the change.
+    pub accepted: OnceLock<bool>,
+}
+
+/// Changes to table modules, shared by every runtime of a context.
+/// Each runtime keeps the last generation it applied and catches up before serving a helper call.
+#[derive(Debug, Default)]
+pub struct SchemaChanges {
+    generation: AtomicU64,
+    changes: RwLock<Vec<Arc<SchemaChange>>>,
+}
+
+impl SchemaChanges {
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Records that the module at `specifier` changed. Only its latest change is kept.
+    pub fn push(&self, scheme: String, specifier: ModuleSpecifier) -> u64 {
+        let mut changes = self.changes.write();
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+        changes.retain(|change| change.specifier != specifier);
+        changes.push(Arc::new(SchemaChange {
+            generation,
+            scheme,
+            specifier,
+            accepted: OnceLock::new(),
+        }));
+
+        generation
+    }
+
+    /// Returns the current generation and the changes made after `generation`.
+    pub fn since(&self, generation: u64) -> (u64, Vec<Arc<SchemaChange>>) {
+        let changes = self.changes.read();
+        let changed = changes
+            .iter()
+            .filter(|change| change.generation > generation)
+            .cloned()
+            .collect();
+
+        (self.generation(), changed)
+    }
+}
@@ -0,0 +1,158 @@
+use schemajs_primitives::table::Table;
+
+/// Lists the changes between two definitions of a table that cannot be applied to its existing data.
+///
+/// Columns can be added as long as they are not required, and indexes can be added as long as
+/// they only cover new columns, since existing rows have no value for them. Anything else that
+/// touches existing columns or indexes is incompatible, as is changing the compression or the
+/// row format existing rows are stored with.
+pub fn incompatible_changes(current: &Table, new: &Table) -> Vec<String> {
+    let mut changes = vec![];
+
+    if current.compression != new.compression {
+        changes.push(format!(
+            "~ compression: {:?} -> {:?}",
+            current.compression, new.compression
+        ));
+    }
+
+    if current.metadata.row_format != new.metadata.row_format {
+        changes.push(format!(
+            "~ row format: {:?} -> {:?}",
+            current.metadata.row_format, new.metadata.row_format
+        ));
+    }
+
+    if current.primary_key != new.primary_key {
+        changes.push(format!(
+            "~ primary key: '{}' -> '{}'",
+            current.primary_key, new.primary_key
+        ));
+    }
+
+    for (name, column) in &current.columns {
+        match new.get_column(name) {
+            None => changes.push(format!("- column '{}'", name)),
+            Some(new_column) => {
+                if new_column.data_type != column.data_type {
+                    changes.push(format!(
+                        "~ column '{}': {:?} -> {:?}",
+                        name, column.data_type, new_column.data_type
+                    ));
+                }
+
+                if new_column.required && !column.required {
+                    changes.push(format!("~ column '{}': became required", name));
+                }
+
+                if new_column.primary_key != column.primary_key {
+                    changes.push(format!("~ column '{}': primary key changed", name));
+                }
+            }
+        }
+    }
+
+    for (name, column) in &new.columns {
+        if !current.columns.contains_key(name) && column.required {
+            changes.push(format!(
+                "+ column '{}': new columns cannot be required",
+                name
+            ));
+        }
+    }
+
+    for index in &current.indexes {
+        match new.indexes.iter().find(|e| e.name == index.name) {
+            None => changes.push(format!("- index '{}'", index.name)),
+            Some(new_index) => {
+                if new_index.members != index.members {
+                    changes.push(format!(
+                        "~ index '{}': {:?} -> {:?}",
+                        index.name, index.members, new_index.members
+                    ));
+                }
+            }
+        }
+    }
+
+    for index in &new.indexes {
+        let is_new = !current.indexes.iter().any(|e| e.name == index.name);
+        let covers_existing = index
+            .members
+            .iter()
+            .any(|member| current.columns.contains_key(member));
+
+        if is_new && covers_existing {
+            changes.push(format!(
+                "+ index '{}': cannot index existing columns {:?}",
+                index.name, index.members
+            ));
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hot_reload::table_diff::incompatible_changes;
+    use schemajs_config::RowFormat;
+    use schemajs_data::compression::Compression;
+    use schemajs_primitives::column::types::DataTypes;
+    use schemajs_primitives::column::Column;
+    use schemajs_primitives::table::Table;
+
+    fn users_table() -> Table {
+        let mut table = Table::new("users")
+            .add_column(Column::new("id", DataTypes::String))
+            .add_column(Column::new("username", DataTypes::String));
+        table.init();
+        table
+    }
+
+    #[test]
+    pub fn test_compatible_changes() {
+        let current = users_table();
+
+        let mut new = Table::new("users")
+            .add_column(Column::new("id", DataTypes::String))
+            .add_column(Column::new("username", DataTypes::String))
+            .add_column(Column::new("email", DataTypes::String));
+        new.init();
+
+        assert!(incompatible_changes(&current, &new).is_empty());
+    }
+
+    #[test]
+    pub fn test_incompatible_changes() {
+        let current = users_table();
+
+        let mut new = Table::new("users")
+            .add_column(Column::new("id", DataTypes::Number))
+            .add_column(Column::new("email", DataTypes::String).set_required(true));
+        new.init();
+
+        let changes = incompatible_changes(&current, &new);
+        assert!(changes.contains(&"~ column 'id': String -> Number".to_string()));
+        assert!(changes.contains(&"- column 'username'".to_string()));
+        assert!(changes.contains(&"- index 'username_indx'".to_string()));
+        assert!(changes.contains(&"+ column 'email': new columns cannot be required".to_string()));
+    }
+
+    #[test]
+    pub fn test_storage_changes_are_incompatible() {
+        let current = users_table();
+
+        let mut new = users_table().set_compression(Compression::Zstd);
+        new.metadata.row_format = RowFormat::Binary;
+
+        let changes = incompatible_changes(&current, &new);
+        assert_eq!(
+            changes,
+            vec![
+                "~ compression: None -> Zstd".to_string(),
+                "~ row format: Json -> Binary".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use crate::context::context::SjsContext;
+use deno_core::futures::executor::block_on;
+use deno_core::ModuleSpecifier;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use schemajs_module_loader::graph::ModuleGraph;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Watches the table modules of every database and the local modules they import, and records
+/// the tables to reload in `SjsContext::schema_changes` when one of them is created or modified.
+///
+/// Only the modules `SjsContext::table_modules` finds are tables. Any other module changing
+/// reloads the tables that import it, directly or not. The project folder is watched as a whole,
+/// so modules imported later are watched too, along with the table folders and the folders of
+/// imported modules outside of it.
+///
+/// The returned watcher stops watching once dropped.
+pub fn watch_schemas(ctx: Arc<SjsContext>) -> anyhow::Result<RecommendedWatcher> {
+    let graph = block_on(ModuleGraph::build(
+        ctx.table_modules()?,
+        &ctx.module_loader(),
+    ));
+
+    let project_folder = ctx.current_folder.canonicalize()?;
+
+    let mut databases = ctx.config.workspace.databases.clone();
+    databases.push(ctx.config.global.default_scheme.clone());
+
+    let mut table_folders = HashSet::new();
+    for database_path in databases {
+        let table_folder = ctx.current_folder.join(&database_path).join("tables");
+        if let Ok(table_folder) = table_folder.canonicalize() {
+            if !table_folder.starts_with(&project_folder) {
+                table_folders.insert(table_folder);
+            }
+        }
+    }
+
+    let mut folders = HashSet::new();
+    for file in graph.files() {
+        if let Some(folder) = file.parent() {
+            let is_watched = folder.starts_with(&project_folder)
+                || table_folders
+                    .iter()
+                    .any(|tables| folder.starts_with(tables));
+            if !is_watched {
+                folders.insert(folder.to_path_buf());
+            }
+        }
+    }
+
+    let graph = Mutex::new(graph);
+    let watch_ctx = ctx.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            return;
+        }
+
+        let loader = watch_ctx.module_loader();
+        let mut graph = graph.lock();
+        for path in event.paths {
+            let path = path.canonicalize().unwrap_or(path);
+            let mut tables = graph.dependents(&path);
+
+            // A table created after the graph was built.
+            if tables.is_empty() && is_js_or_ts(&path) {
+                if let Ok(specifier) = ModuleSpecifier::from_file_path(&path) {
+                    tables.extend(
+                        watch_ctx
+                            .table_modules()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter(|(_, specifiers)| specifiers.contains(&specifier))
+                            .map(|(scheme, _)| (scheme, specifier.clone())),
+                    );
+                }
+            }
+
+            if tables.is_empty() {
+                continue;
+            }
+
+            println!("[Hot Reload] {} changed", path.to_string_lossy());
+            for (scheme, specifier) in tables {
+                // Its imports may have changed along with it.
+                block_on(graph.update(&scheme, specifier.clone(), &loader));
+                watch_ctx.schema_changes.push(scheme, specifier);
+            }
+        }
+    })?;
+
+    watcher.watch(&project_folder, RecursiveMode::Recursive)?;
+    for table_folder in table_folders {
+        watcher.watch(&table_folder, RecursiveMode::Recursive)?;
+    }
+    for folder in folders {
+        watcher.watch(&folder, RecursiveMode::NonRecursive)?;
+    }
+
+    Ok(watcher)
+}
+
+fn is_js_or_ts(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext == "js" || ext == "ts")
+}
@@ -1,6 +1,9 @@
+pub mod bundle;
+pub mod codegen;
 pub mod context;
 mod error;
 mod helpers;
+pub mod hot_reload;
 mod manager;
 pub mod pool;
 pub mod runner;
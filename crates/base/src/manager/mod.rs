@@ -4,6 +4,7 @@ pub mod tasks;
 
 use crate::manager::task::Task;
 use crate::manager::task_duration::TaskDuration;
+use chrono::Utc;
 use parking_lot::RwLock;
 use schemajs_engine::engine::SchemeJsEngine;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -33,28 +34,48 @@ impl SchemeJsManager {
     }
 
     pub fn start_tasks(&self) {
+        for task in &self.tasks {
+            self.start_task(task.clone());
+        }
+    }
+
+    fn start_task(&self, task: Task) {
         let engine = self.runtime.clone();
         let running = self.running.clone();
-
-        for task in &self.tasks {
-            let task_cancel_token = task.cancellation_token.clone();
-            let task = task.clone();
-            let engine = engine.clone();
-            let running = running.clone();
-            let cancel_token = self.cancellation_token.clone();
-            tokio::spawn(async move {
-                select! {
-                    _ = cancel_token.cancelled() => {
-                    }
-                    _ = task_cancel_token.cancelled() => {
-                    }
-                    _ = Self::run_task(task, engine, running) => {
-                    }
+        let task_cancel_token = task.cancellation_token.clone();
+        let cancel_token = self.cancellation_token.clone();
+        tokio::spawn(async move {
+            select! {
+                _ = cancel_token.cancelled() => {
+                }
+                _ = task_cancel_token.cancelled() => {
                 }
-            });
+                _ = Self::run_task(task, engine, running) => {
+                }
+            }
+        });
+    }
+
+    /// Stops the running tasks whose id starts with `prefix` and starts `tasks` in their place.
+    pub fn replace_tasks(&mut self, prefix: &str, tasks: Vec<Task>) {
+        self.tasks.retain(|task| {
+            let replaced = task.id.starts_with(prefix);
+            if replaced {
+                task.cancellation_token.cancel();
+            }
+            !replaced
+        });
+
+        for task in tasks {
+            self.start_task(task.clone());
+            self.tasks.push(task);
         }
     }
 
+    pub fn has_task(&self, id: &str) -> bool {
+        self.tasks.iter().any(|task| task.id == id)
+    }
+
     async fn run_task(task: Task, engine: Arc<RwLock<SchemeJsEngine>>, running: Arc<AtomicBool>) {
         match task.duration {
             TaskDuration::Defined(dur) => {
@@ -67,6 +88,20 @@ impl SchemeJsManager {
                     cb(clone_rt_ref).unwrap_or_else(|_| println!("Error executing task"));
                 }
             }
+            TaskDuration::Cron(schedule) => {
+                for next in schedule.upcoming(Utc) {
+                    if !running.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    let wait = (next - Utc::now()).to_std().unwrap_or_default();
+                    tokio::time::sleep(wait).await;
+                    let clone_rt_ref = engine.clone();
+                    let cb = task.func.cb.clone();
+
+                    cb(clone_rt_ref).unwrap_or_else(|_| println!("Error executing task"));
+                }
+            }
             TaskDuration::Once => {
                 let clone_rt_ref = engine.clone();
                 let cb = task.func.cb.clone();
@@ -80,3 +115,66 @@ impl SchemeJsManager {
         self.cancellation_token.cancel();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::manager::task::Task;
+    use crate::manager::task_duration::TaskDuration;
+    use crate::manager::SchemeJsManager;
+    use parking_lot::RwLock;
+    use schemajs_config::SchemeJsConfig;
+    use schemajs_data::fdm::FileDescriptorManager;
+    use schemajs_engine::engine::SchemeJsEngine;
+    use schemajs_helpers::create_helper_channel;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn counting_task(id: &str, runs: Arc<AtomicUsize>) -> Task {
+        Task::new(
+            id.to_string(),
+            Box::new(move |_| {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }),
+            TaskDuration::Defined(Duration::from_millis(10)),
+        )
+    }
+
+    #[tokio::test]
+    pub async fn test_replace_tasks() {
+        let (helper_tx, _helper_rx) = create_helper_channel(1);
+        let engine = Arc::new(RwLock::new(SchemeJsEngine::new(
+            None,
+            Arc::new(SchemeJsConfig::default()),
+            helper_tx,
+            Arc::new(FileDescriptorManager::new(10)),
+        )));
+        let mut manager = SchemeJsManager::new(engine);
+
+        let old_runs = Arc::new(AtomicUsize::new(0));
+        let kept_runs = Arc::new(AtomicUsize::new(0));
+        let new_runs = Arc::new(AtomicUsize::new(0));
+        manager.add_task(counting_task("public.users.old", old_runs.clone()));
+        manager.add_task(counting_task("public.orders.kept", kept_runs.clone()));
+        manager.start_tasks();
+
+        manager.replace_tasks(
+            "public.users.",
+            vec![counting_task("public.users.new", new_runs.clone())],
+        );
+        assert!(!manager.has_task("public.users.old"));
+        assert!(manager.has_task("public.users.new"));
+        assert!(manager.has_task("public.orders.kept"));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let stopped_at = old_runs.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(old_runs.load(Ordering::SeqCst), stopped_at);
+        assert!(kept_runs.load(Ordering::SeqCst) > 1);
+        assert!(new_runs.load(Ordering::SeqCst) > 1);
+
+        manager.stop_tasks();
+    }
+}
@@ -1,7 +1,9 @@
+use cron::Schedule;
 use std::time::Duration;
 
 #[derive(Clone)]
 pub enum TaskDuration {
     Defined(Duration),
     Once,
+    Cron(Schedule),
 }
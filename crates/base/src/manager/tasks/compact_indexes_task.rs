@@ -0,0 +1,41 @@
+use crate::manager::task::Task;
+use crate::manager::task_duration::TaskDuration;
+use schemajs_index::index_type::IndexTypeValue;
+use std::cell::LazyCell;
+use std::time::Duration;
+
+/// Merges the sorted runs of the hash indexes that have piled up since the last run.
+pub const COMPACT_INDEXES_TASK: LazyCell<Task> = LazyCell::new(|| {
+    Task::new(
+        "2".to_string(),
+        Box::new(move |rt| {
+            let mut shards = vec![];
+
+            {
+                let engine = rt.read();
+                for db in engine.databases.iter() {
+                    let query_manager = &db.query_manager;
+                    for table in query_manager.table_names.read().unwrap().iter() {
+                        let table = query_manager.tables.get(table).unwrap();
+
+                        for index in &table.table.indexes {
+                            if let Some(IndexTypeValue::Hash(hash_index)) =
+                                table.indexes.get(&index.name).as_deref()
+                            {
+                                shards.push(hash_index.index.clone());
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Compacting without holding the engine, so reconciliation and inserts keep going.
+            for shard in shards {
+                while shard.compact() {}
+            }
+
+            Ok(())
+        }),
+        TaskDuration::Defined(Duration::from_secs(30)),
+    )
+});
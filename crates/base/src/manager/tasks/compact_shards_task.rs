@@ -0,0 +1,39 @@
+use crate::manager::task::Task;
+use crate::manager::task_duration::TaskDuration;
+use schemajs_query::db_row::DbRow;
+use schemajs_query::managers::single::table_shard::TableShard;
+use std::cell::LazyCell;
+use std::time::Duration;
+
+/// Rewrites the past master shards of every table without the rows no index points to anymore.
+pub const COMPACT_SHARDS_TASK: LazyCell<Task> = LazyCell::new(|| {
+    Task::new(
+        "3".to_string(),
+        Box::new(move |rt| {
+            let mut tables = vec![];
+
+            {
+                let engine = rt.read();
+                for db in engine.databases.iter() {
+                    let query_manager = &db.query_manager;
+                    for table in query_manager.table_names.read().unwrap().iter() {
+                        let table = query_manager.tables.get(table).unwrap();
+                        tables.push((
+                            table.table.clone(),
+                            table.data.clone(),
+                            table.indexes.clone(),
+                        ));
+                    }
+                }
+            }
+
+            // Compacting without holding the engine, so reconciliation and inserts keep going.
+            for (table, data, indexes) in tables {
+                TableShard::<DbRow>::compact_data(table, &data, &indexes);
+            }
+
+            Ok(())
+        }),
+        TaskDuration::Defined(Duration::from_secs(300)),
+    )
+});
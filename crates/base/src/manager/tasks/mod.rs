@@ -1,8 +1,17 @@
 use crate::manager::task::Task;
+use crate::manager::tasks::compact_indexes_task::COMPACT_INDEXES_TASK;
+use crate::manager::tasks::compact_shards_task::COMPACT_SHARDS_TASK;
 use crate::manager::tasks::reconcile_task::RECONCILE_DB_TASK;
 
+mod compact_indexes_task;
+mod compact_shards_task;
 mod reconcile_task;
+pub mod scheduled_task;
 
 pub fn get_all_internal_tasks() -> Vec<Task> {
-    vec![(*RECONCILE_DB_TASK).clone()]
+    vec![
+        (*RECONCILE_DB_TASK).clone(),
+        (*COMPACT_INDEXES_TASK).clone(),
+        (*COMPACT_SHARDS_TASK).clone(),
+    ]
 }
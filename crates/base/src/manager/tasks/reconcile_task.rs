@@ -12,7 +12,10 @@ pub const RECONCILE_DB_TASK: LazyCell<Task> = LazyCell::new(|| {
                 let query_manager = &db.query_manager;
                 for table in query_manager.table_names.read().unwrap().iter() {
                     let table = query_manager.tables.get(table).unwrap();
-                    table.temps.reconcile_all();
+                    // Rows that couldn't be reconciled stay in their temporary shard.
+                    if let Err(e) = table.temps.reconcile_all() {
+                        println!("Could not reconcile table '{}': {}", table.table.name, e);
+                    }
                 }
             }
             Ok(())
@@ -0,0 +1,158 @@
+use crate::manager::task::Task;
+use crate::manager::task_duration::TaskDuration;
+use cron::Schedule;
+use schemajs_helpers::helper::{Helper, HelperCall, HelperDbContext, HelperType, SjsTableHelpers};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Parses a cron expression, accepting the standard five-field form (`min hour day month weekday`)
+/// besides the six and seven-field forms (with seconds and year) understood by `cron`.
+pub fn parse_cron_expression(expression: &str) -> Result<Schedule, cron::error::Error> {
+    let expression = if expression.split_whitespace().count() == 5 {
+        format!("0 {}", expression)
+    } else {
+        expression.to_string()
+    };
+
+    Schedule::from_str(&expression)
+}
+
+/// Creates a task that, following `expression`, sends the scheduled helpers of `table` to the helpers runtime.
+pub fn create_scheduled_task(
+    db_name: &str,
+    table_name: &str,
+    expression: &str,
+) -> Result<Task, cron::error::Error> {
+    let schedule = parse_cron_expression(expression)?;
+    let db_ctx = HelperDbContext {
+        db: Some(db_name.to_string()),
+        table: Some(table_name.to_string()),
+    };
+    let identifier = expression.to_string();
+    let id = format!(
+        "{}{}",
+        scheduled_task_prefix(db_name, table_name),
+        expression
+    );
+    let task_id = id.clone();
+
+    Ok(Task::new(
+        id,
+        Box::new(move |rt| {
+            let helper_tx = rt.read().helper_tx.clone();
+            let call = HelperCall::Scheduled {
+                db_ctx: db_ctx.clone(),
+                identifier: identifier.clone(),
+            };
+            let task_id = task_id.clone();
+
+            // Runs wait behind the helper calls in flight rather than being dropped.
+            tokio::spawn(async move {
+                if helper_tx.send(call).await.is_err() {
+                    println!(
+                        "[Scheduler] Helpers are not running, '{}' did not run",
+                        task_id
+                    );
+                }
+            });
+
+            Ok(())
+        }),
+        TaskDuration::Cron(schedule),
+    ))
+}
+
+/// Prefix of the ids of the scheduled tasks of a table.
+pub fn scheduled_task_prefix(db_name: &str, table_name: &str) -> String {
+    format!("{}.{}.", db_name, table_name)
+}
+
+/// Creates one task per distinct cron expression declared through `table.schedule` in the loaded tables.
+pub fn get_scheduled_tasks(table_helpers: &SjsTableHelpers) -> Vec<Task> {
+    let mut tasks: Vec<Task> = vec![];
+
+    for db in table_helpers.0.iter() {
+        for table in db.value().iter() {
+            tasks.extend(get_table_scheduled_tasks(
+                db.key(),
+                table.key(),
+                &table.value().0,
+            ));
+        }
+    }
+
+    tasks
+}
+
+/// Creates one task per distinct cron expression among the scheduled `helpers` of a table.
+pub fn get_table_scheduled_tasks(
+    db_name: &str,
+    table_name: &str,
+    helpers: &[Arc<Helper>],
+) -> Vec<Task> {
+    let mut tasks: Vec<Task> = vec![];
+
+    for helper in helpers {
+        if helper.internal_type != HelperType::Scheduled {
+            continue;
+        }
+
+        match create_scheduled_task(db_name, table_name, &helper.identifier) {
+            Ok(task) => {
+                if !tasks.iter().any(|e| e.id == task.id) {
+                    tasks.push(task);
+                }
+            }
+            Err(e) => println!(
+                "Invalid schedule '{}' in table '{}': {}",
+                helper.identifier, table_name, e
+            ),
+        }
+    }
+
+    tasks
+}
+
+#[cfg(test)]
+mod test {
+    use crate::manager::tasks::scheduled_task::{create_scheduled_task, parse_cron_expression};
+    use parking_lot::RwLock;
+    use schemajs_config::SchemeJsConfig;
+    use schemajs_data::fdm::FileDescriptorManager;
+    use schemajs_engine::engine::SchemeJsEngine;
+    use schemajs_helpers::create_helper_channel;
+    use std::sync::Arc;
+
+    #[test]
+    pub fn test_parse_cron_expression() {
+        let five_fields = parse_cron_expression("0 * * * *").unwrap();
+        let six_fields = parse_cron_expression("0 0 * * * *").unwrap();
+        assert_eq!(five_fields.to_string(), six_fields.to_string());
+
+        assert!(parse_cron_expression("not a cron").is_err());
+    }
+
+    #[tokio::test]
+    pub async fn test_scheduled_runs_wait_for_helpers() {
+        let (helper_tx, mut helper_rx) = create_helper_channel(1);
+        let engine = Arc::new(RwLock::new(SchemeJsEngine::new(
+            None,
+            Arc::new(SchemeJsConfig::default()),
+            helper_tx,
+            Arc::new(FileDescriptorManager::new(10)),
+        )));
+        let task = create_scheduled_task("public", "users", "*/5 * * * *").unwrap();
+        assert_eq!(task.id, "public.users.*/5 * * * *");
+
+        // More runs than the channel holds, none of them is dropped.
+        for _ in 0..3 {
+            (task.func.cb)(engine.clone()).unwrap();
+        }
+
+        for _ in 0..3 {
+            let (db_ctx, identifier) = helper_rx.recv().await.unwrap().into_scheduled().unwrap();
+            assert_eq!(db_ctx.table.as_deref(), Some("users"));
+            assert_eq!(identifier, "*/5 * * * *");
+        }
+    }
+}
@@ -13,16 +13,16 @@ impl SjsRuntimePool {
     pub fn new(shared_context: Arc<SjsContext>, max_runtimes: u32) -> Self {
         let provider = SjsPoolProvider { shared_context };
 
-        let a = "";
-
+        // Runtimes are only dropped through `has_broken`, on the thread that used them last.
+        // Idle and lifetime reaping would drop isolates from the pool's own threads.
         let pool = r2d2::Pool::builder()
             .max_size(max_runtimes)
             .min_idle(Some(0))
+            .idle_timeout(None)
+            .max_lifetime(None)
             .build(provider)
             .unwrap();
 
-        let b = "";
-
         Self {
             pool: Arc::new(pool),
         }
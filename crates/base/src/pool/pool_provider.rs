@@ -15,10 +15,15 @@ impl r2d2::ManageConnection for SjsPoolProvider {
         let ctx = self.shared_context.clone();
         let current = tokio::runtime::Runtime::new().unwrap();
 
-        let rt = current
+        let mut rt = current
             .block_on(async move { SchemeJsRuntime::new(ctx).await })
             .map_err(|e| SjsRtError::UnexpectedRuntimeCreation)?;
 
+        // Runtimes are checked out from worker threads, which enter the isolate while using it.
+        unsafe {
+            rt.js_runtime.v8_isolate().exit();
+        }
+
         Ok(rt)
     }
 
@@ -27,8 +32,6 @@ impl r2d2::ManageConnection for SjsPoolProvider {
     }
 
     fn has_broken(&self, conn: &mut Self::Connection) -> bool {
-        conn.acquire_lock()
-            .map_err(|_| Self::Error::BusyRuntime)
-            .is_ok()
+        conn.should_recycle()
     }
 }
@@ -1,8 +1,11 @@
 use crate::context::context::SjsContext;
 use crate::helpers::HelpersManager;
+use crate::hot_reload::watcher::watch_schemas;
 use crate::pool::SjsRuntimePool;
+use notify::RecommendedWatcher;
 use schemajs_helpers::create_helper_channel;
 use schemajs_helpers::helper::HelperCall;
+use schemajs_module_loader::bundle::ModuleBundle;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc::Sender;
@@ -12,6 +15,7 @@ pub struct SjsRunner {
     pub rt_pool: Arc<SjsRuntimePool>,
     pub helpers_manager: HelpersManager,
     pub helper_tx: Sender<HelperCall>,
+    pub schema_watcher: Option<RecommendedWatcher>,
 }
 
 pub struct SjsRunnerConfig {
@@ -19,23 +23,41 @@ pub struct SjsRunnerConfig {
     pub max_runtimes: u32,
     pub config_path: PathBuf,
     pub data_path: Option<PathBuf>,
+    /// Bundle created with `schemajs bundle` to load the table modules from.
+    pub bundle: Option<PathBuf>,
 }
 
 impl SjsRunner {
-    pub fn new(config: SjsRunnerConfig) -> Self {
+    pub fn new(config: SjsRunnerConfig) -> anyhow::Result<Self> {
         let (helper_tx, helper_rx) = create_helper_channel(config.max_helper_processing_capacity);
-        let context = Arc::new(
-            SjsContext::new(config.config_path, config.data_path, helper_tx.clone()).unwrap(),
-        );
+        let mut context = SjsContext::new(config.config_path, config.data_path, helper_tx.clone())?;
+        if let Some(bundle) = config.bundle {
+            context = context.with_bundle(Arc::new(ModuleBundle::read(&bundle)?));
+        }
+        let context = Arc::new(context);
         let rt_pool = Arc::new(SjsRuntimePool::new(context.clone(), config.max_runtimes));
         let helpers_manager = HelpersManager::new(rt_pool.pool.clone(), helper_rx, context.clone());
 
-        Self {
+        // A bundle is immutable, so there is nothing to watch.
+        let schema_watcher = if context.config.process.hot_reload && context.bundle.is_none() {
+            match watch_schemas(context.clone()) {
+                Ok(watcher) => Some(watcher),
+                Err(e) => {
+                    println!("[Hot Reload] Could not watch schema folders: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(Self {
             helper_tx,
             sjs_context: context,
             rt_pool,
             helpers_manager,
-        }
+            schema_watcher,
+        })
     }
 }
 
@@ -56,7 +78,9 @@ mod runner_tests {
             max_runtimes: 3,
             config_path: PathBuf::from("./test_cases/default-db"),
             data_path: None,
-        });
+            bundle: None,
+        })
+        .unwrap();
 
         println!("Before tx created");
 
@@ -1,7 +1,12 @@
 use crate::context::context::SjsContext;
+use crate::helpers::watchdog::{ExecutionWatchdog, HelperLimits, LimitExceeded};
+use crate::hot_reload::table_diff::incompatible_changes;
 use crate::manager::task::Task;
 use crate::manager::task_duration::TaskDuration;
 use crate::manager::tasks::get_all_internal_tasks;
+use crate::manager::tasks::scheduled_task::{
+    get_scheduled_tasks, get_table_scheduled_tasks, scheduled_task_prefix,
+};
 use crate::manager::SchemeJsManager;
 use crate::snapshot;
 use anyhow::{bail, Error, Result};
@@ -18,19 +23,20 @@ use deno_core::{
 use parking_lot::RwLock;
 use schemajs_config::SchemeJsConfig;
 use schemajs_engine::engine::SchemeJsEngine;
+use schemajs_engine::hook_scope::BeforeInsertHookScope;
+use schemajs_engine::op_budget::OpBudget;
 use schemajs_helpers::helper::{
     Helper, HelperCall, HelperDbContext, HelperType, SjsHelpersContainer, SjsTableHelpers,
 };
 use schemajs_internal::get_internal_tables;
 use schemajs_internal::manager::InternalManager;
-use schemajs_module_loader::ts_module_loader::TypescriptModuleLoader;
 use schemajs_primitives::database::Database;
 use schemajs_primitives::table::Table;
 use schemajs_workers::context::{MainWorkerRuntimeOpts, WorkerRuntimeOpts};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::cell::{RefCell, RefMut};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -49,52 +55,34 @@ thread_local! {
     static RUNTIME_CREATION_SEM: Arc<Semaphore> = Arc::new(Semaphore::new(1));
 }
 
+/// Tables a runtime reloads before it is recycled. V8 never frees an evaluated module, and each
+/// reload evaluates the table module and its local imports again, so a runtime reloading tables
+/// would otherwise grow without bound.
+const MAX_TABLE_RELOADS: u64 = 100;
+
 pub struct SchemeJsRuntime {
     pub js_runtime: JsRuntime,
     pub ctx: Arc<SjsContext>,
     pub table_helpers: Arc<SjsTableHelpers>,
     pub busy: AtomicBool,
+    served_calls: u64,
+    recycle: bool,
+    helper_limits: HelperLimits,
+    schema_generation: u64,
+    table_reloads: u64,
 }
 
 impl SchemeJsRuntime {
     pub async fn new(context: Arc<SjsContext>) -> Result<Self> {
-        let extensions: Vec<Extension> = vec![
-            schemajs_primitives::sjs_primitives::init_ops(),
-            schemajs_core::sjs_core::init_ops(),
-            schemajs_engine::sjs_engine::init_ops(),
-            schemajs_helpers::sjs_helpers::init_ops(),
-            schemajs_repl::sjs_repl::init_ops(),
-        ];
-
-        let runtime_opts = RuntimeOptions {
-            extensions,
-            is_main: true,
-            shared_array_buffer_store: None,
-            compiled_wasm_module_store: None,
-            startup_snapshot: snapshot::snapshot(),
-            module_loader: Some(Rc::new(TypescriptModuleLoader::default())),
-            ..Default::default()
-        };
-
-        let mut js_runtime = JsRuntime::new(runtime_opts);
-
-        // Bootstrapping Stage
-        {
-            let init_params = json!({
-                "repl": context.is_repl()
-            });
-            let script = format!("globalThis.bootstrap({})", init_params);
-            js_runtime
-                .execute_script(located_script_name!(), ModuleCodeString::from(script))
-                .expect("Failed to execute bootstrap script");
-        }
+        let mut js_runtime = Self::create_js_runtime(&context);
 
         let table_helpers = Arc::new(SjsTableHelpers(DashMap::new()));
 
+        // Changes made while loading are picked up by the first reload.
+        let schema_generation = context.schema_changes.generation();
+
         {
-            Self::load(context.clone(), table_helpers.clone(), &mut js_runtime)
-                .await
-                .unwrap();
+            Self::load(context.clone(), table_helpers.clone(), &mut js_runtime).await?;
         }
 
         {
@@ -102,11 +90,12 @@ impl SchemeJsRuntime {
             let op_state_rc = js_runtime.op_state();
             let mut op_state = op_state_rc.borrow_mut();
             op_state.put::<Arc<RwLock<SchemeJsEngine>>>(context.engine.clone());
+            op_state.put::<Arc<SjsTableHelpers>>(table_helpers.clone());
         }
 
         {
             if !context.is_loaded() {
-                context.internal_manager.init();
+                context.internal_manager.init()?;
             }
         }
 
@@ -121,6 +110,10 @@ impl SchemeJsRuntime {
                     task_manager.add_task(task);
                 }
 
+                for task in get_scheduled_tasks(&table_helpers) {
+                    task_manager.add_task(task);
+                }
+
                 task_manager.start_tasks();
             }
         }
@@ -132,9 +125,71 @@ impl SchemeJsRuntime {
             ctx: context,
             table_helpers,
             busy: AtomicBool::new(false),
+            served_calls: 0,
+            recycle: false,
+            helper_limits: HelperLimits::default(),
+            schema_generation,
+            table_reloads: 0,
         })
     }
 
+    /// Creates a bootstrapped V8 runtime that loads modules the way `context` says to.
+    fn create_js_runtime(context: &SjsContext) -> JsRuntime {
+        let extensions: Vec<Extension> = vec![
+            schemajs_primitives::sjs_primitives::init_ops(),
+            schemajs_core::sjs_core::init_ops(),
+            schemajs_engine::sjs_engine::init_ops(),
+            schemajs_helpers::sjs_helpers::init_ops(),
+            schemajs_repl::sjs_repl::init_ops(),
+        ];
+
+        let runtime_opts = RuntimeOptions {
+            extensions,
+            is_main: true,
+            shared_array_buffer_store: None,
+            compiled_wasm_module_store: None,
+            startup_snapshot: snapshot::snapshot(),
+            module_loader: Some(Rc::new(context.module_loader())),
+            ..Default::default()
+        };
+
+        let mut js_runtime = JsRuntime::new(runtime_opts);
+
+        // Bootstrapping Stage
+        {
+            let init_params = json!({
+                "repl": context.is_repl()
+            });
+            let script = format!("globalThis.bootstrap({})", init_params);
+            js_runtime
+                .execute_script(located_script_name!(), ModuleCodeString::from(script))
+                .expect("Failed to execute bootstrap script");
+        }
+
+        js_runtime
+    }
+
+    /// Evaluates the table modules of every database without registering them in the engine,
+    /// which is enough to inspect their definitions.
+    pub async fn load_table_definitions(
+        context: Arc<SjsContext>,
+    ) -> Result<Vec<(String, Vec<Table>)>> {
+        let mut js_runtime = Self::create_js_runtime(&context);
+        let mut databases = vec![];
+
+        for (scheme_name, table_specifiers) in context.table_modules()? {
+            let mut tables = vec![];
+            for table_specifier in table_specifiers {
+                let (_, _, tbl, _) = Self::load_table(&mut js_runtime, table_specifier).await?;
+                tables.push(tbl);
+            }
+
+            databases.push((scheme_name, tables));
+        }
+
+        Ok(databases)
+    }
+
     pub async fn load(
         ctx: Arc<SjsContext>,
         helpers: Arc<SjsTableHelpers>,
@@ -143,24 +198,12 @@ impl SchemeJsRuntime {
         let engine_arc = ctx.engine.clone();
         let mut engine = engine_arc.write();
 
-        let conf = ctx.config.clone();
-        let current_folder = ctx.current_folder.clone();
-
-        let def_scheme_name = conf.global.default_scheme.clone();
-        let mut databases = conf.workspace.databases.clone();
-        databases.push(def_scheme_name.clone());
-        let mut evaluated_paths = HashSet::new();
-
-        for database_path in databases {
-            let path = current_folder.join(&database_path);
-
-            if evaluated_paths.contains(&path) {
-                continue;
-            } else {
-                evaluated_paths.insert(path.clone());
-            }
+        let schemas = ctx.table_modules()?;
+        for (scheme_name, _) in &schemas {
+            engine.add_database(scheme_name);
+        }
 
-            let (scheme_name, table_specifiers) = engine.load_database_schema(&path)?;
+        for (scheme_name, table_specifiers) in schemas {
             let db_helpers = helpers
                 .0
                 .entry(scheme_name.clone())
@@ -169,14 +212,14 @@ impl SchemeJsRuntime {
             let mut tables = vec![];
             for table_specifier in table_specifiers {
                 let (_, _, tbl, tbl_helpers) =
-                    Self::load_table(js_runtime, table_specifier).await.unwrap();
+                    Self::load_table(js_runtime, table_specifier).await?;
 
                 db_helpers.insert(tbl.name.clone(), SjsHelpersContainer::new(tbl_helpers));
                 tables.push(tbl);
             }
 
             if !ctx.is_loaded() {
-                engine.register_tables(scheme_name.as_str(), tables);
+                engine.register_tables(scheme_name.as_str(), tables)?;
             }
         }
 
@@ -295,13 +338,121 @@ impl SchemeJsRuntime {
         let (mut table, helpers) = res;
 
         table.init();
+        table.compile_query_schemas()?;
+
+        table.metadata.before_insert = helpers
+            .iter()
+            .any(|helper| helper.internal_type.is_before_insert_hook());
 
         table.metadata.set_module_id(mod_id);
 
         Ok((specifier, mod_id, table, helpers))
     }
 
+    /// Re-evaluates the table modules that changed since this runtime last loaded them, along
+    /// with the local modules they import.
+    ///
+    /// Whether a new table definition is compatible is decided once, by the first runtime that
+    /// evaluates it, which also applies it to the engine. Every runtime then swaps in the helpers
+    /// of the new module, unless the definition was refused.
+    pub async fn reload_changed_tables(&mut self) {
+        let (generation, changes) = self.ctx.schema_changes.since(self.schema_generation);
+        self.schema_generation = generation;
+
+        for change in changes {
+            if change.accepted.get() == Some(&false) {
+                continue;
+            }
+
+            // A module is only evaluated once per specifier. The module loader gives the version
+            // to the local modules the table imports as well.
+            let mut specifier = change.specifier.clone();
+            specifier.set_query(Some(&format!("v={}", change.generation)));
+
+            self.table_reloads += 1;
+            if self.table_reloads >= MAX_TABLE_RELOADS {
+                self.recycle = true;
+            }
+
+            let (table, helpers) = match Self::load_table(&mut self.js_runtime, specifier).await {
+                Ok((_, _, table, helpers)) => (table, helpers),
+                Err(e) => {
+                    println!("[Hot Reload] Could not load {}: {}", change.specifier, e);
+                    continue;
+                }
+            };
+
+            let table_name = table.name.clone();
+            // Tasks are shared between runtimes, so only the runtime applying the change
+            // schedules them again.
+            let accepted = *change.accepted.get_or_init(|| {
+                let accepted = self.apply_table_change(&change.scheme, table);
+                if accepted {
+                    self.reschedule_table(&change.scheme, &table_name, &helpers);
+                }
+                accepted
+            });
+
+            if accepted {
+                self.table_helpers
+                    .0
+                    .entry(change.scheme.clone())
+                    .or_insert_with(|| DashMap::default())
+                    .insert(table_name, SjsHelpersContainer::new(helpers));
+            }
+        }
+    }
+
+    /// Replaces the scheduled tasks of a table with the ones its reloaded helpers declare.
+    fn reschedule_table(&self, scheme: &str, table_name: &str, helpers: &[Arc<Helper>]) {
+        self.ctx.task_manager.write().replace_tasks(
+            &scheduled_task_prefix(scheme, table_name),
+            get_table_scheduled_tasks(scheme, table_name, helpers),
+        );
+    }
+
+    fn apply_table_change(&self, scheme: &str, mut table: Table) -> bool {
+        let engine = self.ctx.engine.read();
+        let Some(db) = engine.find_by_name_ref(scheme) else {
+            println!("[Hot Reload] Unknown database '{}'", scheme);
+            return false;
+        };
+
+        // The row format comes from the database, so it is compared with the one the table
+        // would be given.
+        table.metadata.row_format = self.ctx.config.db_config(scheme).row_format;
+        if let Some(current) = db.query_manager.get_table(&table.name) {
+            let changes = incompatible_changes(&current, &table);
+            if !changes.is_empty() {
+                println!(
+                    "[Hot Reload] Refusing incompatible changes to table '{}.{}', restart to apply them:\n{}",
+                    scheme,
+                    table.name,
+                    changes.join("\n")
+                );
+                return false;
+            }
+        }
+
+        let name = table.name.clone();
+        if let Err(e) = db.query_manager.update_table(table) {
+            println!(
+                "[Hot Reload] Could not reload table '{}.{}': {}",
+                scheme, name, e
+            );
+            return false;
+        }
+
+        println!("[Hot Reload] Reloaded table '{}.{}'", scheme, name);
+        true
+    }
+
     pub async fn call_helper(&mut self, helper_call: HelperCall) {
+        self.helper_limits = match &helper_call.db_ctx().db {
+            Some(db) => HelperLimits::from(&self.ctx.config.db_config(db)),
+            None => HelperLimits::default(),
+        };
+
         match helper_call {
             HelperCall::CustomQuery {
                 identifier,
@@ -312,30 +463,119 @@ impl SchemeJsRuntime {
                 self.set_db_context(&db_ctx);
 
                 let helper = self.table_helpers.find_custom_query_helper(
-                    &db_ctx.db.unwrap(),
-                    &db_ctx.table.unwrap(),
+                    db_ctx.db.as_deref().unwrap(),
+                    db_ctx.table.as_deref().unwrap(),
                     &identifier,
                 );
 
-                self.execute_helper(&req, Some(response), helper).await;
+                // Queries get their context as an argument, like when called with `SchemaJS.query`.
+                let ctx = json!({ "dbName": db_ctx.db, "tblName": db_ctx.table });
+                self.execute_helper(&[req, ctx], Some(response), helper)
+                    .await;
             }
             HelperCall::InsertHook { rows, db_ctx } => {
+                self.call_hook(HelperType::InsertHook, rows, db_ctx).await;
+            }
+            HelperCall::AfterReconcileHook { rows, db_ctx } => {
+                self.call_hook(HelperType::AfterReconcileHook, rows, db_ctx)
+                    .await;
+            }
+            HelperCall::Scheduled { db_ctx, identifier } => {
                 self.set_db_context(&db_ctx);
 
-                let helper = self.table_helpers.find_hook_helper(
-                    &db_ctx.db.unwrap(),
-                    &db_ctx.table.unwrap(),
-                    HelperType::InsertHook,
-                );
-                let arr_to_val = serde_json::to_value(rows);
-                if let Ok(val) = arr_to_val {
-                    if let Some(helpers) = helper {
-                        for single_helper in helpers {
-                            self.execute_helper(&val, None, Some(single_helper)).await
-                        }
+                let helpers = self
+                    .table_helpers
+                    .find_hook_helper(
+                        &db_ctx.db.unwrap(),
+                        &db_ctx.table.unwrap(),
+                        HelperType::Scheduled,
+                    )
+                    .unwrap_or_default();
+
+                for helper in helpers {
+                    if helper.identifier == identifier {
+                        self.execute_helper(&[serde_json::Value::Null], None, Some(helper))
+                            .await;
                     }
                 }
             }
+            HelperCall::BeforeInsertHook {
+                rows,
+                db_ctx,
+                response,
+            } => {
+                self.set_db_context(&db_ctx);
+
+                let helpers = self
+                    .table_helpers
+                    .find_hook_helper(
+                        &db_ctx.db.unwrap(),
+                        &db_ctx.table.unwrap(),
+                        HelperType::BeforeInsertHook,
+                    )
+                    .unwrap_or_default();
+
+                self.js_runtime
+                    .op_state()
+                    .borrow_mut()
+                    .put(BeforeInsertHookScope);
+                let rows = self.run_before_insert_hooks(rows, helpers).await;
+                self.js_runtime
+                    .op_state()
+                    .borrow_mut()
+                    .try_take::<BeforeInsertHookScope>();
+
+                let _ = response.send(rows);
+            }
+        }
+    }
+
+    /// Runs `beforeInsert` hooks in order, each one receiving the rows returned by the previous.
+    async fn run_before_insert_hooks(
+        &mut self,
+        rows: Vec<serde_json::Value>,
+        helpers: Vec<Arc<Helper>>,
+    ) -> Result<Vec<serde_json::Value>, String> {
+        let mut rows = serde_json::Value::Array(rows);
+        for helper in helpers {
+            // A hook may return the rows to insert, nothing to keep them as they are,
+            // or `false` (or throw) to reject the whole insertion.
+            match self.run_helper(std::slice::from_ref(&rows), &helper).await {
+                Ok(serde_json::Value::Null) => {}
+                Ok(serde_json::Value::Bool(false)) => {
+                    return Err("Rows rejected by beforeInsert hook".to_string());
+                }
+                Ok(new_rows @ serde_json::Value::Array(_)) => rows = new_rows,
+                Ok(_) => return Err("beforeInsert hook must return an array of rows".to_string()),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        match rows {
+            serde_json::Value::Array(rows) => Ok(rows),
+            _ => Ok(vec![]),
+        }
+    }
+
+    async fn call_hook(
+        &mut self,
+        hook: HelperType,
+        rows: Vec<serde_json::Value>,
+        db_ctx: HelperDbContext,
+    ) {
+        self.set_db_context(&db_ctx);
+
+        let helper =
+            self.table_helpers
+                .find_hook_helper(&db_ctx.db.unwrap(), &db_ctx.table.unwrap(), hook);
+        let arr_to_val = serde_json::to_value(rows);
+        if let Ok(val) = arr_to_val {
+            if let Some(helpers) = helper {
+                for single_helper in helpers {
+                    self.execute_helper(std::slice::from_ref(&val), None, Some(single_helper))
+                        .await
+                }
+            }
         }
     }
 
@@ -356,43 +596,87 @@ impl SchemeJsRuntime {
 
     async fn execute_helper(
         &mut self,
-        req: &serde_json::Value,
+        args: &[serde_json::Value],
         response: Option<UnboundedSender<serde_json::Value>>,
         helper: Option<Arc<Helper>>,
     ) {
         if let Some(helper) = helper {
-            let req = {
-                let scope = &mut self.js_runtime.handle_scope();
-                serde_v8::to_v8(scope, req).map(|e| v8::Global::new(scope, e))
-            };
-            match req {
-                Ok(req_val) => {
-                    let call = self.js_runtime.call_with_args(&helper.func, &[req_val]);
-                    let result = self
-                        .js_runtime
-                        .with_event_loop_promise(call, PollEventLoopOptions::default())
-                        .await;
-                    match result {
-                        Ok(res) => {
-                            let scope = &mut self.js_runtime.handle_scope();
-                            let local = v8::Local::new(scope, res);
-                            let to_val = serde_v8::from_v8::<serde_json::Value>(scope, local);
-                            let to_val = to_val.ok().unwrap_or_else(|| serde_json::Value::Null);
-                            if let Some(response) = response {
-                                let _ = response.send(to_val);
-                            }
-                        }
-                        Err(e) => {
-                            println!("{:?}", e);
-                            if let Some(response) = response {
-                                let _ = response.send(serde_json::Value::Null);
-                            }
-                        }
+            match self.run_helper(args, &helper).await {
+                Ok(res) => {
+                    if let Some(response) = response {
+                        let _ = response.send(res);
                     }
                 }
-                Err(_) => {}
+                Err(e) => {
+                    println!("{:?}", e);
+                    if let Some(response) = response {
+                        let _ = response.send(serde_json::Value::Null);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn run_helper(
+        &mut self,
+        args: &[serde_json::Value],
+        helper: &Helper,
+    ) -> Result<serde_json::Value> {
+        let args = {
+            let scope = &mut self.js_runtime.handle_scope();
+            args.iter()
+                .map(|arg| serde_v8::to_v8(scope, arg).map(|e| v8::Global::new(scope, e)))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        self.js_runtime
+            .op_state()
+            .borrow_mut()
+            .put(OpBudget::new(self.helper_limits.max_ops));
+        let watchdog = ExecutionWatchdog::start(self.js_runtime.v8_isolate(), &self.helper_limits);
+
+        let call = self.js_runtime.call_with_args(&helper.func, &args);
+        let res = self
+            .js_runtime
+            .with_event_loop_promise(call, PollEventLoopOptions::default())
+            .await;
+
+        if let Some(exceeded) = watchdog.stop(self.js_runtime.v8_isolate()) {
+            // A terminated call may leave pending work behind, the runtime is not reused.
+            self.recycle = true;
+            match exceeded {
+                LimitExceeded::Timeout => bail!("Helper call timed out"),
+                LimitExceeded::Heap => bail!("Helper call exceeded its heap limit"),
             }
         }
+
+        let scope = &mut self.js_runtime.handle_scope();
+        let local = v8::Local::new(scope, res?);
+        let to_val = serde_v8::from_v8::<serde_json::Value>(scope, local);
+        Ok(to_val.ok().unwrap_or_else(|| serde_json::Value::Null))
+    }
+
+    /// Records a served helper call and flags the runtime for recycling once it reaches
+    /// `max_runtime_calls` or `max_runtime_heap_size`. Must be called while the isolate is entered.
+    pub fn track_usage(&mut self) -> bool {
+        self.served_calls += 1;
+
+        let process = &self.ctx.config.process;
+        let heap_size = {
+            let mut stats = v8::HeapStatistics::default();
+            self.js_runtime.v8_isolate().get_heap_statistics(&mut stats);
+            stats.used_heap_size()
+        };
+
+        self.recycle = self.recycle
+            || (process.max_runtime_calls > 0 && self.served_calls >= process.max_runtime_calls)
+            || (process.max_runtime_heap_size > 0 && heap_size >= process.max_runtime_heap_size);
+
+        self.recycle
+    }
+
+    pub fn should_recycle(&self) -> bool {
+        self.recycle
     }
 
     // Method to release the lock
@@ -419,18 +703,35 @@ impl SchemeJsRuntime {
     }
 }
 
+/// Copies a project of `test_cases` into a temporary folder, so tests can change it.
+#[cfg(test)]
+pub(crate) fn copy_test_project(path: &str) -> tempfile::TempDir {
+    let folder = tempfile::tempdir().unwrap();
+    for entry in walkdir::WalkDir::new(path) {
+        let entry = entry.unwrap();
+        let target = folder.path().join(entry.path().strip_prefix(path).unwrap());
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target).unwrap();
+        } else {
+            std::fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+
+    folder
+}
+
 #[cfg(test)]
 mod test {
     use crate::context::context::SjsContext;
     use crate::manager::task::{Task, TaskCallback};
     use crate::manager::task_duration::TaskDuration;
     use crate::manager::SchemeJsManager;
-    use crate::runtime::SchemeJsRuntime;
-    use deno_core::{located_script_name, serde_json, v8};
+    use crate::runtime::{copy_test_project, SchemeJsRuntime};
+    use deno_core::{located_script_name, serde_json, v8, ModuleSpecifier};
     use schemajs_helpers::create_helper_channel;
     use schemajs_helpers::helper::{Helper, HelperCall, HelperDbContext, HelperType};
+    use schemajs_query::db_row::DbRow;
     use schemajs_query::row::Row;
-    use schemajs_query::row_json::RowJson;
     use serde_json::json;
     use std::path::PathBuf;
     use std::sync::Arc;
@@ -657,7 +958,7 @@ mod test {
 
             let engine = context.engine.read();
             let db = engine.find_by_name_ref("public").unwrap();
-            let mut row = RowJson::from_json(
+            let mut row = DbRow::from_json(
                 serde_json::json!({
                     "id": "999",
                     "username": "Luis",
@@ -691,6 +992,187 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    pub async fn test_custom_query_from_js() {
+        let (tx, rx) = create_helper_channel(1);
+        let context =
+            Arc::new(SjsContext::new(PathBuf::from("./test_cases/default-db"), None, tx).unwrap());
+        let mut rt = SchemeJsRuntime::new(context.clone()).await.unwrap();
+
+        let mut unbounded = unbounded_channel();
+        let helper_call = HelperCall::CustomQuery {
+            identifier: "composedEcho".to_string(),
+            req: json!({ "hello": "world" }),
+            db_ctx: HelperDbContext {
+                db: Some("public".to_string()),
+                table: Some("users".to_string()),
+            },
+            response: unbounded.0.clone(),
+        };
+        rt.call_helper(helper_call).await;
+
+        let val = unbounded.1.recv().await.unwrap();
+        assert_eq!(val, json!({ "hello": "world" }));
+    }
+
+    #[tokio::test]
+    pub async fn test_custom_query_context_and_schemas_from_js() {
+        let (tx, rx) = create_helper_channel(1);
+        let context =
+            Arc::new(SjsContext::new(PathBuf::from("./test_cases/default-db"), None, tx).unwrap());
+        let mut rt = SchemeJsRuntime::new(context.clone()).await.unwrap();
+
+        let call = |identifier: &str, req: serde_json::Value| {
+            let (response, rx) = unbounded_channel();
+            let call = HelperCall::CustomQuery {
+                identifier: identifier.to_string(),
+                req,
+                db_ctx: HelperDbContext {
+                    db: Some("public".to_string()),
+                    table: Some("users".to_string()),
+                },
+                response,
+            };
+            (call, rx)
+        };
+
+        // The nested query gets its own context without changing the one of the caller.
+        let (helper_call, mut response) = call("composedContext", json!({}));
+        rt.call_helper(helper_call).await;
+        assert_eq!(
+            response.recv().await.unwrap(),
+            json!({
+                "inner": { "dbName": "public", "tblName": "products" },
+                "outer": { "dbName": "public", "tblName": "users" },
+                "global": "users"
+            })
+        );
+
+        let (helper_call, mut response) = call("composedTypedEcho", json!({ "username": "Luis" }));
+        rt.call_helper(helper_call).await;
+        assert_eq!(
+            response.recv().await.unwrap(),
+            json!({ "username": "Luis" })
+        );
+
+        let (helper_call, mut response) = call("composedTypedEcho", json!({ "username": 1 }));
+        rt.call_helper(helper_call).await;
+        let err = response.recv().await.unwrap();
+        assert!(err
+            .as_str()
+            .unwrap()
+            .contains("Invalid arguments for custom query 'typedEcho'"));
+
+        let (helper_call, mut response) =
+            call("composedTypedEcho", json!({ "username": "invalid" }));
+        rt.call_helper(helper_call).await;
+        let err = response.recv().await.unwrap();
+        assert!(err
+            .as_str()
+            .unwrap()
+            .contains("Custom query 'typedEcho' returned an invalid result"));
+    }
+
+    #[tokio::test]
+    pub async fn test_before_insert_hook() {
+        let (tx, rx) = create_helper_channel(1);
+        let context =
+            Arc::new(SjsContext::new(PathBuf::from("./test_cases/default-db"), None, tx).unwrap());
+        let mut rt = SchemeJsRuntime::new(context.clone()).await.unwrap();
+
+        let before_insert = |rows: serde_json::Value| {
+            let (response, rx) = unbounded_channel();
+            let call = HelperCall::BeforeInsertHook {
+                db_ctx: HelperDbContext {
+                    db: Some("public".to_string()),
+                    table: Some("orders".to_string()),
+                },
+                rows: vec![rows],
+                response,
+            };
+            (call, rx)
+        };
+
+        // Inserting into a table without beforeInsert hooks from a hook is allowed.
+        let (call, mut response) = before_insert(json!({ "id": "audited" }));
+        rt.call_helper(call).await;
+        let rows = response.recv().await.unwrap().unwrap();
+        assert_eq!(rows, vec![json!({ "id": "audited", "status": "pending" })]);
+
+        // Waiting for the hooks of its own table would never return, the insert is rejected.
+        let (call, mut response) = before_insert(json!({ "id": "nested" }));
+        rt.call_helper(call).await;
+        let err = response.recv().await.unwrap().unwrap_err();
+        assert!(err.contains("cannot insert into table 'orders'"));
+    }
+
+    #[tokio::test]
+    pub async fn test_reload_reschedules_tasks() {
+        let folder = copy_test_project("./test_cases/default-db");
+
+        let (tx, _rx) = create_helper_channel(1);
+        let context = Arc::new(SjsContext::new(folder.path().to_path_buf(), None, tx).unwrap());
+        let mut rt = SchemeJsRuntime::new(context.clone()).await.unwrap();
+        assert!(context
+            .task_manager
+            .read()
+            .has_task("public.orders.0 0 1 1 *"));
+
+        let orders = folder.path().join("public/tables/orders.ts");
+        let source = std::fs::read_to_string(&orders).unwrap();
+        std::fs::write(&orders, source.replace("0 0 1 1 *", "0 0 2 1 *")).unwrap();
+        context.schema_changes.push(
+            "public".to_string(),
+            ModuleSpecifier::from_file_path(&orders).unwrap(),
+        );
+        rt.reload_changed_tables().await;
+
+        let task_manager = context.task_manager.read();
+        assert!(!task_manager.has_task("public.orders.0 0 1 1 *"));
+        assert!(task_manager.has_task("public.orders.0 0 2 1 *"));
+        task_manager.stop_tasks();
+    }
+
+    #[tokio::test]
+    pub async fn test_reload_follows_imports() {
+        let folder = copy_test_project("./test_cases/default-db");
+
+        // The schedule of `orders` comes from a module outside of the tables folder.
+        let orders = folder.path().join("public/tables/orders.ts");
+        let schedule = folder.path().join("public/lib/schedule.ts");
+        std::fs::create_dir_all(schedule.parent().unwrap()).unwrap();
+        std::fs::write(&schedule, r#"export const SCHEDULE = "0 0 1 1 *";"#).unwrap();
+        let source = std::fs::read_to_string(&orders).unwrap();
+        std::fs::write(
+            &orders,
+            format!(
+                "import {{ SCHEDULE }} from \"../lib/schedule.ts\";\n{}",
+                source.replace(r#""0 0 1 1 *""#, "SCHEDULE")
+            ),
+        )
+        .unwrap();
+
+        let (tx, _rx) = create_helper_channel(1);
+        let context = Arc::new(SjsContext::new(folder.path().to_path_buf(), None, tx).unwrap());
+        let mut rt = SchemeJsRuntime::new(context.clone()).await.unwrap();
+        assert!(context
+            .task_manager
+            .read()
+            .has_task("public.orders.0 0 1 1 *"));
+
+        std::fs::write(&schedule, r#"export const SCHEDULE = "0 0 2 1 *";"#).unwrap();
+        context.schema_changes.push(
+            "public".to_string(),
+            ModuleSpecifier::from_file_path(&orders).unwrap(),
+        );
+        rt.reload_changed_tables().await;
+
+        let task_manager = context.task_manager.read();
+        assert!(!task_manager.has_task("public.orders.0 0 1 1 *"));
+        assert!(task_manager.has_task("public.orders.0 0 2 1 *"));
+        task_manager.stop_tasks();
+    }
+
     #[tokio::test]
     pub async fn test_runtime_insert_with_manager() -> anyhow::Result<()> {
         let (tx, rx) = create_helper_channel(1);
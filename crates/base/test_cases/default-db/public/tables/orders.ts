@@ -0,0 +1,17 @@
+export default function main() {
+    const { Table, Column, rawInsert } = SchemaJS;
+    return new Table("orders")
+        .addColumn(new Column("id").string())
+        .addColumn(new Column("status").string())
+        .on("beforeInsert", async (rows) => {
+            for (const row of rows) {
+                if (row.id === "audited") {
+                    await rawInsert("public", "users", { id: row.id });
+                } else if (row.id === "nested") {
+                    await rawInsert("public", "orders", { id: row.id });
+                }
+            }
+            return rows.map((row) => ({ ...row, status: "pending" }));
+        })
+        .schedule("0 0 1 1 *", () => {})
+}
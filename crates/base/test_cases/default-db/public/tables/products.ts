@@ -2,4 +2,5 @@ export default function main() {
     const { Table, Column } = SchemaJS;
     return new Table("products")
         .addColumn(new Column("id").string())
+        .addQuery("context", (req, ctx) => ctx)
 }
\ No newline at end of file
@@ -12,4 +12,23 @@ export default function main() {
             return a;
         })
         .addQuery("helloWorld", (req) => { print(JSON.stringify(req)); })
+        .addQuery("echo", (req) => req)
+        .addQuery("composedEcho", async (req) => await query("public", "users", "echo", req))
+        .addQuery("context", (req, ctx) => ctx)
+        .addQuery("composedContext", async (req, ctx) => ({
+            inner: await query("public", "products", "context", req),
+            outer: ctx,
+            global: globalThis.SJS_CONTEXT.tblName
+        }))
+        .addQuery("typedEcho", (req) => req.username === "invalid" ? { username: 1 } : req, {
+            input: { username: new Column("username").string().require(true) },
+            output: { username: new Column("username").string() }
+        })
+        .addQuery("composedTypedEcho", async (req) => {
+            try {
+                return await query("public", "users", "typedEcho", req);
+            } catch (e) {
+                return e.message;
+            }
+        })
 }
\ No newline at end of file
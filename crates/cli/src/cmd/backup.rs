@@ -0,0 +1,53 @@
+use crate::cmd::remote::{connect_client, TlsOpts};
+use colored::Colorize;
+use schemajs_grpc::client::BackupRequest;
+
+pub(crate) struct BackupOpts {
+    pub(crate) ip: String,
+    pub(crate) database: String,
+    pub(crate) username: String,
+    pub(crate) password: String,
+    pub(crate) output: String,
+    pub(crate) all: bool,
+    pub(crate) tls: TlsOpts,
+}
+
+pub(crate) async fn backup_cmd(opts: BackupOpts) {
+    let BackupOpts {
+        ip,
+        database,
+        username,
+        password,
+        output,
+        all,
+        tls,
+    } = opts;
+
+    let Some(client) = connect_client(ip, database.clone(), username, password, tls).await else {
+        return;
+    };
+
+    println!("[{}] Backing up to {:?}", "Info".yellow(), output);
+
+    let backup = client
+        .backup(BackupRequest {
+            destination: output,
+            database: if all { None } else { Some(database) },
+        })
+        .await;
+
+    match backup {
+        Ok(backup) => println!(
+            "[{}] {} file(s) of {} backed up to {:?}",
+            "Success".green(),
+            backup.files,
+            backup.databases.join(", "),
+            backup.destination
+        ),
+        Err(err) => eprintln!(
+            "[{}] Backup failed. Error: {}",
+            "Error".red(),
+            err.message()
+        ),
+    }
+}
@@ -0,0 +1,50 @@
+use base::bundle::bundle_project;
+use colored::Colorize;
+use std::path::PathBuf;
+
+pub(crate) struct BundleOpts {
+    pub(crate) config_file: String,
+    pub(crate) output: String,
+}
+
+pub(crate) async fn bundle_cmd(opts: BundleOpts) {
+    let BundleOpts {
+        config_file,
+        output,
+    } = opts;
+
+    println!("[{}] Bundling {:?}", "Info".yellow(), config_file);
+
+    let bytes = match bundle_project(PathBuf::from(&config_file), None).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            eprintln!(
+                "[{}] Project could not be bundled. Error: {:?}",
+                "Error".red(),
+                err
+            );
+            return;
+        }
+    };
+
+    if let Err(err) = std::fs::write(&output, bytes) {
+        eprintln!(
+            "[{}] Bundle could not be written to {:?}. Error: {:?}",
+            "Error".red(),
+            output,
+            err
+        );
+        return;
+    }
+
+    println!();
+    println!("[{}] Bundle written to {:?}", "Success".green(), output);
+    println!();
+
+    println!("To start the server from it, run:");
+    println!(
+        "  schemajs start --config {:?} --bundle {:?}",
+        config_file, output
+    );
+    println!();
+}
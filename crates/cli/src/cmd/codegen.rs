@@ -0,0 +1,39 @@
+use base::codegen::codegen_project;
+use colored::Colorize;
+use std::path::PathBuf;
+
+pub(crate) struct CodegenOpts {
+    pub(crate) config_file: String,
+    pub(crate) output: String,
+}
+
+pub(crate) async fn codegen_cmd(opts: CodegenOpts) {
+    let CodegenOpts {
+        config_file,
+        output,
+    } = opts;
+
+    let typings = match codegen_project(PathBuf::from(&config_file)).await {
+        Ok(typings) => typings,
+        Err(err) => {
+            eprintln!(
+                "[{}] Typings could not be generated. Error: {:?}",
+                "Error".red(),
+                err
+            );
+            return;
+        }
+    };
+
+    if let Err(err) = std::fs::write(&output, typings) {
+        eprintln!(
+            "[{}] Typings could not be written to {:?}. Error: {:?}",
+            "Error".red(),
+            output,
+            err
+        );
+        return;
+    }
+
+    println!("[{}] Typings written to {:?}", "Success".green(), output);
+}
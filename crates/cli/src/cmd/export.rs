@@ -0,0 +1,108 @@
+use crate::cmd::remote::{connect_client, transfer_format, TlsOpts};
+use colored::Colorize;
+use schemajs_grpc::client::ExportTableRequest;
+use tokio::io::AsyncWriteExt;
+
+pub(crate) struct ExportOpts {
+    pub(crate) ip: String,
+    pub(crate) database: String,
+    pub(crate) username: String,
+    pub(crate) password: String,
+    pub(crate) table: String,
+    pub(crate) output: String,
+    pub(crate) format: Option<String>,
+    pub(crate) tls: TlsOpts,
+}
+
+pub(crate) async fn export_cmd(opts: ExportOpts) {
+    let ExportOpts {
+        ip,
+        database,
+        username,
+        password,
+        table,
+        output,
+        format,
+        tls,
+    } = opts;
+
+    let Some(format) = transfer_format(format, &output) else {
+        eprintln!(
+            "[{}] Unknown format, pass --format ndjson, --format csv or --format parquet",
+            "Error".red()
+        );
+        return;
+    };
+
+    let Some(client) = connect_client(ip, database.clone(), username, password, tls).await else {
+        return;
+    };
+
+    let mut stream = match client
+        .export_table(ExportTableRequest {
+            table_name: table.clone(),
+            format: format as i32,
+            database: Some(database),
+        })
+        .await
+    {
+        Ok(stream) => stream,
+        Err(err) => {
+            eprintln!(
+                "[{}] Export failed. Error: {}",
+                "Error".red(),
+                err.message()
+            );
+            return;
+        }
+    };
+
+    let mut file = match tokio::fs::File::create(&output).await {
+        Ok(file) => file,
+        Err(err) => {
+            eprintln!(
+                "[{}] Could not create {:?}. Error: {}",
+                "Error".red(),
+                output,
+                err
+            );
+            return;
+        }
+    };
+
+    println!(
+        "[{}] Exporting '{}' to {:?}",
+        "Info".yellow(),
+        table,
+        output
+    );
+
+    let mut written = 0;
+    let result = loop {
+        match stream.message().await {
+            Ok(Some(chunk)) => {
+                if let Err(err) = file.write_all(&chunk.data).await {
+                    break Err(err.to_string());
+                }
+                written += chunk.data.len();
+            }
+            Ok(None) => break file.flush().await.map_err(|err| err.to_string()),
+            Err(err) => break Err(err.message().to_string()),
+        }
+    };
+
+    match result {
+        Ok(()) => println!(
+            "[{}] {} byte(s) of '{}' exported to {:?}",
+            "Success".green(),
+            written,
+            table,
+            output
+        ),
+        Err(err) => {
+            // A partial export would look like a complete one.
+            let _ = tokio::fs::remove_file(&output).await;
+            eprintln!("[{}] Export failed. Error: {}", "Error".red(), err);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::cmd::remote::{connect_client, transfer_format, TlsOpts};
+use colored::Colorize;
+
+pub(crate) struct ImportOpts {
+    pub(crate) ip: String,
+    pub(crate) database: String,
+    pub(crate) username: String,
+    pub(crate) password: String,
+    pub(crate) table: String,
+    pub(crate) input: String,
+    pub(crate) format: Option<String>,
+    pub(crate) tls: TlsOpts,
+}
+
+pub(crate) async fn import_cmd(opts: ImportOpts) {
+    let ImportOpts {
+        ip,
+        database,
+        username,
+        password,
+        table,
+        input,
+        format,
+        tls,
+    } = opts;
+
+    let Some(format) = transfer_format(format, &input) else {
+        eprintln!(
+            "[{}] Unknown format, pass --format ndjson, --format csv or --format parquet",
+            "Error".red()
+        );
+        return;
+    };
+
+    let file = match tokio::fs::File::open(&input).await {
+        Ok(file) => file,
+        Err(err) => {
+            eprintln!(
+                "[{}] Could not open {:?}. Error: {}",
+                "Error".red(),
+                input,
+                err
+            );
+            return;
+        }
+    };
+
+    let Some(client) = connect_client(ip, database.clone(), username, password, tls).await else {
+        return;
+    };
+
+    println!(
+        "[{}] Importing {:?} into '{}'",
+        "Info".yellow(),
+        input,
+        table
+    );
+
+    match client
+        .import_table(table.clone(), format, Some(database), file)
+        .await
+    {
+        Ok(response) => println!(
+            "[{}] {} row(s) imported into '{}'",
+            "Success".green(),
+            response.rows,
+            table
+        ),
+        Err(err) => eprintln!(
+            "[{}] Import failed. Error: {}",
+            "Error".red(),
+            err.message()
+        ),
+    }
+}
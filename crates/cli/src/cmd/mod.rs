@@ -1,3 +1,12 @@
+pub mod backup;
+pub mod bundle;
+pub mod codegen;
+pub mod export;
+pub mod import;
 pub mod init;
+mod remote;
 mod repl;
+pub mod restore;
 pub mod start;
+pub mod upgrade_data;
+pub mod verify;
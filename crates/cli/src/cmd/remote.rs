@@ -0,0 +1,83 @@
+use colored::Colorize;
+use schemajs_grpc::client::{SjsClient, TransferFormat};
+use schemajs_grpc::utils::tls::load_client_tls_config;
+use std::path::Path;
+
+/// Certificates given through `--ca`, `--cert` and `--key`.
+pub(crate) struct TlsOpts {
+    pub(crate) ca: Option<String>,
+    pub(crate) cert: Option<String>,
+    pub(crate) key: Option<String>,
+}
+
+impl TlsOpts {
+    fn is_set(&self) -> bool {
+        self.ca.is_some() || self.cert.is_some() || self.key.is_some()
+    }
+}
+
+/// Logs in to the SJS server at `ip`, printing why if it fails.
+///
+/// The connection uses TLS when `ip` is an https url or any of `tls` is set, in which case
+/// an `ip` without scheme is taken as https.
+pub(crate) async fn connect_client(
+    ip: String,
+    database: String,
+    username: String,
+    password: String,
+    tls: TlsOpts,
+) -> Option<SjsClient> {
+    let host = if ip.contains("://") {
+        ip
+    } else if tls.is_set() {
+        format!("https://{}", ip)
+    } else {
+        format!("http://{}", ip)
+    };
+
+    let tls_config = if host.starts_with("https://") {
+        let identity = tls.cert.as_deref().zip(tls.key.as_deref());
+        match load_client_tls_config(tls.ca.as_deref(), identity) {
+            Ok(config) => Some(config),
+            Err(err) => {
+                eprintln!(
+                    "[{}] Could not read the TLS certificates. Error: {}",
+                    "Error".red(),
+                    err
+                );
+                return None;
+            }
+        }
+    } else {
+        None
+    };
+
+    match SjsClient::connect(host.clone(), database, username, password, tls_config).await {
+        Ok(client) => Some(client),
+        Err(err) => {
+            eprintln!(
+                "[{}] Could not connect to {}. Error: {}",
+                "Error".red(),
+                host,
+                err
+            );
+            None
+        }
+    }
+}
+
+/// Format given through `--format`, or the one the extension of `file` stands for.
+pub(crate) fn transfer_format(format: Option<String>, file: &str) -> Option<TransferFormat> {
+    let format = format.or_else(|| {
+        Path::new(file)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+    })?;
+
+    match format.as_str() {
+        "ndjson" | "jsonl" => Some(TransferFormat::Ndjson),
+        "csv" => Some(TransferFormat::Csv),
+        "parquet" => Some(TransferFormat::Parquet),
+        _ => None,
+    }
+}
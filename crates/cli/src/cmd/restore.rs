@@ -0,0 +1,48 @@
+use colored::Colorize;
+use schemajs_data::backup::restore_backup;
+use schemajs_dirs::get_base_path;
+use std::path::PathBuf;
+
+pub(crate) struct RestoreOpts {
+    pub(crate) backup: String,
+    pub(crate) data: Option<String>,
+    pub(crate) overwrite: bool,
+}
+
+pub(crate) fn restore_cmd(opts: RestoreOpts) {
+    let RestoreOpts {
+        backup,
+        data,
+        overwrite,
+    } = opts;
+
+    let data_path = get_base_path(data.map(PathBuf::from));
+
+    println!(
+        "[{}] Restoring {:?} into {:?}",
+        "Info".yellow(),
+        backup,
+        data_path
+    );
+
+    match restore_backup(&PathBuf::from(backup), &data_path, overwrite) {
+        Ok(manifest) => {
+            for db in &manifest.databases {
+                println!("[{}] Restored database '{}'", "Info".yellow(), db);
+            }
+
+            println!(
+                "[{}] {} file(s) restored",
+                "Success".green(),
+                manifest.files.len()
+            );
+        }
+        Err(err) => {
+            eprintln!(
+                "[{}] Backup could not be restored. Error: {}",
+                "Error".red(),
+                err
+            );
+        }
+    }
+}
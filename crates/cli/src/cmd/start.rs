@@ -12,6 +12,7 @@ pub(crate) struct StartOpts {
     pub ip: Option<String>,
     pub config_file: String,
     pub repl: bool,
+    pub bundle: Option<String>,
 }
 
 async fn start_server(ip: Option<String>, runner: Arc<SjsRunner>) {
@@ -36,27 +37,44 @@ pub(crate) async fn start(opts: StartOpts) {
         config_file,
         ip,
         repl: no_repl,
+        bundle,
     } = opts;
 
-    let runner = SjsRunner::new(SjsRunnerConfig {
+    let runner = match SjsRunner::new(SjsRunnerConfig {
         max_helper_processing_capacity: 100,
         max_runtimes: 10,
         config_path: PathBuf::from(config_file),
         data_path: None,
-    });
+        bundle: bundle.map(PathBuf::from),
+    }) {
+        Ok(runner) => runner,
+        Err(err) => {
+            eprintln!("[{}] SJS could not be started: {:#}", "Error".red(), err);
+            return;
+        }
+    };
 
     let arc_runner = Arc::new(runner);
 
+    // The server starts right away so health checks report NOT_SERVING while loading.
+    let runner = arc_runner.clone();
+    tokio::spawn(async move { start_server(ip, runner).await });
+
     {
         // Loader runtime
-        let rt = SchemeJsRuntime::new(arc_runner.sjs_context.clone())
-            .await
-            .unwrap();
-        drop(rt);
+        match SchemeJsRuntime::new(arc_runner.sjs_context.clone()).await {
+            Ok(rt) => drop(rt),
+            Err(err) => {
+                eprintln!(
+                    "[{}] Databases could not be loaded: {:#}",
+                    "Error".red(),
+                    err
+                );
+                return;
+            }
+        }
     }
 
-    let runner = arc_runner.clone();
-    tokio::spawn(async move { start_server(ip, runner).await });
     tokio::time::sleep(Duration::from_secs(1)).await;
 
     if !no_repl {
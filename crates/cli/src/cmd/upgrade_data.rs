@@ -0,0 +1,43 @@
+use colored::Colorize;
+use schemajs_data::upgrade::upgrade_data_folder;
+use schemajs_dirs::get_base_path;
+use std::path::PathBuf;
+
+pub(crate) struct UpgradeDataOpts {
+    pub(crate) data: Option<String>,
+}
+
+pub(crate) fn upgrade_data_cmd(opts: UpgradeDataOpts) {
+    let UpgradeDataOpts { data } = opts;
+
+    let data_path = get_base_path(data.map(PathBuf::from));
+    let dbs_path = data_path.join("dbs");
+
+    if !dbs_path.exists() {
+        eprintln!("[{}] No databases found in {:?}", "Error".red(), data_path);
+        return;
+    }
+
+    println!("[{}] Upgrading shards in {:?}", "Info".yellow(), dbs_path);
+
+    match upgrade_data_folder(&dbs_path) {
+        Ok(upgraded) => {
+            for path in &upgraded {
+                println!("[{}] Upgraded {:?}", "Info".yellow(), path);
+            }
+
+            println!(
+                "[{}] {} shard(s) upgraded to the current format",
+                "Success".green(),
+                upgraded.len()
+            );
+        }
+        Err(err) => {
+            eprintln!(
+                "[{}] Shards could not be upgraded. Error: {:?}",
+                "Error".red(),
+                err
+            );
+        }
+    }
+}
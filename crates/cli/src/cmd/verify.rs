@@ -0,0 +1,64 @@
+use colored::Colorize;
+use schemajs_data::verify::verify_data_folder;
+use schemajs_dirs::get_base_path;
+use std::path::PathBuf;
+
+pub(crate) struct VerifyOpts {
+    pub(crate) data: Option<String>,
+}
+
+pub(crate) fn verify_cmd(opts: VerifyOpts) {
+    let VerifyOpts { data } = opts;
+
+    let data_path = get_base_path(data.map(PathBuf::from));
+    let dbs_path = data_path.join("dbs");
+
+    if !dbs_path.exists() {
+        eprintln!("[{}] No databases found in {:?}", "Error".red(), data_path);
+        return;
+    }
+
+    println!("[{}] Verifying shards in {:?}", "Info".yellow(), dbs_path);
+
+    let report = match verify_data_folder(&dbs_path) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!(
+                "[{}] Shards could not be verified. Error: {:?}",
+                "Error".red(),
+                err
+            );
+            return;
+        }
+    };
+
+    for item in &report.corrupted {
+        match item.index {
+            Some(index) => eprintln!(
+                "[{}] Row {} of {:?}: {}",
+                "Corrupted".red(),
+                index,
+                item.path,
+                item.error
+            ),
+            None => eprintln!("[{}] {:?}: {}", "Corrupted".red(), item.path, item.error),
+        }
+    }
+
+    if report.corrupted.is_empty() {
+        println!(
+            "[{}] {} row(s) in {} shard(s) verified",
+            "Success".green(),
+            report.items,
+            report.shards
+        );
+    } else {
+        eprintln!(
+            "[{}] {} corrupted item(s) found, {} row(s) in {} shard(s) verified",
+            "Error".red(),
+            report.corrupted.len(),
+            report.items,
+            report.shards
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use clap::{arg, crate_version, ArgAction, Command};
+use clap::{arg, crate_version, Arg, ArgAction, Command};
 
 pub(super) fn get_cli() -> Command {
     Command::new(env!("CARGO_BIN_NAME"))
@@ -6,6 +6,14 @@ pub(super) fn get_cli() -> Command {
         .version(format!("SJS {}", crate_version!()))
         .subcommand(get_start_command())
         .subcommand(get_init_command())
+        .subcommand(get_bundle_command())
+        .subcommand(get_codegen_command())
+        .subcommand(get_upgrade_data_command())
+        .subcommand(get_verify_command())
+        .subcommand(get_backup_command())
+        .subcommand(get_restore_command())
+        .subcommand(get_export_command())
+        .subcommand(get_import_command())
 }
 
 fn get_start_command() -> Command {
@@ -28,6 +36,12 @@ fn get_start_command() -> Command {
                 .help("Whether it should initialize the REPL when running")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            arg!(--bundle <FILE>)
+                .help("Load the table modules from a bundle created with 'schemajs bundle'")
+                .required(false)
+                .env("SJS_BUNDLE"),
+        )
 }
 
 fn get_init_command() -> Command {
@@ -39,3 +53,232 @@ fn get_init_command() -> Command {
                 .required(false)
         )
 }
+
+fn get_bundle_command() -> Command {
+    Command::new("bundle")
+        .about("Bundles the table modules of a project and everything they import into a single eszip file")
+        .arg(
+            arg!(-c --config <HOST>)
+                .help("Path to SchemaJS.toml or directory containing it")
+                .default_value("./")
+                .env("SJS_CONFIG"),
+        )
+        .arg(
+            arg!(-o --output <FILE>)
+                .help("Where the bundle is written to")
+                .default_value("app.eszip"),
+        )
+}
+
+fn get_codegen_command() -> Command {
+    Command::new("codegen")
+        .about("Generates TypeScript typings for the tables and custom queries of a project")
+        .arg(
+            arg!(-c --config <HOST>)
+                .help("Path to SchemaJS.toml or directory containing it")
+                .default_value("./")
+                .env("SJS_CONFIG"),
+        )
+        .arg(
+            arg!(-o --output <FILE>)
+                .help("Where the typings are written to")
+                .default_value("schemajs.gen.ts"),
+        )
+}
+
+fn get_upgrade_data_command() -> Command {
+    Command::new("upgrade-data")
+        .about("Rewrites the shards written by older versions of SJS in the current format. The server must not be running")
+        .arg(
+            arg!(-d --data <DIRECTORY>)
+                .help("The data folder of SJS (Defaults to the one used by 'schemajs start')")
+                .required(false)
+                .env("SJS_DATA"),
+        )
+}
+
+fn get_verify_command() -> Command {
+    Command::new("verify")
+        .about("Checks every row of the shards of a data folder against its checksum and reports the corrupted ones")
+        .arg(
+            arg!(-d --data <DIRECTORY>)
+                .help("The data folder of SJS (Defaults to the one used by 'schemajs start')")
+                .required(false)
+                .env("SJS_DATA"),
+        )
+}
+
+fn get_backup_command() -> Command {
+    Command::new("backup")
+        .about("Takes a consistent snapshot of the databases of a running SJS server, written under its backup folder")
+        .arg(
+            arg!(-i --ip <HOST>)
+                .help("Address of the SJS server")
+                .default_value("[::1]:34244")
+                .env("SJS_HOST"),
+        )
+        .arg(
+            arg!(-d --database <NAME>)
+                .help("The database to back up, which the user belongs to")
+                .required(true)
+                .env("SJS_DATABASE"),
+        )
+        .arg(
+            arg!(-u --username <USERNAME>)
+                .help("An admin of the database")
+                .required(true)
+                .env("SJS_USERNAME"),
+        )
+        .arg(
+            arg!(-p --password <PASSWORD>)
+                .help("The password of the user")
+                .required(true)
+                .env("SJS_PASSWORD"),
+        )
+        .arg(
+            arg!(-o --output <DIRECTORY>)
+                .help("Folder the backup is written to, relative to the backup folder of the server. It must not exist or be empty")
+                .required(true),
+        )
+        .arg(
+            arg!(--all)
+                .help("Back up every database of the server instead, which requires a super admin")
+                .action(ArgAction::SetTrue),
+        )
+        .args(tls_args())
+}
+
+fn get_restore_command() -> Command {
+    Command::new("restore")
+        .about("Restores the databases of a backup created with 'schemajs backup'. The server must not be running")
+        .arg(
+            arg!(-b --backup <DIRECTORY>)
+                .help("The backup folder")
+                .required(true),
+        )
+        .arg(
+            arg!(-d --data <DIRECTORY>)
+                .help("The data folder of SJS (Defaults to the one used by 'schemajs start')")
+                .required(false)
+                .env("SJS_DATA"),
+        )
+        .arg(
+            arg!(--overwrite)
+                .help("Replace the databases of the backup that already exist")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+fn get_export_command() -> Command {
+    Command::new("export")
+        .about(
+            "Exports the rows of a table of a running SJS server to a NDJSON, CSV or Parquet file",
+        )
+        .arg(
+            arg!(-i --ip <HOST>)
+                .help("Address of the SJS server")
+                .default_value("[::1]:34244")
+                .env("SJS_HOST"),
+        )
+        .arg(
+            arg!(-d --database <NAME>)
+                .help("The database of the table, which the user belongs to")
+                .required(true)
+                .env("SJS_DATABASE"),
+        )
+        .arg(
+            arg!(-u --username <USERNAME>)
+                .help("A user of the database")
+                .required(true)
+                .env("SJS_USERNAME"),
+        )
+        .arg(
+            arg!(-p --password <PASSWORD>)
+                .help("The password of the user")
+                .required(true)
+                .env("SJS_PASSWORD"),
+        )
+        .arg(
+            arg!(-t --table <TABLE>)
+                .help("The table to export")
+                .required(true),
+        )
+        .arg(
+            arg!(-o --output <FILE>)
+                .help("The file the rows are written to")
+                .required(true),
+        )
+        .arg(
+            arg!(--format <FORMAT>)
+                .help("Format of the file (Defaults to the one of its extension)")
+                .value_parser(["ndjson", "csv", "parquet"])
+                .required(false),
+        )
+        .args(tls_args())
+}
+
+fn get_import_command() -> Command {
+    Command::new("import")
+        .about("Imports the rows of a NDJSON, CSV or Parquet file into a table of a running SJS server")
+        .arg(
+            arg!(-i --ip <HOST>)
+                .help("Address of the SJS server")
+                .default_value("[::1]:34244")
+                .env("SJS_HOST"),
+        )
+        .arg(
+            arg!(-d --database <NAME>)
+                .help("The database of the table, which the user belongs to")
+                .required(true)
+                .env("SJS_DATABASE"),
+        )
+        .arg(
+            arg!(-u --username <USERNAME>)
+                .help("A user of the database")
+                .required(true)
+                .env("SJS_USERNAME"),
+        )
+        .arg(
+            arg!(-p --password <PASSWORD>)
+                .help("The password of the user")
+                .required(true)
+                .env("SJS_PASSWORD"),
+        )
+        .arg(
+            arg!(-t --table <TABLE>)
+                .help("The table the rows are inserted into")
+                .required(true),
+        )
+        .arg(
+            arg!(--input <FILE>)
+                .help("The file to import. CSV files must start with a header naming the columns")
+                .required(true),
+        )
+        .arg(
+            arg!(--format <FORMAT>)
+                .help("Format of the file (Defaults to the one of its extension)")
+                .value_parser(["ndjson", "csv", "parquet"])
+                .required(false),
+        )
+        .args(tls_args())
+}
+
+/// Options to connect to a server over TLS, used when one is set or the address is an https url.
+fn tls_args() -> [Arg; 3] {
+    [
+        arg!(--ca <FILE>)
+            .help("PEM certificate of the CA that issued the one of the server (Defaults to the well-known authorities)")
+            .required(false)
+            .env("SJS_TLS_CA"),
+        arg!(--cert <FILE>)
+            .help("PEM client certificate, for servers that verify the ones of their clients")
+            .required(false)
+            .requires("key")
+            .env("SJS_TLS_CERT"),
+        arg!(--key <FILE>)
+            .help("PEM private key of the client certificate")
+            .required(false)
+            .requires("cert")
+            .env("SJS_TLS_KEY"),
+    ]
+}
@@ -1,10 +1,19 @@
 mod cmd;
 mod flags;
 
+use crate::cmd::backup::{backup_cmd, BackupOpts};
+use crate::cmd::bundle::{bundle_cmd, BundleOpts};
+use crate::cmd::codegen::{codegen_cmd, CodegenOpts};
+use crate::cmd::export::{export_cmd, ExportOpts};
+use crate::cmd::import::{import_cmd, ImportOpts};
 use crate::cmd::init::{init_cmd, InitOpts};
+use crate::cmd::remote::TlsOpts;
+use crate::cmd::restore::{restore_cmd, RestoreOpts};
 use crate::cmd::start::{start, StartOpts};
+use crate::cmd::upgrade_data::{upgrade_data_cmd, UpgradeDataOpts};
+use crate::cmd::verify::{verify_cmd, VerifyOpts};
 use crate::flags::get_cli;
-use clap::crate_version;
+use clap::{crate_version, ArgMatches};
 use colored::Colorize;
 
 #[tokio::main]
@@ -16,11 +25,13 @@ async fn main() {
             let ip = sub_matches.get_one::<String>("ip").cloned();
             let config_file = sub_matches.get_one::<String>("config").cloned().unwrap();
             let no_repl = sub_matches.get_one::<bool>("no-repl").cloned();
+            let bundle = sub_matches.get_one::<String>("bundle").cloned();
 
             let _ = start(StartOpts {
                 ip,
                 config_file,
                 repl: no_repl.unwrap_or(false),
+                bundle,
             })
             .await;
         }
@@ -28,6 +39,98 @@ async fn main() {
             let dir = sub_matches.get_one::<String>("directory").cloned();
             init_cmd(InitOpts { dir });
         }
+        Some(("bundle", sub_matches)) => {
+            let config_file = sub_matches.get_one::<String>("config").cloned().unwrap();
+            let output = sub_matches.get_one::<String>("output").cloned().unwrap();
+            bundle_cmd(BundleOpts {
+                config_file,
+                output,
+            })
+            .await;
+        }
+        Some(("codegen", sub_matches)) => {
+            let config_file = sub_matches.get_one::<String>("config").cloned().unwrap();
+            let output = sub_matches.get_one::<String>("output").cloned().unwrap();
+            codegen_cmd(CodegenOpts {
+                config_file,
+                output,
+            })
+            .await;
+        }
+        Some(("upgrade-data", sub_matches)) => {
+            let data = sub_matches.get_one::<String>("data").cloned();
+            upgrade_data_cmd(UpgradeDataOpts { data });
+        }
+        Some(("verify", sub_matches)) => {
+            let data = sub_matches.get_one::<String>("data").cloned();
+            verify_cmd(VerifyOpts { data });
+        }
+        Some(("backup", sub_matches)) => {
+            let ip = sub_matches.get_one::<String>("ip").cloned().unwrap();
+            let database = sub_matches.get_one::<String>("database").cloned().unwrap();
+            let username = sub_matches.get_one::<String>("username").cloned().unwrap();
+            let password = sub_matches.get_one::<String>("password").cloned().unwrap();
+            let output = sub_matches.get_one::<String>("output").cloned().unwrap();
+            backup_cmd(BackupOpts {
+                ip,
+                database,
+                username,
+                password,
+                output,
+                all: sub_matches.get_flag("all"),
+                tls: tls_opts(sub_matches),
+            })
+            .await;
+        }
+        Some(("restore", sub_matches)) => {
+            let backup = sub_matches.get_one::<String>("backup").cloned().unwrap();
+            let data = sub_matches.get_one::<String>("data").cloned();
+            restore_cmd(RestoreOpts {
+                backup,
+                data,
+                overwrite: sub_matches.get_flag("overwrite"),
+            });
+        }
+        Some(("export", sub_matches)) => {
+            let ip = sub_matches.get_one::<String>("ip").cloned().unwrap();
+            let database = sub_matches.get_one::<String>("database").cloned().unwrap();
+            let username = sub_matches.get_one::<String>("username").cloned().unwrap();
+            let password = sub_matches.get_one::<String>("password").cloned().unwrap();
+            let table = sub_matches.get_one::<String>("table").cloned().unwrap();
+            let output = sub_matches.get_one::<String>("output").cloned().unwrap();
+            let format = sub_matches.get_one::<String>("format").cloned();
+            export_cmd(ExportOpts {
+                ip,
+                database,
+                username,
+                password,
+                table,
+                output,
+                format,
+                tls: tls_opts(sub_matches),
+            })
+            .await;
+        }
+        Some(("import", sub_matches)) => {
+            let ip = sub_matches.get_one::<String>("ip").cloned().unwrap();
+            let database = sub_matches.get_one::<String>("database").cloned().unwrap();
+            let username = sub_matches.get_one::<String>("username").cloned().unwrap();
+            let password = sub_matches.get_one::<String>("password").cloned().unwrap();
+            let table = sub_matches.get_one::<String>("table").cloned().unwrap();
+            let input = sub_matches.get_one::<String>("input").cloned().unwrap();
+            let format = sub_matches.get_one::<String>("format").cloned();
+            import_cmd(ImportOpts {
+                ip,
+                database,
+                username,
+                password,
+                table,
+                input,
+                format,
+                tls: tls_opts(sub_matches),
+            })
+            .await;
+        }
         _ => {
             println!();
             println!("SJS {}", crate_version!());
@@ -39,3 +142,11 @@ async fn main() {
         }
     };
 }
+
+fn tls_opts(sub_matches: &ArgMatches) -> TlsOpts {
+    TlsOpts {
+        ca: sub_matches.get_one::<String>("ca").cloned(),
+        cert: sub_matches.get_one::<String>("cert").cloned(),
+        key: sub_matches.get_one::<String>("key").cloned(),
+    }
+}
@@ -23,6 +23,7 @@ config_constants! {
     const MAX_ROWS_PER_TEMP_SHARD: u64 = 1000;
     const MAX_ROWS_PER_SHARD: u64 = 2_500_000;
     const MAX_RECORDS_PER_HASH_INDEX_SHARD: u64 = 10_000_000;
+    const MEMTABLE_CAPACITY: u64 = 100_000;
     const DEFAULT_SCHEME_NAME: &'static str = "public";
 
     const DEFAULT_ROOT_USER: &'static str = "admin";
@@ -30,6 +31,23 @@ config_constants! {
 
     const DEFAULT_GRPC_HOST: &'static str = "[::1]:34244";
     const DEFAULT_CUSTOM_QUERY_TIMEOUT: u64 = 30;
+    const DEFAULT_MAX_HELPER_HEAP_SIZE: usize = 64 * 1024 * 1024;
+    const DEFAULT_MAX_HELPER_OPS: u64 = 10_000;
 
     const DEFAULT_MAX_FILE_DESCRIPTORS: usize = 2500;
+    const DEFAULT_MAX_RUNTIME_CALLS: u64 = 1000;
+    const DEFAULT_MAX_RUNTIME_HEAP_SIZE: usize = 256 * 1024 * 1024;
+    const DEFAULT_HOT_RELOAD: bool = true;
+
+    const DEFAULT_REMOTE_MODULES: bool = true;
+    const DEFAULT_MODULES_LOCK: bool = true;
+    const DEFAULT_JSR_REGISTRY: &'static str = "https://jsr.io/";
+    const DEFAULT_NPM_REGISTRY: &'static str = "https://registry.npmjs.org/";
+    const DEFAULT_NPM_CDN: &'static str = "https://esm.sh/";
+
+    const DEFAULT_JWT_EXPIRATION: u64 = 3600;
+
+    const DEFAULT_MAX_FAILED_LOGIN_ATTEMPTS: u32 = 5;
+    const DEFAULT_BASE_LOCKOUT: u64 = 1;
+    const DEFAULT_MAX_LOCKOUT: u64 = 900;
 }
@@ -1,9 +1,14 @@
 mod default_config_values;
 
 use crate::default_config_values::{
-    get_DefaultCustomQueryTimeout, get_DefaultMaxFileDescriptors, get_MaxRecordsPerHashIndexShard,
-    get_MaxRowsPerShard, get_MaxRowsPerTempShard, get_MaxTemporaryShards, str_DefaultGrpcHost,
-    str_DefaultRootPwd, str_DefaultRootUser, str_DefaultSchemeName,
+    get_DefaultBaseLockout, get_DefaultCustomQueryTimeout, get_DefaultHotReload,
+    get_DefaultJwtExpiration, get_DefaultMaxFailedLoginAttempts, get_DefaultMaxFileDescriptors,
+    get_DefaultMaxHelperHeapSize, get_DefaultMaxHelperOps, get_DefaultMaxLockout,
+    get_DefaultMaxRuntimeCalls, get_DefaultMaxRuntimeHeapSize, get_DefaultModulesLock,
+    get_DefaultRemoteModules, get_MaxRecordsPerHashIndexShard, get_MaxRowsPerShard,
+    get_MaxRowsPerTempShard, get_MaxTemporaryShards, get_MemtableCapacity, str_DefaultGrpcHost,
+    str_DefaultJsrRegistry, str_DefaultNpmCdn, str_DefaultNpmRegistry, str_DefaultRootPwd,
+    str_DefaultRootUser, str_DefaultSchemeName,
 };
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -17,6 +22,8 @@ pub struct SchemeJsConfig {
     pub db: HashMap<String, DatabaseConfig>,
     pub grpc: GrpcConfig,
     pub process: ProcessConfig,
+    pub auth: AuthenticationConfig,
+    pub modules: ModulesConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -32,12 +39,28 @@ pub struct GlobalConfig {
     pub max_rows_per_temp_shard: u64,
     #[serde(default = "get_MaxRowsPerShard")]
     pub max_rows_per_shard: u64,
+    /// Entries a sorted run of a hash index may hold. Compactions never merge runs past it.
     #[serde(default = "get_MaxRecordsPerHashIndexShard")]
     pub max_records_per_hash_index_shard: u64,
+    /// Entries a hash index keeps in memory before flushing them to disk as a sorted run.
+    #[serde(default = "get_MemtableCapacity")]
+    pub memtable_capacity: u64,
+    /// Bytes the V8 heap may grow during a single helper call before it is terminated. `0` disables the limit.
+    #[serde(default = "get_DefaultMaxHelperHeapSize")]
+    pub max_helper_heap_size: usize,
+    /// Engine ops (inserts, searches...) a single helper call may perform. `0` disables the limit.
+    #[serde(default = "get_DefaultMaxHelperOps")]
+    pub max_helper_ops: u64,
     #[serde(default)]
     pub default_auth: AuthConfig,
+    #[serde(default)]
+    pub row_format: RowFormat,
     #[serde(default = "str_DefaultSchemeName")]
     pub default_scheme: String,
+    /// Folder the backups requested through the admin service are written under. Defaults to
+    /// the `backups` folder of the data folder.
+    #[serde(default)]
+    pub backup_folder: Option<String>,
 }
 
 impl Default for GlobalConfig {
@@ -47,8 +70,13 @@ impl Default for GlobalConfig {
             max_rows_per_temp_shard: get_MaxRowsPerTempShard(),
             max_rows_per_shard: get_MaxRowsPerShard(),
             max_records_per_hash_index_shard: get_MaxRecordsPerHashIndexShard(),
+            memtable_capacity: get_MemtableCapacity(),
+            max_helper_heap_size: get_DefaultMaxHelperHeapSize(),
+            max_helper_ops: get_DefaultMaxHelperOps(),
             default_auth: Default::default(),
+            row_format: Default::default(),
             default_scheme: str_DefaultSchemeName(),
+            backup_folder: None,
         }
     }
 }
@@ -59,8 +87,13 @@ pub struct DatabaseConfig {
     pub max_rows_per_temp_shard: u64,
     pub max_rows_per_shard: u64,
     pub max_records_per_hash_index_shard: u64,
+    pub memtable_capacity: u64,
+    /// Seconds a helper call may run before its execution is terminated. `0` disables the limit.
     pub custom_query_timeout: u64,
+    pub max_helper_heap_size: usize,
+    pub max_helper_ops: u64,
     pub default_auth: AuthConfig,
+    pub row_format: RowFormat,
 }
 
 impl Default for DatabaseConfig {
@@ -70,8 +103,12 @@ impl Default for DatabaseConfig {
             max_rows_per_temp_shard: get_MaxRowsPerTempShard(),
             max_rows_per_shard: get_MaxRowsPerShard(),
             max_records_per_hash_index_shard: get_MaxRecordsPerHashIndexShard(),
+            memtable_capacity: get_MemtableCapacity(),
             custom_query_timeout: get_DefaultCustomQueryTimeout(),
+            max_helper_heap_size: get_DefaultMaxHelperHeapSize(),
+            max_helper_ops: get_DefaultMaxHelperOps(),
             default_auth: Default::default(),
+            row_format: Default::default(),
         }
     }
 }
@@ -83,12 +120,29 @@ impl DatabaseConfig {
             max_rows_per_temp_shard: global_config.max_rows_per_temp_shard,
             max_rows_per_shard: global_config.max_rows_per_shard,
             max_records_per_hash_index_shard: global_config.max_records_per_hash_index_shard,
+            memtable_capacity: global_config.memtable_capacity,
             custom_query_timeout: grpc.custom_query_timeout,
+            max_helper_heap_size: global_config.max_helper_heap_size,
+            max_helper_ops: global_config.max_helper_ops,
             default_auth: global_config.default_auth.clone(),
+            row_format: global_config.row_format,
         }
     }
 }
 
+/// How the rows of a database are stored in its data shards.
+///
+/// Rows already stored in the other format stay readable after it is changed.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RowFormat {
+    /// Rows as JSON objects keyed by column name.
+    #[default]
+    Json,
+    /// Rows as a compact binary encoding keyed by column ordinal.
+    Binary,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct AuthConfig {
     #[serde(default = "str_DefaultRootUser")]
@@ -112,6 +166,8 @@ pub struct GrpcConfig {
     pub host: String,
     #[serde(default = "get_DefaultCustomQueryTimeout")]
     pub custom_query_timeout: u64,
+    #[serde(default)]
+    pub tls: Option<GrpcTlsConfig>,
 }
 
 impl Default for GrpcConfig {
@@ -119,20 +175,163 @@ impl Default for GrpcConfig {
         Self {
             host: str_DefaultGrpcHost(),
             custom_query_timeout: get_DefaultCustomQueryTimeout(),
+            tls: None,
         }
     }
 }
 
+/// TLS settings for the gRPC server (`[grpc.tls]`).
+#[derive(Debug, Deserialize, Clone)]
+pub struct GrpcTlsConfig {
+    /// PEM encoded server certificate chain.
+    pub cert: String,
+    /// PEM encoded server private key.
+    pub key: String,
+    /// PEM encoded CA used to verify client certificates. Enables mutual TLS when set.
+    pub client_ca: Option<String>,
+    /// Authenticate clients with a verified certificate as the user `client_cert_users` binds
+    /// its Common Name to, without requiring a password.
+    #[serde(default)]
+    pub client_cert_auth: bool,
+    /// Users of a database that client certificates authenticate as, by Common Name
+    /// (`[grpc.tls.client_cert_users]`). Certificates whose Common Name is not listed are refused.
+    #[serde(default)]
+    pub client_cert_users: HashMap<String, ClientCertUser>,
+}
+
+/// User a client certificate authenticates as, looked up in `database` whichever database
+/// the request is for.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ClientCertUser {
+    pub database: String,
+    pub user: String,
+}
+
+/// Server-wide authentication settings (`[auth]`).
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct AuthenticationConfig {
+    /// When present, sessions are issued as signed JWTs instead of opaque tokens.
+    #[serde(default)]
+    pub jwt: Option<JwtConfig>,
+    #[serde(default)]
+    pub lockout: LockoutConfig,
+}
+
+/// Brute-force protection for logins (`[auth.lockout]`).
+///
+/// Failed attempts are counted per username and per peer IP. Once `max_failed_attempts`
+/// is reached, every further failure locks the key for `base_lockout * 2^n` seconds,
+/// capped at `max_lockout`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct LockoutConfig {
+    /// Failed attempts allowed before locking. `0` disables the lockout.
+    #[serde(default = "get_DefaultMaxFailedLoginAttempts")]
+    pub max_failed_attempts: u32,
+    #[serde(default = "get_DefaultBaseLockout")]
+    pub base_lockout: u64,
+    #[serde(default = "get_DefaultMaxLockout")]
+    pub max_lockout: u64,
+}
+
+impl Default for LockoutConfig {
+    fn default() -> Self {
+        Self {
+            max_failed_attempts: get_DefaultMaxFailedLoginAttempts(),
+            base_lockout: get_DefaultBaseLockout(),
+            max_lockout: get_DefaultMaxLockout(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub enum JwtAlgorithm {
+    #[default]
+    HS256,
+    EdDSA,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct JwtConfig {
+    #[serde(default)]
+    pub algorithm: JwtAlgorithm,
+    /// Shared secret used by `HS256`.
+    pub secret: Option<String>,
+    /// PEM encoded Ed25519 private key used by `EdDSA` to sign tokens.
+    pub private_key_file: Option<String>,
+    /// PEM encoded Ed25519 public key used by `EdDSA` to verify tokens.
+    pub public_key_file: Option<String>,
+    /// Lifetime of an issued token, in seconds.
+    #[serde(default = "get_DefaultJwtExpiration")]
+    pub expiration: u64,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct ProcessConfig {
     #[serde(default = "get_DefaultMaxFileDescriptors")]
     pub max_file_descriptors_in_cache: usize,
+    /// Helper calls a pooled runtime serves before it is recycled. `0` disables the limit.
+    #[serde(default = "get_DefaultMaxRuntimeCalls")]
+    pub max_runtime_calls: u64,
+    /// Used V8 heap size, in bytes, after which a pooled runtime is recycled. `0` disables the limit.
+    #[serde(default = "get_DefaultMaxRuntimeHeapSize")]
+    pub max_runtime_heap_size: usize,
+    /// Watch the table folders and reload changed tables and helpers without restarting.
+    #[serde(default = "get_DefaultHotReload")]
+    pub hot_reload: bool,
 }
 
 impl Default for ProcessConfig {
     fn default() -> Self {
         Self {
             max_file_descriptors_in_cache: get_DefaultMaxFileDescriptors(),
+            max_runtime_calls: get_DefaultMaxRuntimeCalls(),
+            max_runtime_heap_size: get_DefaultMaxRuntimeHeapSize(),
+            hot_reload: get_DefaultHotReload(),
+        }
+    }
+}
+
+/// Modules imported by table scripts (`[modules]`).
+///
+/// Remote modules (`https:`, `jsr:` and `npm:`) are cached in the data folder and,
+/// unless `lock` is disabled, pinned in a `SchemaJS.lock` next to the config file.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ModulesConfig {
+    /// Allow table scripts to import remote modules.
+    #[serde(default = "get_DefaultRemoteModules")]
+    pub remote: bool,
+    /// Only load remote modules from the cache, failing for any module that was never downloaded.
+    #[serde(default)]
+    pub cached_only: bool,
+    /// Pin the checksum of every remote module in `SchemaJS.lock`.
+    #[serde(default = "get_DefaultModulesLock")]
+    pub lock: bool,
+    #[serde(default = "str_DefaultJsrRegistry")]
+    pub jsr_registry: String,
+    /// Registry the versions of `npm:` packages are resolved against.
+    #[serde(default = "str_DefaultNpmRegistry")]
+    pub npm_registry: String,
+    /// CDN `npm:` packages are loaded from as ES modules.
+    #[serde(default = "str_DefaultNpmCdn")]
+    pub npm_cdn: String,
+    /// Import map of the table scripts, relative to the config folder.
+    #[serde(default)]
+    pub imports: HashMap<String, String>,
+    #[serde(default)]
+    pub scopes: HashMap<String, HashMap<String, String>>,
+}
+
+impl Default for ModulesConfig {
+    fn default() -> Self {
+        Self {
+            remote: get_DefaultRemoteModules(),
+            cached_only: false,
+            lock: get_DefaultModulesLock(),
+            jsr_registry: str_DefaultJsrRegistry(),
+            npm_registry: str_DefaultNpmRegistry(),
+            npm_cdn: str_DefaultNpmCdn(),
+            imports: HashMap::new(),
+            scopes: HashMap::new(),
         }
     }
 }
@@ -150,6 +349,10 @@ impl SchemeJsConfig {
             pub grpc: GrpcConfig,
             #[serde(default)]
             pub process: ProcessConfig,
+            #[serde(default)]
+            pub auth: AuthenticationConfig,
+            #[serde(default)]
+            pub modules: ModulesConfig,
         }
 
         // Parse the TOML string to SchemeJsConfig
@@ -161,8 +364,12 @@ impl SchemeJsConfig {
             pub max_rows_per_temp_shard: Option<u64>,
             pub max_rows_per_shard: Option<u64>,
             pub max_records_per_hash_index_shard: Option<u64>,
+            pub memtable_capacity: Option<u64>,
             pub custom_query_timeout: Option<u64>,
+            pub max_helper_heap_size: Option<usize>,
+            pub max_helper_ops: Option<u64>,
             pub default_auth: Option<AuthConfig>,
+            pub row_format: Option<RowFormat>,
         }
 
         #[derive(Deserialize, Default)]
@@ -192,12 +399,22 @@ impl SchemeJsConfig {
                         max_records_per_hash_index_shard: val
                             .max_records_per_hash_index_shard
                             .unwrap_or_else(|| global.global.max_records_per_hash_index_shard),
+                        memtable_capacity: val
+                            .memtable_capacity
+                            .unwrap_or_else(|| global.global.memtable_capacity),
                         custom_query_timeout: val
                             .custom_query_timeout
                             .unwrap_or_else(|| global.grpc.custom_query_timeout),
+                        max_helper_heap_size: val
+                            .max_helper_heap_size
+                            .unwrap_or_else(|| global.global.max_helper_heap_size),
+                        max_helper_ops: val
+                            .max_helper_ops
+                            .unwrap_or_else(|| global.global.max_helper_ops),
                         default_auth: val
                             .default_auth
                             .unwrap_or_else(|| global.global.default_auth.clone()),
+                        row_format: val.row_format.unwrap_or(global.global.row_format),
                     },
                 );
             }
@@ -209,6 +426,8 @@ impl SchemeJsConfig {
             db: actual_db,
             grpc: global.grpc,
             process: global.process,
+            auth: global.auth,
+            modules: global.modules,
         })
     }
 
@@ -236,8 +455,12 @@ impl SchemeJsConfig {
 
 #[cfg(test)]
 mod tests {
-    use crate::default_config_values::{get_DefaultRootPwd, get_MaxTemporaryShards};
-    use crate::SchemeJsConfig;
+    use crate::default_config_values::{
+        get_DefaultJwtExpiration, get_DefaultMaxHelperHeapSize, get_DefaultMaxLockout,
+        get_DefaultRootPwd, get_MaxRecordsPerHashIndexShard, get_MaxTemporaryShards,
+        str_DefaultNpmCdn, str_DefaultNpmRegistry,
+    };
+    use crate::{ClientCertUser, JwtAlgorithm, RowFormat, SchemeJsConfig};
 
     #[test]
     fn test_toml_config() {
@@ -260,4 +483,200 @@ mod tests {
         assert_eq!(db.default_auth.username, "lion");
         assert_eq!(db.default_auth.password, get_DefaultRootPwd());
     }
+
+    #[test]
+    fn test_helper_limits_config() {
+        let config: SchemeJsConfig = SchemeJsConfig::from_str(
+            r#"
+  [global]
+  max_helper_ops = 50
+  [db.public]
+  max_helper_ops = 10
+  [db.analytics]
+  max_helper_heap_size = 0
+"#,
+        )
+        .unwrap();
+
+        let public_db = config.db.get("public").unwrap();
+        assert_eq!(public_db.max_helper_ops, 10);
+        assert_eq!(
+            public_db.max_helper_heap_size,
+            get_DefaultMaxHelperHeapSize()
+        );
+
+        let analytics_db = config.db.get("analytics").unwrap();
+        assert_eq!(analytics_db.max_helper_ops, 50);
+        assert_eq!(analytics_db.max_helper_heap_size, 0);
+
+        let other_db = config.db_config("other");
+        assert_eq!(other_db.max_helper_ops, 50);
+    }
+
+    #[test]
+    fn test_hash_index_config() {
+        let config: SchemeJsConfig = SchemeJsConfig::from_str(
+            r#"
+  [global]
+  memtable_capacity = 500
+  [db.public]
+  memtable_capacity = 10
+"#,
+        )
+        .unwrap();
+
+        let public_db = config.db.get("public").unwrap();
+        assert_eq!(public_db.memtable_capacity, 10);
+        assert_eq!(
+            public_db.max_records_per_hash_index_shard,
+            get_MaxRecordsPerHashIndexShard()
+        );
+        assert_eq!(config.db_config("other").memtable_capacity, 500);
+    }
+
+    #[test]
+    fn test_jwt_config() {
+        let config = SchemeJsConfig::from_str("").unwrap();
+        assert!(config.auth.jwt.is_none());
+
+        let config: SchemeJsConfig = SchemeJsConfig::from_str(
+            r#"
+  [auth.jwt]
+  secret = "my-secret"
+"#,
+        )
+        .unwrap();
+
+        let jwt = config.auth.jwt.unwrap();
+        assert_eq!(jwt.algorithm, JwtAlgorithm::HS256);
+        assert_eq!(jwt.secret.unwrap(), "my-secret");
+        assert_eq!(jwt.expiration, get_DefaultJwtExpiration());
+
+        let config: SchemeJsConfig = SchemeJsConfig::from_str(
+            r#"
+  [auth.jwt]
+  algorithm = "EdDSA"
+  private_key_file = "./keys/private.pem"
+  public_key_file = "./keys/public.pem"
+  expiration = 60
+"#,
+        )
+        .unwrap();
+
+        let jwt = config.auth.jwt.unwrap();
+        assert_eq!(jwt.algorithm, JwtAlgorithm::EdDSA);
+        assert_eq!(jwt.private_key_file.unwrap(), "./keys/private.pem");
+        assert_eq!(jwt.expiration, 60);
+    }
+
+    #[test]
+    fn test_grpc_tls_config() {
+        let config = SchemeJsConfig::from_str("").unwrap();
+        assert!(config.grpc.tls.is_none());
+
+        let config: SchemeJsConfig = SchemeJsConfig::from_str(
+            r#"
+  [grpc.tls]
+  cert = "./certs/server.pem"
+  key = "./certs/server.key"
+  client_ca = "./certs/ca.pem"
+"#,
+        )
+        .unwrap();
+
+        let tls = config.grpc.tls.unwrap();
+        assert_eq!(tls.cert, "./certs/server.pem");
+        assert_eq!(tls.key, "./certs/server.key");
+        assert_eq!(tls.client_ca.unwrap(), "./certs/ca.pem");
+        assert!(!tls.client_cert_auth);
+        assert!(tls.client_cert_users.is_empty());
+
+        let config: SchemeJsConfig = SchemeJsConfig::from_str(
+            r#"
+  [grpc.tls]
+  cert = "./certs/server.pem"
+  key = "./certs/server.key"
+  client_ca = "./certs/ca.pem"
+  client_cert_auth = true
+
+  [grpc.tls.client_cert_users]
+  "reporting-service" = { database = "analytics", user = "reporter" }
+"#,
+        )
+        .unwrap();
+
+        let tls = config.grpc.tls.unwrap();
+        assert!(tls.client_cert_auth);
+        assert_eq!(
+            tls.client_cert_users.get("reporting-service"),
+            Some(&ClientCertUser {
+                database: "analytics".to_string(),
+                user: "reporter".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_lockout_config() {
+        let config: SchemeJsConfig = SchemeJsConfig::from_str(
+            r#"
+  [auth.lockout]
+  max_failed_attempts = 3
+  base_lockout = 2
+"#,
+        )
+        .unwrap();
+
+        let lockout = config.auth.lockout;
+        assert_eq!(lockout.max_failed_attempts, 3);
+        assert_eq!(lockout.base_lockout, 2);
+        assert_eq!(lockout.max_lockout, get_DefaultMaxLockout());
+    }
+
+    #[test]
+    fn test_modules_config() {
+        let config = SchemeJsConfig::from_str("").unwrap();
+        assert!(config.modules.remote);
+        assert!(config.modules.lock);
+        assert!(config.modules.imports.is_empty());
+
+        let config: SchemeJsConfig = SchemeJsConfig::from_str(
+            r#"
+  [modules]
+  cached_only = true
+  [modules.imports]
+  "@std/path" = "jsr:@std/path@^1.0.0"
+  "lodash" = "npm:lodash@4"
+"#,
+        )
+        .unwrap();
+
+        assert!(config.modules.cached_only);
+        assert_eq!(
+            config.modules.imports.get("lodash").unwrap(),
+            "npm:lodash@4"
+        );
+        assert_eq!(config.modules.npm_cdn, str_DefaultNpmCdn());
+        assert_eq!(config.modules.npm_registry, str_DefaultNpmRegistry());
+    }
+
+    #[test]
+    fn test_row_format_config() {
+        let config: SchemeJsConfig = SchemeJsConfig::from_str(
+            r#"
+  [global]
+  row_format = "binary"
+  [db.public]
+  row_format = "json"
+  [db.analytics]
+  max_helper_ops = 1
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.global.row_format, RowFormat::Binary);
+        assert_eq!(config.db_config("public").row_format, RowFormat::Json);
+        assert_eq!(config.db_config("analytics").row_format, RowFormat::Binary);
+        assert_eq!(config.db_config("other").row_format, RowFormat::Binary);
+    }
 }
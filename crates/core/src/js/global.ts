@@ -1,5 +1,5 @@
 import * as SJsPrimitives from "ext:sjs_primitives/src/js/index.ts"
-import { insertRow, searchRows } from "ext:sjs_engine/src/js/ops.ts";
+import { customQuery, insertRow, searchRows } from "ext:sjs_engine/src/js/ops.ts";
 import { QueryBuilder } from "ext:sjs_engine/src/js/query.ts";
 const core = globalThis.Deno.core;
 class SchemaJS {
@@ -45,8 +45,16 @@ class SchemaJS {
     }
 
     static get query() {
-        return (q: QueryBuilder) => {
-            if(!(q instanceof QueryBuilder)) {
+        return (...data) => {
+            const [q] = data;
+            if(typeof q === "string") {
+                if(data.length < 3) {
+                    throw new Error("Custom queries are called with `SchemaJS.query(db_name, table_name, query_name, args)`");
+                }
+
+                const [dbName, tblName, name, args] = data;
+                return customQuery(dbName, tblName, name, args);
+            } else if(!(q instanceof QueryBuilder)) {
                 throw new Error("Queries must be performed with SchemaJS.QueryBuilder");
             } else {
                 return searchRows(q.dbName, q.tableName, q.build())
@@ -0,0 +1,452 @@
+use crate::format::FORMAT_VERSION;
+use crate::shard::map_shard::COMPACTING_PREFIX;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// File of a backup folder describing its content.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Folder of a backup, and of the data folder of SJS, the databases are in.
+const DATABASES_FOLDER: &str = "dbs";
+
+/// Bytes read at once while files are copied.
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// Path of the file relative to the backup folder, with `/` as separator.
+    pub path: String,
+    pub size: u64,
+    pub crc32c: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Format version of the shards of the backup.
+    pub format_version: u16,
+    /// When the backup was created, in seconds since the unix epoch.
+    pub created_at: u64,
+    pub databases: Vec<String>,
+    pub files: Vec<BackupFile>,
+}
+
+/// A backup being written to a folder, one database at a time.
+///
+/// Files are copied rather than hard-linked, since shards are mutated in place once the
+/// database is written again. The manifest is only written by `finish`, so a folder without
+/// it is an incomplete backup.
+#[derive(Debug)]
+pub struct Backup {
+    folder: PathBuf,
+    manifest: BackupManifest,
+}
+
+impl Backup {
+    /// Starts a backup in `folder`, which must not exist or be empty.
+    pub fn create(folder: &Path) -> io::Result<Self> {
+        if folder.exists() && std::fs::read_dir(folder)?.next().is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Backup folder {:?} is not empty", folder),
+            ));
+        }
+
+        std::fs::create_dir_all(folder.join(DATABASES_FOLDER))?;
+
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+
+        Ok(Self {
+            folder: folder.to_path_buf(),
+            manifest: BackupManifest {
+                format_version: FORMAT_VERSION,
+                created_at,
+                databases: vec![],
+                files: vec![],
+            },
+        })
+    }
+
+    /// Copies the folder of the database `name`. Nothing must be written to it meanwhile.
+    pub fn add_database(&mut self, name: &str, db_folder: &Path) -> io::Result<()> {
+        let relative = PathBuf::from(DATABASES_FOLDER).join(name);
+        self.copy_folder(db_folder, &relative)?;
+        self.manifest.databases.push(name.to_string());
+        Ok(())
+    }
+
+    fn copy_folder(&mut self, source: &Path, relative: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(self.folder.join(relative))?;
+
+        let mut entries = std::fs::read_dir(source)?.collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+            let relative = relative.join(entry.file_name());
+
+            if path.is_dir() {
+                self.copy_folder(&path, &relative)?;
+                continue;
+            }
+
+            if !Self::is_needed(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+
+            let mut source = File::open(&path)?;
+            let mut destination = File::create(self.folder.join(&relative))?;
+            let (size, crc32c) = copy_with_crc32c(&mut source, &mut destination)?;
+            destination.sync_all()?;
+
+            self.manifest.files.push(BackupFile {
+                path: to_manifest_path(&relative),
+                size,
+                crc32c,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Temporary shards are reconciled before a database is backed up and shards being
+    /// compacted are leftovers until they are swapped in, so neither is needed to restore it.
+    fn is_needed(file_name: &str) -> bool {
+        !file_name.starts_with("temp_") && !file_name.starts_with(COMPACTING_PREFIX)
+    }
+
+    /// Writes the manifest, completing the backup.
+    pub fn finish(self) -> io::Result<BackupManifest> {
+        let manifest = serde_json::to_vec_pretty(&self.manifest)?;
+        std::fs::write(self.folder.join(MANIFEST_FILE), manifest)?;
+        Ok(self.manifest)
+    }
+}
+
+fn to_manifest_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Copies `reader` into `writer` a buffer at a time, returning the number of bytes copied and
+/// their CRC32C.
+fn copy_with_crc32c(reader: &mut impl Read, writer: &mut impl Write) -> io::Result<(u64, u32)> {
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    let mut size = 0;
+    let mut crc32c = 0;
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+
+        writer.write_all(&buffer[..read])?;
+        crc32c = crc32c::crc32c_append(crc32c, &buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok((size, crc32c))
+}
+
+/// Whether `path` is a relative path that can't leave the folder it is joined to.
+fn is_contained(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Whether `name` can be the name of a database folder.
+fn is_database_name(name: &str) -> bool {
+    let path = Path::new(name);
+    is_contained(path) && path.components().count() == 1 && !name.starts_with('.')
+}
+
+/// Resolves `destination`, the folder a backup was requested to be written to, under `root`.
+/// Only relative paths that stay inside `root` are accepted.
+pub fn resolve_backup_folder(root: &Path, destination: &str) -> io::Result<PathBuf> {
+    if !is_contained(Path::new(destination)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Backup destination {:?} must be a relative path inside the backup folder",
+                destination
+            ),
+        ));
+    }
+
+    Ok(root.join(destination))
+}
+
+/// Reads the manifest of a backup, checking that the databases and files it lists are inside
+/// the backup.
+pub fn read_manifest(backup_folder: &Path) -> io::Result<BackupManifest> {
+    let bytes = std::fs::read(backup_folder.join(MANIFEST_FILE))?;
+    let manifest: BackupManifest = serde_json::from_slice(&bytes)?;
+
+    let invalid = |what: String| {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Backup manifest lists {}", what),
+        ))
+    };
+
+    for db in &manifest.databases {
+        if !is_database_name(db) {
+            return invalid(format!("the invalid database name {:?}", db));
+        }
+    }
+
+    let databases_folder = Path::new(DATABASES_FOLDER);
+    for file in &manifest.files {
+        let path = Path::new(&file.path);
+        if !is_contained(path) || !path.starts_with(databases_folder) {
+            return invalid(format!("the file {:?} outside of its databases", file.path));
+        }
+    }
+
+    Ok(manifest)
+}
+
+/// Checks every file of a backup against its manifest, returning the paths of the files that
+/// are missing or do not match.
+pub fn verify_backup(backup_folder: &Path) -> io::Result<Vec<String>> {
+    let manifest = read_manifest(backup_folder)?;
+    let mut mismatched = vec![];
+
+    for file in &manifest.files {
+        let matches = File::open(backup_folder.join(&file.path))
+            .and_then(|mut source| copy_with_crc32c(&mut source, &mut io::sink()))
+            .map(|(size, crc32c)| size == file.size && crc32c == file.crc32c)
+            .unwrap_or(false);
+
+        if !matches {
+            mismatched.push(file.path.clone());
+        }
+    }
+
+    Ok(mismatched)
+}
+
+/// Restores the databases of a backup into `data_folder`, the data folder of SJS, returning
+/// its manifest. The server must not be running.
+///
+/// The backup is verified first, see `read_manifest` and `verify_backup`. Databases that already exist are only replaced if
+/// `overwrite` is set, each one is copied next to its final location before it is renamed in.
+pub fn restore_backup(
+    backup_folder: &Path,
+    data_folder: &Path,
+    overwrite: bool,
+) -> io::Result<BackupManifest> {
+    let manifest = read_manifest(backup_folder)?;
+
+    if manifest.format_version > FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Backup was written with the unsupported format version {}",
+                manifest.format_version
+            ),
+        ));
+    }
+
+    let mismatched = verify_backup(backup_folder)?;
+    if !mismatched.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Backup files are missing or corrupted: {:?}", mismatched),
+        ));
+    }
+
+    let dbs_folder = data_folder.join(DATABASES_FOLDER);
+    if !overwrite {
+        for db in &manifest.databases {
+            if dbs_folder.join(db).exists() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("Database '{}' already exists", db),
+                ));
+            }
+        }
+    }
+
+    for db in &manifest.databases {
+        let target = dbs_folder.join(db);
+        let restoring = dbs_folder.join(format!(".{}.restoring", db));
+        let _ = std::fs::remove_dir_all(&restoring);
+        std::fs::create_dir_all(&restoring)?;
+
+        let prefix = format!("{}/{}/", DATABASES_FOLDER, db);
+        for file in &manifest.files {
+            let Some(relative) = file.path.strip_prefix(&prefix) else {
+                continue;
+            };
+
+            let destination = restoring.join(relative);
+            if let Some(parent) = destination.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(backup_folder.join(&file.path), destination)?;
+        }
+
+        // Empty folders, such as the ones of indexes, are not listed in the manifest.
+        copy_folder_structure(&backup_folder.join(DATABASES_FOLDER).join(db), &restoring)?;
+
+        if target.exists() {
+            std::fs::remove_dir_all(&target)?;
+        }
+        std::fs::rename(&restoring, &target)?;
+    }
+
+    Ok(manifest)
+}
+
+fn copy_folder_structure(source: &Path, destination: &Path) -> io::Result<()> {
+    for entry in std::fs::read_dir(source)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let destination = destination.join(path.file_name().unwrap());
+            std::fs::create_dir_all(&destination)?;
+            copy_folder_structure(&path, &destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::backup::{resolve_backup_folder, restore_backup, verify_backup, Backup};
+    use std::io::ErrorKind;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_backup_and_restore() {
+        let data = tempdir().unwrap();
+        let db_folder = data.path().join("dbs/public");
+        std::fs::create_dir_all(db_folder.join("users/indx")).unwrap();
+        std::fs::create_dir_all(db_folder.join("users/temps")).unwrap();
+        std::fs::write(db_folder.join("users/data_a_0.data"), b"rows").unwrap();
+        std::fs::write(db_folder.join("users/temps/temp_b_0.data"), b"temp").unwrap();
+
+        let backup_dir = tempdir().unwrap();
+        let backup_folder = backup_dir.path().join("backup");
+        let mut backup = Backup::create(&backup_folder).unwrap();
+        backup.add_database("public", &db_folder).unwrap();
+        let manifest = backup.finish().unwrap();
+
+        assert_eq!(manifest.databases, vec!["public".to_string()]);
+        assert_eq!(manifest.files.len(), 1);
+        assert_eq!(manifest.files[0].path, "dbs/public/users/data_a_0.data");
+        assert!(verify_backup(&backup_folder).unwrap().is_empty());
+        assert_eq!(
+            Backup::create(&backup_folder).unwrap_err().kind(),
+            ErrorKind::AlreadyExists
+        );
+
+        std::fs::write(db_folder.join("users/data_a_0.data"), b"newer rows").unwrap();
+        assert_eq!(
+            restore_backup(&backup_folder, data.path(), false)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::AlreadyExists
+        );
+
+        restore_backup(&backup_folder, data.path(), true).unwrap();
+        assert_eq!(
+            std::fs::read(db_folder.join("users/data_a_0.data")).unwrap(),
+            b"rows"
+        );
+        assert!(db_folder.join("users/indx").is_dir());
+        assert!(!db_folder.join("users/temps/temp_b_0.data").exists());
+
+        // Corrupted backups are not restored.
+        std::fs::write(
+            backup_folder.join("dbs/public/users/data_a_0.data"),
+            b"rowz",
+        )
+        .unwrap();
+        assert_eq!(
+            verify_backup(&backup_folder).unwrap(),
+            vec!["dbs/public/users/data_a_0.data".to_string()]
+        );
+        assert_eq!(
+            restore_backup(&backup_folder, data.path(), true)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_resolve_backup_folder() {
+        let root = tempdir().unwrap();
+        assert_eq!(
+            resolve_backup_folder(root.path(), "nightly/monday").unwrap(),
+            root.path().join("nightly/monday")
+        );
+
+        for destination in ["", "/tmp/backup", "../backup", "nightly/../../backup", "./"] {
+            assert_eq!(
+                resolve_backup_folder(root.path(), destination)
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidInput,
+                "{:?}",
+                destination
+            );
+        }
+    }
+
+    #[test]
+    fn test_restore_rejects_paths_outside_of_the_backup() {
+        let data = tempdir().unwrap();
+        let db_folder = data.path().join("dbs/public");
+        std::fs::create_dir_all(&db_folder).unwrap();
+        std::fs::write(db_folder.join("data_a_0.data"), b"rows").unwrap();
+
+        let backup_dir = tempdir().unwrap();
+        let mut backup = Backup::create(backup_dir.path()).unwrap();
+        backup.add_database("public", &db_folder).unwrap();
+        let manifest = backup.finish().unwrap();
+
+        let outside = data.path().join("outside");
+        std::fs::create_dir_all(&outside).unwrap();
+
+        let tampered = [
+            serde_json::json!({ "databases": ["../outside"] }),
+            serde_json::json!({ "databases": [".."] }),
+            serde_json::json!({ "files": [{ "path": "dbs/../../outside/file", "size": 0, "crc32c": 0 }] }),
+            serde_json::json!({ "files": [{ "path": "/etc/passwd", "size": 0, "crc32c": 0 }] }),
+        ];
+        for changes in tampered {
+            let mut tampered = serde_json::to_value(&manifest).unwrap();
+            for (key, value) in changes.as_object().unwrap() {
+                tampered[key] = value.clone();
+            }
+            std::fs::write(
+                backup_dir.path().join("manifest.json"),
+                serde_json::to_vec(&tampered).unwrap(),
+            )
+            .unwrap();
+
+            assert_eq!(
+                restore_backup(backup_dir.path(), data.path(), true)
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidData
+            );
+            assert!(outside.is_dir());
+        }
+    }
+}
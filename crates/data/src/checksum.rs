@@ -0,0 +1,51 @@
+use crate::errors::ShardErrors;
+
+/// Size of the CRC32C every item of a data shard is prefixed with.
+pub const CHECKSUM_SIZE: usize = size_of::<u32>();
+
+/// Prefixes an item with the CRC32C of its bytes, as it is stored in data shards.
+pub fn with_checksum(item: &[u8]) -> Vec<u8> {
+    let mut stored = Vec::with_capacity(CHECKSUM_SIZE + item.len());
+    stored.extend_from_slice(&crc32c::crc32c(item).to_le_bytes());
+    stored.extend_from_slice(item);
+    stored
+}
+
+/// Verifies a stored item against its CRC32C, returning the item without it.
+pub fn verify_checksum(mut stored: Vec<u8>) -> Result<Vec<u8>, ShardErrors> {
+    if stored.len() < CHECKSUM_SIZE {
+        return Err(ShardErrors::Corrupted);
+    }
+
+    let item = stored.split_off(CHECKSUM_SIZE);
+    let checksum = u32::from_le_bytes(stored.try_into().unwrap());
+
+    if crc32c::crc32c(&item) == checksum {
+        Ok(item)
+    } else {
+        Err(ShardErrors::Corrupted)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::checksum::{verify_checksum, with_checksum};
+    use crate::errors::ShardErrors;
+
+    #[test]
+    fn test_checksum() {
+        let stored = with_checksum(b"Hello World");
+        assert_eq!(verify_checksum(stored.clone()).unwrap(), b"Hello World");
+
+        let mut flipped = stored.clone();
+        flipped[6] ^= 0b100;
+        assert!(matches!(
+            verify_checksum(flipped),
+            Err(ShardErrors::Corrupted)
+        ));
+        assert!(matches!(
+            verify_checksum(stored[..3].to_vec()),
+            Err(ShardErrors::Corrupted)
+        ));
+    }
+}
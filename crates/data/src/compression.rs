@@ -0,0 +1,55 @@
+use crate::errors::ShardErrors;
+use serde::{Deserialize, Serialize};
+
+/// Compression applied to every item of a `DataShard`.
+///
+/// It is stored in the shard header as a single byte, `None` being `0`
+/// so shards written before compression existed are read as uncompressed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Zstd,
+    Lz4,
+}
+
+/// Level used for zstd, favouring speed since rows are compressed on every insert.
+const ZSTD_LEVEL: i32 = 3;
+
+impl Compression {
+    pub fn to_byte(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Zstd => 1,
+            Compression::Lz4 => 2,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Zstd),
+            2 => Some(Compression::Lz4),
+            _ => None,
+        }
+    }
+
+    pub fn compress(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Compression::None => data.to_vec(),
+            Compression::Zstd => zstd::bulk::compress(data, ZSTD_LEVEL).unwrap(),
+            Compression::Lz4 => lz4_flex::compress_prepend_size(data),
+        }
+    }
+
+    pub fn decompress(self, data: Vec<u8>) -> Result<Vec<u8>, ShardErrors> {
+        match self {
+            Compression::None => Ok(data),
+            Compression::Zstd => zstd::stream::decode_all(data.as_slice())
+                .map_err(|_| ShardErrors::DecompressionError),
+            Compression::Lz4 => lz4_flex::decompress_size_prepended(&data)
+                .map_err(|_| ShardErrors::DecompressionError),
+        }
+    }
+}
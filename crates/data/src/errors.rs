@@ -26,4 +26,18 @@ pub enum ShardErrors {
     UnknownShard,
     #[error("Invalid locking detected")]
     InvalidLocking,
+    #[error("Item could not be decompressed")]
+    DecompressionError,
+    #[error("Shard was written with the unknown compression {0}")]
+    UnknownCompression(u8),
+    #[error("Shard was written by an older version of SchemaJS, run `schemajs upgrade-data` to upgrade it")]
+    OutdatedFormat,
+    #[error("Shard was written with the unsupported format version {0}")]
+    UnsupportedFormatVersion(u16),
+    #[error("Shard file could not be opened: {0}")]
+    FileError(String),
+    #[error("Reconciled rows could not be handed to the reconcile callback")]
+    ReconcileCallbackFailed,
+    #[error("Item is corrupted")]
+    Corrupted,
 }
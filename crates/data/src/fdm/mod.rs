@@ -80,6 +80,11 @@ impl FileDescriptorManager {
         }
     }
 
+    /// Drops the cached descriptor of `path`, so the file can be deleted or created again.
+    pub fn remove_path(&self, path: &PathBuf) {
+        self.cache.write().pop(path);
+    }
+
     pub fn remove_paths(&self, paths: Vec<PathBuf>) {
         let fdm = self.cache.clone();
         if !paths.is_empty() && self.max_size >= { fdm.read().len() } {
@@ -0,0 +1,158 @@
+use crate::errors::ShardErrors;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Version of the on-disk layout of shard files written by this build.
+///
+/// - `1`: Shard files start with a magic number and their format version.
+/// - `2`: Every item of a data shard is prefixed with its CRC32C.
+/// - `3`: The compression of a data shard is stored in the prefix, instead of the highest
+///   byte of its `max_offsets`.
+pub const FORMAT_VERSION: u16 = 3;
+
+/// Size of the prefix every shard file starts with: the magic number of its kind,
+/// the format version as a `u16`, the compression of data shards and a reserved byte.
+pub const FORMAT_PREFIX_SIZE: usize = 4 + 2 + 1 + 1;
+
+/// Position in the prefix of the byte holding the compression of a data shard.
+pub const FORMAT_COMPRESSION_POS: usize = 4 + 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardFileKind {
+    Data,
+    Kv,
+}
+
+impl ShardFileKind {
+    pub fn magic(&self) -> [u8; 4] {
+        match self {
+            ShardFileKind::Data => *b"SJSD",
+            ShardFileKind::Kv => *b"SJSK",
+        }
+    }
+
+    /// Guesses the kind of a shard file from its name, as given by the shards of tables
+    /// (`data_`), their temporary shards (`temp_`), their indexes (`indx`) and the row
+    /// locators of those (`locator_`).
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        if file_name.starts_with("data_") || file_name.starts_with("temp_") {
+            Some(ShardFileKind::Data)
+        } else if (file_name.starts_with("indx") && !file_name.ends_with(".bloom"))
+            || file_name.starts_with("locator_")
+        {
+            Some(ShardFileKind::Kv)
+        } else {
+            None
+        }
+    }
+
+    pub fn format_prefix(&self) -> [u8; FORMAT_PREFIX_SIZE] {
+        let mut prefix = [0u8; FORMAT_PREFIX_SIZE];
+        prefix[0..4].copy_from_slice(&self.magic());
+        prefix[4..6].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        prefix
+    }
+
+    /// Returns the format version of a shard file of this kind from its first bytes.
+    ///
+    /// Files written before the format was versioned do not start with a magic number,
+    /// those are reported as version `0`.
+    pub fn read_format_version(&self, bytes: &[u8]) -> u16 {
+        match bytes.get(0..FORMAT_PREFIX_SIZE) {
+            Some(prefix) if prefix[0..4] == self.magic() => {
+                u16::from_le_bytes([prefix[4], prefix[5]])
+            }
+            _ => 0,
+        }
+    }
+
+    /// Validates that a shard file of this kind can be read by this build.
+    pub fn validate(&self, bytes: &[u8]) -> Result<(), ShardErrors> {
+        match self.read_format_version(bytes) {
+            FORMAT_VERSION => Ok(()),
+            version if version < FORMAT_VERSION => Err(ShardErrors::OutdatedFormat),
+            version => Err(ShardErrors::UnsupportedFormatVersion(version)),
+        }
+    }
+}
+
+/// Checks that every shard file under `folder` was written in the format of this build,
+/// so outdated data is reported before any shard is opened rather than when a table loads.
+pub fn check_data_folder(folder: &Path) -> io::Result<()> {
+    for (path, kind) in find_shard_files(folder)? {
+        let mut prefix = Vec::with_capacity(FORMAT_PREFIX_SIZE);
+        File::open(&path)?
+            .take(FORMAT_PREFIX_SIZE as u64)
+            .read_to_end(&mut prefix)?;
+
+        // Empty shards are initialized with the current format once they are opened.
+        if prefix.is_empty() {
+            continue;
+        }
+
+        kind.validate(&prefix).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Shard {:?} cannot be opened: {}", path, err),
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Lists the shard files under `folder` and its subfolders, along with their kind.
+pub fn find_shard_files(folder: &Path) -> io::Result<Vec<(PathBuf, ShardFileKind)>> {
+    let mut files = vec![];
+
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            files.extend(find_shard_files(&path)?);
+            continue;
+        }
+
+        let kind = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(ShardFileKind::from_file_name);
+
+        if let Some(kind) = kind {
+            files.push((path, kind));
+        }
+    }
+
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::format::{check_data_folder, ShardFileKind};
+
+    #[test]
+    fn test_check_data_folder() {
+        let folder = tempfile::tempdir().unwrap();
+        let table = folder.path().join("public").join("users");
+        std::fs::create_dir_all(&table).unwrap();
+
+        std::fs::write(table.join("data_empty_0.data"), []).unwrap();
+        std::fs::write(table.join("notes.txt"), "not a shard").unwrap();
+        std::fs::write(
+            table.join("data_current_1.data"),
+            ShardFileKind::Data.format_prefix(),
+        )
+        .unwrap();
+        assert!(check_data_folder(folder.path()).is_ok());
+
+        // Written before the format was versioned.
+        std::fs::write(table.join("locator_data.data"), [0u8; 32]).unwrap();
+        let err = check_data_folder(folder.path()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("locator_data.data"));
+        assert!(err.to_string().contains("schemajs upgrade-data"));
+    }
+}
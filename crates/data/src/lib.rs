@@ -1,9 +1,15 @@
+pub mod backup;
+pub mod checksum;
+pub mod compression;
 pub mod data_handler;
 pub mod errors;
 pub mod fdm;
+pub mod format;
 pub mod shard;
 pub mod temp_offset_types;
+pub mod upgrade;
 pub mod utils;
+pub mod verify;
 
 // https://doc.rust-lang.org/std/mem/fn.size_of.html
 pub const U64_SIZE: usize = size_of::<u64>();
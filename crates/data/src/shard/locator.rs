@@ -0,0 +1,208 @@
+use crate::errors::ShardErrors;
+use crate::fdm::FileDescriptorManager;
+use crate::shard::shards::kv::config::KvShardConfig;
+use crate::shard::shards::kv::shard::KvShard;
+use crate::shard::shards::UUID_BYTE_LEN;
+use crate::shard::Shard;
+use crate::U64_SIZE;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Size of a locator entry: the id of the shard a row is stored in and its index in it.
+const LOCATION_SIZE: usize = UUID_BYTE_LEN as usize + U64_SIZE;
+
+/// Where a row is currently stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowLocation {
+    pub shard_id: Uuid,
+    pub index: u64,
+}
+
+impl RowLocation {
+    fn to_bytes(location: Option<RowLocation>) -> [u8; LOCATION_SIZE] {
+        let mut bytes = [0u8; LOCATION_SIZE];
+        // Removed rows are stored as a nil shard id.
+        if let Some(location) = location {
+            bytes[0..UUID_BYTE_LEN as usize].copy_from_slice(location.shard_id.as_bytes());
+            bytes[UUID_BYTE_LEN as usize..].copy_from_slice(&location.index.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<RowLocation> {
+        let shard_id = Uuid::from_slice(bytes.get(0..UUID_BYTE_LEN as usize)?).ok()?;
+        if shard_id.is_nil() {
+            return None;
+        }
+
+        let index = bytes.get(UUID_BYTE_LEN as usize..LOCATION_SIZE)?;
+        Some(RowLocation {
+            shard_id,
+            index: u64::from_le_bytes(index.try_into().ok()?),
+        })
+    }
+}
+
+/// Persisted mapping from the row ids of a `MapShard` to the shard and index the rows are
+/// stored at.
+///
+/// Row ids are handed out in insertion order and never change, so they can be kept by indexes
+/// while rows are moved between shards or the shard size changes.
+#[derive(Debug)]
+pub struct RowLocator {
+    shard: KvShard,
+}
+
+impl RowLocator {
+    pub fn new(path: PathBuf, fdm: Arc<FileDescriptorManager>) -> Result<Self, ShardErrors> {
+        let shard = KvShard::new(
+            path,
+            KvShardConfig {
+                value_size: LOCATION_SIZE,
+                max_capacity: None,
+            },
+            None,
+            fdm,
+        );
+
+        Ok(Self { shard })
+    }
+
+    pub fn len(&self) -> u64 {
+        self.shard.header.read().items_len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns where the row `row_id` is stored, `None` if it was removed.
+    pub fn get(&self, row_id: u64) -> Result<Option<RowLocation>, ShardErrors> {
+        if row_id >= self.len() {
+            return Err(ShardErrors::OutOfRange);
+        }
+
+        let entry = self.shard.read_item_from_index(row_id as usize)?;
+        Ok(RowLocation::from_bytes(&entry))
+    }
+
+    /// Assigns row ids to newly stored rows, returning the id of the first one.
+    pub fn push(&self, locations: &[Option<RowLocation>]) -> Result<u64, ShardErrors> {
+        let first_row_id = self.len();
+        if locations.is_empty() {
+            return Ok(first_row_id);
+        }
+
+        let entries: Vec<[u8; LOCATION_SIZE]> = locations
+            .iter()
+            .map(|location| RowLocation::to_bytes(*location))
+            .collect();
+        let entries: Vec<&[u8]> = entries.iter().map(|entry| entry.as_slice()).collect();
+        self.shard.insert_item(&entries)?;
+
+        Ok(first_row_id)
+    }
+
+    /// Points an existing row id to a new location, or marks the row as removed.
+    pub fn set(&self, row_id: u64, location: Option<RowLocation>) -> Result<(), ShardErrors> {
+        if row_id >= self.len() {
+            return Err(ShardErrors::OutOfRange);
+        }
+
+        self.shard
+            .set_element(row_id as usize, &RowLocation::to_bytes(location))
+    }
+
+    /// Returns the row ids of the rows stored in `shard_id`, by their index in the shard.
+    ///
+    /// Goes through every stored location rather than keeping an entry per row in memory, as it
+    /// is only needed when a shard is compacted.
+    pub fn rows_in_shard(&self, shard_id: Uuid) -> Result<HashMap<u64, u64>, ShardErrors> {
+        let mut rows = HashMap::new();
+        for row_id in 0..self.len() {
+            if let Some(location) = self.get(row_id)? {
+                if location.shard_id == shard_id {
+                    rows.insert(location.index, row_id);
+                }
+            }
+        }
+
+        Ok(rows)
+    }
+
+    /// Writes the locations set so far through to the disk.
+    pub fn sync(&self) -> Result<(), ShardErrors> {
+        self.shard
+            .data
+            .write()
+            .operate(|file| file.sync_data())
+            .map_err(|err| ShardErrors::FileError(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fdm::FileDescriptorManager;
+    use crate::shard::locator::{RowLocation, RowLocator};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_rows_in_shard() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("locator_data.data");
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let at = |shard_id, index| Some(RowLocation { shard_id, index });
+
+        {
+            let locator =
+                RowLocator::new(path.clone(), Arc::new(FileDescriptorManager::new(10))).unwrap();
+            locator
+                .push(&[at(first, 0), at(first, 1), at(second, 0), None])
+                .unwrap();
+
+            // Compacting `first` moves row 1 to index 0 and removes row 0.
+            locator.set(1, at(first, 0)).unwrap();
+            locator.set(0, None).unwrap();
+            assert_eq!(
+                locator
+                    .rows_in_shard(first)
+                    .unwrap()
+                    .into_iter()
+                    .collect::<Vec<_>>(),
+                vec![(0, 1)]
+            );
+            assert_eq!(
+                locator
+                    .rows_in_shard(second)
+                    .unwrap()
+                    .into_iter()
+                    .collect::<Vec<_>>(),
+                vec![(0, 2)]
+            );
+            locator.sync().unwrap();
+        }
+
+        // The rows are found from the stored locations.
+        let locator = RowLocator::new(path, Arc::new(FileDescriptorManager::new(10))).unwrap();
+        assert_eq!(
+            locator
+                .rows_in_shard(first)
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![(0, 1)]
+        );
+        assert_eq!(
+            locator
+                .rows_in_shard(second)
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![(0, 2)]
+        );
+        assert!(locator.rows_in_shard(Uuid::new_v4()).unwrap().is_empty());
+    }
+}
@@ -1,19 +1,62 @@
 use crate::errors::ShardErrors;
 use crate::fdm::FileDescriptorManager;
+use crate::shard::locator::{RowLocation, RowLocator};
+use crate::shard::shards::UUID_BYTE_LEN;
 use crate::shard::{AvailableSpace, Shard, ShardConfig};
 use crate::utils::fs::list_files_with_prefix;
+use crate::U64_SIZE;
 use indexmap::IndexMap;
 use parking_lot::RwLock;
+use std::fs::File;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Prefix of the files past master shards are rewritten to while they are compacted.
+pub const COMPACTING_PREFIX: &str = "compacting_";
+
+/// Prefix of the file the row locator of a `MapShard` is stored in.
+const LOCATOR_PREFIX: &str = "locator_";
+
+/// Suffix of the file the new locations of the rows of a compacted shard are written to before
+/// it is swapped in, see `MapShard::swap_shard`.
+const JOURNAL_SUFFIX: &str = ".journal";
+
+/// Index written to a journal for the rows left out of a compacted shard.
+const REMOVED_INDEX: u64 = u64::MAX;
+
+/// A row that was given a new index in its shard when the shard was compacted.
+#[derive(Debug)]
+pub struct MovedRow {
+    pub row_id: u64,
+    pub from: u64,
+    pub to: u64,
+}
+
+/// A past master shard rewritten by `MapShard::rewrite_shard`, waiting to be swapped in.
+#[derive(Debug)]
+pub struct CompactedShard {
+    pub shard_id: String,
+    pub path: PathBuf,
+    temp_path: PathBuf,
+    pub moved: Vec<MovedRow>,
+    /// Ids of the rows that were left out of the rewritten shard.
+    pub removed: Vec<u64>,
+}
+
+/// Shards of a table or index, filled one after the other.
+///
+/// Rows are addressed by a row id, given in insertion order, which the `RowLocator` of the
+/// shards maps to the shard and index the row is stored at.
 #[derive(Debug)]
 pub struct MapShard<S: Shard<Opts>, Opts: ShardConfig> {
     pub current_master_shard: S,
     pub past_master_shards: RwLock<IndexMap<String, S>>,
     pub shard_prefix: String,
     pub shards_folder: PathBuf,
+    pub locator: RowLocator,
+    current_master_id: Uuid,
     config: Opts,
     fdm: Arc<FileDescriptorManager>,
 }
@@ -26,7 +69,22 @@ impl<S: Shard<Opts>, Opts: ShardConfig> MapShard<S, Opts> {
         fdm: Arc<FileDescriptorManager>,
     ) -> Self {
         let shards_folder = shards_folder.as_ref().to_path_buf();
-        let shard_files = list_files_with_prefix(&shards_folder, shard_prefix).unwrap();
+        Self::open(shards_folder.clone(), shard_prefix, config, fdm)
+            .unwrap_or_else(|err| panic!("Shards {:?} could not be opened: {}", shards_folder, err))
+    }
+
+    /// Opens the shards of `shards_folder` named after `shard_prefix`, finishing the shard swaps
+    /// a crash interrupted first.
+    pub fn open<P: AsRef<Path> + Clone>(
+        shards_folder: P,
+        shard_prefix: &str,
+        config: Opts,
+        fdm: Arc<FileDescriptorManager>,
+    ) -> Result<Self, ShardErrors> {
+        let shards_folder = shards_folder.as_ref().to_path_buf();
+        let journals = Self::restore_compacted_shards(&shards_folder, shard_prefix)?;
+        let shard_files = list_files_with_prefix(&shards_folder, shard_prefix)
+            .map_err(|err| ShardErrors::FileError(err.to_string()))?;
         let mut sorted_files: Vec<(usize, String, PathBuf)> = Vec::new();
 
         for path in shard_files {
@@ -66,19 +124,202 @@ impl<S: Shard<Opts>, Opts: ShardConfig> MapShard<S, Opts> {
             }
         }
 
-        MapShard {
-            current_master_shard: S::new(
-                current_master_shard,
-                config.clone(),
-                Some(maybe_new_shard_id),
-                fdm.clone(),
-            ),
+        let locator = RowLocator::new(
+            shards_folder.join(format!(
+                "{}{}.data",
+                LOCATOR_PREFIX,
+                shard_prefix.trim_end_matches('_')
+            )),
+            fdm.clone(),
+        )?;
+
+        let current_master_shard = S::new(
+            current_master_shard,
+            config.clone(),
+            Some(maybe_new_shard_id),
+            fdm.clone(),
+        );
+
+        let map_shard = MapShard {
+            current_master_id: Self::shard_id(&current_master_shard),
+            current_master_shard,
             past_master_shards: RwLock::new(past_master_shards),
             shard_prefix: shard_prefix.to_string(),
             shards_folder,
+            locator,
             config,
             fdm,
+        };
+
+        for journal in journals {
+            Self::apply_journal(&map_shard.locator, &journal)?;
         }
+
+        if map_shard.locator.is_empty() {
+            map_shard.locate_existing_rows()?;
+        }
+
+        Ok(map_shard)
+    }
+
+    /// Path of the journal of the shard rewritten to `temp_path`.
+    fn journal_path(temp_path: &Path) -> PathBuf {
+        let mut path = temp_path.as_os_str().to_owned();
+        path.push(JOURNAL_SUFFIX);
+        PathBuf::from(path)
+    }
+
+    /// Puts the compacted shards whose journal was written in place of the shards they were
+    /// rewritten from, returning their journals. Leftovers of compactions interrupted before
+    /// that are removed.
+    fn restore_compacted_shards(
+        shards_folder: &Path,
+        shard_prefix: &str,
+    ) -> Result<Vec<PathBuf>, ShardErrors> {
+        let compacting_prefix = format!("{}{}", COMPACTING_PREFIX, shard_prefix);
+        let files = list_files_with_prefix(shards_folder, &compacting_prefix)
+            .map_err(|err| ShardErrors::FileError(err.to_string()))?;
+        let (journals, leftovers): (Vec<PathBuf>, Vec<PathBuf>) = files
+            .into_iter()
+            .partition(|path| path.to_string_lossy().ends_with(JOURNAL_SUFFIX));
+
+        for journal in &journals {
+            let temp_path = PathBuf::from(
+                journal
+                    .to_string_lossy()
+                    .trim_end_matches(JOURNAL_SUFFIX)
+                    .to_string(),
+            );
+
+            // Once renamed, the compacted shard is not found under its temporary name anymore.
+            if temp_path.exists() {
+                let file_name = temp_path.file_name().unwrap().to_string_lossy();
+                let path = shards_folder.join(file_name.trim_start_matches(COMPACTING_PREFIX));
+                std::fs::rename(&temp_path, &path)
+                    .map_err(|err| ShardErrors::FileError(err.to_string()))?;
+            }
+        }
+
+        for leftover in leftovers {
+            if !journals.contains(&Self::journal_path(&leftover)) {
+                std::fs::remove_file(&leftover)
+                    .map_err(|err| ShardErrors::FileError(err.to_string()))?;
+            }
+        }
+
+        Ok(journals)
+    }
+
+    /// Writes where the rows of `compacted` are once it is swapped in, next to it. The journal
+    /// only appears under its name once it is complete.
+    fn write_journal(compacted: &CompactedShard) -> Result<(), ShardErrors> {
+        let shard_id =
+            Uuid::parse_str(&compacted.shard_id).map_err(|_| ShardErrors::UnknownShard)?;
+
+        let mut bytes = shard_id.as_bytes().to_vec();
+        let rows = compacted
+            .moved
+            .iter()
+            .map(|row| (row.row_id, row.to))
+            .chain(
+                compacted
+                    .removed
+                    .iter()
+                    .map(|row_id| (*row_id, REMOVED_INDEX)),
+            );
+        for (row_id, index) in rows {
+            bytes.extend_from_slice(&row_id.to_le_bytes());
+            bytes.extend_from_slice(&index.to_le_bytes());
+        }
+
+        let journal = Self::journal_path(&compacted.temp_path);
+        let mut partial = journal.clone().into_os_string();
+        partial.push(".partial");
+
+        let write = || -> std::io::Result<()> {
+            // The compacted shard must be on disk before the journal says it can be used.
+            File::open(&compacted.temp_path)?.sync_all()?;
+
+            let mut file = File::create(&partial)?;
+            file.write_all(&bytes)?;
+            file.sync_all()?;
+            std::fs::rename(&partial, &journal)
+        };
+
+        write().map_err(|err| ShardErrors::FileError(err.to_string()))
+    }
+
+    /// Points the row ids of `journal` to their new location and removes it. Applying a journal
+    /// twice gives the same locations.
+    fn apply_journal(locator: &RowLocator, journal: &Path) -> Result<(), ShardErrors> {
+        let bytes =
+            std::fs::read(journal).map_err(|err| ShardErrors::FileError(err.to_string()))?;
+        let shard_id = bytes
+            .get(0..UUID_BYTE_LEN as usize)
+            .and_then(|bytes| Uuid::from_slice(bytes).ok())
+            .ok_or(ShardErrors::Corrupted)?;
+
+        for entry in bytes[UUID_BYTE_LEN as usize..].chunks(U64_SIZE * 2) {
+            if entry.len() != U64_SIZE * 2 {
+                return Err(ShardErrors::Corrupted);
+            }
+
+            let row_id = u64::from_le_bytes(entry[..U64_SIZE].try_into().unwrap());
+            let index = u64::from_le_bytes(entry[U64_SIZE..].try_into().unwrap());
+            let location = (index != REMOVED_INDEX).then_some(RowLocation { shard_id, index });
+            locator.set(row_id, location)?;
+        }
+
+        locator.sync()?;
+        std::fs::remove_file(journal).map_err(|err| ShardErrors::FileError(err.to_string()))
+    }
+
+    /// Assigns row ids to the rows of shards written before they had a locator.
+    ///
+    /// Those rows were addressed by their global position, the number of past master shards
+    /// before them times the breaking point plus their index. Row ids are given the same way,
+    /// leaving the positions of missing rows empty, so existing index entries keep pointing to them.
+    fn locate_existing_rows(&self) -> Result<(), ShardErrors> {
+        let breaking_point = self.current_master_shard.breaking_point();
+        let mut locations = vec![];
+
+        let reader = self.past_master_shards.read();
+        let shards = reader
+            .iter()
+            .map(|(shard_id, shard)| (Uuid::parse_str(shard_id).ok(), shard, true))
+            .chain(std::iter::once((
+                Some(self.current_master_id),
+                &self.current_master_shard,
+                false,
+            )));
+
+        for (shard_id, shard, is_past) in shards {
+            // A shard never holds more rows than its breaking point.
+            let rows = (shard.get_last_index() + 1).max(0) as u64;
+            let rows = shard.breaking_point().map_or(rows, |max| rows.min(max));
+            let slots = match breaking_point {
+                Some(breaking_point) if is_past => breaking_point.max(rows),
+                _ => rows,
+            };
+
+            locations.extend((0..slots).map(|index| {
+                shard_id
+                    .filter(|_| index < rows)
+                    .map(|shard_id| RowLocation { shard_id, index })
+            }));
+        }
+
+        self.locator.push(&locations)?;
+        Ok(())
+    }
+
+    /// Id of a shard, as found in its file name. Shards whose name has no id, such as the ones of
+    /// unlimited indexes, are identified by the id in their header.
+    fn shard_id(shard: &S) -> Uuid {
+        Self::extract_shard_signature(shard.get_path())
+            .and_then(|(_, uuid, _)| Uuid::parse_str(&uuid).ok())
+            .or_else(|| Uuid::parse_str(&shard.get_id()).ok())
+            .unwrap_or_default()
     }
 
     fn generate_shard_name(shard_prefix: &str, maybe_new_shard_id: Uuid, number: usize) -> String {
@@ -108,6 +349,12 @@ impl<S: Shard<Opts>, Opts: ShardConfig> MapShard<S, Opts> {
         Some((number, uuid, path))
     }
 
+    /// Replaces the config new shards are created with. Existing shards are left as they are.
+    pub fn set_config(&mut self, config: Opts) {
+        self.config = config;
+    }
+
+    /// Inserts rows after the existing ones, returning the row id of the first one.
     pub fn insert_rows(&mut self, data: &[&[u8]]) -> usize {
         self.raw_insert_rows(data, false)
     }
@@ -119,8 +366,8 @@ impl<S: Shard<Opts>, Opts: ShardConfig> MapShard<S, Opts> {
                     .unwrap();
             let new_shard_number = shard_number + 1;
 
+            let shard_id = Uuid::new_v4();
             let shard = {
-                let shard_id = Uuid::new_v4();
                 let shard_path = self.shards_folder.clone().join(Self::generate_shard_name(
                     self.shard_prefix.as_str(),
                     shard_id,
@@ -138,6 +385,7 @@ impl<S: Shard<Opts>, Opts: ShardConfig> MapShard<S, Opts> {
             // Add to past master
             {
                 let old_master = std::mem::replace(&mut self.current_master_shard, shard);
+                self.current_master_id = shard_id;
                 let mut past_ms_writer = self.past_master_shards.write();
                 let (_, shard_id, _) =
                     Self::extract_shard_signature(old_master.get_path()).unwrap();
@@ -145,6 +393,10 @@ impl<S: Shard<Opts>, Opts: ShardConfig> MapShard<S, Opts> {
             }
         }
 
+        if data.is_empty() {
+            return self.locator.len() as usize;
+        }
+
         let available_space_in_master = self.current_master_shard.available_space();
 
         if let AvailableSpace::Fixed(size) = available_space_in_master {
@@ -160,31 +412,29 @@ impl<S: Shard<Opts>, Opts: ShardConfig> MapShard<S, Opts> {
 
         let insert_data = &data[0..up_to];
 
-        let items_pos = {
-            let local_index = self.current_master_shard.insert_item(insert_data).unwrap();
-            let breaking_point = self.breaking_point();
-            match breaking_point {
-                None => local_index as usize,
-                Some(breaking_point) => {
-                    let reader = self.past_master_shards.read();
-                    let curr_items = reader.len() * breaking_point as usize;
-                    curr_items + local_index as usize
-                }
-            }
+        let row_id = {
+            let first_index = (self.current_master_shard.get_last_index() + 1) as u64;
+            self.current_master_shard.insert_item(insert_data).unwrap();
+
+            let locations: Vec<Option<RowLocation>> = (first_index..first_index + up_to as u64)
+                .map(|index| {
+                    Some(RowLocation {
+                        shard_id: self.current_master_id,
+                        index,
+                    })
+                })
+                .collect();
+
+            self.locator.push(&locations).unwrap() as usize
         };
 
         if data.len() > up_to {
             // Some data didn't fit, insert remaining data into a new shard
             let remaining_data = &data[up_to..];
-            let remaining_index = self.raw_insert_rows(remaining_data, true);
-            items_pos + remaining_index
-        } else {
-            items_pos
+            self.raw_insert_rows(remaining_data, true);
         }
-    }
 
-    fn breaking_point(&self) -> Option<u64> {
-        self.current_master_shard.breaking_point()
+        row_id
     }
 
     pub fn get_element_from_specific(
@@ -199,60 +449,178 @@ impl<S: Shard<Opts>, Opts: ShardConfig> MapShard<S, Opts> {
         self.get_element_from_specific(&self.current_master_shard, index)
     }
 
-    pub fn get_element(&self, index: usize) -> Result<Vec<u8>, ShardErrors> {
-        let breaking_point = self.breaking_point();
+    /// Reads the row `row_id`, wherever it is currently stored.
+    pub fn get_element(&self, row_id: usize) -> Result<Vec<u8>, ShardErrors> {
+        let location = self
+            .locator
+            .get(row_id as u64)?
+            .ok_or(ShardErrors::UnknownEntry)?;
 
-        match breaking_point {
-            None => self.get_element_from_master(index),
-            Some(breaking_point) => {
-                let breaking_point_usize = breaking_point as usize;
+        if location.shard_id == self.current_master_id {
+            return self.get_element_from_master(location.index as usize);
+        }
 
-                let reader = self.past_master_shards.read();
-                let shard_reversed = {
-                    let mut combined_shards: Vec<&S> = reader.values().rev().collect();
-                    combined_shards.push(&self.current_master_shard);
-                    combined_shards
-                };
+        let reader = self.past_master_shards.read();
+        let shard = reader
+            .get(&location.shard_id.to_string())
+            .ok_or(ShardErrors::UnknownShard)?;
 
-                // Calculate the total number of shards
-                let num_shards = shard_reversed.len();
-                // Determine which shard the index belongs to
-                let shard_index = index / breaking_point_usize;
+        self.get_element_from_specific(shard, location.index as usize)
+    }
 
-                if shard_index >= num_shards {
-                    return Err(ShardErrors::OutOfRange);
-                }
+    /// Rewrites the past master shard `shard_id` next to it, with only the rows `keep` returns
+    /// true for, given their row id and bytes. Items no row id points to are left out as well.
+    /// Readers keep using the current shard until the rewritten one is swapped in through
+    /// `swap_shard`.
+    ///
+    /// Returns `None` if every item is kept, since there is no space to reclaim.
+    pub fn rewrite_shard<F>(
+        &self,
+        shard_id: &str,
+        mut keep: F,
+    ) -> Result<Option<CompactedShard>, ShardErrors>
+    where
+        F: FnMut(u64, &[u8]) -> bool,
+    {
+        let reader = self.past_master_shards.read();
+        let shard = reader.get(shard_id).ok_or(ShardErrors::UnknownShard)?;
+        let uuid = Uuid::parse_str(shard_id).map_err(|_| ShardErrors::UnknownShard)?;
+        let rows = self.locator.rows_in_shard(uuid)?;
+
+        let items = (shard.get_last_index() + 1) as u64;
+        let mut kept = vec![];
+        let mut removed = vec![];
+        for index in 0..items {
+            let Some(&row_id) = rows.get(&index) else {
+                continue;
+            };
 
-                // Calculate the local index within the selected shard
-                let local_index = index % breaking_point_usize;
+            let item = shard.read_item_from_index(index as usize)?;
+            if keep(row_id, &item) {
+                kept.push((row_id, index, item));
+            } else {
+                removed.push(row_id);
+            }
+        }
+
+        if kept.len() as u64 == items {
+            return Ok(None);
+        }
 
-                self.get_element_from_specific(shard_reversed[shard_index], local_index)
+        let path = shard.get_path();
+        let file_name = path.file_name().unwrap().to_string_lossy();
+        let temp_path = self
+            .shards_folder
+            .join(format!("{}{}", COMPACTING_PREFIX, file_name));
+
+        // Leftovers of an interrupted compaction would otherwise be opened as they are.
+        self.fdm.remove_path(&temp_path);
+        let _ = std::fs::remove_file(Self::journal_path(&temp_path));
+        let _ = std::fs::remove_file(&temp_path);
+
+        let compacted = S::new(
+            temp_path.clone(),
+            self.config.clone(),
+            Some(uuid),
+            self.fdm.clone(),
+        );
+
+        let mut moved = vec![];
+        for (to, (row_id, from, item)) in kept.into_iter().enumerate() {
+            compacted.insert_item(&[&item])?;
+
+            let to = to as u64;
+            if from != to {
+                moved.push(MovedRow { row_id, from, to });
             }
         }
+
+        self.fdm.remove_path(&temp_path);
+
+        Ok(Some(CompactedShard {
+            shard_id: shard_id.to_string(),
+            path,
+            temp_path,
+            moved,
+            removed,
+        }))
+    }
+
+    /// Replaces a past master shard with its rewritten version and points the row ids of its
+    /// rows to their new index.
+    ///
+    /// The new locations are written to a journal before the rewritten file is renamed over the
+    /// previous one, and the journal is removed once the locator is updated. A swap interrupted
+    /// by a crash is finished from the journal when the shards are opened again, so row ids never
+    /// point to another row.
+    pub fn swap_shard(&mut self, compacted: &CompactedShard) -> Result<(), ShardErrors> {
+        let mut writer = self.past_master_shards.write();
+        if !writer.contains_key(&compacted.shard_id) {
+            return Err(ShardErrors::UnknownShard);
+        }
+
+        Self::write_journal(compacted)?;
+
+        if std::fs::rename(&compacted.temp_path, &compacted.path).is_err() {
+            // The previous shard is still in place, so its rows must not be moved.
+            let _ = std::fs::remove_file(Self::journal_path(&compacted.temp_path));
+            return Err(ShardErrors::FlushingError);
+        }
+        self.fdm.remove_path(&compacted.path);
+
+        let shard_id = Uuid::parse_str(&compacted.shard_id).ok();
+        let shard = S::new(
+            compacted.path.clone(),
+            self.config.clone(),
+            shard_id,
+            self.fdm.clone(),
+        );
+        writer.insert(compacted.shard_id.clone(), shard);
+
+        Self::apply_journal(&self.locator, &Self::journal_path(&compacted.temp_path))
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::compression::Compression;
+    use crate::errors::ShardErrors;
     use crate::fdm::FileDescriptorManager;
-    use crate::shard::map_shard::MapShard;
+    use crate::shard::map_shard::{MapShard, COMPACTING_PREFIX};
     use crate::shard::shards::data_shard::config::DataShardConfig;
     use crate::shard::shards::data_shard::shard::DataShard;
     use crate::shard::Shard;
     use parking_lot::RwLock;
+    use std::path::Path;
     use std::sync::Arc;
     use uuid::Uuid;
 
+    fn copy_fixture(name: &str) -> tempfile::TempDir {
+        let fixture = std::env::current_dir()
+            .unwrap()
+            .join("test_cases/fake-db-folder")
+            .join(name);
+        let folder = tempfile::tempdir().unwrap();
+        for entry in std::fs::read_dir(fixture).unwrap() {
+            let entry = entry.unwrap();
+            std::fs::copy(entry.path(), folder.path().join(entry.file_name())).unwrap();
+        }
+
+        folder
+    }
+
     #[tokio::test]
     pub async fn test_context_creation_empty_table() {
-        let fake_empty_table_path = std::env::current_dir()
-            .unwrap()
-            .join("./test_cases/fake-db-folder/fake-empty-table");
+        // Opening the shards writes their locator, so the fixture is opened from a copy.
+        let fake_empty_table = copy_fixture("fake-empty-table");
 
         let context = MapShard::<DataShard, DataShardConfig>::new(
-            fake_empty_table_path,
+            fake_empty_table.path(),
             "data_",
-            DataShardConfig { max_offsets: None },
+            DataShardConfig {
+                max_offsets: None,
+                compression: Compression::None,
+            },
             Arc::new(FileDescriptorManager::new(2500)),
         );
 
@@ -271,13 +639,14 @@ mod test {
 
     #[tokio::test]
     pub async fn test_context_creation_partial_table() {
-        let fake_partial_folder_path = std::env::current_dir()
-            .unwrap()
-            .join("./test_cases/fake-db-folder/fake-partial-folder");
+        let fake_partial_folder = copy_fixture("fake-partial-folder");
         let context = MapShard::<DataShard, DataShardConfig>::new(
-            fake_partial_folder_path,
+            fake_partial_folder.path(),
             "data_",
-            DataShardConfig { max_offsets: None },
+            DataShardConfig {
+                max_offsets: None,
+                compression: Compression::None,
+            },
             Arc::new(FileDescriptorManager::new(2500)),
         );
         assert!(!context.past_master_shards.read().is_empty());
@@ -307,6 +676,7 @@ mod test {
             "data_",
             DataShardConfig {
                 max_offsets: Some(1),
+                compression: Compression::None,
             },
             Arc::new(FileDescriptorManager::new(2500)),
         );
@@ -354,17 +724,14 @@ mod test {
 
     #[tokio::test]
     pub async fn test_global_get_element() {
-        let fake_partial_folder_path = std::env::current_dir().unwrap().join(format!(
-            "./test_cases/fake-db-folder/{}",
-            Uuid::new_v4().to_string()
-        ));
-        std::fs::create_dir(&fake_partial_folder_path).unwrap();
+        let folder = tempfile::tempdir().unwrap();
 
         let mut context = MapShard::<DataShard, DataShardConfig>::new(
-            fake_partial_folder_path.clone(),
+            folder.path(),
             "data_",
             DataShardConfig {
                 max_offsets: Some(1),
+                compression: Compression::None,
             },
             Arc::new(FileDescriptorManager::new(2500)),
         );
@@ -377,5 +744,227 @@ mod test {
         ]);
 
         context.get_element(3).unwrap();
+        assert_eq!(context.get_element(0).unwrap(), b"1".to_vec());
+    }
+
+    #[tokio::test]
+    pub async fn test_get_element_follows_shard_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path();
+
+        let open = || {
+            MapShard::<DataShard, DataShardConfig>::new(
+                folder,
+                "data_",
+                DataShardConfig {
+                    max_offsets: Some(1),
+                    compression: Compression::None,
+                },
+                Arc::new(FileDescriptorManager::new(2500)),
+            )
+        };
+
+        // More than ten shards, so shard 10 sorts after shard 9 only when ordered by number.
+        let items: Vec<Vec<u8>> = (0..12).map(|i| i.to_string().into_bytes()).collect();
+        {
+            let mut context = open();
+            for item in &items {
+                context.insert_rows(&[item]);
+            }
+        }
+
+        let check = || {
+            let context = open();
+            for (row_id, item) in items.iter().enumerate() {
+                assert_eq!(&context.get_element(row_id).unwrap(), item);
+            }
+        };
+
+        check();
+        // Without a locator, rows are found from the position of their shard.
+        std::fs::remove_file(folder.join("locator_data.data")).unwrap();
+        check();
+    }
+
+    #[tokio::test]
+    pub async fn test_compact_past_master_shard() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path();
+
+        let mut context = MapShard::<DataShard, DataShardConfig>::new(
+            folder,
+            "data_",
+            DataShardConfig {
+                max_offsets: Some(2),
+                compression: Compression::None,
+            },
+            Arc::new(FileDescriptorManager::new(2500)),
+        );
+
+        for item in [b"1", b"2", b"3", b"4", b"5"] {
+            context.insert_rows(&[item]);
+        }
+
+        let shard_id = context
+            .past_master_shards
+            .read()
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+
+        let compacted = context
+            .rewrite_shard(&shard_id, |row_id, _| row_id != 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(compacted.removed, vec![0]);
+        assert_eq!(compacted.moved.len(), 1);
+        assert_eq!(
+            (
+                compacted.moved[0].row_id,
+                compacted.moved[0].from,
+                compacted.moved[0].to
+            ),
+            (1, 1, 0)
+        );
+
+        // Readers see the previous shard until it is swapped in.
+        assert_eq!(context.get_element(0).unwrap(), b"1".to_vec());
+        context.swap_shard(&compacted).unwrap();
+
+        assert!(context.get_element(0).is_err());
+        assert_eq!(context.get_element(1).unwrap(), b"2".to_vec());
+        assert_eq!(context.get_element(2).unwrap(), b"3".to_vec());
+        assert_eq!(context.get_element(4).unwrap(), b"5".to_vec());
+        assert!(context
+            .rewrite_shard(&shard_id, |_, _| true)
+            .unwrap()
+            .is_none());
+        // Three shards and the locator.
+        assert_eq!(std::fs::read_dir(folder).unwrap().count(), 4);
+    }
+
+    #[tokio::test]
+    pub async fn test_interrupted_swap_is_finished_on_open() {
+        let open = |folder: &Path| {
+            MapShard::<DataShard, DataShardConfig>::new(
+                folder,
+                "data_",
+                DataShardConfig {
+                    max_offsets: Some(2),
+                    compression: Compression::None,
+                },
+                Arc::new(FileDescriptorManager::new(2500)),
+            )
+        };
+
+        // Compacts the first shard, stopping after `steps` steps of the swap, as a crash would.
+        let compact_until = |steps: usize| {
+            let folder = tempfile::tempdir().unwrap();
+            let mut context = open(folder.path());
+            for item in [b"1", b"2", b"3", b"4", b"5"] {
+                context.insert_rows(&[item]);
+            }
+
+            let shard_id = context
+                .past_master_shards
+                .read()
+                .keys()
+                .next()
+                .unwrap()
+                .clone();
+            let compacted = context
+                .rewrite_shard(&shard_id, |row_id, _| row_id != 0)
+                .unwrap()
+                .unwrap();
+
+            if steps > 0 {
+                MapShard::<DataShard, DataShardConfig>::write_journal(&compacted).unwrap();
+            }
+            if steps > 1 {
+                std::fs::rename(&compacted.temp_path, &compacted.path).unwrap();
+            }
+
+            folder
+        };
+
+        let leftovers = |folder: &Path| {
+            std::fs::read_dir(folder)
+                .unwrap()
+                .filter(|entry| {
+                    let name = entry.as_ref().unwrap().file_name();
+                    name.to_string_lossy().starts_with(COMPACTING_PREFIX)
+                })
+                .count()
+        };
+
+        // Interrupted before the new locations were written, the previous shard is kept.
+        let folder = compact_until(0);
+        let context = open(folder.path());
+        assert_eq!(context.get_element(0).unwrap(), b"1".to_vec());
+        assert_eq!(context.get_element(1).unwrap(), b"2".to_vec());
+        assert_eq!(leftovers(folder.path()), 0);
+
+        // Interrupted before or after the rename, the swap is finished.
+        for steps in [1, 2] {
+            let folder = compact_until(steps);
+            let context = open(folder.path());
+            assert!(context.get_element(0).is_err());
+            assert_eq!(context.get_element(1).unwrap(), b"2".to_vec());
+            assert_eq!(context.get_element(2).unwrap(), b"3".to_vec());
+            assert_eq!(context.get_element(4).unwrap(), b"5".to_vec());
+            assert_eq!(leftovers(folder.path()), 0);
+            drop(context);
+
+            // Opening it again finds nothing left to do.
+            let context = open(folder.path());
+            assert_eq!(context.get_element(1).unwrap(), b"2".to_vec());
+        }
+    }
+
+    #[tokio::test]
+    pub async fn test_row_ids_survive_shard_size_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path();
+
+        let open = |max_offsets: u64| {
+            MapShard::<DataShard, DataShardConfig>::new(
+                folder,
+                "data_",
+                DataShardConfig {
+                    max_offsets: Some(max_offsets),
+                    compression: Compression::None,
+                },
+                Arc::new(FileDescriptorManager::new(2500)),
+            )
+        };
+
+        {
+            let mut context = open(2);
+            for item in [b"1", b"2", b"3"] {
+                context.insert_rows(&[item]);
+            }
+        }
+
+        // Shards written before the locator existed keep their global positions as row ids.
+        std::fs::remove_file(folder.join("locator_data.data")).unwrap();
+        {
+            let context = open(2);
+            assert_eq!(context.get_element(0).unwrap(), b"1".to_vec());
+            assert_eq!(context.get_element(2).unwrap(), b"3".to_vec());
+            assert!(matches!(
+                context.get_element(3),
+                Err(ShardErrors::OutOfRange)
+            ));
+        }
+
+        let mut context = open(3);
+        assert_eq!(context.insert_rows(&[b"4", b"5", b"6", b"7"]), 3);
+        for (row_id, item) in [b"1", b"2", b"3", b"4", b"5", b"6", b"7"]
+            .iter()
+            .enumerate()
+        {
+            assert_eq!(context.get_element(row_id).unwrap(), item.to_vec());
+        }
     }
 }
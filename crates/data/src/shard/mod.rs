@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use uuid::Uuid;
 
+pub mod locator;
 pub mod map_shard;
 pub mod shards;
 pub mod temp_collection;
@@ -1,9 +1,12 @@
+use crate::compression::Compression;
 use crate::shard::{ShardConfig, TempShardConfig};
 use crate::temp_offset_types::TempOffsetTypes;
 
 #[derive(Clone, Debug)]
 pub struct DataShardConfig {
     pub max_offsets: Option<u64>,
+    /// Compression of the items of new shards. Existing shards keep the one in their header.
+    pub compression: Compression,
 }
 
 impl ShardConfig for DataShardConfig {}
@@ -15,8 +18,11 @@ pub struct TempDataShardConfig {
 
 impl TempShardConfig<DataShardConfig> for TempDataShardConfig {
     fn to_config(&self) -> DataShardConfig {
+        // Temporary shards are short-lived, so their items are compressed
+        // only once they are reconciled into the table.
         DataShardConfig {
             max_offsets: self.max_offsets.get_real_offset(),
+            compression: Compression::None,
         }
     }
 }
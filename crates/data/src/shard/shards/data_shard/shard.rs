@@ -1,3 +1,4 @@
+use crate::checksum::{verify_checksum, with_checksum};
 use crate::data_handler::DataHandler;
 use crate::errors::ShardErrors;
 use crate::fdm::FileDescriptorManager;
@@ -53,35 +54,60 @@ impl DataShard {
                     end_reading as u64
                 };
 
-                let length = (end_pos - start_pos) as usize;
+                // Offsets out of order can only come from a damaged header.
+                let length = end_pos
+                    .checked_sub(start_pos)
+                    .ok_or(ShardErrors::Corrupted)? as usize;
 
                 let read_bytes = data_reader.read_pointer(start_pos, length);
                 match read_bytes {
                     None => Err(ShardErrors::ErrorReadingByteRange),
-                    Some(b) => Ok(b),
+                    Some(b) => header_read
+                        .get_compression()
+                        .decompress(verify_checksum(b)?),
                 }
             }
         }
     }
 }
 
-impl Shard<DataShardConfig> for DataShard {
-    fn new(
+impl DataShard {
+    /// Opens the shard at `path`, creating it when the file is empty, and fails when its header
+    /// can't be read.
+    pub fn open(
         path: PathBuf,
         opts: DataShardConfig,
         uuid: Option<Uuid>,
         fdm: Arc<FileDescriptorManager>,
-    ) -> Self {
-        let data_handler = unsafe { DataHandler::new(path.clone(), fdm) }.unwrap();
+    ) -> Result<Self, ShardErrors> {
+        let data_handler = unsafe { DataHandler::new(path.clone(), fdm) }
+            .map_err(|err| ShardErrors::FileError(err.to_string()))?;
         let arc_dh = Arc::new(data_handler);
-        let header = DataShardHeader::new_from_file(arc_dh.clone(), opts.max_offsets, uuid);
-
-        DataShard {
-            path: path.clone(),
-            data: arc_dh.clone(),
+        let header = DataShardHeader::new_from_file(
+            arc_dh.clone(),
+            opts.max_offsets,
+            opts.compression,
+            uuid,
+        )?;
+
+        Ok(DataShard {
+            path,
+            data: arc_dh,
             id: header.id,
             header: RwLock::new(header),
-        }
+        })
+    }
+}
+
+impl Shard<DataShardConfig> for DataShard {
+    fn new(
+        path: PathBuf,
+        opts: DataShardConfig,
+        uuid: Option<Uuid>,
+        fdm: Arc<FileDescriptorManager>,
+    ) -> Self {
+        Self::open(path.clone(), opts, uuid, fdm)
+            .unwrap_or_else(|err| panic!("Shard {:?} could not be opened: {}", path, err))
     }
 
     fn has_space(&self) -> bool {
@@ -120,8 +146,15 @@ impl Shard<DataShardConfig> for DataShard {
 
     fn insert_item(&self, data: &[&[u8]]) -> Result<u64, ShardErrors> {
         let mut header_write = self.header.write();
+        let compression = header_write.get_compression();
+        let stored: Vec<Vec<u8>> = data
+            .iter()
+            .map(|item| with_checksum(&compression.compress(item)))
+            .collect();
+        let data: Vec<&[u8]> = stored.iter().map(|item| item.as_slice()).collect();
+
         let op = self.data.write().operate(|file| {
-            let write_data = flatten(data);
+            let write_data = flatten(&data);
 
             // Calculate the current end of the file
             let end_of_file = file
@@ -162,10 +195,14 @@ impl Shard<DataShardConfig> for DataShard {
 
 #[cfg(test)]
 mod test {
+    use crate::compression::Compression;
+    use crate::data_handler::DataHandler;
     use crate::errors::ShardErrors;
     use crate::fdm::FileDescriptorManager;
+    use crate::format::FORMAT_COMPRESSION_POS;
     use crate::shard::shards::data_shard::config::DataShardConfig;
     use crate::shard::shards::data_shard::shard::DataShard;
+    use crate::shard::shards::data_shard::shard_header::DataShardHeader;
     use crate::shard::Shard;
     use std::fs::File;
     use std::io::Read;
@@ -183,6 +220,7 @@ mod test {
 
         let config = DataShardConfig {
             max_offsets: Some(10),
+            compression: Compression::None,
         };
 
         let data_shard = DataShard::new(
@@ -233,6 +271,72 @@ mod test {
         // assert_eq!(res, shards.header.read().unwrap().offsets);
     }
 
+    #[tokio::test]
+    pub async fn test_compressed_data_shard() {
+        for compression in [Compression::Zstd, Compression::Lz4] {
+            let temp_dir = tempdir().unwrap();
+            let file_path = temp_dir
+                .path()
+                .join(format!("{}.bin", Uuid::new_v4().to_string()));
+
+            let data_shard = DataShard::new(
+                file_path.clone(),
+                DataShardConfig {
+                    max_offsets: Some(10),
+                    compression,
+                },
+                None,
+                Arc::new(FileDescriptorManager::new(2500)),
+            );
+
+            let row = "{\"user_email\":\"hello@example.com\"}".repeat(20);
+            data_shard
+                .insert_item(&[row.as_bytes(), "Venezuela".as_bytes()])
+                .unwrap();
+            assert!(std::fs::metadata(&file_path).unwrap().len() < row.len() as u64);
+
+            // The compression in the header wins over the one of the config.
+            let data_shard = DataShard::new(
+                file_path,
+                DataShardConfig {
+                    max_offsets: Some(10),
+                    compression: Compression::None,
+                },
+                None,
+                Arc::new(FileDescriptorManager::new(2500)),
+            );
+            assert_eq!(data_shard.header.read().get_compression(), compression);
+            assert_eq!(data_shard.header.read().get_max_offsets(), 10);
+            assert_eq!(data_shard.read_item_from_index(0).unwrap(), row.as_bytes());
+            assert_eq!(data_shard.read_item_from_index(1).unwrap(), b"Venezuela");
+        }
+    }
+
+    #[tokio::test]
+    pub async fn test_unknown_compression_is_an_error() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("data_unknown_0.data");
+        let fdm = Arc::new(FileDescriptorManager::new(2500));
+
+        DataShard::new(
+            file_path.clone(),
+            DataShardConfig {
+                max_offsets: Some(10),
+                compression: Compression::Zstd,
+            },
+            None,
+            fdm.clone(),
+        );
+
+        let mut bytes = std::fs::read(&file_path).unwrap();
+        bytes[FORMAT_COMPRESSION_POS] = 9;
+        std::fs::write(&file_path, bytes).unwrap();
+
+        let data = Arc::new(unsafe { DataHandler::new(file_path, fdm) }.unwrap());
+        let err = DataShardHeader::new_from_file(data, None, Compression::None, None).unwrap_err();
+        assert!(matches!(err, ShardErrors::UnknownCompression(9)));
+    }
+
     #[tokio::test]
     pub async fn test_data_shard_from_file() {
         let temp_dir = tempdir().unwrap();
@@ -242,6 +346,7 @@ mod test {
 
         let config = DataShardConfig {
             max_offsets: Some(10),
+            compression: Compression::None,
         };
 
         let data_shard = DataShard::new(
@@ -274,6 +379,7 @@ mod test {
             file_path.clone(),
             DataShardConfig {
                 max_offsets: Some(10),
+                compression: Compression::None,
             },
             None,
             Arc::new(FileDescriptorManager::new(2500)),
@@ -293,6 +399,7 @@ mod test {
             file_path,
             DataShardConfig {
                 max_offsets: Some(2),
+                compression: Compression::None,
             },
             None,
             Arc::new(FileDescriptorManager::new(2500)),
@@ -335,6 +442,7 @@ mod test {
             file_path,
             DataShardConfig {
                 max_offsets: Some(2),
+                compression: Compression::None,
             },
             None,
             Arc::new(FileDescriptorManager::new(2500)),
@@ -1,5 +1,7 @@
+use crate::compression::Compression;
 use crate::data_handler::DataHandler;
 use crate::errors::ShardErrors;
+use crate::format::{ShardFileKind, FORMAT_COMPRESSION_POS, FORMAT_PREFIX_SIZE};
 use crate::shard::shards::UUID_BYTE_LEN;
 use crate::{I64_SIZE, U64_SIZE};
 use parking_lot::RwLock;
@@ -12,11 +14,10 @@ use uuid::Uuid;
 
 pub const DEFAULT_MAX_OFFSETS: u64 = 100;
 
-// TODO: Header version
-
 #[derive(Debug)]
 pub struct DataShardHeader {
     max_offsets: u64,
+    compression: Compression,
     last_offset_index: i64, // Even though this is realistically a u64, we use i64 because if everything is empty, it will be -1 which can't be with u64
     pub max_offset_positions: usize,
     pub id: Uuid,
@@ -27,9 +28,15 @@ pub struct DataShardHeader {
 }
 
 impl DataShardHeader {
-    pub fn new(max_offsets: u64, uuid: Option<Uuid>, data: Arc<RwLock<DataHandler>>) -> Self {
+    pub fn new(
+        max_offsets: u64,
+        compression: Compression,
+        uuid: Option<Uuid>,
+        data: Arc<RwLock<DataHandler>>,
+    ) -> Self {
         Self {
             max_offsets,
+            compression,
             last_offset_index: -1,
             id: uuid.unwrap_or_else(Uuid::new_v4),
             max_offset_positions: Self::calculate_offset_pos(max_offsets as usize),
@@ -45,25 +52,32 @@ impl DataShardHeader {
     }
 
     fn calculate_header_size(max_offsets: u64) -> usize {
+        let format_prefix_size = FORMAT_PREFIX_SIZE;
         let max_offsets_size = U64_SIZE;
         let last_offset_index_size = I64_SIZE;
         let offsets_size = Self::calculate_offset_space_size(max_offsets);
         let id_len = UUID_BYTE_LEN as usize;
 
-        max_offsets_size + last_offset_index_size + offsets_size + id_len
+        format_prefix_size + max_offsets_size + last_offset_index_size + offsets_size + id_len
     }
 
     pub fn get_max_offsets(&self) -> u64 {
         self.max_offsets
     }
 
+    pub fn get_compression(&self) -> Compression {
+        self.compression
+    }
+
     pub fn new_from_file(
         file: Arc<RwLock<DataHandler>>,
         max_offsets: Option<u64>,
+        compression: Compression,
         uuid: Option<Uuid>,
-    ) -> Self {
+    ) -> Result<Self, ShardErrors> {
         let mut header = DataShardHeader::new(
             max_offsets.unwrap_or(DEFAULT_MAX_OFFSETS),
+            compression,
             uuid,
             file.clone(),
         );
@@ -71,10 +85,10 @@ impl DataShardHeader {
         if file.read().len() == 0 {
             header.initialize_empty_file();
         } else {
-            header.read_header();
+            header.read_header()?;
         }
 
-        header
+        Ok(header)
     }
 
     /// Initializes an empty file with max_offsets and zeroed offsets
@@ -88,9 +102,16 @@ impl DataShardHeader {
                 // Create a buffer for the header
                 let mut buffer = Vec::with_capacity(self.header_size);
 
+                {
+                    // Write the magic number, format version and compression
+                    let mut prefix = ShardFileKind::Data.format_prefix();
+                    prefix[FORMAT_COMPRESSION_POS] = self.compression.to_byte();
+                    buffer.extend_from_slice(&prefix);
+                }
+
                 {
                     // Write max_offsets to the buffer
-                    let max_offsets_bytes = (self.max_offsets).to_le_bytes();
+                    let max_offsets_bytes = self.max_offsets.to_le_bytes();
                     buffer.extend_from_slice(&max_offsets_bytes);
                 }
 
@@ -121,38 +142,68 @@ impl DataShardHeader {
     }
 
     /// Reads the header (max_offsets and offsets) from the file
-    fn read_header(&mut self) {
+    fn read_header(&mut self) -> Result<(), ShardErrors> {
         let reader = self.data.read();
+        let bytes = reader.get_bytes(0, reader.len()).unwrap_or_default();
+        ShardFileKind::Data.validate(bytes)?;
+
+        // A header cut short can only come from a damaged file.
+        let field = |start: usize, len: usize| {
+            bytes
+                .get(start..start + len)
+                .ok_or(ShardErrors::ErrorReadingByteRange)
+        };
+
         {
-            let max_offset_bytes = reader.get_bytes(0, U64_SIZE).unwrap();
-            let max_offset_bytes: [u8; 8] = max_offset_bytes.try_into().unwrap();
-            self.max_offsets = u64::from_le_bytes(max_offset_bytes);
+            let compression = field(FORMAT_COMPRESSION_POS, 1)?[0];
+            self.compression = Compression::from_byte(compression)
+                .ok_or(ShardErrors::UnknownCompression(compression))?;
+        }
+
+        {
+            let max_offset_bytes = field(FORMAT_PREFIX_SIZE, U64_SIZE)?;
+            self.max_offsets = u64::from_le_bytes(max_offset_bytes.try_into().unwrap());
         }
 
         self.max_offset_positions = Self::calculate_offset_pos(self.max_offsets as usize);
 
         {
-            let last_offset_index_bytes = reader.read_pointer(U64_SIZE as u64, I64_SIZE).unwrap();
-            let last_offset_index_bytes: [u8; 8] = last_offset_index_bytes.try_into().unwrap();
-            self.last_offset_index = i64::from_le_bytes(last_offset_index_bytes);
+            let last_offset_index_bytes = field(FORMAT_PREFIX_SIZE + U64_SIZE, I64_SIZE)?;
+            self.last_offset_index =
+                i64::from_le_bytes(last_offset_index_bytes.try_into().unwrap());
+        }
+
+        {
+            let id_bytes = field(
+                FORMAT_PREFIX_SIZE + U64_SIZE + I64_SIZE,
+                UUID_BYTE_LEN as usize,
+            )?;
+            self.id = Uuid::from_bytes_le(id_bytes.try_into().unwrap());
         }
 
         {
-            let id_bytes = reader
-                .read_pointer((U64_SIZE + I64_SIZE) as u64, UUID_BYTE_LEN as usize)
-                .unwrap();
-            let id_bytes = id_bytes.try_into().unwrap();
-            self.id = Uuid::from_bytes_le(id_bytes);
+            // Every offset slot follows the shard id, used or not.
+            let offsets_space = bytes.len().saturating_sub(Self::calculate_offset_pos(0));
+            if (offsets_space / U64_SIZE) < self.max_offsets as usize {
+                return Err(ShardErrors::ErrorReadingByteRange);
+            }
+
+            if self.last_offset_index >= self.max_offsets as i64 {
+                return Err(ShardErrors::Corrupted);
+            }
         }
+
+        Ok(())
     }
 
     fn calculate_offset_pos(index: usize) -> usize {
+        let format_prefix = FORMAT_PREFIX_SIZE;
         let max_offsets = U64_SIZE;
         let last_used_offset = I64_SIZE;
         let id_len = UUID_BYTE_LEN as usize;
         let offsets_from_pos = index * U64_SIZE;
 
-        max_offsets + last_used_offset + id_len + offsets_from_pos
+        format_prefix + max_offsets + last_used_offset + id_len + offsets_from_pos
     }
 
     pub fn add_next_offset(&mut self, value: u64, file: &mut File) -> Result<(), ShardErrors> {
@@ -165,8 +216,12 @@ impl DataShardHeader {
                     let offset_bytes = value.to_le_bytes();
                     write_at(file, &offset_bytes, pos as u64)
                         .expect("Failed to write offset to file");
-                    write_at(file, &available_index.to_le_bytes(), U64_SIZE as u64)
-                        .map_err(|_| ShardErrors::ErrorAddingHeaderOffset)?;
+                    write_at(
+                        file,
+                        &available_index.to_le_bytes(),
+                        (FORMAT_PREFIX_SIZE + U64_SIZE) as u64,
+                    )
+                    .map_err(|_| ShardErrors::ErrorAddingHeaderOffset)?;
                     self.last_offset_index = available_index as i64;
                     Ok(())
                 }
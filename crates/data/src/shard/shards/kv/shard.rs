@@ -31,6 +31,19 @@ impl KvShard {
         reader.read_pointer(starting_point, self.value_size)
     }
 
+    /// Overwrites the element at `index`, which must already exist.
+    pub fn set_element(&self, index: usize, value: &[u8]) -> Result<(), ShardErrors> {
+        if index as i64 > self.get_last_index() || value.len() != self.value_size {
+            return Err(ShardErrors::UnknownEntry);
+        }
+
+        let offset = Self::get_element_offset(index, self.value_size) as u64;
+        self.data
+            .write()
+            .operate(|file| write_at(file, value, offset).map(|_| ()))
+            .map_err(|_| ShardErrors::ErrorAddingEntry)
+    }
+
     fn get_element_offset(index: usize, value_size: usize) -> usize {
         get_element_offset(index, value_size)
     }
@@ -72,7 +85,8 @@ impl Shard<KvShardConfig> for KvShard {
             Some(0),
             opts.max_capacity,
             opts.value_size as u64,
-        );
+        )
+        .unwrap_or_else(|err| panic!("Shard {:?} could not be opened: {}", path, err));
 
         Self {
             path,
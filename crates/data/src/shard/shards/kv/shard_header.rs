@@ -1,4 +1,6 @@
 use crate::data_handler::DataHandler;
+use crate::errors::ShardErrors;
+use crate::format::{ShardFileKind, FORMAT_PREFIX_SIZE};
 use crate::shard::shards::UUID_BYTE_LEN;
 use crate::utils::fs::write_at;
 use crate::U64_SIZE;
@@ -40,7 +42,7 @@ impl KvShardHeader {
         items_len: Option<u64>,
         max_capacity: Option<u64>,
         value_size: u64,
-    ) -> Self {
+    ) -> Result<Self, ShardErrors> {
         let mut header = KvShardHeader::new(
             items_len.unwrap_or(0),
             max_capacity,
@@ -54,18 +56,19 @@ impl KvShardHeader {
         if file_len == 0 {
             header.initialize_empty_file();
         } else {
-            header.read_header();
+            header.read_header()?;
         }
 
-        header
+        Ok(header)
     }
 
     pub fn header_size() -> usize {
+        let format_prefix_size = FORMAT_PREFIX_SIZE;
         let max_capacity_size = U64_SIZE;
         let items_len_size = U64_SIZE;
         let value_size = U64_SIZE;
         let id_len = UUID_BYTE_LEN as usize;
-        max_capacity_size + items_len_size + value_size + id_len
+        format_prefix_size + max_capacity_size + items_len_size + value_size + id_len
     }
 
     fn initialize_empty_file(&mut self) {
@@ -78,6 +81,11 @@ impl KvShardHeader {
                 // Create a buffer for the header
                 let mut buffer = Vec::with_capacity(Self::header_size());
 
+                {
+                    // Write the magic number and format version
+                    buffer.extend_from_slice(&ShardFileKind::Kv.format_prefix());
+                }
+
                 {
                     // Write max_offsets to the buffer
                     let max_capacity_bytes = (self.max_capacity).unwrap_or(0).to_le_bytes();
@@ -111,23 +119,29 @@ impl KvShardHeader {
             .unwrap();
     }
 
-    fn read_header(&mut self) {
+    fn read_header(&mut self) -> Result<(), ShardErrors> {
         let reader = self.data.read();
+        ShardFileKind::Kv.validate(reader.get_bytes(0, reader.len()).unwrap_or_default())?;
+
         {
-            let max_capacity_bytes = reader.get_bytes(0, U64_SIZE).unwrap();
+            let max_capacity_bytes = reader
+                .get_bytes(FORMAT_PREFIX_SIZE, FORMAT_PREFIX_SIZE + U64_SIZE)
+                .unwrap();
             let max_capacity_bytes: [u8; 8] = max_capacity_bytes.try_into().unwrap();
             self.max_capacity = Some(u64::from_le_bytes(max_capacity_bytes));
         }
 
         {
-            let items_len_bytes = reader.read_pointer(U64_SIZE as u64, U64_SIZE).unwrap();
+            let items_len_bytes = reader
+                .read_pointer((FORMAT_PREFIX_SIZE + U64_SIZE) as u64, U64_SIZE)
+                .unwrap();
             let items_len_bytes: [u8; 8] = items_len_bytes.try_into().unwrap();
             self.items_len = u64::from_le_bytes(items_len_bytes);
         }
 
         {
             let value_size_bytes = reader
-                .read_pointer(U64_SIZE as u64 + U64_SIZE as u64, U64_SIZE)
+                .read_pointer((FORMAT_PREFIX_SIZE + U64_SIZE + U64_SIZE) as u64, U64_SIZE)
                 .unwrap();
             let value_size_bytes: [u8; 8] = value_size_bytes.try_into().unwrap();
             self.value_size = u64::from_le_bytes(value_size_bytes);
@@ -136,18 +150,25 @@ impl KvShardHeader {
         {
             let id_bytes = reader
                 .read_pointer(
-                    (U64_SIZE + U64_SIZE + U64_SIZE) as u64,
+                    (FORMAT_PREFIX_SIZE + U64_SIZE + U64_SIZE + U64_SIZE) as u64,
                     UUID_BYTE_LEN as usize,
                 )
                 .unwrap();
             let id_bytes = id_bytes.try_into().unwrap();
             self.id = Uuid::from_bytes_le(id_bytes);
         }
+
+        Ok(())
     }
 
     pub fn increment_len(&mut self, len: Option<u64>, file: &mut File) -> u64 {
         self.items_len += len.unwrap_or(1);
-        write_at(file, &self.items_len.to_le_bytes(), U64_SIZE as u64).unwrap();
+        write_at(
+            file,
+            &self.items_len.to_le_bytes(),
+            (FORMAT_PREFIX_SIZE + U64_SIZE) as u64,
+        )
+        .unwrap();
 
         self.items_len
     }
@@ -3,11 +3,14 @@ use crate::fdm::FileDescriptorManager;
 use crate::shard::map_shard::MapShard;
 use crate::shard::temp_map_shard::TempMapShard;
 use crate::shard::{Shard, ShardConfig, TempShardConfig};
-use parking_lot::RwLock;
+use parking_lot::{RwLock, RwLockWriteGuard};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+pub type TempShardGuard<'a, S, Opts, TempOpts> =
+    RwLockWriteGuard<'a, TempMapShard<S, Opts, TempOpts>>;
+
 #[derive(Debug)]
 pub struct TempCollection<S: Shard<Opts>, Opts: ShardConfig, TempOpts: TempShardConfig<Opts>> {
     pub target_shard: Arc<RwLock<MapShard<S, Opts>>>,
@@ -50,10 +53,25 @@ impl<S: Shard<Opts>, Opts: ShardConfig, TempOpts: TempShardConfig<Opts>>
         &self.temps[index]
     }
 
-    pub fn reconcile_all(&self) {
+    pub fn reconcile_all(&self) -> Result<(), ShardErrors> {
         for temp in self.temps.iter() {
-            temp.write().reconcile_all()
+            temp.write().reconcile_all()?;
         }
+
+        Ok(())
+    }
+
+    /// Reconciles every temporary shard and keeps them locked, so no row is inserted or
+    /// reconciled into the target shard until the guards are dropped.
+    pub fn quiesce(&self) -> Result<Vec<TempShardGuard<'_, S, Opts, TempOpts>>, ShardErrors> {
+        self.temps
+            .iter()
+            .map(|temp| {
+                let mut temp = temp.write();
+                temp.reconcile_all()?;
+                Ok(temp)
+            })
+            .collect()
     }
 
     pub fn insert(&self, data: &[&[u8]]) -> Result<u64, ShardErrors> {
@@ -81,7 +81,7 @@ impl<S: Shard<Opts>, Opts: ShardConfig, TempOpts: TempShardConfig<Opts>>
 
         let shard_index = match find_usable_shard {
             None => {
-                self.reconcile_specific(None);
+                self.reconcile_specific(None)?;
                 let shard = self.create_shard();
                 self.temp_shards.push(shard);
                 self.temp_shards.len() - 1
@@ -118,57 +118,89 @@ impl<S: Shard<Opts>, Opts: ShardConfig, TempOpts: TempShardConfig<Opts>>
         }
     }
 
-    fn reconcile(&self, from: &S, target: &mut MapShard<S, Opts>) {
+    /// Moves the rows of `from` into `target` and hands them to the reconcile callback.
+    ///
+    /// Every row is read before anything is inserted, so `target` is left untouched when a row
+    /// can't be read. When the callback fails the rows are already in `target` and
+    /// `ShardErrors::ReconcileCallbackFailed` is returned.
+    fn reconcile(&self, from: &S, target: &mut MapShard<S, Opts>) -> Result<(), ShardErrors> {
         let (shard, indexes) = Self::get_reconciliation_data(from);
+        let items = indexes
+            .map(|item_index| shard.read_item_from_index(item_index as usize))
+            .collect::<Result<Vec<_>, _>>()?;
+
         let mut reconciling_items = vec![];
-        // TODO: What if the row is inserted `target.insert_rows` but, the reconciling (call_on_reconcile) fails?
-        for item_index in indexes {
-            let binary_item = shard.read_item_from_index(item_index as usize).unwrap();
+        for binary_item in items {
             let pos = target.insert_rows(&[&binary_item]);
             reconciling_items.push(DataWithIndex {
                 data: binary_item,
                 index: pos as u64,
             });
         }
-        self.call_on_reconcile(reconciling_items).unwrap();
+
+        self.call_on_reconcile(reconciling_items)
+            .map_err(|_| ShardErrors::ReconcileCallbackFailed)
+    }
+
+    /// Whether the rows of a shard whose reconciliation returned `result` are in the target shard.
+    fn is_reconciled(result: &Result<(), ShardErrors>) -> bool {
+        matches!(result, Ok(()) | Err(ShardErrors::ReconcileCallbackFailed))
     }
 
-    pub fn reconcile_all(&mut self) {
+    /// Reconciles the temporary shards oldest first, stopping at the first error. Shards whose
+    /// rows made it into the parent shard are removed, the others are kept.
+    pub fn reconcile_all(&mut self) -> Result<(), ShardErrors> {
         let mut parent_writer = self.parent_shard.write();
 
+        let mut result = Ok(());
+        let mut reconciled = 0;
         for from_shard in self.temp_shards.iter() {
-            self.reconcile(from_shard, &mut parent_writer);
+            result = self.reconcile(from_shard, &mut parent_writer);
+            if Self::is_reconciled(&result) {
+                reconciled += 1;
+            }
+
+            if result.is_err() {
+                break;
+            }
         }
 
-        let paths: Vec<PathBuf> = self.temp_shards.iter().map(|i| i.get_path()).collect();
+        let paths: Vec<PathBuf> = self
+            .temp_shards
+            .drain(..reconciled)
+            .map(|i| i.get_path())
+            .collect();
         self.fdm.remove_paths(paths);
 
-        self.temp_shards.clear()
+        result
     }
 
-    pub fn reconcile_specific(&mut self, shard_position: Option<usize>) {
-        let pos = {
-            let index = shard_position.or_else(|| self.temp_shards.len().checked_sub(1));
-
-            if let Some(index) = index {
-                if let Some(shard) = self.temp_shards.get(index) {
-                    let mut parent_shard = self.parent_shard.write();
-                    self.reconcile(shard, &mut parent_shard);
-                    index
-                } else {
-                    return;
-                }
-            } else {
-                return;
-            }
+    pub fn reconcile_specific(&mut self, shard_position: Option<usize>) -> Result<(), ShardErrors> {
+        let index = shard_position.or_else(|| self.temp_shards.len().checked_sub(1));
+        let Some(index) = index else {
+            return Ok(());
+        };
+        let Some(shard) = self.temp_shards.get(index) else {
+            return Ok(());
+        };
+
+        let result = {
+            let mut parent_shard = self.parent_shard.write();
+            self.reconcile(shard, &mut parent_shard)
         };
 
-        self.temp_shards.remove(pos);
+        if Self::is_reconciled(&result) {
+            self.temp_shards.remove(index);
+        }
+
+        result
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::compression::Compression;
+    use crate::errors::ShardErrors;
     use crate::fdm::FileDescriptorManager;
     use crate::shard::map_shard::MapShard;
     use crate::shard::shards::data_shard::config::{DataShardConfig, TempDataShardConfig};
@@ -195,7 +227,10 @@ mod test {
         let ctx = MapShard::<DataShard, DataShardConfig>::new(
             data_path.clone(),
             "localdata_",
-            DataShardConfig { max_offsets: None },
+            DataShardConfig {
+                max_offsets: None,
+                compression: Compression::None,
+            },
             Arc::new(FileDescriptorManager::new(2500)),
         );
 
@@ -278,6 +313,57 @@ mod test {
         assert_eq!("0:Hello world".as_bytes().to_vec(), parent_item_1);
         assert_eq!("1:Hello Cats".as_bytes().to_vec(), parent_item_2);
 
+        std::fs::remove_dir_all(data_path).unwrap()
+    }
+    #[tokio::test]
+    pub async fn test_reconcile_keeps_unreadable_shard() {
+        let data_path = std::env::current_dir()
+            .unwrap()
+            .join("test_cases/data")
+            .join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&data_path).unwrap();
+
+        let parent_shard = Arc::new(RwLock::new(MapShard::<DataShard, DataShardConfig>::new(
+            data_path.clone(),
+            "localdata_",
+            DataShardConfig {
+                max_offsets: None,
+                compression: Compression::None,
+            },
+            Arc::new(FileDescriptorManager::new(2500)),
+        )));
+
+        let mut shard = TempMapShard::<DataShard, DataShardConfig, TempDataShardConfig>::new(
+            data_path.clone(),
+            "tempdata_",
+            parent_shard.clone(),
+            TempDataShardConfig {
+                max_offsets: TempOffsetTypes::Custom(Some(2)),
+            },
+            Arc::new(FileDescriptorManager::new(2500)),
+        );
+        shard.raw_insert_rows(&[b"0:Hello world"]).unwrap();
+        shard.raw_insert_rows(&[b"1:Hello Cats"]).unwrap();
+
+        // Flip a bit of the second row.
+        let temp_path = shard.temp_shards.first().unwrap().get_path();
+        let mut bytes = std::fs::read(&temp_path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        std::fs::write(&temp_path, bytes).unwrap();
+
+        let result = shard.reconcile_all();
+        assert!(matches!(result, Err(ShardErrors::Corrupted)));
+
+        // Nothing was moved and the temporary shard is kept.
+        assert_eq!(shard.temp_shards.len(), 1);
+        let parent_items_len = parent_shard
+            .read()
+            .current_master_shard
+            .header
+            .read()
+            .get_last_offset_index();
+        assert_eq!(parent_items_len, -1);
+
         std::fs::remove_dir_all(data_path).unwrap()
     }
 }
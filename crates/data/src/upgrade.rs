@@ -0,0 +1,266 @@
+use crate::checksum::with_checksum;
+use crate::errors::ShardErrors;
+use crate::format::{
+    find_shard_files, ShardFileKind, FORMAT_COMPRESSION_POS, FORMAT_PREFIX_SIZE, FORMAT_VERSION,
+};
+use crate::shard::shards::UUID_BYTE_LEN;
+use crate::{I64_SIZE, U64_SIZE};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Offset of the first item offset in the header of a data shard, after the format prefix.
+const DATA_OFFSETS_START: usize = U64_SIZE + I64_SIZE + UUID_BYTE_LEN as usize;
+
+/// Before version `3`, the compression of a data shard was the highest byte of `max_offsets`.
+const LEGACY_COMPRESSION_SHIFT: u32 = 56;
+
+/// Rewrites every shard file under `folder` that was written by an older format version,
+/// returning the paths of the upgraded files.
+///
+/// Shard files are recognized by their name, anything else in the folder is left untouched.
+/// Shards must not be open while they are upgraded.
+pub fn upgrade_data_folder(folder: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut upgraded = vec![];
+
+    for (path, kind) in find_shard_files(folder)? {
+        if upgrade_shard_file(&path, kind)? {
+            upgraded.push(path);
+        }
+    }
+
+    Ok(upgraded)
+}
+
+/// Rewrites a shard file in the current format, returning whether it had to be upgraded.
+pub fn upgrade_shard_file(path: &Path, kind: ShardFileKind) -> io::Result<bool> {
+    let mut bytes = std::fs::read(path)?;
+
+    // Empty shards are initialized with the current format once they are opened.
+    if bytes.is_empty() {
+        return Ok(false);
+    }
+
+    let version = kind.read_format_version(&bytes);
+    if version == FORMAT_VERSION {
+        return Ok(false);
+    } else if version > FORMAT_VERSION {
+        return Err(invalid_data(ShardErrors::UnsupportedFormatVersion(version)));
+    }
+
+    if version < 1 {
+        bytes = add_format_prefix(kind, bytes)?;
+    }
+
+    if version < 2 && kind == ShardFileKind::Data {
+        bytes = add_checksums(bytes)?;
+    }
+
+    if version < 3 && kind == ShardFileKind::Data {
+        move_compression(&mut bytes)?;
+    }
+
+    // The compression byte is kept, it was moved into the prefix above.
+    bytes[0..FORMAT_COMPRESSION_POS]
+        .copy_from_slice(&kind.format_prefix()[0..FORMAT_COMPRESSION_POS]);
+
+    // Written next to the shard first, so the shard is never left half rewritten.
+    let upgrade_path = path.with_extension("upgrading");
+    std::fs::write(&upgrade_path, bytes)?;
+    std::fs::rename(&upgrade_path, path)?;
+
+    Ok(true)
+}
+
+fn invalid_data(err: ShardErrors) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+fn read_u64(bytes: &[u8], pos: usize) -> io::Result<u64> {
+    bytes
+        .get(pos..pos + U64_SIZE)
+        .map(|value| u64::from_le_bytes(value.try_into().unwrap()))
+        .ok_or_else(|| invalid_data(ShardErrors::ErrorReadingByteRange))
+}
+
+/// Reads `max_offsets` from a data shard older than version `3`.
+fn read_max_offsets(bytes: &[u8]) -> io::Result<usize> {
+    Ok((read_u64(bytes, FORMAT_PREFIX_SIZE)? & ((1 << LEGACY_COMPRESSION_SHIFT) - 1)) as usize)
+}
+
+/// Version `1`: Adds the format prefix. Offsets of data shards are positions in the file,
+/// which move by the size of the prefix.
+fn add_format_prefix(kind: ShardFileKind, bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    let mut upgraded = Vec::with_capacity(FORMAT_PREFIX_SIZE + bytes.len());
+    upgraded.extend_from_slice(&kind.format_prefix());
+    upgraded.extend_from_slice(&bytes);
+
+    if kind == ShardFileKind::Data {
+        for index in 0..read_max_offsets(&upgraded)? {
+            let pos = FORMAT_PREFIX_SIZE + DATA_OFFSETS_START + index * U64_SIZE;
+            let offset = read_u64(&upgraded, pos)?;
+
+            // Zero marks an unused offset.
+            if offset != 0 {
+                upgraded[pos..pos + U64_SIZE]
+                    .copy_from_slice(&(offset + FORMAT_PREFIX_SIZE as u64).to_le_bytes());
+            }
+        }
+    }
+
+    Ok(upgraded)
+}
+
+/// Version `2`: Prefixes every item of a data shard with its CRC32C.
+fn add_checksums(bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    let max_offsets = read_max_offsets(&bytes)?;
+    let last_offset_index = read_u64(&bytes, FORMAT_PREFIX_SIZE + U64_SIZE)? as i64;
+    let offsets_start = FORMAT_PREFIX_SIZE + DATA_OFFSETS_START;
+    let header_size = offsets_start + max_offsets * U64_SIZE;
+    let items = (last_offset_index + 1).clamp(0, max_offsets as i64) as usize;
+
+    let offsets = (0..items)
+        .map(|index| read_u64(&bytes, offsets_start + index * U64_SIZE))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let mut upgraded = bytes
+        .get(0..header_size)
+        .ok_or_else(|| invalid_data(ShardErrors::ErrorReadingByteRange))?
+        .to_vec();
+
+    for (index, start) in offsets.iter().enumerate() {
+        let end = offsets
+            .get(index + 1)
+            .copied()
+            .unwrap_or(bytes.len() as u64);
+        let item = bytes
+            .get(*start as usize..end as usize)
+            .ok_or_else(|| invalid_data(ShardErrors::Corrupted))?;
+
+        let pos = offsets_start + index * U64_SIZE;
+        let offset = upgraded.len() as u64;
+        upgraded[pos..pos + U64_SIZE].copy_from_slice(&offset.to_le_bytes());
+        upgraded.extend(with_checksum(item));
+    }
+
+    Ok(upgraded)
+}
+
+/// Version `3`: Moves the compression of a data shard from `max_offsets` into the prefix.
+fn move_compression(bytes: &mut [u8]) -> io::Result<()> {
+    let max_offsets = read_u64(bytes, FORMAT_PREFIX_SIZE)?;
+    let compression = (max_offsets >> LEGACY_COMPRESSION_SHIFT) as u8;
+    let max_offsets = max_offsets & ((1 << LEGACY_COMPRESSION_SHIFT) - 1);
+
+    bytes[FORMAT_COMPRESSION_POS] = compression;
+    bytes[FORMAT_PREFIX_SIZE..FORMAT_PREFIX_SIZE + U64_SIZE]
+        .copy_from_slice(&max_offsets.to_le_bytes());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::compression::Compression;
+    use crate::fdm::FileDescriptorManager;
+    use crate::format::{
+        ShardFileKind, FORMAT_COMPRESSION_POS, FORMAT_PREFIX_SIZE, FORMAT_VERSION,
+    };
+    use crate::shard::shards::data_shard::config::DataShardConfig;
+    use crate::shard::shards::data_shard::shard::DataShard;
+    use crate::shard::Shard;
+    use crate::upgrade::upgrade_data_folder;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_upgrade_legacy_data_shard() {
+        let folder = std::env::current_dir()
+            .unwrap()
+            .join("test_cases/fake-db-folder")
+            .join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&folder).unwrap();
+        let path = folder.join("data_upgrade_0.data");
+
+        // A shard written before the format was versioned, with two items.
+        let header_size = 8 + 8 + 16 + 10 * 8;
+        let mut legacy = vec![];
+        legacy.extend(10u64.to_le_bytes());
+        legacy.extend(1i64.to_le_bytes());
+        legacy.extend(Uuid::new_v4().to_bytes_le());
+        legacy.extend((header_size as u64).to_le_bytes());
+        legacy.extend((header_size as u64 + 5).to_le_bytes());
+        legacy.resize(header_size, 0);
+        legacy.extend(b"HelloWorld");
+        std::fs::write(&path, legacy).unwrap();
+
+        let upgraded = upgrade_data_folder(&folder).unwrap();
+        assert_eq!(upgraded, vec![path.clone()]);
+        assert_eq!(
+            ShardFileKind::Data.read_format_version(&std::fs::read(&path).unwrap()),
+            FORMAT_VERSION
+        );
+        assert!(upgrade_data_folder(&folder).unwrap().is_empty());
+
+        let shard = DataShard::new(
+            path.clone(),
+            DataShardConfig {
+                max_offsets: Some(10),
+                compression: Default::default(),
+            },
+            None,
+            Arc::new(FileDescriptorManager::new(10)),
+        );
+        assert_eq!(shard.get_last_index(), 1);
+        assert_eq!(shard.read_item_from_index(0).unwrap(), b"Hello".to_vec());
+        assert_eq!(shard.read_item_from_index(1).unwrap(), b"World".to_vec());
+
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_upgrade_moves_compression_into_prefix() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("data_compressed_0.data");
+        let config = DataShardConfig {
+            max_offsets: Some(10),
+            compression: Compression::Lz4,
+        };
+
+        {
+            let shard = DataShard::new(
+                path.clone(),
+                config.clone(),
+                None,
+                Arc::new(FileDescriptorManager::new(10)),
+            );
+            shard.insert_item(&[b"Hello", b"World"]).unwrap();
+        }
+
+        // Rewritten as a version 2 shard, with the compression in `max_offsets`.
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[4..6].copy_from_slice(&2u16.to_le_bytes());
+        bytes[FORMAT_COMPRESSION_POS] = 0;
+        let legacy_max_offsets = 10u64 | ((Compression::Lz4.to_byte() as u64) << 56);
+        bytes[FORMAT_PREFIX_SIZE..FORMAT_PREFIX_SIZE + 8]
+            .copy_from_slice(&legacy_max_offsets.to_le_bytes());
+        std::fs::write(&path, bytes).unwrap();
+
+        assert_eq!(
+            upgrade_data_folder(folder.path()).unwrap(),
+            vec![path.clone()]
+        );
+
+        let shard = DataShard::new(
+            path,
+            DataShardConfig {
+                max_offsets: Some(10),
+                compression: Compression::None,
+            },
+            None,
+            Arc::new(FileDescriptorManager::new(10)),
+        );
+        assert_eq!(shard.header.read().get_compression(), Compression::Lz4);
+        assert_eq!(shard.header.read().get_max_offsets(), 10);
+        assert_eq!(shard.read_item_from_index(1).unwrap(), b"World".to_vec());
+    }
+}
@@ -0,0 +1,180 @@
+use crate::errors::ShardErrors;
+use crate::fdm::FileDescriptorManager;
+use crate::format::{find_shard_files, ShardFileKind};
+use crate::shard::shards::data_shard::config::DataShardConfig;
+use crate::shard::shards::data_shard::shard::DataShard;
+use crate::shard::Shard;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A shard file, or a single item of it, that could not be read back.
+#[derive(Debug)]
+pub struct CorruptedItem {
+    pub path: PathBuf,
+    /// Index of the item in its shard, `None` if the whole shard is unreadable.
+    pub index: Option<usize>,
+    pub error: ShardErrors,
+}
+
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub shards: usize,
+    pub items: usize,
+    pub corrupted: Vec<CorruptedItem>,
+}
+
+/// Reads back every item of the data shards under `folder`, reporting the ones whose
+/// checksum does not match and the shard files that cannot be opened.
+///
+/// Shards must not be written while they are verified.
+pub fn verify_data_folder(folder: &Path) -> io::Result<VerifyReport> {
+    let fdm = Arc::new(FileDescriptorManager::new(100));
+    let mut report = VerifyReport::default();
+
+    for (path, kind) in find_shard_files(folder)? {
+        let bytes = std::fs::read(&path)?;
+        if bytes.is_empty() {
+            continue;
+        }
+
+        report.shards += 1;
+
+        if let Err(error) = kind.validate(&bytes) {
+            report.corrupted.push(CorruptedItem {
+                path,
+                index: None,
+                error,
+            });
+            continue;
+        }
+
+        if kind != ShardFileKind::Data {
+            continue;
+        }
+
+        let shard = match DataShard::open(
+            path.clone(),
+            DataShardConfig {
+                max_offsets: None,
+                compression: Default::default(),
+            },
+            None,
+            fdm.clone(),
+        ) {
+            Ok(shard) => shard,
+            Err(error) => {
+                report.corrupted.push(CorruptedItem {
+                    path,
+                    index: None,
+                    error,
+                });
+                continue;
+            }
+        };
+
+        for index in 0..(shard.get_last_index() + 1) as usize {
+            match shard.read_item_from_index(index) {
+                Ok(_) => report.items += 1,
+                Err(error) => report.corrupted.push(CorruptedItem {
+                    path: path.clone(),
+                    index: Some(index),
+                    error,
+                }),
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::errors::ShardErrors;
+    use crate::fdm::FileDescriptorManager;
+    use crate::shard::shards::data_shard::config::DataShardConfig;
+    use crate::shard::shards::data_shard::shard::DataShard;
+    use crate::shard::Shard;
+    use crate::verify::verify_data_folder;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_verify_corrupted_item() {
+        let folder = std::env::current_dir()
+            .unwrap()
+            .join("test_cases/fake-db-folder")
+            .join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&folder).unwrap();
+        let path = folder.join("data_verify_0.data");
+
+        {
+            let shard = DataShard::new(
+                path.clone(),
+                DataShardConfig {
+                    max_offsets: Some(10),
+                    compression: Default::default(),
+                },
+                None,
+                Arc::new(FileDescriptorManager::new(10)),
+            );
+            shard.insert_item(&[b"Hello", b"World"]).unwrap();
+        }
+
+        let report = verify_data_folder(&folder).unwrap();
+        assert_eq!(report.shards, 1);
+        assert_eq!(report.items, 2);
+        assert!(report.corrupted.is_empty());
+
+        // Flip a bit of the second item.
+        let mut bytes = std::fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        std::fs::write(&path, bytes).unwrap();
+
+        let report = verify_data_folder(&folder).unwrap();
+        assert_eq!(report.items, 1);
+        assert_eq!(report.corrupted.len(), 1);
+        assert_eq!(report.corrupted[0].index, Some(1));
+        assert!(matches!(report.corrupted[0].error, ShardErrors::Corrupted));
+
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_verify_truncated_header() {
+        let folder = std::env::current_dir()
+            .unwrap()
+            .join("test_cases/fake-db-folder")
+            .join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&folder).unwrap();
+        let path = folder.join("data_verify_0.data");
+
+        {
+            DataShard::new(
+                path.clone(),
+                DataShardConfig {
+                    max_offsets: Some(10),
+                    compression: Default::default(),
+                },
+                None,
+                Arc::new(FileDescriptorManager::new(10)),
+            );
+        }
+
+        // Cut the file in the middle of the offsets.
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 8]).unwrap();
+
+        let report = verify_data_folder(&folder).unwrap();
+        assert_eq!(report.shards, 1);
+        assert_eq!(report.items, 0);
+        assert_eq!(report.corrupted.len(), 1);
+        assert_eq!(report.corrupted[0].index, None);
+        assert!(matches!(
+            report.corrupted[0].error,
+            ShardErrors::ErrorReadingByteRange
+        ));
+
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+}
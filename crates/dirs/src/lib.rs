@@ -59,3 +59,17 @@ pub fn create_indx_folder(base_path: Option<PathBuf>, db_name: &str, table_name:
 
     path
 }
+
+pub fn create_remote_modules_folder(base_path: Option<PathBuf>) -> PathBuf {
+    let path = get_base_path(base_path).join("remote");
+
+    if !path.exists() {
+        let _ = std::fs::create_dir_all(path.clone());
+    }
+
+    path
+}
+
+pub fn get_backups_path(base_path: Option<PathBuf>) -> PathBuf {
+    get_base_path(base_path).join("backups")
+}
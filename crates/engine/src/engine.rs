@@ -1,17 +1,16 @@
 use crate::engine_db::EngineDb;
-use crate::utils::fs::is_js_or_ts;
-use anyhow::bail;
+use crate::utils::fs::find_table_modules;
 use deno_core::ModuleSpecifier;
 use schemajs_config::{DatabaseConfig, SchemeJsConfig};
 use schemajs_data::fdm::FileDescriptorManager;
-use schemajs_dirs::create_scheme_js_folder;
+use schemajs_dirs::{create_scheme_js_folder, get_backups_path};
 use schemajs_helpers::helper::HelperCall;
 use schemajs_primitives::table::Table;
+use schemajs_query::errors::QueryError;
 use std::future::Future;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 use tokio::sync::mpsc::Sender;
-use walkdir::WalkDir;
 
 pub struct SchemeJsEngine {
     pub databases: Vec<Arc<EngineDb>>,
@@ -43,39 +42,26 @@ impl SchemeJsEngine {
         &mut self,
         path: &PathBuf,
     ) -> anyhow::Result<(String, Vec<ModuleSpecifier>)> {
-        if !path.exists() {
-            bail!(
-                "Trying to access a database schema that does not exist: {}",
-                path.to_string_lossy()
-            );
-        }
-
-        let schema_name = path.file_name().unwrap().to_str().unwrap();
+        let (schema_name, table_specifiers) = find_table_modules(path)?;
 
         {
-            self.add_database(schema_name);
+            self.add_database(&schema_name);
         }
 
-        let table_path = path.join("tables").canonicalize()?;
-        let table_walker = WalkDir::new(table_path).into_iter().filter_map(|e| e.ok());
-
-        let mut table_specifiers = vec![];
-
-        for table_file in table_walker {
-            if is_js_or_ts(&table_file) {
-                let url = ModuleSpecifier::from_file_path(table_file.path()).unwrap();
-                table_specifiers.push(url);
-            }
-        }
-
-        Ok((schema_name.to_string(), table_specifiers))
+        Ok((schema_name, table_specifiers))
     }
 
-    pub fn register_tables(&self, schema_name: &str, loaded_tables: Vec<Table>) {
+    pub fn register_tables(
+        &self,
+        schema_name: &str,
+        loaded_tables: Vec<Table>,
+    ) -> Result<(), QueryError> {
         let mut db = self.find_by_name_ref(schema_name).unwrap();
         for table in loaded_tables {
-            db.add_table(table);
+            db.add_table(table)?;
         }
+
+        Ok(())
     }
 
     pub fn contains_db(&self, name: &str) -> bool {
@@ -86,6 +72,14 @@ impl SchemeJsEngine {
         self.databases.iter().find(|i| i.name == name)
     }
 
+    /// Folder backups are written under, see `GlobalConfig::backup_folder`.
+    pub fn backup_folder(&self) -> PathBuf {
+        match &self.config.global.backup_folder {
+            Some(folder) => PathBuf::from(folder),
+            None => get_backups_path(self.data_path_dir.clone()),
+        }
+    }
+
     pub fn add_database(&mut self, name: &str) {
         self.databases.push(Arc::new(EngineDb::new(
             self.data_path_dir.clone(),
@@ -107,8 +101,8 @@ mod test {
     use schemajs_primitives::column::types::{DataTypes, DataValue};
     use schemajs_primitives::column::Column;
     use schemajs_primitives::table::Table;
+    use schemajs_query::db_row::DbRow;
     use schemajs_query::row::Row;
-    use schemajs_query::row_json::{RowData, RowJson};
     use serde_json::json;
     use std::collections::HashMap;
     use std::sync::{Arc, RwLock};
@@ -172,12 +166,14 @@ mod test {
                     columns: cols,
                     indexes: vec![],
                     primary_key: "".to_string(),
+                    queries: vec![],
+                    compression: Default::default(),
                     metadata: Default::default(),
                 };
 
                 let mut writer = db_engine.write().unwrap();
                 let mut db = writer.find_by_name_ref("rust-test-random").unwrap();
-                db.add_table(table);
+                db.add_table(table).unwrap();
             }
         }
 
@@ -190,7 +186,7 @@ mod test {
             let tbl = db.query_manager.get_table("users").unwrap();
             db.query_manager
                 .insert(
-                    RowJson::from_json(
+                    DbRow::from_json(
                         json!({
                             "_uid": "97ad4bba-98c5-4a9e-80d8-6bf6302fb883",
                             "id": "1"
@@ -209,7 +205,7 @@ mod test {
             let tbl = db.query_manager.get_table("users").unwrap();
             db.query_manager
                 .insert(
-                    RowJson::from_json(
+                    DbRow::from_json(
                         json!({
                             "_uid": "2ec92148-646d-4521-974f-b4a6d422c195",
                             "id": "2"
@@ -229,13 +225,13 @@ mod test {
             let mut reader = db_engine.write().unwrap();
             let mut db = reader.find_by_name_ref("rust-test-random").unwrap();
             let tbl = db.query_manager.tables.get("users").unwrap();
-            tbl.temps.reconcile_all();
+            tbl.temps.reconcile_all().unwrap();
 
             let a = tbl.data.read().get_element(0).unwrap();
             let b = tbl.data.read().get_element(1).unwrap();
 
-            let a = RowJson::from_slice(a.as_slice(), tbl.table.clone());
-            let b = RowJson::from_slice(b.as_slice(), tbl.table.clone());
+            let a = DbRow::from_slice(a.as_slice(), tbl.table.clone());
+            let b = DbRow::from_slice(b.as_slice(), tbl.table.clone());
 
             let a_val = a
                 .get_value(tbl.table.get_column("id").unwrap())
@@ -1,10 +1,12 @@
 use schemajs_config::DatabaseConfig;
+use schemajs_data::backup::Backup;
 use schemajs_data::fdm::FileDescriptorManager;
 use schemajs_dirs::create_scheme_js_db;
 use schemajs_helpers::helper::HelperCall;
 use schemajs_primitives::table::Table;
+use schemajs_query::db_row::DbRow;
+use schemajs_query::errors::QueryError;
 use schemajs_query::managers::single::SingleQueryManager;
-use schemajs_query::row_json::RowJson;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc::error::SendError;
@@ -13,7 +15,7 @@ use tokio::sync::mpsc::Sender;
 #[derive(Debug)]
 pub struct EngineDb {
     pub db_folder: PathBuf,
-    pub query_manager: Arc<SingleQueryManager<RowJson>>,
+    pub query_manager: Arc<SingleQueryManager<DbRow>>,
     pub name: String,
     pub db_config: Arc<DatabaseConfig>,
     helper_tx: Sender<HelperCall>,
@@ -51,7 +53,15 @@ impl EngineDb {
         self.helper_tx.send(call).await
     }
 
-    pub fn add_table(&self, table: Table) {
-        self.query_manager.register_table(table);
+    pub fn add_table(&self, table: Table) -> Result<(), QueryError> {
+        self.query_manager.register_table(table)
+    }
+
+    /// Copies the files of the database into `backup` while nothing is written to them,
+    /// see `SingleQueryManager::quiesce`.
+    pub fn backup(&self, backup: &mut Backup) -> std::io::Result<()> {
+        self.query_manager
+            .quiesce(|| backup.add_database(&self.name, &self.db_folder))
+            .map_err(|err| std::io::Error::other(err.to_string()))?
     }
 }
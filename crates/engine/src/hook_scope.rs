@@ -0,0 +1,19 @@
+use deno_core::OpState;
+use schemajs_primitives::table::Table;
+use schemajs_query::errors::QueryError;
+
+/// Put in the `OpState` while the `beforeInsert` hooks of a table run.
+///
+/// A hook waiting for the `beforeInsert` hooks of the rows it inserts holds its runtime while
+/// needing another one, which can exhaust the pool and deadlock, or recurse forever when it
+/// inserts into its own table. Such inserts are rejected instead.
+#[derive(Debug)]
+pub struct BeforeInsertHookScope;
+
+pub(crate) fn check_nested_insert(state: &OpState, table: &Table) -> Result<(), QueryError> {
+    if table.metadata.before_insert && state.has::<BeforeInsertHookScope>() {
+        return Err(QueryError::NestedBeforeInsertHook(table.name.clone()));
+    }
+
+    Ok(())
+}
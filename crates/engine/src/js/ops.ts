@@ -9,4 +9,16 @@ export const insertRow = async (dbName: string, tableName: string, data: any) =>
 
 export const searchRows = async (dbName: string, tableName: string, data: any) => {
     return await core.ops.op_engine_search_rows(dbName, tableName, data);
-}
\ No newline at end of file
+}
+
+export const customQuery = async (dbName: string, tableName: string, identifier: string, args: any) => {
+    const cb = core.ops.op_engine_find_custom_query(dbName, tableName, identifier);
+    core.ops.op_engine_validate_custom_query(dbName, tableName, identifier, false, args ?? null);
+
+    // The query gets the context of its own table, like when called through the RPC. The global
+    // context is left alone, other calls may run while this one is awaited.
+    const result = await cb(args, { dbName, tblName: tableName });
+    core.ops.op_engine_validate_custom_query(dbName, tableName, identifier, true, result ?? null);
+
+    return result;
+}
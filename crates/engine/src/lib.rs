@@ -1,3 +1,4 @@
+use crate::ops::custom_query::{op_engine_find_custom_query, op_engine_validate_custom_query};
 use crate::ops::insert::op_engine_insert_row;
 use crate::ops::query::op_engine_search_rows;
 use deno_core::error::AnyError;
@@ -5,6 +6,8 @@ use deno_core::{op2, OpState};
 
 pub mod engine;
 pub mod engine_db;
+pub mod hook_scope;
+pub mod op_budget;
 mod ops;
 mod query_error;
 pub mod utils;
@@ -19,6 +22,12 @@ pub fn sjs_op_print(state: &mut OpState, #[string] msg: &str) -> Result<(), AnyE
 
 deno_core::extension!(
     sjs_engine,
-    ops = [op_engine_insert_row, op_engine_search_rows, sjs_op_print],
+    ops = [
+        op_engine_insert_row,
+        op_engine_search_rows,
+        op_engine_find_custom_query,
+        op_engine_validate_custom_query,
+        sjs_op_print
+    ],
     esm = ["src/js/ops.ts", "src/js/context.ts", "src/js/query.ts",]
 );
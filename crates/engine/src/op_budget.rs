@@ -0,0 +1,52 @@
+use deno_core::OpState;
+use schemajs_query::errors::QueryError;
+
+/// Engine ops the helper call being executed may still perform.
+/// It is put in the `OpState` before every helper call. Runtimes without one are not limited.
+#[derive(Debug, Default)]
+pub struct OpBudget {
+    limit: u64,
+    used: u64,
+}
+
+impl OpBudget {
+    /// A `limit` of `0` lets the call perform any number of ops.
+    pub fn new(limit: u64) -> Self {
+        Self { limit, used: 0 }
+    }
+
+    pub fn consume(&mut self) -> Result<(), QueryError> {
+        self.used += 1;
+
+        if self.limit > 0 && self.used > self.limit {
+            return Err(QueryError::OpBudgetExceeded(self.limit));
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn consume_op_budget(state: &mut OpState) -> Result<(), QueryError> {
+    match state.try_borrow_mut::<OpBudget>() {
+        Some(budget) => budget.consume(),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::op_budget::OpBudget;
+
+    #[test]
+    pub fn test_op_budget() {
+        let mut budget = OpBudget::new(2);
+        assert!(budget.consume().is_ok());
+        assert!(budget.consume().is_ok());
+        assert!(budget.consume().unwrap_err().is_op_budget_exceeded());
+
+        let mut unlimited = OpBudget::new(0);
+        for _ in 0..100 {
+            assert!(unlimited.consume().is_ok());
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::engine::SchemeJsEngine;
+use crate::op_budget::consume_op_budget;
+use deno_core::{op2, serde_json, v8, OpState};
+use parking_lot::RwLock;
+use schemajs_helpers::helper::SjsTableHelpers;
+use schemajs_query::errors::QueryError;
+use std::sync::Arc;
+
+/// Finds a custom query registered with `Table.addQuery` so it can be called from the current runtime.
+#[op2]
+#[global]
+pub fn op_engine_find_custom_query(
+    state: &mut OpState,
+    #[string] db_name: String,
+    #[string] table_name: String,
+    #[string] identifier: String,
+) -> Result<v8::Global<v8::Function>, QueryError> {
+    consume_op_budget(state)?;
+
+    let helpers = state.borrow::<Arc<SjsTableHelpers>>();
+    helpers
+        .find_custom_query_helper(&db_name, &table_name, &identifier)
+        .map(|helper| helper.func.clone())
+        .ok_or_else(|| QueryError::UnknownCustomQuery(identifier))
+}
+
+/// Checks the arguments of a custom query, or its result if `output` is set, against the
+/// schema it was registered with.
+#[op2]
+pub fn op_engine_validate_custom_query(
+    state: &mut OpState,
+    #[string] db_name: String,
+    #[string] table_name: String,
+    #[string] identifier: String,
+    output: bool,
+    #[serde] value: serde_json::Value,
+) -> Result<(), QueryError> {
+    let query = {
+        let engine = state.borrow::<Arc<RwLock<SchemeJsEngine>>>().read();
+        engine
+            .find_by_name_ref(&db_name)
+            .and_then(|db| db.query_manager.get_table(&table_name))
+            .and_then(|table| table.get_custom_query(&identifier).cloned())
+    };
+
+    let Some(query) = query else {
+        return Ok(());
+    };
+
+    let result = if output {
+        query.validate_output(&value)
+    } else {
+        query.validate_input(&value)
+    };
+
+    result.map_err(|violations| {
+        let reason = violations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        if output {
+            QueryError::InvalidCustomQueryResult(identifier, reason)
+        } else {
+            QueryError::InvalidCustomQueryArgs(identifier, reason)
+        }
+    })
+}
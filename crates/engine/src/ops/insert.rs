@@ -1,9 +1,11 @@
 use crate::engine::SchemeJsEngine;
+use crate::hook_scope::check_nested_insert;
+use crate::op_budget::consume_op_budget;
 use deno_core::{op2, serde_json, OpState};
 use parking_lot::RwLock;
+use schemajs_query::db_row::DbRow;
 use schemajs_query::errors::QueryError;
 use schemajs_query::row::Row;
-use schemajs_query::row_json::{RowData, RowJson};
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -17,21 +19,24 @@ pub async fn op_engine_insert_row(
     #[string] table_name: String,
     #[serde] mut row: serde_json::Value,
 ) -> Result<Option<Uuid>, QueryError> {
-    let mut mut_state = state.borrow_mut();
-    let state = mut_state
+    consume_op_budget(&mut state.borrow_mut())?;
+
+    let engine = state
+        .borrow_mut()
         .borrow_mut::<Arc<RwLock<SchemeJsEngine>>>()
         .clone();
 
     let query_manager = {
-        let read_engine = state.read();
+        let read_engine = engine.read();
         let db = read_engine.find_by_name_ref(db_name.as_str()).unwrap();
         db.query_manager.clone()
     };
 
     let table = query_manager.get_table(&table_name);
     if let Some(table) = table {
-        return query_manager
-            .insert(RowJson::from_json(row, table).map_err(|_| QueryError::InvalidSerialization)?);
+        check_nested_insert(&state.borrow(), &table)?;
+        let row = DbRow::from_json(row, table).map_err(|_| QueryError::InvalidSerialization)?;
+        return query_manager.insert_with_hooks(vec![row], false).await;
     }
 
     return Err(QueryError::InvalidInsertion);
@@ -1,2 +1,3 @@
+pub mod custom_query;
 pub mod insert;
 pub mod query;
@@ -1,4 +1,5 @@
 use crate::engine::SchemeJsEngine;
+use crate::op_budget::consume_op_budget;
 use deno_core::{op2, OpState};
 use parking_lot::RwLock;
 use schemajs_query::errors::QueryError;
@@ -18,6 +19,8 @@ pub async fn op_engine_search_rows(
     #[serde] args: QueryOps,
 ) -> Result<Vec<Value>, QueryError> {
     let mut mut_state = state.borrow_mut();
+    consume_op_budget(&mut mut_state)?;
+
     let state = mut_state
         .borrow_mut::<Arc<RwLock<SchemeJsEngine>>>()
         .clone();
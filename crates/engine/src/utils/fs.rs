@@ -1,4 +1,6 @@
-use walkdir::DirEntry;
+use deno_core::ModuleSpecifier;
+use std::path::PathBuf;
+use walkdir::{DirEntry, WalkDir};
 
 pub fn is_js_or_ts(entry: &DirEntry) -> bool {
     entry
@@ -6,3 +8,30 @@ pub fn is_js_or_ts(entry: &DirEntry) -> bool {
         .extension()
         .map_or(false, |ext| ext == "js" || ext == "ts")
 }
+
+/// Finds the table modules of the database schema at `path`, in `<path>/tables`.
+/// The name of the database is the name of its folder.
+pub fn find_table_modules(path: &PathBuf) -> anyhow::Result<(String, Vec<ModuleSpecifier>)> {
+    if !path.exists() {
+        anyhow::bail!(
+            "Trying to access a database schema that does not exist: {}",
+            path.to_string_lossy()
+        );
+    }
+
+    let schema_name = path.file_name().unwrap().to_str().unwrap();
+
+    let table_path = path.join("tables").canonicalize()?;
+    let table_walker = WalkDir::new(table_path).into_iter().filter_map(|e| e.ok());
+
+    let mut table_specifiers = vec![];
+
+    for table_file in table_walker {
+        if is_js_or_ts(&table_file) {
+            let url = ModuleSpecifier::from_file_path(table_file.path()).unwrap();
+            table_specifiers.push(url);
+        }
+    }
+
+    Ok((schema_name.to_string(), table_specifiers))
+}
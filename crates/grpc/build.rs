@@ -1,13 +1,20 @@
+use std::path::PathBuf;
+
 fn main() {
     let protos = [
+        "proto/admin/admin.proto",
         "proto/connection/connection.proto",
         "proto/shared/data_value.proto",
         "proto/shared/row.proto",
         "proto/query/query.proto",
+        "proto/transfer/transfer.proto",
     ];
 
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+
     tonic_build::configure()
         .emit_rerun_if_changed(true)
+        .file_descriptor_set_path(out_dir.join("sjs_descriptor.bin"))
         .compile(&protos, &["./proto"])
         .unwrap();
 }
@@ -0,0 +1,216 @@
+use crate::services::admin::admin_service::proto_admin_service_client::ProtoAdminServiceClient;
+pub use crate::services::admin::admin_service::{BackupRequest, BackupResponse};
+use crate::services::connection::connection_service::proto_connection_service_client::ProtoConnectionServiceClient;
+use crate::services::connection::connection_service::CheckConnectionRequest;
+use crate::services::transfer::transfer_service::proto_transfer_service_client::ProtoTransferServiceClient;
+pub use crate::services::transfer::transfer_service::{
+    ExportTableRequest, ImportTableChunk, ImportTableResponse, TransferChunk, TransferFormat,
+};
+use crate::utils::common::AUTH_HEADER;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::MetadataValue;
+use tonic::transport::Channel;
+pub use tonic::transport::ClientTlsConfig;
+use tonic::{Request, Status, Streaming};
+
+/// Size of the chunks files are imported in.
+const IMPORT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Number of chunks read ahead of the ones being sent.
+const IMPORT_BUFFERED_CHUNKS: usize = 16;
+
+/// Client of a running SJS server, logged in as a user of one of its databases.
+pub struct SjsClient {
+    channel: Channel,
+    token: String,
+}
+
+impl SjsClient {
+    /// Connects to the server at `host`, such as `http://[::1]:34244`, and logs in.
+    ///
+    /// `tls` is only used when `host` is an `https` url.
+    pub async fn connect(
+        host: String,
+        database: String,
+        username: String,
+        password: String,
+        tls: Option<ClientTlsConfig>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut endpoint = Channel::from_shared(host)?;
+        if let Some(tls) = tls {
+            endpoint = endpoint.tls_config(tls)?;
+        }
+        let channel = endpoint.connect().await?;
+
+        let response = ProtoConnectionServiceClient::new(channel.clone())
+            .check_connection(CheckConnectionRequest {
+                database,
+                username,
+                password,
+            })
+            .await?
+            .into_inner();
+
+        match response.token {
+            Some(token) if response.is_connected => Ok(Self { channel, token }),
+            _ => Err("Invalid credentials".into()),
+        }
+    }
+
+    fn request<T>(&self, message: T) -> Result<Request<T>, Status> {
+        let token = MetadataValue::try_from(self.token.as_str())
+            .map_err(|_| Status::unauthenticated("Invalid session"))?;
+
+        let mut request = Request::new(message);
+        request.metadata_mut().insert(AUTH_HEADER, token);
+        Ok(request)
+    }
+
+    pub async fn backup(&self, request: BackupRequest) -> Result<BackupResponse, Status> {
+        let response = ProtoAdminServiceClient::new(self.channel.clone())
+            .backup(self.request(request)?)
+            .await?;
+
+        Ok(response.into_inner())
+    }
+
+    /// Streams the rows of a table, encoded in the requested format.
+    pub async fn export_table(
+        &self,
+        request: ExportTableRequest,
+    ) -> Result<Streaming<TransferChunk>, Status> {
+        let response = ProtoTransferServiceClient::new(self.channel.clone())
+            .export_table(self.request(request)?)
+            .await?;
+
+        Ok(response.into_inner())
+    }
+
+    /// Streams `reader` to be imported into `table_name`, `IMPORT_CHUNK_SIZE` bytes at a time.
+    /// The import is aborted if `reader` fails.
+    pub async fn import_table<R: AsyncRead + Unpin + Send + 'static>(
+        &self,
+        table_name: String,
+        format: TransferFormat,
+        database: Option<String>,
+        mut reader: R,
+    ) -> Result<ImportTableResponse, Status> {
+        let (tx, rx) = mpsc::channel(IMPORT_BUFFERED_CHUNKS);
+        let (err_tx, err_rx) = oneshot::channel();
+
+        tokio::spawn(async move {
+            // The table is only named by the first chunk, which is sent even if `reader` is empty.
+            let mut first = Some((table_name, format, database));
+
+            loop {
+                let mut data = vec![0; IMPORT_CHUNK_SIZE];
+                let read = match reader.read(&mut data).await {
+                    Ok(read) => read,
+                    Err(err) => {
+                        let _ = err_tx.send(err);
+                        // Ending the stream would complete the import, it is cancelled instead.
+                        tx.closed().await;
+                        return;
+                    }
+                };
+
+                if read == 0 && first.is_none() {
+                    return;
+                }
+
+                data.truncate(read);
+                let mut chunk = ImportTableChunk {
+                    data,
+                    ..Default::default()
+                };
+                if let Some((table_name, format, database)) = first.take() {
+                    chunk.table_name = table_name;
+                    chunk.format = format as i32;
+                    chunk.database = database;
+                }
+
+                if tx.send(chunk).await.is_err() || read == 0 {
+                    return;
+                }
+            }
+        });
+
+        let mut client = ProtoTransferServiceClient::new(self.channel.clone());
+        let request = self.request(ReceiverStream::new(rx))?;
+
+        // Dropping the call cancels the stream, so the server does not take a partial file
+        // for a complete one.
+        tokio::select! {
+            response = client.import_table(request) => Ok(response?.into_inner()),
+            Ok(err) = err_rx => Err(Status::aborted(format!("Could not read the file: {}", err))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::client::SjsClient;
+    use crate::services::connection::connection_service::proto_connection_service_server::ProtoConnectionServiceServer;
+    use crate::services::connection::ConnectionService;
+    use crate::utils::common::test_internal_manager;
+    use crate::utils::tls::test::test_cert_path;
+    use crate::utils::tls::{load_client_tls_config, load_server_tls_config};
+    use schemajs_config::GrpcTlsConfig;
+    use std::collections::HashMap;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::Server;
+
+    #[tokio::test]
+    async fn test_connect_with_tls() {
+        let data = tempfile::tempdir().unwrap();
+        let manager = test_internal_manager(data.path(), &[]);
+        manager.init().unwrap();
+
+        let server_tls = load_server_tls_config(&GrpcTlsConfig {
+            cert: test_cert_path("server.pem"),
+            key: test_cert_path("server.key"),
+            client_ca: Some(test_cert_path("ca.pem")),
+            client_cert_auth: false,
+            client_cert_users: HashMap::new(),
+        })
+        .unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(
+            Server::builder()
+                .tls_config(server_tls)
+                .unwrap()
+                .add_service(ProtoConnectionServiceServer::new(ConnectionService::new(
+                    manager,
+                )))
+                .serve_with_incoming(incoming),
+        );
+
+        let ca = test_cert_path("ca.pem");
+        let cert = test_cert_path("client.pem");
+        let key = test_cert_path("client.key");
+        let identity = Some((cert.as_str(), key.as_str()));
+        let connect = |host: String, identity: Option<(&str, &str)>| {
+            SjsClient::connect(
+                host,
+                "public".to_string(),
+                "admin".to_string(),
+                "admin".to_string(),
+                Some(load_client_tls_config(Some(&ca), identity).unwrap()),
+            )
+        };
+
+        let https = format!("https://localhost:{}", port);
+        assert!(connect(https.clone(), identity).await.is_ok());
+
+        // The server only accepts clients with a certificate its CA issued.
+        assert!(connect(https, None).await.is_err());
+        // Nor does it speak plain http.
+        let http = format!("http://localhost:{}", port);
+        assert!(connect(http, identity).await.is_err());
+    }
+}
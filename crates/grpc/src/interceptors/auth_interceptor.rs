@@ -1,6 +1,10 @@
+use crate::utils::common::{check_initialized, AUTH_HEADER};
+use crate::utils::tls::get_common_name;
+use schemajs_internal::auth::types::UserContext;
 use schemajs_internal::manager::InternalManager;
 use std::sync::Arc;
 use tonic::body::BoxBody;
+use tonic::transport::server::{TcpConnectInfo, TlsConnectInfo};
 use tonic::{async_trait, Request, Status};
 use tonic_middleware::RequestInterceptor;
 
@@ -9,14 +13,57 @@ pub struct AuthInterceptor {
     pub(crate) engine: Arc<InternalManager>,
 }
 
+impl AuthInterceptor {
+    fn authenticate_client_cert(
+        &self,
+        req: &tonic::codegen::http::Request<BoxBody>,
+    ) -> Option<Arc<UserContext>> {
+        let certs = req
+            .extensions()
+            .get::<TlsConnectInfo<TcpConnectInfo>>()?
+            .peer_certs()?;
+
+        self.client_cert_user(certs.first()?)
+    }
+
+    /// Maps a DER encoded client certificate verified during the mTLS handshake to the SchemaJS
+    /// user `client_cert_users` binds its Common Name to. The user is looked up in the database
+    /// it is bound to, so the database a request names never changes who the certificate is.
+    fn client_cert_user(&self, cert: &[u8]) -> Option<Arc<UserContext>> {
+        let config = self.engine.get_config();
+        let tls = config
+            .grpc
+            .tls
+            .as_ref()
+            .filter(|tls| tls.client_ca.is_some() && tls.client_cert_auth)?;
+
+        let common_name = get_common_name(cert)?;
+        let bound = tls.client_cert_users.get(&common_name)?;
+
+        self.engine
+            .auth_manager()
+            .find_trusted_user(&bound.database, &bound.user)
+            .map(|user| Arc::new(UserContext::new(user)))
+    }
+}
+
 #[async_trait]
 impl RequestInterceptor for AuthInterceptor {
     async fn intercept(
         &self,
         mut req: tonic::codegen::http::Request<BoxBody>,
     ) -> Result<tonic::codegen::http::Request<BoxBody>, Status> {
-        match req.headers().get("x-sjs-auth") {
-            None => Err(Status::unauthenticated("Unknown Authentication")),
+        // Users cannot be looked up before the internal tables are loaded.
+        check_initialized(&self.engine)?;
+
+        match req.headers().get(AUTH_HEADER) {
+            None => match self.authenticate_client_cert(&req) {
+                Some(user_ctx) => {
+                    req.extensions_mut().insert(user_ctx);
+                    Ok(req)
+                }
+                None => Err(Status::unauthenticated("Unknown Authentication")),
+            },
             Some(val) => {
                 let ctx = self
                     .engine
@@ -32,3 +79,90 @@ impl RequestInterceptor for AuthInterceptor {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::interceptors::auth_interceptor::AuthInterceptor;
+    use crate::utils::common::{
+        test_internal_manager, test_internal_manager_with_config, AUTH_HEADER,
+    };
+    use crate::utils::tls::test::read_test_cert;
+    use schemajs_config::SchemeJsConfig;
+    use tonic::body::empty_body;
+    use tonic::codegen::http::Request;
+    use tonic::Code;
+    use tonic_middleware::RequestInterceptor;
+
+    #[tokio::test]
+    async fn test_unavailable_until_initialized() {
+        let data = tempfile::tempdir().unwrap();
+        let interceptor = AuthInterceptor {
+            engine: test_internal_manager(data.path(), &[]),
+        };
+        let request = || {
+            Request::builder()
+                .header(AUTH_HEADER, "token")
+                .body(empty_body())
+                .unwrap()
+        };
+
+        let status = interceptor.intercept(request()).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+
+        interceptor.engine.init().unwrap();
+        let status = interceptor.intercept(request()).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_client_cert_user() {
+        let interceptor = |client_cert_auth: bool, users: &str| {
+            let config = SchemeJsConfig::from_str(&format!(
+                r#"
+  [grpc.tls]
+  cert = "./test_cases/tls/server.pem"
+  key = "./test_cases/tls/server.key"
+  client_ca = "./test_cases/tls/ca.pem"
+  client_cert_auth = {}
+
+  [grpc.tls.client_cert_users]
+  {}
+"#,
+                client_cert_auth, users
+            ))
+            .unwrap();
+
+            let data = tempfile::tempdir().unwrap();
+            let engine = test_internal_manager_with_config(data.path(), &["analytics"], config);
+            engine.init().unwrap();
+            (data, AuthInterceptor { engine })
+        };
+        // Issued for CN=alice.
+        let cert = read_test_cert("client.pem");
+
+        let (_data, bound) = interceptor(
+            true,
+            r#"alice = { database = "analytics", user = "admin" }"#,
+        );
+        let user = bound.client_cert_user(&cert).unwrap();
+        assert_eq!(user.get_user().identifier, "admin");
+        assert_eq!(user.get_user().scheme, "analytics");
+
+        // The Common Name alone is not a user of any database.
+        let (_data, unbound) =
+            interceptor(true, r#"bob = { database = "analytics", user = "admin" }"#);
+        assert!(unbound.client_cert_user(&cert).is_none());
+
+        let (_data, missing_user) = interceptor(
+            true,
+            r#"alice = { database = "analytics", user = "alice" }"#,
+        );
+        assert!(missing_user.client_cert_user(&cert).is_none());
+
+        let (_data, disabled) = interceptor(
+            false,
+            r#"alice = { database = "analytics", user = "admin" }"#,
+        );
+        assert!(disabled.client_cert_user(&cert).is_none());
+    }
+}
@@ -1,6 +1,10 @@
+pub mod client;
 pub mod interceptors;
 pub mod server;
 mod services;
 pub mod utils;
 
 pub type GrpcResponse<T> = Result<tonic::Response<T>, tonic::Status>;
+
+/// Encoded descriptors of every `sjs.*` service, used by server reflection.
+pub(crate) const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("sjs_descriptor");
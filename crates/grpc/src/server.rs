@@ -1,4 +1,6 @@
 use crate::interceptors::auth_interceptor::AuthInterceptor;
+use crate::services::admin::admin_service::proto_admin_service_server::ProtoAdminServiceServer;
+use crate::services::admin::AdminService;
 use crate::services::connection::connection_service::proto_connection_service_server::ProtoConnectionServiceServer;
 use crate::services::connection::ConnectionService;
 use crate::services::query::custom_query::custom_query_service::proto_custom_query_service_server::ProtoCustomQueryServiceServer;
@@ -7,10 +9,17 @@ use crate::services::query::insert::insert_service::proto_row_insert_service_ser
 use crate::services::query::insert::InsertService;
 use crate::services::query::query_data::query_service::proto_query_service_server::ProtoQueryServiceServer;
 use crate::services::query::query_data::QueryService;
+use crate::services::transfer::transfer_service::proto_transfer_service_server::ProtoTransferServiceServer;
+use crate::services::transfer::TransferService;
+use crate::utils::tls::load_server_tls_config;
+use crate::FILE_DESCRIPTOR_SET;
 use schemajs_internal::manager::InternalManager;
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
 use tonic::transport::Server;
+use tonic_health::server::{health_reporter, HealthReporter};
+use tonic_health::ServingStatus;
 use tonic_middleware::InterceptorFor;
 
 pub struct GrpcServer {
@@ -32,9 +41,68 @@ impl GrpcServer {
         }
     }
 
+    /// Reports every service as NOT_SERVING until the databases are loaded and
+    /// `InternalManager::init` has run.
+    async fn report_readiness(db_manager: Arc<InternalManager>, mut reporter: HealthReporter) {
+        while !db_manager.is_initialized() {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        reporter
+            .set_serving::<ProtoConnectionServiceServer<ConnectionService>>()
+            .await;
+        reporter
+            .set_serving::<ProtoRowInsertServiceServer<InsertService>>()
+            .await;
+        reporter
+            .set_serving::<ProtoQueryServiceServer<QueryService>>()
+            .await;
+        reporter
+            .set_serving::<ProtoCustomQueryServiceServer<CustomQueryService>>()
+            .await;
+        reporter
+            .set_serving::<ProtoAdminServiceServer<AdminService>>()
+            .await;
+        reporter
+            .set_serving::<ProtoTransferServiceServer<TransferService>>()
+            .await;
+        reporter
+            .set_service_status("", ServingStatus::Serving)
+            .await;
+    }
+
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
         let curr_db = self.db_manager.clone();
 
+        let (mut reporter, health_service) = health_reporter();
+        reporter
+            .set_service_status("", ServingStatus::NotServing)
+            .await;
+        reporter
+            .set_not_serving::<ProtoConnectionServiceServer<ConnectionService>>()
+            .await;
+        reporter
+            .set_not_serving::<ProtoRowInsertServiceServer<InsertService>>()
+            .await;
+        reporter
+            .set_not_serving::<ProtoQueryServiceServer<QueryService>>()
+            .await;
+        reporter
+            .set_not_serving::<ProtoCustomQueryServiceServer<CustomQueryService>>()
+            .await;
+        reporter
+            .set_not_serving::<ProtoAdminServiceServer<AdminService>>()
+            .await;
+        reporter
+            .set_not_serving::<ProtoTransferServiceServer<TransferService>>()
+            .await;
+        tokio::spawn(Self::report_readiness(curr_db.clone(), reporter));
+
+        let reflection_service = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+            .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+            .build_v1()?;
+
         let connection_service =
             ProtoConnectionServiceServer::new(ConnectionService::new(curr_db.clone()));
 
@@ -45,7 +113,18 @@ impl GrpcServer {
         let custom_query_service =
             ProtoCustomQueryServiceServer::new(CustomQueryService::new(curr_db.clone()));
 
-        let _ = Server::builder()
+        let admin_service = ProtoAdminServiceServer::new(AdminService::new(curr_db.clone()));
+
+        let transfer_service =
+            ProtoTransferServiceServer::new(TransferService::new(curr_db.clone()));
+
+        let mut server = Server::builder();
+
+        if let Some(tls) = &curr_db.get_config().grpc.tls {
+            server = server.tls_config(load_server_tls_config(tls)?)?;
+        }
+
+        let _ = server
             .add_service(InterceptorFor::new(
                 insert_service,
                 AuthInterceptor {
@@ -64,7 +143,21 @@ impl GrpcServer {
                     engine: curr_db.clone(),
                 },
             ))
+            .add_service(InterceptorFor::new(
+                admin_service,
+                AuthInterceptor {
+                    engine: curr_db.clone(),
+                },
+            ))
+            .add_service(InterceptorFor::new(
+                transfer_service,
+                AuthInterceptor {
+                    engine: curr_db.clone(),
+                },
+            ))
             .add_service(connection_service)
+            .add_service(health_service)
+            .add_service(reflection_service)
             .serve(self.ip.clone())
             .await?;
 
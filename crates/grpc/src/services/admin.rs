@@ -0,0 +1,132 @@
+pub mod admin_service {
+    tonic::include_proto!("sjs.admin");
+}
+
+use crate::utils::common::find_database;
+use crate::{define_sjs_grpc_service, GrpcResponse};
+use admin_service::{BackupRequest, BackupResponse};
+use schemajs_data::backup::{resolve_backup_folder, Backup};
+use schemajs_internal::auth::types::UserContext;
+use std::io::ErrorKind;
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+
+define_sjs_grpc_service!(AdminService);
+
+#[tonic::async_trait]
+impl admin_service::proto_admin_service_server::ProtoAdminService for AdminService {
+    async fn backup(&self, request: Request<BackupRequest>) -> GrpcResponse<BackupResponse> {
+        let ctx = match request.extensions().get::<Arc<UserContext>>() {
+            Some(ctx) => ctx.clone(),
+            None => return Err(Status::unauthenticated("Invalid session")),
+        };
+
+        let BackupRequest {
+            destination,
+            database,
+        } = request.into_inner();
+        let user = ctx.get_user();
+
+        let databases = match database {
+            Some(database) => {
+                if !(user.is_admin || user.is_super_admin) {
+                    return Err(Status::permission_denied(
+                        "Only admins can back up a database",
+                    ));
+                }
+
+                vec![find_database(
+                    &self.db_manager,
+                    ctx.clone(),
+                    Some(database),
+                )?]
+            }
+            None => {
+                if !user.is_super_admin {
+                    return Err(Status::permission_denied(
+                        "Only super admins can back up every database",
+                    ));
+                }
+
+                self.db_manager.engine().read().databases.clone()
+            }
+        };
+
+        // Backups are only written under the backup folder of the server.
+        let folder = {
+            let root = self.db_manager.engine().read().backup_folder();
+            resolve_backup_folder(&root, &destination)
+                .map_err(|err| Status::invalid_argument(err.to_string()))?
+        };
+
+        // Databases are copied one after the other, each one is only quiesced while it is copied.
+        let manifest = tokio::task::spawn_blocking(move || {
+            let mut backup = Backup::create(&folder)?;
+            for db in &databases {
+                db.backup(&mut backup)?;
+            }
+            backup.finish()
+        })
+        .await
+        .map_err(|err| Status::internal(err.to_string()))?
+        .map_err(|err| match err.kind() {
+            ErrorKind::AlreadyExists => Status::already_exists(err.to_string()),
+            _ => Status::internal(format!("Backup failed: {}", err)),
+        })?;
+
+        Ok(Response::new(BackupResponse {
+            destination,
+            databases: manifest.databases,
+            files: manifest.files.len() as u64,
+            created_at: manifest.created_at,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::admin::admin_service::proto_admin_service_server::ProtoAdminService;
+    use crate::services::admin::admin_service::BackupRequest;
+    use crate::services::admin::AdminService;
+    use crate::utils::common::test_internal_manager;
+    use schemajs_internal::auth::types::UserContext;
+    use schemajs_internal::users::user::User;
+    use std::sync::Arc;
+    use tonic::{Code, Request};
+
+    #[tokio::test]
+    async fn test_backup_stays_in_backup_folder() {
+        let data = tempfile::tempdir().unwrap();
+        let service = AdminService::new(test_internal_manager(data.path(), &["public"]));
+        let request = |destination: &str| {
+            let mut request = Request::new(BackupRequest {
+                destination: destination.to_string(),
+                database: None,
+            });
+            request
+                .extensions_mut()
+                .insert(Arc::new(UserContext::new(User {
+                    identifier: "admin".to_string(),
+                    hashed_password: String::new(),
+                    created_at: 0,
+                    updated_at: 0,
+                    is_admin: true,
+                    is_super_admin: true,
+                    roles: vec![],
+                    scheme: "public".to_string(),
+                })));
+            request
+        };
+
+        let outside = data.path().join("outside");
+        for destination in [outside.to_str().unwrap(), "../outside", ""] {
+            let status = service.backup(request(destination)).await.unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+        }
+        assert!(!outside.exists());
+
+        let response = service.backup(request("nightly")).await.unwrap();
+        assert_eq!(response.get_ref().databases, vec!["public".to_string()]);
+        assert!(data.path().join("backups/nightly/manifest.json").exists());
+    }
+}
@@ -2,10 +2,11 @@ pub mod connection_service {
     tonic::include_proto!("sjs.connection");
 }
 
+use crate::utils::common::check_initialized;
 use crate::{define_sjs_grpc_service, GrpcResponse};
 use connection_service::{CheckConnectionRequest, CheckConnectionResponse};
-use schemajs_internal::auth::types::VerifyUserArgs;
-use tonic::Response;
+use schemajs_internal::auth::types::{AuthError, VerifyUserArgs};
+use tonic::{Response, Status};
 
 define_sjs_grpc_service!(ConnectionService);
 
@@ -17,24 +18,59 @@ impl connection_service::proto_connection_service_server::ProtoConnectionService
         &self,
         request: tonic::Request<CheckConnectionRequest>,
     ) -> GrpcResponse<CheckConnectionResponse> {
+        check_initialized(&self.db_manager)?;
+
+        let peer = request.remote_addr().map(|addr| addr.ip().to_string());
         let inner_req = request.into_inner();
         let auth_manager = self.db_manager.auth_manager();
-        let valid_user = auth_manager.authenticate(VerifyUserArgs {
-            scheme_name: inner_req.database,
-            identifier: inner_req.username,
-            password: inner_req.password,
-        });
-
-        if let Ok(token) = valid_user {
-            Ok(Response::new(CheckConnectionResponse {
+        let valid_user = auth_manager.authenticate(
+            VerifyUserArgs {
+                scheme_name: inner_req.database,
+                identifier: inner_req.username,
+                password: inner_req.password,
+            },
+            peer,
+        );
+
+        match valid_user {
+            Ok(token) => Ok(Response::new(CheckConnectionResponse {
                 is_connected: true,
-                token: Some(token.to_string()),
-            }))
-        } else {
-            Ok(Response::new(CheckConnectionResponse {
+                token: Some(token),
+            })),
+            Err(err @ AuthError::LockedOut(_)) => Err(Status::resource_exhausted(err.to_string())),
+            Err(_) => Ok(Response::new(CheckConnectionResponse {
                 is_connected: false,
                 token: None,
-            }))
+            })),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::services::connection::connection_service::proto_connection_service_server::ProtoConnectionService;
+    use crate::services::connection::connection_service::CheckConnectionRequest;
+    use crate::services::connection::ConnectionService;
+    use crate::utils::common::test_internal_manager;
+    use tonic::{Code, Request};
+
+    #[tokio::test]
+    async fn test_unavailable_until_initialized() {
+        let data = tempfile::tempdir().unwrap();
+        let service = ConnectionService::new(test_internal_manager(data.path(), &[]));
+        let request = || {
+            Request::new(CheckConnectionRequest {
+                database: "public".to_string(),
+                username: "admin".to_string(),
+                password: "admin".to_string(),
+            })
+        };
+
+        let status = service.check_connection(request()).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+
+        service.db_manager.init().unwrap();
+        let response = service.check_connection(request()).await.unwrap();
+        assert!(response.get_ref().is_connected);
+    }
+}
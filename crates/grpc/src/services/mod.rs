@@ -1,4 +1,6 @@
+pub mod admin;
 pub mod connection;
 pub mod macros;
 pub mod query;
 pub mod shared;
+pub mod transfer;
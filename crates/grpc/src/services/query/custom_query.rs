@@ -1,19 +1,21 @@
 use crate::define_sjs_grpc_service;
 use crate::services::query::custom_query::custom_query_service::{
-    CustomQueryRequest, CustomQueryResponse,
+    CustomQueryDescriptor, CustomQueryRequest, CustomQueryResponse, ListCustomQueriesRequest,
+    ListCustomQueriesResponse,
 };
 use crate::services::shared::shared;
-use crate::utils::common::find_database;
+use crate::utils::common::{find_database, requested_database};
 use crate::utils::json::{serde_json_to_prost, to_prost_struct};
 use prost_types::Any;
 use schemajs_helpers::helper::{HelperCall, HelperDbContext};
 use schemajs_internal::auth::types::UserContext;
+use schemajs_primitives::table::custom_query::SchemaViolation;
 use serde_json::Value;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::select;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
-use tonic::{Request, Response, Status};
+use tonic::{Code, Request, Response, Status};
 
 pub mod custom_query_service {
     tonic::include_proto!("sjs.query");
@@ -23,9 +25,28 @@ define_sjs_grpc_service!(CustomQueryService, {
     pub async fn execute_custom_query(
         &self,
         user_context: Arc<UserContext>,
+        database: Option<String>,
         req: CustomQueryRequest,
     ) -> Result<Value, Status> {
-        let db = find_database(&self.db_manager, user_context)?;
+        let db = find_database(&self.db_manager, user_context, database)?;
+        let req_val: Value =
+            serde_json::from_str(&req.req).map_err(|_| Status::internal("Invalid Payload"))?;
+
+        let query = db
+            .query_manager
+            .get_table(&req.table_name)
+            .and_then(|tbl| tbl.get_custom_query(&req.identifier).cloned());
+
+        if let Some(query) = &query {
+            query.validate_input(&req_val).map_err(|violations| {
+                Self::violations_status(
+                    Code::InvalidArgument,
+                    "Invalid custom query arguments",
+                    violations,
+                )
+            })?;
+        }
+
         let (helper_response_tx, mut helper_response_rx) = self.create_response_handlers();
         let result = db
             .call_helper(HelperCall::CustomQuery {
@@ -34,8 +55,7 @@ define_sjs_grpc_service!(CustomQueryService, {
                     table: Some(req.table_name),
                 },
                 identifier: req.identifier,
-                req: serde_json::from_str(&req.req)
-                    .map_err(|_| Status::internal("Invalid Payload"))?,
+                req: req_val,
                 response: helper_response_tx,
             })
             .await;
@@ -59,7 +79,55 @@ define_sjs_grpc_service!(CustomQueryService, {
             _ = timeout => Err(())
         };
 
-        resp.map_err(|_| Status::aborted("Custom query timed out"))
+        let resp = resp.map_err(|_| Status::aborted("Custom query timed out"))?;
+
+        if let Some(query) = &query {
+            query.validate_output(&resp).map_err(|violations| {
+                Self::violations_status(
+                    Code::Internal,
+                    "Custom query returned an invalid result",
+                    violations,
+                )
+            })?;
+        }
+
+        Ok(resp)
+    }
+
+    pub fn list_queries(
+        &self,
+        user_context: Arc<UserContext>,
+        database: Option<String>,
+        table_name: Option<String>,
+    ) -> Result<Vec<CustomQueryDescriptor>, Status> {
+        let db = find_database(&self.db_manager, user_context, database)?;
+        let table_names = db.query_manager.table_names.read().unwrap().clone();
+
+        let queries = table_names
+            .iter()
+            .filter(|name| table_name.as_ref().map_or(true, |tbl| tbl == *name))
+            .filter_map(|name| db.query_manager.get_table(name))
+            .filter(|tbl| !tbl.metadata.internal)
+            .flat_map(|tbl| {
+                tbl.queries
+                    .iter()
+                    .map(|query| CustomQueryDescriptor {
+                        table_name: tbl.name.clone(),
+                        identifier: query.name.clone(),
+                        input_schema: query.input.as_ref().map(|schema| schema.to_string()),
+                        output_schema: query.output.as_ref().map(|schema| schema.to_string()),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        Ok(queries)
+    }
+
+    fn violations_status(code: Code, message: &str, violations: Vec<SchemaViolation>) -> Status {
+        let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+
+        Status::new(code, format!("{}: {}", message, details.join("; ")))
     }
 
     fn create_response_handlers(&self) -> (UnboundedSender<Value>, UnboundedReceiver<Value>) {
@@ -80,8 +148,10 @@ impl custom_query_service::proto_custom_query_service_server::ProtoCustomQuerySe
             None => return Err(Status::unauthenticated("Invalid session")),
         };
 
+        let ctx = ctx.clone();
+        let database = requested_database(&request, request.get_ref().database.clone());
         let process_custom_query = self
-            .execute_custom_query(ctx.clone(), request.into_inner())
+            .execute_custom_query(ctx, database, request.into_inner())
             .await;
         match process_custom_query {
             Ok(val) => {
@@ -95,4 +165,155 @@ impl custom_query_service::proto_custom_query_service_server::ProtoCustomQuerySe
             Err(s) => Err(s),
         }
     }
+
+    async fn list_custom_queries(
+        &self,
+        request: Request<ListCustomQueriesRequest>,
+    ) -> Result<Response<ListCustomQueriesResponse>, Status> {
+        let ctx = match request.extensions().get::<Arc<UserContext>>() {
+            Some(ctx) => ctx,
+            None => return Err(Status::unauthenticated("Invalid session")),
+        };
+
+        let ctx = ctx.clone();
+        let database = requested_database(&request, request.get_ref().database.clone());
+        let queries = self.list_queries(ctx, database, request.into_inner().table_name)?;
+
+        Ok(Response::new(ListCustomQueriesResponse { queries }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::services::query::custom_query::custom_query_service::proto_custom_query_service_server::ProtoCustomQueryService;
+    use crate::services::query::custom_query::custom_query_service::{
+        CustomQueryRequest, ListCustomQueriesRequest,
+    };
+    use crate::services::query::custom_query::CustomQueryService;
+    use crate::utils::common::{find_database, test_internal_manager};
+    use schemajs_internal::auth::types::UserContext;
+    use schemajs_internal::users::user::User;
+    use schemajs_primitives::table::custom_query::CustomQuery;
+    use schemajs_primitives::table::Table;
+    use serde_json::json;
+    use std::sync::Arc;
+    use tonic::{Code, Request};
+
+    fn user_context() -> Arc<UserContext> {
+        Arc::new(UserContext::new(User {
+            identifier: "jane".to_string(),
+            hashed_password: String::new(),
+            created_at: 0,
+            updated_at: 0,
+            is_admin: false,
+            is_super_admin: false,
+            roles: vec![],
+            scheme: "public".to_string(),
+        }))
+    }
+
+    fn test_service(data: &std::path::Path) -> CustomQueryService {
+        let manager = test_internal_manager(data, &["public"]);
+        let db = find_database(&manager, user_context(), None).unwrap();
+
+        let mut users = Table::new("users");
+        users.queries.push(CustomQuery::new(
+            "findByName",
+            Some(json!({
+                "type": "object",
+                "properties": { "name": { "type": "string" } },
+                "required": ["name"]
+            })),
+            Some(json!({ "type": "array" })),
+        ));
+        users.queries.push(CustomQuery::new("count", None, None));
+        users.compile_query_schemas().unwrap();
+        db.query_manager.register_table(users).unwrap();
+
+        let mut products = Table::new("products");
+        products
+            .queries
+            .push(CustomQuery::new("cheapest", None, None));
+        db.query_manager.register_table(products).unwrap();
+
+        CustomQueryService::new(manager)
+    }
+
+    fn request<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request.extensions_mut().insert(user_context());
+        request
+    }
+
+    #[tokio::test]
+    async fn test_list_custom_queries() {
+        let data = tempfile::tempdir().unwrap();
+        let service = test_service(data.path());
+
+        let queries = service
+            .list_custom_queries(request(ListCustomQueriesRequest::default()))
+            .await
+            .unwrap()
+            .into_inner()
+            .queries;
+        let mut names: Vec<_> = queries
+            .iter()
+            .map(|query| format!("{}.{}", query.table_name, query.identifier))
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["products.cheapest", "users.count", "users.findByName"]
+        );
+
+        let queries = service
+            .list_custom_queries(request(ListCustomQueriesRequest {
+                table_name: Some("users".to_string()),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .queries;
+        assert_eq!(queries.len(), 2);
+
+        let find_by_name = queries
+            .iter()
+            .find(|query| query.identifier == "findByName")
+            .unwrap();
+        let input: serde_json::Value =
+            serde_json::from_str(find_by_name.input_schema.as_ref().unwrap()).unwrap();
+        assert_eq!(input["required"], json!(["name"]));
+        assert_eq!(
+            find_by_name.output_schema.as_deref(),
+            Some(r#"{"type":"array"}"#)
+        );
+
+        let count = queries
+            .iter()
+            .find(|query| query.identifier == "count")
+            .unwrap();
+        assert_eq!(count.input_schema, None);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_arguments_are_rejected() {
+        let data = tempfile::tempdir().unwrap();
+        let service = test_service(data.path());
+
+        let status = service
+            .custom_query(request(CustomQueryRequest {
+                table_name: "users".to_string(),
+                identifier: "findByName".to_string(),
+                req: json!({ "name": 1 }).to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(status
+            .message()
+            .starts_with("Invalid custom query arguments: /name: "));
+    }
 }
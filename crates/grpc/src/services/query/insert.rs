@@ -4,9 +4,12 @@ use crate::services::query::insert::insert_service::{
 };
 use crate::services::shared::shared;
 use crate::services::shared::shared::data_value::ValueType;
-use crate::utils::common::convert_to_data_value;
+use crate::utils::common::{
+    check_internal_table_access, convert_to_data_value, find_database, requested_database,
+};
 use schemajs_internal::auth::types::UserContext;
 use schemajs_primitives::column::types::DataValue;
+use schemajs_query::errors::QueryError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::str::FromStr;
@@ -19,18 +22,16 @@ pub mod insert_service {
 }
 
 define_sjs_grpc_service!(InsertService, {
-    pub fn insert_rows_into_db(
+    pub async fn insert_rows_into_db(
         &self,
         user_context: Arc<UserContext>,
+        database: Option<String>,
         rows: Vec<RowInsert>,
     ) -> Result<bool, Status> {
-        let engine = self.db_manager.engine();
-        let db_manager = engine.read();
-        let user = user_context.get_user();
-        let db = match db_manager.find_by_name_ref(&user.scheme) {
-            Some(db) => db,
-            None => return Err(Status::not_found("Database not found")),
-        };
+        let db = find_database(&self.db_manager, user_context.clone(), database)?;
+        for row in &rows {
+            check_internal_table_access(&db, &user_context, &row.table_name, true)?;
+        }
 
         let new_rows: Vec<(String, HashMap<String, DataValue>)> = rows
             .into_iter()
@@ -50,9 +51,16 @@ define_sjs_grpc_service!(InsertService, {
             })
             .collect();
 
-        let insert = db.query_manager.insert_from_value_map(new_rows, false);
+        let insert = db
+            .query_manager
+            .insert_from_value_map(new_rows, false)
+            .await;
 
-        Ok(insert.is_ok())
+        match insert {
+            Ok(_) => Ok(true),
+            Err(QueryError::InsertionRejected(reason)) => Err(Status::failed_precondition(reason)),
+            Err(_) => Ok(false),
+        }
     }
 });
 
@@ -67,7 +75,11 @@ impl insert_service::proto_row_insert_service_server::ProtoRowInsertService for
             None => return Err(Status::unauthenticated("Invalid session")),
         };
 
-        let inserted = self.insert_rows_into_db(ctx.clone(), request.into_inner().rows)?;
+        let ctx = ctx.clone();
+        let database = requested_database(&request, request.get_ref().database.clone());
+        let inserted = self
+            .insert_rows_into_db(ctx, database, request.into_inner().rows)
+            .await?;
 
         if !inserted {
             Err(Status::aborted("There was an issue inserting rows"))
@@ -5,7 +5,10 @@ use crate::services::query::query_data::query_service::{
 use crate::services::shared::shared;
 use crate::services::shared::shared::data_value::ValueType;
 use crate::services::shared::shared::DataValue as GrpcDataValue;
-use crate::utils::common::{convert_to_grpc_value, find_database, from_grpc_ops_to_sjs_ops};
+use crate::utils::common::{
+    check_internal_table_access, convert_to_grpc_value, find_database, from_grpc_ops_to_sjs_ops,
+    requested_database,
+};
 use schemajs_internal::auth::types::UserContext;
 use schemajs_primitives::column::types::DataValue;
 use schemajs_query::row::Row;
@@ -21,10 +24,12 @@ define_sjs_grpc_service!(QueryService, {
     pub fn query_rows_from_db(
         &self,
         user_context: Arc<UserContext>,
+        database: Option<String>,
         table_name: String,
         operation: Option<GrpcQueryOps>,
     ) -> Result<Vec<DataMap>, Status> {
-        let db = find_database(&self.db_manager, user_context)?;
+        let db = find_database(&self.db_manager, user_context.clone(), database)?;
+        check_internal_table_access(&db, &user_context, &table_name, false)?;
         if let Some(op) = operation {
             let query_ops = from_grpc_ops_to_sjs_ops(op);
             if let Ok(qops) = query_ops {
@@ -84,9 +89,10 @@ impl query_service::proto_query_service_server::ProtoQueryService for QueryServi
         })
         .clone();
 
+        let database = requested_database(&request, request.get_ref().database.clone());
         let inner = request.into_inner();
 
-        let rows = self.query_rows_from_db(ctx, inner.table_name, inner.query)?;
+        let rows = self.query_rows_from_db(ctx, database, inner.table_name, inner.query)?;
 
         Ok(Response::new(QueryResponse { values: rows }))
     }
@@ -0,0 +1,183 @@
+pub mod transfer_service {
+    tonic::include_proto!("sjs.transfer");
+}
+
+use crate::utils::common::{check_internal_table_access, find_database, requested_database};
+use crate::{define_sjs_grpc_service, GrpcResponse};
+use schemajs_internal::auth::types::UserContext;
+use schemajs_query::errors::QueryError;
+use schemajs_query::transfer::{export_table, import_table, TransferError, TransferFormat};
+use std::io::{BufWriter, ErrorKind, Read, Write};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::{channel, Sender};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+use transfer_service::{
+    ExportTableRequest, ImportTableChunk, ImportTableResponse, TransferChunk,
+    TransferFormat as ProtoTransferFormat,
+};
+
+/// Size of the chunks tables are exported in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Number of exported chunks buffered while the client receives the previous ones.
+const EXPORT_BUFFERED_CHUNKS: usize = 16;
+
+define_sjs_grpc_service!(TransferService);
+
+fn to_transfer_format(format: i32) -> Result<TransferFormat, Status> {
+    match ProtoTransferFormat::try_from(format) {
+        Ok(ProtoTransferFormat::Ndjson) => Ok(TransferFormat::Ndjson),
+        Ok(ProtoTransferFormat::Csv) => Ok(TransferFormat::Csv),
+        Ok(ProtoTransferFormat::Parquet) => Ok(TransferFormat::Parquet),
+        Err(_) => Err(Status::invalid_argument(format!(
+            "Unknown transfer format {}",
+            format
+        ))),
+    }
+}
+
+fn transfer_status(err: TransferError) -> Status {
+    match err {
+        TransferError::Query(QueryError::InvalidTable(table)) => {
+            Status::not_found(format!("Unknown table '{}'", table))
+        }
+        TransferError::Query(err) => Status::internal(err.to_string()),
+        TransferError::Io(err) => Status::aborted(err.to_string()),
+        err => Status::invalid_argument(err.to_string()),
+    }
+}
+
+/// Sends whatever is written to it as the chunks of an export stream.
+struct ChunkSender {
+    tx: Sender<Result<TransferChunk, Status>>,
+}
+
+impl Write for ChunkSender {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.tx
+            .blocking_send(Ok(TransferChunk { data: buf.to_vec() }))
+            .map_err(|_| std::io::Error::new(ErrorKind::BrokenPipe, "Export stream was closed"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads the data of the chunks of an import stream, waiting for the client to send the
+/// next chunk once the current one is read.
+struct ChunkReceiver {
+    stream: Streaming<ImportTableChunk>,
+    handle: Handle,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for ChunkReceiver {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.handle.block_on(self.stream.message()) {
+                Ok(Some(chunk)) => {
+                    self.chunk = chunk.data;
+                    self.pos = 0;
+                }
+                Ok(None) => return Ok(0),
+                Err(status) => {
+                    return Err(std::io::Error::new(
+                        ErrorKind::ConnectionAborted,
+                        status.message().to_string(),
+                    ))
+                }
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len() - self.pos);
+        buf[..len].copy_from_slice(&self.chunk[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+#[tonic::async_trait]
+impl transfer_service::proto_transfer_service_server::ProtoTransferService for TransferService {
+    type ExportTableStream = ReceiverStream<Result<TransferChunk, Status>>;
+
+    async fn export_table(
+        &self,
+        request: Request<ExportTableRequest>,
+    ) -> GrpcResponse<Self::ExportTableStream> {
+        let ctx = match request.extensions().get::<Arc<UserContext>>() {
+            Some(ctx) => ctx.clone(),
+            None => return Err(Status::unauthenticated("Invalid session")),
+        };
+
+        let database = requested_database(&request, request.get_ref().database.clone());
+        let ExportTableRequest {
+            table_name, format, ..
+        } = request.into_inner();
+        let format = to_transfer_format(format)?;
+
+        let db = find_database(&self.db_manager, ctx.clone(), database)?;
+        check_internal_table_access(&db, &ctx, &table_name, false)?;
+        if db.query_manager.get_table(&table_name).is_none() {
+            return Err(Status::not_found(format!("Unknown table '{}'", table_name)));
+        }
+
+        // Rows are read and encoded on a blocking thread, which waits whenever the client
+        // falls behind by more than `EXPORT_BUFFERED_CHUNKS` chunks.
+        let (tx, rx) = channel(EXPORT_BUFFERED_CHUNKS);
+        tokio::task::spawn_blocking(move || {
+            let writer = BufWriter::with_capacity(CHUNK_SIZE, ChunkSender { tx: tx.clone() });
+            if let Err(err) = export_table(&db.query_manager, &table_name, format, writer) {
+                let _ = tx.blocking_send(Err(transfer_status(err)));
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn import_table(
+        &self,
+        request: Request<Streaming<ImportTableChunk>>,
+    ) -> GrpcResponse<ImportTableResponse> {
+        let ctx = match request.extensions().get::<Arc<UserContext>>() {
+            Some(ctx) => ctx.clone(),
+            None => return Err(Status::unauthenticated("Invalid session")),
+        };
+
+        let header_database = requested_database(&request, None);
+        let mut stream = request.into_inner();
+        let ImportTableChunk {
+            table_name,
+            format,
+            database,
+            data,
+        } = stream
+            .message()
+            .await?
+            .ok_or_else(|| Status::invalid_argument("Import stream is empty"))?;
+        let format = to_transfer_format(format)?;
+
+        let db = find_database(&self.db_manager, ctx.clone(), database.or(header_database))?;
+        check_internal_table_access(&db, &ctx, &table_name, true)?;
+
+        let reader = ChunkReceiver {
+            stream,
+            handle: Handle::current(),
+            chunk: data,
+            pos: 0,
+        };
+
+        let rows = tokio::task::spawn_blocking(move || {
+            import_table(&db.query_manager, &table_name, format, reader)
+        })
+        .await
+        .map_err(|err| Status::internal(err.to_string()))?
+        .map_err(transfer_status)?;
+
+        Ok(Response::new(ImportTableResponse { rows }))
+    }
+}
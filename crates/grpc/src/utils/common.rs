@@ -10,7 +10,7 @@ use schemajs_primitives::column::types::DataValue;
 use schemajs_query::ops::query_ops::{QueryOps, QueryVal};
 use std::str::FromStr;
 use std::sync::Arc;
-use tonic::Status;
+use tonic::{Request, Status};
 use uuid::Uuid;
 
 pub fn convert_to_data_value(val: ValueType) -> DataValue {
@@ -35,19 +35,84 @@ pub fn convert_to_grpc_value(val: &DataValue) -> ValueType {
     }
 }
 
+pub const DATABASE_HEADER: &str = "x-sjs-db";
+
+/// Header the session token of a user is sent in, as returned by `CheckConnection`.
+pub const AUTH_HEADER: &str = "x-sjs-auth";
+
+/// Database requested by the client, either through the message itself or the `x-sjs-db` header.
+/// The message field takes precedence.
+pub fn requested_database<T>(request: &Request<T>, database: Option<String>) -> Option<String> {
+    database.or_else(|| {
+        request
+            .metadata()
+            .get(DATABASE_HEADER)
+            .and_then(|val| val.to_str().ok())
+            .map(|val| val.to_string())
+    })
+}
+
+/// Requests are only served once the databases are loaded and `InternalManager::init` has run.
+pub fn check_initialized(internal_manager: &InternalManager) -> Result<(), Status> {
+    if internal_manager.is_initialized() {
+        Ok(())
+    } else {
+        Err(Status::unavailable("SJS is still loading its databases"))
+    }
+}
+
 pub fn find_database(
     internal_manager: &Arc<InternalManager>,
     user_context: Arc<UserContext>,
+    database: Option<String>,
 ) -> Result<Arc<EngineDb>, Status> {
     let engine = internal_manager.clone().engine();
     let db_manager = engine.read();
     let user = user_context.get_user();
-    match db_manager.find_by_name_ref(&user.scheme) {
+    let db_name = database.unwrap_or_else(|| user.scheme.clone());
+
+    if !user.can_access_database(&db_name) {
+        return Err(Status::permission_denied(format!(
+            "User does not have access to database '{}'",
+            db_name
+        )));
+    }
+
+    match db_manager.find_by_name_ref(&db_name) {
         Some(db) => Ok(db.clone()),
         None => return Err(Status::not_found("Database not found")),
     }
 }
 
+/// Internal tables (users, login audit, ...) are read-only through the public API
+/// and can only be queried by admins.
+pub fn check_internal_table_access(
+    db: &EngineDb,
+    user_context: &UserContext,
+    table_name: &str,
+    write: bool,
+) -> Result<(), Status> {
+    let is_internal = db
+        .query_manager
+        .get_table(table_name)
+        .map(|tbl| tbl.metadata.internal)
+        .unwrap_or(false);
+
+    if !is_internal {
+        return Ok(());
+    }
+
+    let user = user_context.get_user();
+    if write || !(user.is_admin || user.is_super_admin) {
+        return Err(Status::permission_denied(format!(
+            "Table '{}' is internal",
+            table_name
+        )));
+    }
+
+    Ok(())
+}
+
 pub fn grpc_query_val_to_sjs_value(val: GrpcQueryVal) -> QueryVal {
     QueryVal {
         key: val.key,
@@ -84,3 +149,119 @@ pub fn from_grpc_ops_to_sjs_ops(query_ops: GrpcQueryOps) -> Result<QueryOps, ()>
         Some(op) => grpc_operation_to_sjs_op(op),
     }
 }
+
+/// Internal manager over an engine holding empty `databases` in `data`.
+#[cfg(test)]
+pub(crate) fn test_internal_manager(
+    data: &std::path::Path,
+    databases: &[&str],
+) -> Arc<InternalManager> {
+    test_internal_manager_with_config(data, databases, schemajs_config::SchemeJsConfig::default())
+}
+
+/// Same as `test_internal_manager`, with the engine running with `config`.
+#[cfg(test)]
+pub(crate) fn test_internal_manager_with_config(
+    data: &std::path::Path,
+    databases: &[&str],
+    config: schemajs_config::SchemeJsConfig,
+) -> Arc<InternalManager> {
+    use parking_lot::RwLock;
+    use schemajs_data::fdm::FileDescriptorManager;
+    use schemajs_engine::engine::SchemeJsEngine;
+    use schemajs_helpers::create_helper_channel;
+
+    let mut engine = SchemeJsEngine::new(
+        Some(data.to_path_buf()),
+        Arc::new(config),
+        create_helper_channel(1).0,
+        Arc::new(FileDescriptorManager::new(100)),
+    );
+    for database in databases {
+        engine.add_database(database);
+    }
+
+    Arc::new(InternalManager::new(Arc::new(RwLock::new(engine))).unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::common::{
+        check_initialized, find_database, requested_database, test_internal_manager,
+        DATABASE_HEADER,
+    };
+    use schemajs_internal::auth::types::UserContext;
+    use schemajs_internal::users::roles::Role;
+    use schemajs_internal::users::user::User;
+    use std::sync::Arc;
+    use tonic::{Code, Request};
+
+    fn user_context(is_super_admin: bool, roles: Vec<Role>) -> Arc<UserContext> {
+        Arc::new(UserContext::new(User {
+            identifier: "jane".to_string(),
+            hashed_password: String::new(),
+            created_at: 0,
+            updated_at: 0,
+            is_admin: false,
+            is_super_admin,
+            roles,
+            scheme: "public".to_string(),
+        }))
+    }
+
+    #[test]
+    fn test_find_database() {
+        let data = tempfile::tempdir().unwrap();
+        let manager = test_internal_manager(data.path(), &["public", "analytics"]);
+
+        let member = user_context(false, vec![]);
+        let db = find_database(&manager, member.clone(), None).unwrap();
+        assert_eq!(db.name, "public");
+        let denied = find_database(&manager, member, Some("analytics".to_string()));
+        assert_eq!(denied.err().unwrap().code(), Code::PermissionDenied);
+
+        let granted = user_context(
+            false,
+            vec![Role::DatabaseAccess(vec!["analytics".to_string()])],
+        );
+        let db = find_database(&manager, granted.clone(), Some("analytics".to_string())).unwrap();
+        assert_eq!(db.name, "analytics");
+        let missing = find_database(
+            &manager,
+            user_context(true, vec![]),
+            Some("billing".to_string()),
+        );
+        assert_eq!(missing.err().unwrap().code(), Code::NotFound);
+    }
+
+    #[test]
+    fn test_check_initialized() {
+        let data = tempfile::tempdir().unwrap();
+        let manager = test_internal_manager(data.path(), &[]);
+        let status = check_initialized(&manager).unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+
+        manager.init().unwrap();
+        assert!(check_initialized(&manager).is_ok());
+    }
+
+    #[test]
+    fn test_requested_database() {
+        let mut request = Request::new(());
+        assert_eq!(requested_database(&request, None), None);
+
+        request
+            .metadata_mut()
+            .insert(DATABASE_HEADER, "analytics".parse().unwrap());
+        assert_eq!(
+            requested_database(&request, None),
+            Some("analytics".to_string())
+        );
+
+        // The message field takes precedence over the header.
+        assert_eq!(
+            requested_database(&request, Some("billing".to_string())),
+            Some("billing".to_string())
+        );
+    }
+}
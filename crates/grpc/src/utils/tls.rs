@@ -0,0 +1,127 @@
+use schemajs_config::GrpcTlsConfig;
+use tonic::transport::{Certificate, ClientTlsConfig, Identity, ServerTlsConfig};
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+pub fn load_server_tls_config(config: &GrpcTlsConfig) -> std::io::Result<ServerTlsConfig> {
+    let cert = std::fs::read(&config.cert)?;
+    let key = std::fs::read(&config.key)?;
+
+    let mut tls = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+
+    if let Some(client_ca) = &config.client_ca {
+        let ca = std::fs::read(client_ca)?;
+        tls = tls.client_ca_root(Certificate::from_pem(ca));
+    }
+
+    Ok(tls)
+}
+
+/// TLS config for connecting to a server whose certificate is issued by the PEM `ca`,
+/// or by a well-known authority if it is not given.
+///
+/// `identity` is the PEM certificate and key the client presents, for servers that verify them.
+pub fn load_client_tls_config(
+    ca: Option<&str>,
+    identity: Option<(&str, &str)>,
+) -> std::io::Result<ClientTlsConfig> {
+    let mut tls = ClientTlsConfig::new();
+
+    tls = match ca {
+        Some(ca) => tls.ca_certificate(Certificate::from_pem(std::fs::read(ca)?)),
+        None => tls.with_webpki_roots(),
+    };
+
+    if let Some((cert, key)) = identity {
+        let cert = std::fs::read(cert)?;
+        let key = std::fs::read(key)?;
+        tls = tls.identity(Identity::from_pem(cert, key));
+    }
+
+    Ok(tls)
+}
+
+/// Common Name of the subject of a DER encoded certificate.
+pub fn get_common_name(der: &[u8]) -> Option<String> {
+    let (_, cert) = X509Certificate::from_der(der).ok()?;
+    let common_name = cert
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(|cn| cn.to_string());
+
+    common_name
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::utils::tls::{get_common_name, load_client_tls_config, load_server_tls_config};
+    use schemajs_config::GrpcTlsConfig;
+    use std::collections::HashMap;
+    use x509_parser::pem::parse_x509_pem;
+
+    pub(crate) fn test_cert_path(name: &str) -> String {
+        format!("./test_cases/tls/{}", name)
+    }
+
+    /// DER encoding of one of the PEM certificates in `test_cases/tls`.
+    pub(crate) fn read_test_cert(name: &str) -> Vec<u8> {
+        let pem = std::fs::read(test_cert_path(name)).unwrap();
+        parse_x509_pem(&pem).unwrap().1.contents
+    }
+
+    #[test]
+    fn test_get_common_name() {
+        assert_eq!(
+            get_common_name(&read_test_cert("client.pem")),
+            Some("alice".to_string())
+        );
+        assert_eq!(
+            get_common_name(&read_test_cert("server.pem")),
+            Some("localhost".to_string())
+        );
+        assert_eq!(get_common_name(b"not a certificate"), None);
+    }
+
+    #[test]
+    fn test_load_server_tls_config() {
+        let config = |cert: &str, key: &str, client_ca: Option<&str>| GrpcTlsConfig {
+            cert: test_cert_path(cert),
+            key: test_cert_path(key),
+            client_ca: client_ca.map(test_cert_path),
+            client_cert_auth: false,
+            client_cert_users: HashMap::new(),
+        };
+
+        assert!(load_server_tls_config(&config("server.pem", "server.key", None)).is_ok());
+        assert!(
+            load_server_tls_config(&config("server.pem", "server.key", Some("ca.pem"))).is_ok()
+        );
+
+        let missing = load_server_tls_config(&config("missing.pem", "server.key", None));
+        assert_eq!(missing.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+        let missing_ca =
+            load_server_tls_config(&config("server.pem", "server.key", Some("missing.pem")));
+        assert_eq!(missing_ca.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_load_client_tls_config() {
+        let ca = test_cert_path("ca.pem");
+        let cert = test_cert_path("client.pem");
+        let key = test_cert_path("client.key");
+
+        assert!(load_client_tls_config(None, None).is_ok());
+        assert!(load_client_tls_config(Some(&ca), None).is_ok());
+        assert!(load_client_tls_config(Some(&ca), Some((&cert, &key))).is_ok());
+
+        let missing = test_cert_path("missing.pem");
+        let missing_ca = load_client_tls_config(Some(&missing), None);
+        assert_eq!(missing_ca.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+        let missing_key = load_client_tls_config(Some(&ca), Some((&cert, &missing)));
+        assert_eq!(
+            missing_key.unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        );
+    }
+}
@@ -6,10 +6,13 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::mpsc::UnboundedSender;
 
-#[derive(Serialize, Deserialize, EnumAsInner, Debug, Clone)]
+#[derive(Serialize, Deserialize, EnumAsInner, Debug, Clone, PartialEq)]
 pub enum HelperType {
     CustomQuery,
     InsertHook,
+    BeforeInsertHook,
+    AfterReconcileHook,
+    Scheduled,
 }
 
 #[derive(Debug)]
@@ -61,10 +64,13 @@ impl SjsTableHelpers {
         match self.0.get(db_name) {
             None => None,
             Some(val) => match hook {
-                HelperType::InsertHook => {
+                HelperType::InsertHook
+                | HelperType::BeforeInsertHook
+                | HelperType::AfterReconcileHook
+                | HelperType::Scheduled => {
                     let helper: Option<Vec<Arc<Helper>>> = val.get(table).map(|e| {
                         e.0.iter()
-                            .filter(|e| e.internal_type.is_insert_hook())
+                            .filter(|e| e.internal_type == hook)
                             .map(|e| e.clone())
                             .collect()
                     });
@@ -95,4 +101,32 @@ pub enum HelperCall {
         db_ctx: HelperDbContext,
         rows: Vec<Value>,
     },
+    /// Runs the `beforeInsert` hooks of a table in order, each one receiving the rows returned by the previous.
+    /// The final rows are sent through `response`, or the reason they were rejected.
+    BeforeInsertHook {
+        db_ctx: HelperDbContext,
+        rows: Vec<Value>,
+        response: UnboundedSender<Result<Vec<Value>, String>>,
+    },
+    AfterReconcileHook {
+        db_ctx: HelperDbContext,
+        rows: Vec<Value>,
+    },
+    /// Runs the scheduled helpers of a table whose cron expression is `identifier`.
+    Scheduled {
+        db_ctx: HelperDbContext,
+        identifier: String,
+    },
+}
+
+impl HelperCall {
+    pub fn db_ctx(&self) -> &HelperDbContext {
+        match self {
+            HelperCall::CustomQuery { db_ctx, .. }
+            | HelperCall::InsertHook { db_ctx, .. }
+            | HelperCall::BeforeInsertHook { db_ctx, .. }
+            | HelperCall::AfterReconcileHook { db_ctx, .. }
+            | HelperCall::Scheduled { db_ctx, .. } => db_ctx,
+        }
+    }
 }
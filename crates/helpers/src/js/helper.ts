@@ -1,6 +1,9 @@
 export enum HelperType {
     CustomQuery = "CustomQuery",
-    InsertHook = "InsertHook"
+    InsertHook = "InsertHook",
+    BeforeInsertHook = "BeforeInsertHook",
+    AfterReconcileHook = "AfterReconcileHook",
+    Scheduled = "Scheduled"
 }
 
 export type HelperCbType = (...args: any[]) => any;
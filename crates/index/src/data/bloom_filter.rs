@@ -0,0 +1,148 @@
+use std::path::Path;
+
+/// Magic number persisted filters start with, followed by their format version as a `u16`.
+const MAGIC: [u8; 4] = *b"SJSB";
+const FORMAT_VERSION: u16 = 1;
+
+/// Size of the header of a persisted filter: the magic number, the format version,
+/// the number of hashes and of bits.
+const HEADER_SIZE: usize = 4 + 2 + 4 + 8;
+
+/// A bloom filter over the keys of an immutable shard.
+///
+/// `contains` never returns false for a key that was inserted, so a shard whose filter
+/// does not contain a key can be skipped without reading it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// Creates a filter sized for `items` keys with the given false positive rate.
+    pub fn new(items: u64, false_positive_rate: f64) -> Self {
+        let items = items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-(items * false_positive_rate.ln()) / (ln2 * ln2))
+            .ceil()
+            .max(64.0);
+        let hashes = ((bits / items) * ln2).round().clamp(1.0, 16.0) as u32;
+
+        Self {
+            bits: vec![0; (bits as usize).div_ceil(64)],
+            hashes,
+        }
+    }
+
+    fn bits_len(&self) -> u64 {
+        self.bits.len() as u64 * 64
+    }
+
+    /// Positions of the bits of a key, derived from two hashes of it.
+    fn positions(&self, key: &[u8]) -> impl Iterator<Item = u64> {
+        let h1 = hash(key, 0);
+        let h2 = hash(key, h1) | 1;
+        let len = self.bits_len();
+
+        (0..self.hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % len)
+    }
+
+    pub fn insert(&mut self, key: &[u8]) {
+        for position in self.positions(key).collect::<Vec<_>>() {
+            self.bits[(position / 64) as usize] |= 1 << (position % 64);
+        }
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.positions(key)
+            .all(|position| self.bits[(position / 64) as usize] & (1 << (position % 64)) != 0)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.bits.len() * 8);
+        bytes.extend(MAGIC);
+        bytes.extend(FORMAT_VERSION.to_le_bytes());
+        bytes.extend(self.hashes.to_le_bytes());
+        bytes.extend(self.bits_len().to_le_bytes());
+        for word in &self.bits {
+            bytes.extend(word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Parses a persisted filter, returning `None` for filters of another format version,
+    /// which are then rebuilt from their run.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(0..4)? != MAGIC
+            || u16::from_le_bytes(bytes.get(4..6)?.try_into().ok()?) != FORMAT_VERSION
+        {
+            return None;
+        }
+
+        let hashes = u32::from_le_bytes(bytes.get(6..10)?.try_into().ok()?);
+        let bits_len = u64::from_le_bytes(bytes.get(10..HEADER_SIZE)?.try_into().ok()?);
+        let words = bytes.get(HEADER_SIZE..)?;
+
+        if hashes == 0 || bits_len == 0 || words.len() as u64 * 8 != bits_len {
+            return None;
+        }
+
+        Some(Self {
+            bits: words
+                .chunks_exact(8)
+                .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+                .collect(),
+            hashes,
+        })
+    }
+
+    /// Reads a persisted filter, returning `None` if it is missing or unreadable.
+    pub fn read(path: &Path) -> Option<Self> {
+        Self::from_bytes(&std::fs::read(path).ok()?)
+    }
+
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+}
+
+/// FNV-1a followed by the finalizer of SplitMix64. Filters are persisted,
+/// so the hash must not change between builds, unlike the one of `std`.
+fn hash(key: &[u8], seed: u64) -> u64 {
+    let mut hash = 0xcbf29ce484222325 ^ seed;
+    for byte in key {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+    hash ^ (hash >> 31)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::bloom_filter::BloomFilter;
+
+    #[test]
+    fn test_bloom_filter() {
+        let mut filter = BloomFilter::new(1000, 0.01);
+        for i in 0..1000u64 {
+            filter.insert(&i.to_le_bytes());
+        }
+
+        for i in 0..1000u64 {
+            assert!(filter.contains(&i.to_le_bytes()));
+        }
+
+        let false_positives = (1000..11000u64)
+            .filter(|i| filter.contains(&i.to_le_bytes()))
+            .count();
+        assert!(false_positives < 300);
+
+        let restored = BloomFilter::from_bytes(&filter.to_bytes()).unwrap();
+        assert_eq!(restored, filter);
+        assert!(BloomFilter::from_bytes(&filter.to_bytes()[..20]).is_none());
+        assert!(BloomFilter::from_bytes(&filter.to_bytes()[6..]).is_none());
+    }
+}
@@ -0,0 +1,891 @@
+use crate::data::bloom_filter::BloomFilter;
+use crate::data::index_data_unit::IndexDataUnit;
+use crate::types::{IndexKey, IndexValue};
+use crate::utils::get_entry_size;
+use parking_lot::{Mutex, MutexGuard, RwLock, RwLockUpgradableReadGuard};
+use schemajs_data::fdm::FileDescriptorManager;
+use schemajs_data::shard::shards::kv::config::KvShardConfig;
+use schemajs_data::shard::shards::kv::shard::KvShard;
+use schemajs_data::shard::Shard;
+use schemajs_data::utils::fs::list_files_with_prefix;
+use schemajs_data::U64_SIZE;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Runs of a similar size that must pile up before they are merged together.
+pub const MIN_RUNS_TO_COMPACT: usize = 4;
+
+/// Runs are of a similar size when they fall in the same power of this factor.
+const RUN_SIZE_FACTOR: u64 = 4;
+
+/// Entries written to a run at once when flushing or compacting.
+const RUN_WRITE_BATCH: usize = 1024;
+
+/// False positive rate of the bloom filter of every run.
+const BLOOM_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// An immutable `KvShard` of entries sorted by key.
+#[derive(Debug)]
+struct SortedRun {
+    number: usize,
+    shard: KvShard,
+    /// Filter over the keys of the run, persisted next to it, so lookups of keys
+    /// that are not in the run do not search it.
+    filter: BloomFilter,
+}
+
+impl SortedRun {
+    fn len(&self) -> u64 {
+        self.shard.header.read().items_len
+    }
+
+    fn filter_path(path: &Path) -> PathBuf {
+        path.with_extension("bloom")
+    }
+}
+
+#[derive(Debug)]
+struct Memtable<K: IndexKey, V: IndexValue> {
+    entries: BTreeMap<K, V>,
+    /// Append-only log of the entries, replayed when the index is opened again.
+    wal: KvShard,
+    wal_number: u64,
+    /// Logs whose entries are in the memtable and not flushed yet, including `wal`.
+    wal_paths: Vec<PathBuf>,
+}
+
+/// A full memtable waiting to be written as a sorted run. Lookups keep finding its entries
+/// until the run is in place.
+#[derive(Debug)]
+struct SealedMemtable<K: IndexKey, V: IndexValue> {
+    entries: BTreeMap<K, V>,
+    /// Logs of the entries, removed once the run is written.
+    wal_paths: Vec<PathBuf>,
+}
+
+/// Holds off every write to the files of an `LsmIndexShard` until it is dropped.
+pub struct FrozenIndex<'a, K: IndexKey, V: IndexValue> {
+    _memtable: RwLockUpgradableReadGuard<'a, Memtable<K, V>>,
+    _flushing: MutexGuard<'a, ()>,
+    _compaction: MutexGuard<'a, ()>,
+}
+
+/// A log-structured index.
+///
+/// Inserts go to an in-memory memtable, backed by an append-only log, which is sealed once
+/// it holds `memtable_capacity` entries and then flushed as an immutable sorted run
+/// (a `KvShard`) without holding off other inserts. Lookups check the memtable, the sealed
+/// memtables and then every run from newest to oldest, so a newer entry for a key shadows
+/// the older ones. Runs of a similar size are merged by `compact`, up to `max_run_len` entries.
+///
+/// Runs are named like the shards of a `MapShard`, so the sorted shards written by
+/// `IndexShard` are picked up as runs.
+#[derive(Debug)]
+pub struct LsmIndexShard<K: IndexKey, V: IndexValue> {
+    folder: PathBuf,
+    prefix: String,
+    key_size: usize,
+    value_size: usize,
+    memtable_capacity: Option<u64>,
+    max_run_len: Option<u64>,
+    memtable: RwLock<Memtable<K, V>>,
+    /// Memtables waiting to be flushed, oldest first.
+    sealed: RwLock<Vec<Arc<SealedMemtable<K, V>>>>,
+    /// Sorted runs, oldest first.
+    runs: RwLock<Vec<Arc<SortedRun>>>,
+    /// Held while writing sealed memtables, so runs are appended in the order they filled up.
+    flushing: Mutex<()>,
+    compaction: Mutex<()>,
+    fdm: Arc<FileDescriptorManager>,
+}
+
+impl<K: IndexKey, V: IndexValue> LsmIndexShard<K, V> {
+    pub fn new<P: AsRef<Path>>(
+        folder: P,
+        index_name: String,
+        key_size: usize,
+        value_size: usize,
+        memtable_capacity: Option<u64>,
+        max_run_len: Option<u64>,
+        fdm: Arc<FileDescriptorManager>,
+    ) -> Self {
+        let folder = folder.as_ref().to_path_buf();
+        let prefix = format!("indx{}_", index_name);
+        let files = list_files_with_prefix(&folder, &prefix).unwrap();
+
+        let mut run_files: Vec<(usize, Uuid, PathBuf)> = files
+            .iter()
+            .filter_map(|path| Self::run_signature(&prefix, path))
+            .collect();
+        run_files.sort_by_key(|(number, _, _)| *number);
+
+        let mut wal_files: Vec<(u64, PathBuf)> = files
+            .iter()
+            .filter_map(|path| Self::wal_signature(&prefix, path))
+            .collect();
+        wal_files.sort_by_key(|(number, _)| *number);
+
+        let config = KvShardConfig {
+            value_size: get_entry_size(key_size, value_size),
+            max_capacity: None,
+        };
+
+        let runs = run_files
+            .into_iter()
+            .map(|(number, id, path)| {
+                (
+                    number,
+                    KvShard::new(path, config.clone(), Some(id), fdm.clone()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let (wal_number, wal_path) = wal_files
+            .last()
+            .cloned()
+            .unwrap_or_else(|| (0, Self::wal_path(&folder, &prefix, 0)));
+
+        let shard = Self {
+            memtable: RwLock::new(Memtable {
+                entries: BTreeMap::new(),
+                wal: KvShard::new(wal_path.clone(), config, None, fdm.clone()),
+                wal_number,
+                wal_paths: vec![],
+            }),
+            sealed: RwLock::new(vec![]),
+            runs: RwLock::new(vec![]),
+            flushing: Mutex::new(()),
+            compaction: Mutex::new(()),
+            folder,
+            prefix,
+            key_size,
+            value_size,
+            memtable_capacity,
+            max_run_len,
+            fdm,
+        };
+
+        *shard.runs.write() = runs
+            .into_iter()
+            .map(|(number, run)| Arc::new(shard.open_run(number, run)))
+            .collect();
+
+        let interrupted = {
+            let mut memtable = shard.memtable.write();
+            for (_, path) in &wal_files {
+                shard.replay_wal(&mut memtable, path);
+            }
+
+            memtable.wal_paths = wal_files.into_iter().map(|(_, path)| path).collect();
+            if memtable.wal_paths.is_empty() {
+                memtable.wal_paths.push(wal_path);
+            }
+
+            // More than one log is left behind when a flush was interrupted.
+            let interrupted = memtable.wal_paths.len() > 1;
+            if interrupted {
+                shard.seal_memtable(&mut memtable);
+            }
+            interrupted
+        };
+
+        if interrupted {
+            shard.flush_sealed();
+        }
+
+        shard
+    }
+
+    /// Loads the bloom filter of a run, rebuilding it from the entries of the run
+    /// when it is missing or unreadable.
+    fn open_run(&self, number: usize, shard: KvShard) -> SortedRun {
+        let filter_path = SortedRun::filter_path(&shard.get_path());
+        let items_len = shard.header.read().items_len;
+
+        let filter = BloomFilter::read(&filter_path).unwrap_or_else(|| {
+            let mut filter = BloomFilter::new(items_len, BLOOM_FALSE_POSITIVE_RATE);
+            for index in 0..items_len as usize {
+                if let Some((key, _)) = self
+                    .read_entry(&shard, index)
+                    .and_then(|entry| self.decode(&entry))
+                {
+                    filter.insert(&Into::<Vec<u8>>::into(key));
+                }
+            }
+
+            let _ = filter.write(&filter_path);
+            filter
+        });
+
+        SortedRun {
+            number,
+            shard,
+            filter,
+        }
+    }
+
+    fn run_signature(prefix: &str, path: &Path) -> Option<(usize, Uuid, PathBuf)> {
+        let file_name = path.file_name()?.to_str()?;
+        let signature = file_name.strip_prefix(prefix)?.strip_suffix(".data")?;
+        let (id, number) = signature.split_once('_')?;
+
+        Some((
+            number.parse().ok()?,
+            Uuid::parse_str(id).ok()?,
+            path.to_path_buf(),
+        ))
+    }
+
+    fn wal_signature(prefix: &str, path: &Path) -> Option<(u64, PathBuf)> {
+        let file_name = path.file_name()?.to_str()?;
+        let number = file_name.strip_prefix(prefix)?.strip_suffix(".wal")?;
+
+        Some((number.parse().ok()?, path.to_path_buf()))
+    }
+
+    fn wal_path(folder: &Path, prefix: &str, number: u64) -> PathBuf {
+        folder.join(format!("{}{}.wal", prefix, number))
+    }
+
+    fn replay_wal(&self, memtable: &mut Memtable<K, V>, path: &PathBuf) {
+        let wal = if *path == memtable.wal.get_path() {
+            None
+        } else {
+            Some(KvShard::new(
+                path.clone(),
+                KvShardConfig {
+                    value_size: get_entry_size(self.key_size, self.value_size),
+                    max_capacity: None,
+                },
+                None,
+                self.fdm.clone(),
+            ))
+        };
+        let wal = wal.as_ref().unwrap_or(&memtable.wal);
+
+        let entries: Vec<(K, V)> = (0..wal.header.read().items_len as usize)
+            .filter_map(|index| self.read_entry(wal, index))
+            .filter_map(|entry| self.decode(&entry))
+            .collect();
+
+        memtable.entries.extend(entries);
+    }
+
+    fn encode(&self, key: K, value: V) -> Vec<u8> {
+        let key: Vec<u8> = IndexDataUnit::new(key.into()).into();
+        let value: Vec<u8> = IndexDataUnit::new(value.into()).into();
+
+        let mut entry = key;
+        entry.extend(value);
+
+        IndexDataUnit::new(entry).into()
+    }
+
+    fn decode(&self, entry: &[u8]) -> Option<(K, V)> {
+        let unit = IndexDataUnit::try_from(entry).ok()?;
+        let data = unit.data;
+        let key = IndexDataUnit::try_from(data.get(0..(U64_SIZE + self.key_size))?).ok()?;
+        let value = IndexDataUnit::try_from(data.get((U64_SIZE + self.key_size)..)?).ok()?;
+
+        Some((K::from(key), V::from(value)))
+    }
+
+    fn read_entry(&self, shard: &KvShard, index: usize) -> Option<Vec<u8>> {
+        if index as u64 >= shard.header.read().items_len {
+            return None;
+        }
+
+        shard.read_item_from_index(index).ok()
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        self.insert_many(vec![(key, value)]);
+    }
+
+    /// Logs the entries and adds them to the memtable, flushing it if it is full.
+    /// The memtable is only locked while it is swapped out, so other inserts and lookups
+    /// go on while the run is written.
+    pub fn insert_many(&self, data: Vec<(K, V)>) {
+        if data.is_empty() {
+            return;
+        }
+
+        let entries: Vec<Vec<u8>> = data
+            .iter()
+            .map(|(key, value)| self.encode(key.clone(), value.clone()))
+            .collect();
+        let entries: Vec<&[u8]> = entries.iter().map(|entry| entry.as_slice()).collect();
+
+        let full = {
+            let mut memtable = self.memtable.write();
+            memtable.wal.insert_item(&entries).unwrap();
+            memtable.entries.extend(data);
+
+            let full = self
+                .memtable_capacity
+                .is_some_and(|capacity| memtable.entries.len() as u64 >= capacity);
+            if full {
+                self.seal_memtable(&mut memtable);
+            }
+            full
+        };
+
+        if full {
+            self.flush_sealed();
+        }
+    }
+
+    pub fn get(&self, target: &K) -> Option<V> {
+        if let Some(value) = self.memtable.read().entries.get(target) {
+            return Some(value.clone());
+        }
+
+        // A sealed memtable is only dropped once its run is in `runs`.
+        let sealed = self
+            .sealed
+            .read()
+            .iter()
+            .rev()
+            .find_map(|sealed| sealed.entries.get(target).cloned());
+        if sealed.is_some() {
+            return sealed;
+        }
+
+        let target_bytes: Vec<u8> = target.clone().into();
+        let runs = self.runs.read();
+        runs.iter()
+            .rev()
+            .filter(|run| run.filter.contains(&target_bytes))
+            .find_map(|run| self.search_run(run, target))
+    }
+
+    fn search_run(&self, run: &SortedRun, target: &K) -> Option<V> {
+        let mut left = 0;
+        let mut right = run.shard.get_last_index();
+
+        while left <= right {
+            let mid = left + (right - left) / 2;
+            let (key, value) = self.decode(&self.read_entry(&run.shard, mid as usize)?)?;
+
+            match key.cmp(target) {
+                Ordering::Less => left = mid + 1,
+                Ordering::Greater => right = mid - 1,
+                Ordering::Equal => return Some(value),
+            }
+        }
+
+        None
+    }
+
+    /// Writes the memtable as a new sorted run.
+    pub fn flush(&self) {
+        {
+            let mut memtable = self.memtable.write();
+            self.seal_memtable(&mut memtable);
+        }
+
+        self.flush_sealed();
+    }
+
+    /// Moves the entries of the memtable to the sealed memtables and starts a new log.
+    fn seal_memtable(&self, memtable: &mut Memtable<K, V>) {
+        memtable.wal_number += 1;
+        let wal_path = Self::wal_path(&self.folder, &self.prefix, memtable.wal_number);
+        memtable.wal = KvShard::new(
+            wal_path.clone(),
+            KvShardConfig {
+                value_size: get_entry_size(self.key_size, self.value_size),
+                max_capacity: None,
+            },
+            None,
+            self.fdm.clone(),
+        );
+
+        let sealed = SealedMemtable {
+            entries: std::mem::take(&mut memtable.entries),
+            wal_paths: std::mem::replace(&mut memtable.wal_paths, vec![wal_path]),
+        };
+        self.sealed.write().push(Arc::new(sealed));
+    }
+
+    /// Writes every sealed memtable as a run, oldest first, and drops their logs.
+    fn flush_sealed(&self) {
+        let _flushing = self.flushing.lock();
+
+        loop {
+            let Some(sealed) = self.sealed.read().first().cloned() else {
+                break;
+            };
+
+            if !sealed.entries.is_empty() {
+                // Only flushes append runs, so the number is still free once the run is written.
+                let number = self.runs.read().last().map_or(0, |run| run.number + 1);
+                let run = self.write_run(
+                    number,
+                    sealed.entries.len() as u64,
+                    sealed.entries.iter().map(|(key, value)| {
+                        let entry = self.encode(key.clone(), value.clone());
+                        (key.clone(), entry)
+                    }),
+                );
+                self.runs.write().push(Arc::new(run));
+            }
+
+            self.sealed.write().remove(0);
+            for path in &sealed.wal_paths {
+                self.remove_file(path);
+            }
+        }
+    }
+
+    /// Writes entries, already sorted by key, as a run and persists its bloom filter,
+    /// sized for `items` keys.
+    fn write_run(
+        &self,
+        number: usize,
+        items: u64,
+        entries: impl Iterator<Item = (K, Vec<u8>)>,
+    ) -> SortedRun {
+        let id = Uuid::new_v4();
+        let path = self
+            .folder
+            .join(format!("{}{}_{}.data", self.prefix, id, number));
+        let shard = KvShard::new(
+            path,
+            KvShardConfig {
+                value_size: get_entry_size(self.key_size, self.value_size),
+                max_capacity: None,
+            },
+            Some(id),
+            self.fdm.clone(),
+        );
+
+        let mut filter = BloomFilter::new(items, BLOOM_FALSE_POSITIVE_RATE);
+        let mut batch: Vec<Vec<u8>> = Vec::with_capacity(RUN_WRITE_BATCH);
+        for (key, entry) in entries {
+            filter.insert(&Into::<Vec<u8>>::into(key));
+            batch.push(entry);
+
+            if batch.len() == RUN_WRITE_BATCH {
+                Self::write_batch(&shard, &mut batch);
+            }
+        }
+        Self::write_batch(&shard, &mut batch);
+
+        // A missing filter is rebuilt when the index is opened again.
+        let _ = filter.write(&SortedRun::filter_path(&shard.get_path()));
+
+        SortedRun {
+            number,
+            shard,
+            filter,
+        }
+    }
+
+    fn write_batch(shard: &KvShard, batch: &mut Vec<Vec<u8>>) {
+        if batch.is_empty() {
+            return;
+        }
+
+        let entries: Vec<&[u8]> = batch.iter().map(|entry| entry.as_slice()).collect();
+        shard.insert_item(&entries).unwrap();
+        batch.clear();
+    }
+
+    fn remove_file(&self, path: &PathBuf) {
+        self.fdm.remove_path(path);
+        let _ = std::fs::remove_file(path);
+    }
+
+    /// Waits for a running compaction and keeps inserts, flushes and compactions from writing
+    /// to the files of the index while the returned guard is alive. Lookups keep going.
+    pub fn freeze(&self) -> FrozenIndex<'_, K, V> {
+        // Compactions and flushes never wait for the memtable, and flushes never wait
+        // for compactions, so the locks are taken in this order.
+        let compaction = self.compaction.lock();
+        let flushing = self.flushing.lock();
+        let memtable = self.memtable.upgradable_read();
+
+        FrozenIndex {
+            _memtable: memtable,
+            _flushing: flushing,
+            _compaction: compaction,
+        }
+    }
+
+    pub fn runs_len(&self) -> usize {
+        self.runs.read().len()
+    }
+
+    pub fn needs_compaction(&self) -> bool {
+        self.pick_compaction(&self.runs.read()).is_some()
+    }
+
+    /// Finds the newest group of contiguous runs of a similar size big enough to be merged,
+    /// whose merged run would not hold more than `max_run_len` entries.
+    fn pick_compaction(&self, runs: &[Arc<SortedRun>]) -> Option<Range<usize>> {
+        let base = self.memtable_capacity.unwrap_or(1).max(1);
+        let tier = |run: &SortedRun| {
+            let mut tier = 0;
+            let mut bound = base;
+            while run.len() > bound {
+                bound = bound.saturating_mul(RUN_SIZE_FACTOR);
+                tier += 1;
+            }
+            tier
+        };
+
+        let mut end = runs.len();
+        while end > 0 {
+            let current_tier = tier(&runs[end - 1]);
+            let mut start = end - 1;
+            while start > 0 && tier(&runs[start - 1]) == current_tier {
+                start -= 1;
+            }
+
+            let merged_len: u64 = runs[start..end].iter().map(|run| run.len()).sum();
+            let fits = self.max_run_len.map_or(true, |max| merged_len <= max);
+            if end - start >= MIN_RUNS_TO_COMPACT && fits {
+                return Some(start..end);
+            }
+
+            end = start;
+        }
+
+        None
+    }
+
+    /// Merges a group of runs of a similar size into a single run.
+    /// Returns whether there was anything to compact.
+    pub fn compact(&self) -> bool {
+        let Some(_compacting) = self.compaction.try_lock() else {
+            return false;
+        };
+
+        let (range, group) = {
+            let runs = self.runs.read();
+            let Some(range) = self.pick_compaction(&runs) else {
+                return false;
+            };
+            let group = runs[range.clone()].to_vec();
+            (range, group)
+        };
+
+        // Runs are only ever appended while compacting, so the group stays at `range`.
+        let number = group.last().unwrap().number;
+        let items = group.iter().map(|run| run.len()).sum();
+        let merged = self.write_run(number, items, MergedRuns::new(self, &group));
+        self.runs.write().splice(range, [Arc::new(merged)]);
+
+        for run in group {
+            let path = run.shard.get_path();
+            self.remove_file(&path);
+            let _ = std::fs::remove_file(SortedRun::filter_path(&path));
+        }
+
+        true
+    }
+}
+
+/// Iterates the entries of sorted runs in key order.
+/// When a key is in several runs, only the entry of the newest one is kept.
+struct MergedRuns<'a, K: IndexKey, V: IndexValue> {
+    index: &'a LsmIndexShard<K, V>,
+    runs: &'a [Arc<SortedRun>],
+    positions: Vec<usize>,
+    heads: Vec<Option<(K, Vec<u8>)>>,
+}
+
+impl<'a, K: IndexKey, V: IndexValue> MergedRuns<'a, K, V> {
+    fn new(index: &'a LsmIndexShard<K, V>, runs: &'a [Arc<SortedRun>]) -> Self {
+        let mut merged = Self {
+            index,
+            runs,
+            positions: vec![0; runs.len()],
+            heads: vec![None; runs.len()],
+        };
+
+        for i in 0..runs.len() {
+            merged.heads[i] = merged.read_head(i);
+        }
+
+        merged
+    }
+
+    fn read_head(&self, run: usize) -> Option<(K, Vec<u8>)> {
+        let entry = self
+            .index
+            .read_entry(&self.runs[run].shard, self.positions[run])?;
+        let (key, _) = self.index.decode(&entry)?;
+        Some((key, entry))
+    }
+
+    fn advance(&mut self, run: usize) {
+        self.positions[run] += 1;
+        self.heads[run] = self.read_head(run);
+    }
+}
+
+impl<'a, K: IndexKey, V: IndexValue> Iterator for MergedRuns<'a, K, V> {
+    type Item = (K, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Runs are oldest first, so on equal keys the later run wins.
+        let mut winner: Option<usize> = None;
+        for (i, head) in self.heads.iter().enumerate() {
+            if let Some((key, _)) = head {
+                let is_smaller = match winner.and_then(|w| self.heads[w].as_ref()) {
+                    None => true,
+                    Some((winner_key, _)) => key <= winner_key,
+                };
+
+                if is_smaller {
+                    winner = Some(i);
+                }
+            }
+        }
+
+        let winner = winner?;
+        let (key, entry) = self.heads[winner].take()?;
+
+        for i in 0..self.heads.len() {
+            let same_key = matches!(&self.heads[i], Some((other, _)) if *other == key);
+            if i == winner || same_key {
+                self.advance(i);
+            }
+        }
+
+        Some((key, entry))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::lsm_shard::LsmIndexShard;
+    use crate::keys::string_index::StringIndexKey;
+    use crate::vals::raw_value::RawIndexValue;
+    use schemajs_data::fdm::FileDescriptorManager;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    fn key(s: &str) -> StringIndexKey {
+        StringIndexKey(format!("{:<32}", s))
+    }
+
+    fn new_index(
+        folder: &std::path::Path,
+        capacity: Option<u64>,
+    ) -> LsmIndexShard<StringIndexKey, RawIndexValue> {
+        new_index_with_max_run(folder, capacity, None)
+    }
+
+    fn new_index_with_max_run(
+        folder: &std::path::Path,
+        capacity: Option<u64>,
+        max_run_len: Option<u64>,
+    ) -> LsmIndexShard<StringIndexKey, RawIndexValue> {
+        LsmIndexShard::new(
+            folder,
+            "indx".to_string(),
+            32,
+            8,
+            capacity,
+            max_run_len,
+            Arc::new(FileDescriptorManager::new(2500)),
+        )
+    }
+
+    #[tokio::test]
+    pub async fn test_lookups_across_runs() {
+        let temp_dir = tempdir().unwrap();
+        let index = new_index(temp_dir.path(), Some(2));
+
+        for i in 0..9u64 {
+            index.insert(
+                key(&format!("key{}", 8 - i)),
+                i.to_le_bytes().to_vec().into(),
+            );
+        }
+
+        // Two entries per run, the last one is still in the memtable.
+        assert_eq!(index.runs_len(), 4);
+
+        for i in 0..9u64 {
+            let value = index.get(&key(&format!("key{}", 8 - i))).unwrap();
+            assert_eq!(value.0, i.to_le_bytes().to_vec());
+        }
+
+        assert!(index.get(&key("missing")).is_none());
+        assert!(index.get(&key("a")).is_none());
+        assert!(index.get(&key("z")).is_none());
+    }
+
+    #[tokio::test]
+    pub async fn test_memtable_is_recovered_from_log() {
+        let temp_dir = tempdir().unwrap();
+
+        {
+            let index = new_index(temp_dir.path(), Some(100));
+            index.insert(key("a"), vec![1u8; 8].into());
+            index.insert(key("b"), vec![2u8; 8].into());
+            assert_eq!(index.runs_len(), 0);
+        }
+
+        let index = new_index(temp_dir.path(), Some(100));
+        assert_eq!(index.get(&key("a")).unwrap().0, vec![1u8; 8]);
+        assert_eq!(index.get(&key("b")).unwrap().0, vec![2u8; 8]);
+
+        index.flush();
+        assert_eq!(index.runs_len(), 1);
+
+        let index = new_index(temp_dir.path(), Some(100));
+        assert_eq!(index.runs_len(), 1);
+        assert_eq!(index.get(&key("a")).unwrap().0, vec![1u8; 8]);
+    }
+
+    #[tokio::test]
+    pub async fn test_compaction() {
+        let temp_dir = tempdir().unwrap();
+        let index = new_index(temp_dir.path(), Some(1));
+
+        for i in 0..8u64 {
+            index.insert(
+                key(&format!("key{}", i % 6)),
+                i.to_le_bytes().to_vec().into(),
+            );
+        }
+        assert_eq!(index.runs_len(), 8);
+        assert!(index.needs_compaction());
+
+        while index.compact() {}
+        assert!(index.runs_len() < 8);
+        assert!(!index.needs_compaction());
+
+        // Newer entries shadow older ones for the same key.
+        assert_eq!(index.get(&key("key0")).unwrap().0, 6u64.to_le_bytes());
+        assert_eq!(index.get(&key("key1")).unwrap().0, 7u64.to_le_bytes());
+        for i in 2..6u64 {
+            assert_eq!(
+                index.get(&key(&format!("key{}", i))).unwrap().0,
+                i.to_le_bytes()
+            );
+        }
+
+        let runs = index.runs_len();
+        drop(index);
+        let index = new_index(temp_dir.path(), Some(1));
+        assert_eq!(index.runs_len(), runs);
+        assert_eq!(index.get(&key("key1")).unwrap().0, 7u64.to_le_bytes());
+    }
+
+    #[tokio::test]
+    pub async fn test_compaction_respects_max_run_len() {
+        let temp_dir = tempdir().unwrap();
+        let index = new_index_with_max_run(temp_dir.path(), Some(1), Some(3));
+
+        for i in 0..4u64 {
+            index.insert(key(&format!("key{}", i)), i.to_le_bytes().to_vec().into());
+        }
+
+        assert_eq!(index.runs_len(), 4);
+        assert!(!index.needs_compaction());
+        assert!(!index.compact());
+    }
+
+    #[tokio::test]
+    pub async fn test_inserts_go_on_while_flushing() {
+        let temp_dir = tempdir().unwrap();
+        let index = Arc::new(new_index(temp_dir.path(), Some(2)));
+
+        // Stands in for a slow run write.
+        let flushing = index.flushing.lock();
+        let writer = {
+            let index = index.clone();
+            std::thread::spawn(move || {
+                index.insert_many(vec![
+                    (key("a"), vec![1u8; 8].into()),
+                    (key("b"), vec![2u8; 8].into()),
+                ])
+            })
+        };
+
+        while index.sealed.read().is_empty() {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert!(!writer.is_finished());
+
+        // The memtable was swapped out, so inserts and lookups of sealed entries go on.
+        index.insert(key("c"), vec![3u8; 8].into());
+        assert_eq!(index.get(&key("a")).unwrap().0, vec![1u8; 8]);
+        assert_eq!(index.get(&key("c")).unwrap().0, vec![3u8; 8]);
+        assert_eq!(index.runs_len(), 0);
+
+        drop(flushing);
+        writer.join().unwrap();
+        assert_eq!(index.runs_len(), 1);
+        assert!(index.sealed.read().is_empty());
+        assert_eq!(index.get(&key("b")).unwrap().0, vec![2u8; 8]);
+    }
+
+    #[tokio::test]
+    pub async fn test_bloom_filters_are_rebuilt() {
+        let temp_dir = tempdir().unwrap();
+        let filters = |path: &std::path::Path| {
+            std::fs::read_dir(path)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "bloom"))
+                .collect::<Vec<_>>()
+        };
+
+        {
+            let index = new_index(temp_dir.path(), Some(2));
+            for i in 0..4u64 {
+                index.insert(key(&format!("key{}", i)), vec![i as u8; 8].into());
+            }
+            assert_eq!(index.runs_len(), 2);
+        }
+
+        let written = filters(temp_dir.path());
+        assert_eq!(written.len(), 2);
+        for path in &written {
+            std::fs::remove_file(path).unwrap();
+        }
+
+        let index = new_index(temp_dir.path(), Some(2));
+        assert_eq!(filters(temp_dir.path()).len(), 2);
+        for i in 0..4u64 {
+            assert_eq!(
+                index.get(&key(&format!("key{}", i))).unwrap().0,
+                vec![i as u8; 8]
+            );
+        }
+        assert!(index.get(&key("missing")).is_none());
+    }
+
+    #[tokio::test]
+    pub async fn test_freeze_holds_off_inserts() {
+        let temp_dir = tempdir().unwrap();
+        let index = Arc::new(new_index(temp_dir.path(), Some(100)));
+        index.insert(key("a"), vec![1u8; 8].into());
+
+        let frozen = index.freeze();
+        let writer = {
+            let index = index.clone();
+            std::thread::spawn(move || index.insert(key("b"), vec![2u8; 8].into()))
+        };
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!writer.is_finished());
+        assert!(!index.compact());
+        assert_eq!(index.get(&key("a")).unwrap().0, vec![1u8; 8]);
+
+        drop(frozen);
+        writer.join().unwrap();
+        assert_eq!(index.get(&key("b")).unwrap().0, vec![2u8; 8]);
+    }
+}
@@ -1,2 +1,4 @@
+pub mod bloom_filter;
 pub mod index_data_unit;
 pub mod index_shard;
+pub mod lsm_shard;
@@ -1,5 +1,5 @@
 use crate::composite_key::CompositeKey;
-use crate::data::index_shard::IndexShard;
+use crate::data::lsm_shard::LsmIndexShard;
 use crate::implementations::hash::hash_index_header::{
     HASH_INDEX_KEY_SIZE, HASH_INDEX_TOTAL_ENTRY_SIZE, HASH_INDEX_VALUE_SIZE,
 };
@@ -15,7 +15,7 @@ use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct HashIndex {
-    pub index: Arc<IndexShard<IndexKeySha256, RawIndexValue>>,
+    pub index: Arc<LsmIndexShard<IndexKeySha256, RawIndexValue>>,
 }
 
 impl HashIndex {
@@ -23,15 +23,16 @@ impl HashIndex {
         path: P,
         index_name: Option<String>,
         capacity: Option<u64>,
+        max_run_len: Option<u64>,
         fdm: Arc<FileDescriptorManager>,
     ) -> Self {
-        let index_shard = IndexShard::new(
+        let index_shard = LsmIndexShard::new(
             path,
             index_name.unwrap_or_else(|| "hashindx".to_string()),
             HASH_INDEX_KEY_SIZE,
             HASH_INDEX_VALUE_SIZE,
             capacity,
-            Some(true),
+            max_run_len,
             fdm,
         );
 
@@ -45,10 +46,9 @@ impl HashIndex {
     }
 
     pub fn find_index(&self, find: IndexKeySha256) -> Option<u64> {
-        match self.index.binary_search(find) {
-            None => return None,
-            Some((_, _, val)) => Some(u64::from_le_bytes(val.0.as_slice().try_into().unwrap())),
-        }
+        self.index
+            .get(&find)
+            .map(|val| u64::from_le_bytes(val.0.as_slice().try_into().unwrap()))
     }
 }
 
@@ -58,7 +58,7 @@ impl Index for HashIndex {
     }
 
     fn bulk_insert(&self, data: Vec<(IndexKeyType, u64)>) {
-        self.index.raw_insert(
+        self.index.insert_many(
             data.into_iter()
                 .map(|i| {
                     (
@@ -112,6 +112,7 @@ mod test {
             hashindx.clone(),
             None,
             None,
+            None,
             Arc::new(FileDescriptorManager::new(2500)),
         );
 
@@ -132,6 +133,7 @@ mod test {
             hashindx.clone(),
             None,
             Some(2),
+            None,
             Arc::new(FileDescriptorManager::new(2500)),
         );
 
@@ -0,0 +1,54 @@
+use schemajs_primitives::column::types::DataTypes;
+use schemajs_primitives::column::Column;
+use schemajs_primitives::table::Table;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Entry of the append-only login audit log.
+#[derive(Serialize, Deserialize)]
+pub struct LoginAuditEntry {
+    pub identifier: String,
+    pub scheme: String,
+    pub peer: Option<String>,
+    pub success: bool,
+    pub reason: String,
+    pub created_at: u64,
+}
+
+pub const INTERNAL_LOGIN_AUDIT_TABLE_NAME: &str = "sjs_login_audit";
+
+pub(crate) static INTERNAL_LOGIN_AUDIT_TABLE: LazyLock<Table> = LazyLock::new(|| {
+    let mut tbl = Table::new(INTERNAL_LOGIN_AUDIT_TABLE_NAME)
+        .add_column(Column::new("identifier", DataTypes::String).set_required(true))
+        .add_column(Column::new("scheme", DataTypes::String).set_required(true))
+        .add_column(Column::new("peer", DataTypes::String))
+        .add_column(Column::new("success", DataTypes::Boolean).set_required(true))
+        .add_column(Column::new("reason", DataTypes::String).set_default_index(false))
+        .add_column(Column::new("created_at", DataTypes::Number).set_default_index(false))
+        .set_internal(true);
+
+    tbl.init();
+
+    tbl
+});
+
+pub fn create_login_audit_entry(
+    identifier: String,
+    scheme: String,
+    peer: Option<String>,
+    success: bool,
+    reason: &str,
+) -> LoginAuditEntry {
+    LoginAuditEntry {
+        identifier,
+        scheme,
+        peer,
+        success,
+        reason: reason.to_string(),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    }
+}
@@ -1,31 +1,51 @@
-use crate::auth::types::{UserContext, VerifyUserArgs};
-use crate::users::user::{create_user, User, INTERNAL_USER_TABLE, INTERNAL_USER_TABLE_NAME};
+use crate::audit::login::{create_login_audit_entry, INTERNAL_LOGIN_AUDIT_TABLE_NAME};
+use crate::auth::jwt::{JwtAuth, JwtAuthError};
+use crate::auth::lockout::LoginThrottle;
+use crate::auth::types::{AuthError, UserContext, VerifyUserArgs};
+use crate::users::user::{
+    create_user, roles_from_value, User, INTERNAL_USER_TABLE, INTERNAL_USER_TABLE_NAME,
+};
 use dashmap::DashMap;
 use parking_lot::RwLock;
 use schemajs_engine::engine::SchemeJsEngine;
 use schemajs_engine::engine_db::EngineDb;
 use schemajs_primitives::column::types::DataValue;
+use schemajs_query::db_row::DbRow;
 use schemajs_query::ops::query_ops::{QueryOps, QueryVal};
 use schemajs_query::row::Row;
-use schemajs_query::row_json::{RowData, RowJson};
 use std::sync::Arc;
 use uuid::Uuid;
 
 pub struct AuthManager {
     engine: Arc<RwLock<SchemeJsEngine>>,
     authenticated_users: DashMap<String, Arc<UserContext>>,
+    jwt: Option<JwtAuth>,
+    throttle: LoginThrottle,
 }
 
 impl AuthManager {
-    pub fn new(engine: Arc<RwLock<SchemeJsEngine>>) -> Self {
-        Self {
+    pub fn new(engine: Arc<RwLock<SchemeJsEngine>>) -> Result<Self, JwtAuthError> {
+        let config = engine.read().config.clone();
+        let jwt = config.auth.jwt.as_ref().map(JwtAuth::new).transpose()?;
+
+        Ok(Self {
             engine,
             authenticated_users: DashMap::new(),
-        }
+            jwt,
+            throttle: LoginThrottle::new(config.auth.lockout.clone()),
+        })
     }
 
-    pub fn check_token(&self, uuid: &str) -> Result<Arc<UserContext>, ()> {
-        let reader = self.authenticated_users.get(uuid);
+    pub fn check_token(&self, token: &str) -> Result<Arc<UserContext>, ()> {
+        // Signed tokens are self-contained, any process sharing the key can verify them.
+        if let Some(jwt) = &self.jwt {
+            return jwt
+                .verify(token)
+                .map(|claims| Arc::new(UserContext::new(claims.into())))
+                .map_err(|_| ());
+        }
+
+        let reader = self.authenticated_users.get(token);
         if let Some(ctx) = reader {
             Ok(ctx.value().clone())
         } else {
@@ -33,18 +53,91 @@ impl AuthManager {
         }
     }
 
-    pub fn authenticate(&self, args: VerifyUserArgs) -> Result<Uuid, ()> {
-        let user = self.verify_user(args);
+    pub fn authenticate(
+        &self,
+        args: VerifyUserArgs,
+        peer: Option<String>,
+    ) -> Result<String, AuthError> {
+        let scheme_name = args.scheme_name.clone();
+        let identifier = args.identifier.clone();
+        let mut throttle_keys = vec![LoginThrottle::user_key(&scheme_name, &identifier)];
+        if let Some(peer) = &peer {
+            throttle_keys.push(LoginThrottle::peer_key(peer));
+        }
 
-        if let Some(user) = user {
-            let token = Uuid::new_v4();
-            let ctx = UserContext::new(user);
-            self.authenticated_users
-                .insert(token.to_string(), Arc::new(ctx));
-            return Ok(token);
+        let locked_for = throttle_keys
+            .iter()
+            .filter_map(|key| self.throttle.locked_for(key))
+            .max();
+        if let Some(remaining) = locked_for {
+            self.audit_login(&scheme_name, &identifier, peer, false, "locked_out");
+            return Err(AuthError::LockedOut(remaining.as_secs().max(1)));
         }
 
-        Err(())
+        match self.verify_user(args) {
+            Some(user) => {
+                throttle_keys
+                    .iter()
+                    .for_each(|key| self.throttle.register_success(key));
+                self.audit_login(&scheme_name, &identifier, peer, true, "success");
+                self.issue_token(user)
+            }
+            None => {
+                throttle_keys
+                    .iter()
+                    .for_each(|key| self.throttle.register_failure(key));
+                self.audit_login(
+                    &scheme_name,
+                    &identifier,
+                    peer,
+                    false,
+                    "invalid_credentials",
+                );
+                Err(AuthError::InvalidCredentials)
+            }
+        }
+    }
+
+    fn issue_token(&self, user: User) -> Result<String, AuthError> {
+        if let Some(jwt) = &self.jwt {
+            return jwt.issue(&user).map_err(|_| AuthError::TokenIssuance);
+        }
+
+        let token = Uuid::new_v4().to_string();
+        let ctx = UserContext::new(user);
+        self.authenticated_users
+            .insert(token.clone(), Arc::new(ctx));
+        Ok(token)
+    }
+
+    /// Appends an entry to the login audit table of the targeted database.
+    fn audit_login(
+        &self,
+        scheme_name: &str,
+        identifier: &str,
+        peer: Option<String>,
+        success: bool,
+        reason: &str,
+    ) {
+        let engine = self.engine.read();
+        let db = match engine.find_by_name_ref(scheme_name) {
+            Some(db) => db,
+            None => return,
+        };
+
+        if let Some(tbl) = db.query_manager.get_table(INTERNAL_LOGIN_AUDIT_TABLE_NAME) {
+            let entry = create_login_audit_entry(
+                identifier.to_string(),
+                scheme_name.to_string(),
+                peer,
+                success,
+                reason,
+            );
+
+            if let Ok(row) = DbRow::from_json(serde_json::to_value(entry).unwrap(), tbl) {
+                let _ = db.query_manager.raw_insert(&mut [row], false);
+            }
+        }
     }
 
     pub fn verify_user(&self, args: VerifyUserArgs) -> Option<User> {
@@ -55,45 +148,14 @@ impl AuthManager {
 
             if let Some(user) = u {
                 let hashed_password = user
-                    .get_value(table.get_column("hashed_password").unwrap())
-                    .unwrap()
+                    .get_value(table.get_column("hashed_password").unwrap())?
                     .to_string();
 
+                // A malformed hash must fail the login rather than panic the server.
                 let is_password_correct =
-                    bcrypt::verify(args.password, hashed_password.as_str()).unwrap();
+                    bcrypt::verify(args.password, hashed_password.as_str()).unwrap_or(false);
                 if is_password_correct {
-                    return Some(User {
-                        identifier: args.identifier,
-                        hashed_password,
-                        created_at: user
-                            .get_value(table.get_column("created_at").unwrap())
-                            .unwrap()
-                            .as_number()
-                            .unwrap()
-                            .as_u64()
-                            .unwrap(),
-                        updated_at: user
-                            .get_value(table.get_column("updated_at").unwrap())
-                            .unwrap()
-                            .as_number()
-                            .unwrap()
-                            .as_u64()
-                            .unwrap(),
-                        is_admin: user
-                            .get_value(table.get_column("is_admin").unwrap())
-                            .unwrap()
-                            .as_boolean()
-                            .unwrap_or_else(|| &false)
-                            .clone(),
-                        is_super_admin: user
-                            .get_value(table.get_column("is_super_admin").unwrap())
-                            .unwrap()
-                            .as_boolean()
-                            .unwrap_or_else(|| &false)
-                            .clone(),
-                        roles: vec![],
-                        scheme: args.scheme_name,
-                    });
+                    return Self::row_to_user(&user, args.identifier, args.scheme_name);
                 }
             }
         }
@@ -101,6 +163,68 @@ impl AuthManager {
         None
     }
 
+    /// Looks up a user whose identity was already established by other means,
+    /// such as a client certificate verified during the TLS handshake.
+    pub fn find_trusted_user(&self, scheme_name: &str, identifier: &str) -> Option<User> {
+        let engine = self.engine.read();
+        let db = engine.find_by_name_ref(scheme_name)?;
+        let user = Self::search_user(db, &identifier.to_string())?;
+
+        Self::row_to_user(&user, identifier.to_string(), scheme_name.to_string())
+    }
+
+    /// Reads a user out of its row. Users whose roles can not be read are rejected rather than
+    /// let in without their roles.
+    fn row_to_user(user: &DbRow, identifier: String, scheme: String) -> Option<User> {
+        let table = &*INTERNAL_USER_TABLE;
+        let roles = match roles_from_value(user.get_value(table.get_column("roles").unwrap())) {
+            Ok(roles) => roles,
+            Err(e) => {
+                eprintln!(
+                    "[Auth] Rejecting user '{}' of '{}', its roles could not be read: {}",
+                    identifier, scheme, e
+                );
+                return None;
+            }
+        };
+
+        Some(User {
+            identifier,
+            hashed_password: user
+                .get_value(table.get_column("hashed_password").unwrap())
+                .unwrap()
+                .to_string(),
+            created_at: user
+                .get_value(table.get_column("created_at").unwrap())
+                .unwrap()
+                .as_number()
+                .unwrap()
+                .as_u64()
+                .unwrap(),
+            updated_at: user
+                .get_value(table.get_column("updated_at").unwrap())
+                .unwrap()
+                .as_number()
+                .unwrap()
+                .as_u64()
+                .unwrap(),
+            is_admin: user
+                .get_value(table.get_column("is_admin").unwrap())
+                .unwrap()
+                .as_boolean()
+                .unwrap_or_else(|| &false)
+                .clone(),
+            is_super_admin: user
+                .get_value(table.get_column("is_super_admin").unwrap())
+                .unwrap()
+                .as_boolean()
+                .unwrap_or_else(|| &false)
+                .clone(),
+            roles,
+            scheme,
+        })
+    }
+
     pub fn init_default_user(&self, db_name: &str) {
         let mut engine = self.engine.write();
         let db = engine.find_by_name_ref(&db_name).unwrap();
@@ -115,7 +239,7 @@ impl AuthManager {
                 .query_manager
                 .get_table(INTERNAL_USER_TABLE_NAME)
                 .unwrap();
-            let user_row = RowJson::from_json(
+            let user_row = DbRow::from_json(
                 serde_json::to_value(create_user(
                     user.clone(),
                     pass.clone(),
@@ -132,7 +256,7 @@ impl AuthManager {
         }
     }
 
-    fn search_user(db: &EngineDb, scheme_username: &String) -> Option<RowJson> {
+    fn search_user(db: &EngineDb, scheme_username: &String) -> Option<DbRow> {
         let users = db
             .query_manager
             .search_manager
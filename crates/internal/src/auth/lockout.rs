@@ -0,0 +1,206 @@
+use dashmap::DashMap;
+use schemajs_config::LockoutConfig;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Number of tracked keys past which expired ones are evicted.
+const MIN_SWEEP_THRESHOLD: usize = 1024;
+
+struct FailedAttempts {
+    failures: u32,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// Tracks failed logins per key (username or peer IP) and applies an exponential lockout.
+///
+/// Keys are forgotten once they are not locked and have not failed for `max_lockout` seconds,
+/// so the map only holds keys with recent failures.
+pub struct LoginThrottle {
+    config: LockoutConfig,
+    attempts: DashMap<String, FailedAttempts>,
+    sweep_threshold: AtomicUsize,
+}
+
+impl LoginThrottle {
+    pub fn new(config: LockoutConfig) -> Self {
+        Self {
+            config,
+            attempts: DashMap::new(),
+            sweep_threshold: AtomicUsize::new(MIN_SWEEP_THRESHOLD),
+        }
+    }
+
+    pub fn user_key(scheme_name: &str, identifier: &str) -> String {
+        format!("user:{}/{}", scheme_name, identifier)
+    }
+
+    pub fn peer_key(peer: &str) -> String {
+        format!("peer:{}", peer)
+    }
+
+    /// Remaining lockout for `key`, if it is currently locked.
+    pub fn locked_for(&self, key: &str) -> Option<Duration> {
+        let attempts = self.attempts.get(key)?;
+        let locked_until = attempts.locked_until?;
+        locked_until
+            .checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    pub fn register_failure(&self, key: &str) {
+        let max_failed_attempts = self.config.max_failed_attempts;
+        if max_failed_attempts == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        {
+            let mut attempts =
+                self.attempts
+                    .entry(key.to_string())
+                    .or_insert_with(|| FailedAttempts {
+                        failures: 0,
+                        last_failure: now,
+                        locked_until: None,
+                    });
+            if self.is_expired(&attempts, now) {
+                attempts.failures = 0;
+            }
+            attempts.failures = attempts.failures.saturating_add(1);
+            attempts.last_failure = now;
+
+            if attempts.failures >= max_failed_attempts {
+                let exponent = attempts.failures - max_failed_attempts;
+                let lockout = self
+                    .config
+                    .base_lockout
+                    .saturating_mul(2u64.saturating_pow(exponent))
+                    .min(self.config.max_lockout);
+                attempts.locked_until = Some(now + Duration::from_secs(lockout));
+            }
+        }
+
+        // The entry is released above, sweeping while holding it would deadlock.
+        if self.attempts.len() >= self.sweep_threshold.load(Ordering::Relaxed) {
+            self.evict_expired(now);
+        }
+    }
+
+    pub fn register_success(&self, key: &str) {
+        self.attempts.remove(key);
+    }
+
+    /// Whether the failures of a key are old enough to be forgotten.
+    fn is_expired(&self, attempts: &FailedAttempts, now: Instant) -> bool {
+        let unlocked = attempts
+            .locked_until
+            .map_or(true, |locked_until| locked_until <= now);
+        unlocked
+            && now.duration_since(attempts.last_failure)
+                >= Duration::from_secs(self.config.max_lockout)
+    }
+
+    /// Drops expired keys. The next sweep happens once the map doubles, which keeps
+    /// sweeping amortized constant time per failure.
+    fn evict_expired(&self, now: Instant) {
+        self.attempts
+            .retain(|_, attempts| !self.is_expired(attempts, now));
+        self.sweep_threshold.store(
+            (self.attempts.len() * 2).max(MIN_SWEEP_THRESHOLD),
+            Ordering::Relaxed,
+        );
+    }
+
+    #[cfg(test)]
+    fn tracked_keys(&self) -> usize {
+        self.attempts.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::auth::lockout::{LoginThrottle, MIN_SWEEP_THRESHOLD};
+    use schemajs_config::LockoutConfig;
+
+    #[test]
+    fn test_exponential_lockout() {
+        let throttle = LoginThrottle::new(LockoutConfig {
+            max_failed_attempts: 3,
+            base_lockout: 10,
+            max_lockout: 25,
+        });
+        let key = LoginThrottle::user_key("public", "admin");
+
+        throttle.register_failure(&key);
+        throttle.register_failure(&key);
+        assert!(throttle.locked_for(&key).is_none());
+
+        throttle.register_failure(&key);
+        let first = throttle.locked_for(&key).unwrap();
+        assert!(first.as_secs() <= 10 && first.as_secs() >= 9);
+
+        throttle.register_failure(&key);
+        let second = throttle.locked_for(&key).unwrap();
+        assert!(second.as_secs() <= 20 && second.as_secs() >= 19);
+
+        throttle.register_failure(&key);
+        let capped = throttle.locked_for(&key).unwrap();
+        assert!(capped.as_secs() <= 25 && capped.as_secs() >= 24);
+
+        assert!(throttle
+            .locked_for(&LoginThrottle::peer_key("127.0.0.1"))
+            .is_none());
+
+        throttle.register_success(&key);
+        assert!(throttle.locked_for(&key).is_none());
+    }
+
+    #[test]
+    fn test_disabled_lockout() {
+        let throttle = LoginThrottle::new(LockoutConfig {
+            max_failed_attempts: 0,
+            base_lockout: 10,
+            max_lockout: 25,
+        });
+        let key = LoginThrottle::peer_key("127.0.0.1");
+
+        for _ in 0..10 {
+            throttle.register_failure(&key);
+        }
+
+        assert!(throttle.locked_for(&key).is_none());
+    }
+
+    #[test]
+    fn test_expired_keys_are_evicted() {
+        let throttle = LoginThrottle::new(LockoutConfig {
+            max_failed_attempts: 3,
+            base_lockout: 0,
+            max_lockout: 0,
+        });
+
+        for peer in 0..5000 {
+            throttle.register_failure(&LoginThrottle::peer_key(&peer.to_string()));
+        }
+
+        // Every failure expires right away, so sweeps keep the map under the threshold.
+        assert!(throttle.tracked_keys() <= MIN_SWEEP_THRESHOLD);
+    }
+
+    #[test]
+    fn test_locked_keys_are_kept() {
+        let throttle = LoginThrottle::new(LockoutConfig {
+            max_failed_attempts: 1,
+            base_lockout: 60,
+            max_lockout: 60,
+        });
+
+        for peer in 0..2000 {
+            throttle.register_failure(&LoginThrottle::peer_key(&peer.to_string()));
+        }
+
+        assert_eq!(throttle.tracked_keys(), 2000);
+        assert!(throttle.locked_for(&LoginThrottle::peer_key("0")).is_some());
+    }
+}
@@ -1,2 +1,4 @@
 pub mod auth_manager;
+pub mod jwt;
+pub mod lockout;
 pub mod types;
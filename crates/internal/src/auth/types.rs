@@ -1,6 +1,17 @@
 use crate::users::user::User;
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Too many failed login attempts, retry in {0} seconds")]
+    LockedOut(u64),
+    #[error("Session token could not be issued")]
+    TokenIssuance,
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct VerifyUserArgs {
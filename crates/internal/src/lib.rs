@@ -1,9 +1,13 @@
 use schemajs_primitives::table::Table;
 
+pub mod audit;
 pub mod auth;
 pub mod manager;
 pub mod users;
 
 pub fn get_internal_tables() -> Vec<Table> {
-    vec![(&*users::user::INTERNAL_USER_TABLE).clone()]
+    vec![
+        (&*users::user::INTERNAL_USER_TABLE).clone(),
+        (&*audit::login::INTERNAL_LOGIN_AUDIT_TABLE).clone(),
+    ]
 }
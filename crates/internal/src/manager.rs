@@ -1,28 +1,33 @@
 use crate::auth::auth_manager::AuthManager;
+use crate::auth::jwt::JwtAuthError;
 use crate::get_internal_tables;
 use parking_lot::RwLock;
 use schemajs_config::SchemeJsConfig;
 use schemajs_engine::engine::SchemeJsEngine;
+use schemajs_query::errors::QueryError;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 pub struct InternalManager {
     _engine: Arc<RwLock<SchemeJsEngine>>,
     auth_manager: Arc<AuthManager>,
+    initialized: AtomicBool,
 }
 
 impl InternalManager {
-    pub fn new(engine: Arc<RwLock<SchemeJsEngine>>) -> Self {
-        Self {
+    pub fn new(engine: Arc<RwLock<SchemeJsEngine>>) -> Result<Self, JwtAuthError> {
+        Ok(Self {
             _engine: engine.clone(),
-            auth_manager: Arc::new(AuthManager::new(engine)),
-        }
+            auth_manager: Arc::new(AuthManager::new(engine)?),
+            initialized: AtomicBool::new(false),
+        })
     }
 
     pub fn get_config(&self) -> Arc<SchemeJsConfig> {
         self._engine.read().config.clone()
     }
 
-    pub fn init(&self) {
+    pub fn init(&self) -> Result<(), QueryError> {
         {
             let mut writer = self._engine.write();
             let default_scheme_name = writer.config.global.default_scheme.clone();
@@ -43,7 +48,7 @@ impl InternalManager {
                 .map(|e| e.name.clone())
                 .collect();
             for schema_name in &db_names {
-                read_engine.register_tables(schema_name, get_internal_tables());
+                read_engine.register_tables(schema_name, get_internal_tables())?;
             }
 
             db_names
@@ -54,6 +59,14 @@ impl InternalManager {
                 self.auth_manager.init_default_user(&db_name);
             }
         }
+
+        self.initialized.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Whether databases and internal tables are loaded and ready to serve requests.
+    pub fn is_initialized(&self) -> bool {
+        self.initialized.load(Ordering::SeqCst)
     }
 
     pub fn engine(&self) -> Arc<RwLock<SchemeJsEngine>> {
@@ -1,2 +1,2 @@
-mod roles;
+pub mod roles;
 pub mod user;
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Enum representing different levels of access within the system.
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub enum Role {
     /// Permissions for specific tables, encapsulated in a container for clarity.
     TablePermissions(Vec<PermissionTableContainer>),
@@ -17,17 +17,20 @@ pub enum Role {
 
     /// Can create new databases in the system.
     CanCreateDatabase,
+
+    /// Can operate on the listed databases besides the one the user belongs to.
+    DatabaseAccess(Vec<String>),
 }
 
 /// Struct representing permissions for a specific table.
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct PermissionTableContainer {
     pub table_name: String,        // Name of the table
     pub actions: Vec<TableAction>, // Actions allowed on this table
 }
 
 /// Enum representing possible actions a user can take on a table.
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub enum TableAction {
     View,   // Read/query data from the table
     Modify, // Insert, update, or delete data
@@ -1,5 +1,5 @@
 use crate::users::roles::Role;
-use schemajs_primitives::column::types::DataTypes;
+use schemajs_primitives::column::types::{DataTypes, DataValue};
 use schemajs_primitives::column::Column;
 use schemajs_primitives::table::Table;
 use serde::{Deserialize, Serialize};
@@ -14,10 +14,63 @@ pub struct User {
     pub updated_at: u64,
     pub is_admin: bool,
     pub is_super_admin: bool,
+    /// Stored as JSON text in the `roles` column. See `roles_from_value` for older rows.
+    #[serde(with = "roles_as_json")]
     pub roles: Vec<Role>,
     pub scheme: String,
 }
 
+mod roles_as_json {
+    use crate::users::roles::Role;
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(roles: &[Role], serializer: S) -> Result<S::Ok, S::Error> {
+        let roles = serde_json::to_string(roles).map_err(S::Error::custom)?;
+        serializer.serialize_str(&roles)
+    }
+
+    /// Also reads the array and the null users were serialized with before roles were stored
+    /// as JSON text.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Role>, D::Error> {
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Null => Ok(vec![]),
+            serde_json::Value::String(roles) => {
+                serde_json::from_str(&roles).map_err(D::Error::custom)
+            }
+            roles => serde_json::from_value(roles).map_err(D::Error::custom),
+        }
+    }
+}
+
+/// Reads the roles stored in the `roles` column of a `sjs_users` row.
+///
+/// Rows written before roles were stored as JSON text hold no roles: the array they were
+/// serialized as did not fit the string column and was stored as null. Any other value that
+/// is not a JSON list of roles is an error, so that a user never silently loses its grants.
+pub(crate) fn roles_from_value(value: Option<DataValue>) -> Result<Vec<Role>, String> {
+    match value {
+        None | Some(DataValue::Null) => Ok(vec![]),
+        Some(DataValue::String(roles)) => serde_json::from_str(&roles).map_err(|e| e.to_string()),
+        Some(value) => Err(format!("expected JSON text, found {:?}", value)),
+    }
+}
+
+impl User {
+    /// Whether the user is allowed to operate on `db_name`.
+    pub fn can_access_database(&self, db_name: &str) -> bool {
+        if self.is_super_admin || self.scheme == db_name {
+            return true;
+        }
+
+        self.roles.iter().any(|role| match role {
+            Role::DatabaseAccess(dbs) => dbs.iter().any(|db| db == db_name),
+            _ => false,
+        })
+    }
+}
+
 pub const INTERNAL_USER_TABLE_NAME: &str = "sjs_users";
 
 pub(crate) static INTERNAL_USER_TABLE: LazyLock<Table> = LazyLock::new(|| {
@@ -78,3 +131,114 @@ pub fn create_user(
         scheme,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::users::roles::Role;
+    use crate::users::user::{create_user, roles_from_value, User, INTERNAL_USER_TABLE};
+    use schemajs_primitives::column::types::DataValue;
+    use schemajs_query::db_row::DbRow;
+    use schemajs_query::row::Row;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn user(is_super_admin: bool, roles: Vec<Role>) -> User {
+        User {
+            identifier: "jane".to_string(),
+            hashed_password: String::new(),
+            created_at: 0,
+            updated_at: 0,
+            is_admin: false,
+            is_super_admin,
+            roles,
+            scheme: "public".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_can_access_database() {
+        let member = user(false, vec![]);
+        assert!(member.can_access_database("public"));
+        assert!(!member.can_access_database("analytics"));
+
+        let granted = user(
+            false,
+            vec![Role::DatabaseAccess(vec!["analytics".to_string()])],
+        );
+        assert!(granted.can_access_database("analytics"));
+        assert!(!granted.can_access_database("billing"));
+
+        let super_admin = user(true, vec![]);
+        assert!(super_admin.can_access_database("billing"));
+    }
+
+    #[test]
+    fn test_roles_are_stored_as_json_text() {
+        let user = create_user(
+            "jane".to_string(),
+            "secret".to_string(),
+            false,
+            false,
+            vec![Role::DatabaseAccess(vec!["analytics".to_string()])],
+            "public".to_string(),
+        );
+
+        let value = serde_json::to_value(&user).unwrap();
+        assert_eq!(
+            value["roles"],
+            json!(r#"[{"DatabaseAccess":["analytics"]}]"#)
+        );
+
+        let table = Arc::new(INTERNAL_USER_TABLE.clone());
+        let row = DbRow::from_json(value, table.clone()).unwrap();
+        let roles = row
+            .get_value(table.get_column("roles").unwrap())
+            .unwrap()
+            .as_string()
+            .map(|roles| serde_json::from_str::<Vec<Role>>(roles).unwrap())
+            .unwrap();
+        assert!(matches!(&roles[..], [Role::DatabaseAccess(dbs)] if dbs == &["analytics"]));
+    }
+
+    #[test]
+    fn test_roles_of_rows_written_before_json_text() {
+        let table = Arc::new(INTERNAL_USER_TABLE.clone());
+        let legacy = json!({
+            "identifier": "jane",
+            "scheme": "public",
+            "hashed_password": "hash",
+            "created_at": 0,
+            "updated_at": 0,
+            "is_admin": false,
+            "is_super_admin": false,
+            "roles": [{"DatabaseAccess": ["analytics"]}]
+        });
+
+        // The array was stored as null, so there are no roles left to read.
+        let row = DbRow::from_json(legacy.clone(), table.clone()).unwrap();
+        let roles = roles_from_value(row.get_value(table.get_column("roles").unwrap())).unwrap();
+        assert!(roles.is_empty());
+        assert!(roles_from_value(None).unwrap().is_empty());
+
+        // Serialized users keep reading their roles in either representation.
+        let user: User = serde_json::from_value(legacy).unwrap();
+        assert!(user.can_access_database("analytics"));
+        let mut legacy_without_roles = serde_json::to_value(&user).unwrap();
+        legacy_without_roles["roles"] = json!(null);
+        let user: User = serde_json::from_value(legacy_without_roles).unwrap();
+        assert!(user.roles.is_empty());
+    }
+
+    #[test]
+    fn test_unreadable_roles_are_errors() {
+        let roles = roles_from_value(Some(DataValue::String(
+            r#"[{"DatabaseAccess":["analytics"]}]"#.to_string(),
+        )))
+        .unwrap();
+        assert!(matches!(&roles[..], [Role::DatabaseAccess(dbs)] if dbs == &["analytics"]));
+
+        assert!(roles_from_value(Some(DataValue::String("admin".to_string()))).is_err());
+        assert!(roles_from_value(Some(DataValue::String(r#"["Unknown"]"#.to_string()))).is_err());
+        assert!(roles_from_value(Some(DataValue::Boolean(true))).is_err());
+    }
+}
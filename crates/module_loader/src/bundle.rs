@@ -0,0 +1,223 @@
+use crate::ts_module_loader::{LoadedModule, TypescriptModuleLoader};
+use anyhow::{anyhow, Context};
+use deno_ast::MediaType;
+use deno_core::error::AnyError;
+use deno_core::futures::executor::block_on;
+use deno_core::futures::io::{BufReader, Cursor};
+use deno_core::serde_json;
+use deno_core::{ModuleLoader, ModuleSpecifier, ModuleType, ResolutionKind};
+use deno_graph::{DefaultModuleAnalyzer, DependencyDescriptor, DynamicArgument, ModuleAnalyzer};
+use eszip::EszipV2;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Specifier the manifest of a bundle is stored under.
+pub const BUNDLE_MANIFEST: &str = "schemajs:manifest";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleModuleType {
+    JavaScript,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleDatabase {
+    pub name: String,
+    /// Specifiers of the table modules of the database.
+    pub tables: Vec<ModuleSpecifier>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub databases: Vec<BundleDatabase>,
+    /// Every module in the bundle, already transpiled to JavaScript.
+    pub modules: BTreeMap<ModuleSpecifier, BundleModuleType>,
+    /// Specifiers that were loaded from a different url, such as `jsr:` and `npm:` packages.
+    pub redirects: BTreeMap<ModuleSpecifier, ModuleSpecifier>,
+    /// What every import of every module resolved to when bundling,
+    /// so the import map is not needed to load the bundle.
+    pub resolutions: BTreeMap<ModuleSpecifier, BTreeMap<String, ModuleSpecifier>>,
+}
+
+/// The table modules of a schema project and everything they import, read from an eszip archive.
+///
+/// A bundle is immutable: modules are only ever loaded from it, never from disk or the network.
+#[derive(Debug)]
+pub struct ModuleBundle {
+    pub manifest: BundleManifest,
+    sources: HashMap<ModuleSpecifier, Arc<str>>,
+}
+
+impl ModuleBundle {
+    /// Loads the table modules of every database and all the modules they import,
+    /// and writes them into an eszip archive.
+    pub async fn create(
+        databases: Vec<(String, Vec<ModuleSpecifier>)>,
+        loader: &TypescriptModuleLoader,
+    ) -> Result<Vec<u8>, AnyError> {
+        let mut manifest = BundleManifest::default();
+        let mut eszip = EszipV2::default();
+        let mut visited = HashSet::new();
+        let mut pending = VecDeque::new();
+
+        for (name, tables) in databases {
+            pending.extend(tables.iter().cloned());
+            manifest.databases.push(BundleDatabase { name, tables });
+        }
+
+        while let Some(specifier) = pending.pop_front() {
+            if !visited.insert(specifier.clone()) {
+                continue;
+            }
+
+            let module = loader
+                .load_module(&specifier)
+                .await
+                .with_context(|| format!("Failed bundling {}", specifier))?;
+
+            for dependency in Self::dependencies(&module).await? {
+                let resolved = loader.resolve(
+                    &dependency,
+                    module.specifier.as_str(),
+                    ResolutionKind::Import,
+                )?;
+                manifest
+                    .resolutions
+                    .entry(module.specifier.clone())
+                    .or_default()
+                    .insert(dependency, resolved.clone());
+                pending.push_back(resolved);
+            }
+
+            if module.specifier != specifier {
+                manifest
+                    .redirects
+                    .insert(specifier.clone(), module.specifier.clone());
+            }
+
+            if manifest.modules.contains_key(&module.specifier) {
+                continue;
+            }
+
+            let module_type = match module.module_type {
+                ModuleType::Json => BundleModuleType::Json,
+                _ => BundleModuleType::JavaScript,
+            };
+
+            manifest
+                .modules
+                .insert(module.specifier.clone(), module_type);
+            eszip.add_opaque_data(
+                module.specifier.to_string(),
+                Arc::from(module.code.into_bytes()),
+            );
+        }
+
+        eszip.add_opaque_data(
+            BUNDLE_MANIFEST.to_string(),
+            Arc::from(serde_json::to_vec(&manifest)?),
+        );
+
+        Ok(eszip.into_bytes())
+    }
+
+    /// Specifiers statically imported by a module, or dynamically with a string literal.
+    pub(crate) async fn dependencies(module: &LoadedModule) -> Result<Vec<String>, AnyError> {
+        if let ModuleType::Json = module.module_type {
+            return Ok(vec![]);
+        }
+
+        let info = DefaultModuleAnalyzer
+            .analyze(
+                &module.specifier,
+                module.code.as_str().into(),
+                MediaType::JavaScript,
+            )
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+
+        Ok(info
+            .dependencies
+            .into_iter()
+            .filter_map(|dependency| match dependency {
+                DependencyDescriptor::Static(dependency) => Some(dependency.specifier),
+                DependencyDescriptor::Dynamic(dependency) => match dependency.argument {
+                    DynamicArgument::String(specifier) => Some(specifier),
+                    _ => None,
+                },
+            })
+            .collect())
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, AnyError> {
+        block_on(async move {
+            let (eszip, loader) = EszipV2::parse(BufReader::new(Cursor::new(bytes))).await?;
+            loader.await?;
+
+            let manifest = Self::read_source(&eszip, BUNDLE_MANIFEST).await?;
+            let manifest: BundleManifest = serde_json::from_slice(&manifest)?;
+
+            let mut sources = HashMap::new();
+            for specifier in manifest.modules.keys() {
+                let source = Self::read_source(&eszip, specifier.as_str()).await?;
+                sources.insert(
+                    specifier.clone(),
+                    Arc::from(String::from_utf8(source.to_vec())?),
+                );
+            }
+
+            Ok(Self { manifest, sources })
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self, AnyError> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed reading bundle {}", path.display()))?;
+
+        Self::from_bytes(bytes)
+    }
+
+    async fn read_source(eszip: &EszipV2, specifier: &str) -> Result<Arc<[u8]>, AnyError> {
+        let module = eszip
+            .get_module(specifier)
+            .ok_or_else(|| anyhow!("{} is missing from the bundle", specifier))?;
+
+        module
+            .source()
+            .await
+            .ok_or_else(|| anyhow!("Source of {} is missing from the bundle", specifier))
+    }
+
+    /// Returns what `specifier` resolved to when it was imported by `referrer` while bundling.
+    pub fn resolve(&self, specifier: &str, referrer: &str) -> Option<ModuleSpecifier> {
+        let referrer = ModuleSpecifier::parse(referrer).ok()?;
+        self.manifest
+            .resolutions
+            .get(&referrer)?
+            .get(specifier)
+            .cloned()
+    }
+
+    pub fn get(&self, specifier: &ModuleSpecifier) -> Result<LoadedModule, AnyError> {
+        let found = self.manifest.redirects.get(specifier).unwrap_or(specifier);
+
+        let (module_type, code) = self
+            .manifest
+            .modules
+            .get(found)
+            .zip(self.sources.get(found))
+            .ok_or_else(|| anyhow!("{} is not in the bundle", specifier))?;
+
+        Ok(LoadedModule {
+            specifier: found.clone(),
+            module_type: match module_type {
+                BundleModuleType::JavaScript => ModuleType::JavaScript,
+                BundleModuleType::Json => ModuleType::Json,
+            },
+            code: code.to_string(),
+        })
+    }
+}
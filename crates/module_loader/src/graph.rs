@@ -0,0 +1,155 @@
+use crate::bundle::ModuleBundle;
+use crate::ts_module_loader::TypescriptModuleLoader;
+use deno_core::{ModuleLoader, ModuleSpecifier, ResolutionKind};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// The local files every table module imports, directly or through other modules,
+/// so the tables depending on a file are known when it changes.
+///
+/// Remote modules are left out, since they are cached and never change on disk.
+#[derive(Debug, Default)]
+pub struct ModuleGraph {
+    /// Database of each table module and its local files, the table module included.
+    tables: HashMap<ModuleSpecifier, (String, HashSet<PathBuf>)>,
+}
+
+impl ModuleGraph {
+    pub async fn build(
+        databases: Vec<(String, Vec<ModuleSpecifier>)>,
+        loader: &TypescriptModuleLoader,
+    ) -> Self {
+        let mut graph = Self::default();
+        for (scheme, tables) in databases {
+            for table in tables {
+                graph.update(&scheme, table, loader).await;
+            }
+        }
+
+        graph
+    }
+
+    /// Follows the imports of the table module `table` again, as they may have changed.
+    ///
+    /// A module that cannot be loaded, such as one in the middle of being edited, is kept
+    /// without its imports.
+    pub async fn update(
+        &mut self,
+        scheme: &str,
+        table: ModuleSpecifier,
+        loader: &TypescriptModuleLoader,
+    ) {
+        let mut files = HashSet::new();
+        let mut visited = HashSet::new();
+        let mut pending = VecDeque::from([table.clone()]);
+
+        while let Some(specifier) = pending.pop_front() {
+            if !visited.insert(specifier.clone()) {
+                continue;
+            }
+
+            let Ok(path) = specifier.to_file_path() else {
+                continue;
+            };
+            files.insert(path);
+
+            let Ok(module) = loader.load_module(&specifier).await else {
+                continue;
+            };
+            let Ok(dependencies) = ModuleBundle::dependencies(&module).await else {
+                continue;
+            };
+
+            for dependency in dependencies {
+                if let Ok(resolved) = loader.resolve(
+                    &dependency,
+                    module.specifier.as_str(),
+                    ResolutionKind::Import,
+                ) {
+                    pending.push_back(resolved);
+                }
+            }
+        }
+
+        self.tables.insert(table, (scheme.to_string(), files));
+    }
+
+    /// Returns the table modules that are or import the file at `path`, with their database.
+    pub fn dependents(&self, path: &Path) -> Vec<(String, ModuleSpecifier)> {
+        self.tables
+            .iter()
+            .filter(|(_, (_, files))| files.contains(path))
+            .map(|(table, (scheme, _))| (scheme.clone(), table.clone()))
+            .collect()
+    }
+
+    /// Every local file of the graph.
+    pub fn files(&self) -> HashSet<&PathBuf> {
+        self.tables
+            .values()
+            .flat_map(|(_, files)| files.iter())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::graph::ModuleGraph;
+    use crate::ts_module_loader::TypescriptModuleLoader;
+    use deno_core::ModuleSpecifier;
+    use std::path::Path;
+
+    fn write(path: &Path, code: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, code).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dependents() {
+        let folder = tempfile::tempdir().unwrap();
+        let folder = folder.path().canonicalize().unwrap();
+        let users = folder.join("public/tables/users.ts");
+        let orders = folder.join("public/tables/orders.ts");
+        let names = folder.join("lib/names.ts");
+        let shared = folder.join("lib/shared.ts");
+
+        write(
+            &users,
+            "import { name } from '../../lib/names.ts';\nexport default () => name;",
+        );
+        write(&orders, "export default () => 'orders';");
+        write(
+            &names,
+            "import { suffix } from './shared.ts';\nexport const name = 'users' + suffix;",
+        );
+        write(&shared, "export const suffix = '';");
+
+        let users_specifier = ModuleSpecifier::from_file_path(&users).unwrap();
+        let orders_specifier = ModuleSpecifier::from_file_path(&orders).unwrap();
+        let loader = TypescriptModuleLoader::default();
+        let mut graph = ModuleGraph::build(
+            vec![(
+                "public".to_string(),
+                vec![users_specifier.clone(), orders_specifier.clone()],
+            )],
+            &loader,
+        )
+        .await;
+
+        let users_table = vec![("public".to_string(), users_specifier.clone())];
+        assert_eq!(graph.dependents(&users), users_table);
+        assert_eq!(graph.dependents(&shared), users_table);
+        assert_eq!(
+            graph.dependents(&orders),
+            vec![("public".to_string(), orders_specifier)]
+        );
+        assert!(graph.dependents(&folder.join("lib/other.ts")).is_empty());
+        assert_eq!(graph.files().len(), 4);
+
+        // Imports removed from a table are no longer followed once it is updated.
+        write(&users, "export default () => 'users';");
+        graph.update("public", users_specifier, &loader).await;
+        assert_eq!(graph.dependents(&users), users_table);
+        assert!(graph.dependents(&shared).is_empty());
+    }
+}
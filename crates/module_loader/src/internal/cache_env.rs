@@ -0,0 +1,47 @@
+use deno_cache_dir::DenoCacheEnv;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// `DenoCacheEnv` backed by the real file system.
+#[derive(Debug, Clone, Default)]
+pub struct RealDenoCacheEnv;
+
+impl DenoCacheEnv for RealDenoCacheEnv {
+    fn read_file_bytes(&self, path: &Path) -> std::io::Result<Option<Vec<u8>>> {
+        match std::fs::read(path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn atomic_write_file(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Readers must never see a partially written file.
+        let temp_path = path.with_extension(format!("tmp{}", rand::random::<u32>()));
+        std::fs::write(&temp_path, bytes)?;
+        std::fs::rename(&temp_path, path).inspect_err(|_| {
+            let _ = std::fs::remove_file(&temp_path);
+        })
+    }
+
+    fn modified(&self, path: &Path) -> std::io::Result<Option<SystemTime>> {
+        match std::fs::metadata(path) {
+            Ok(metadata) => Ok(Some(metadata.modified()?)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn time_now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
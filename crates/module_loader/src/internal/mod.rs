@@ -1,5 +1,6 @@
 mod auth_tokens;
-mod cache_setting;
+pub mod cache_env;
+pub mod cache_setting;
 pub mod file_fetcher;
-mod http_util;
+pub mod http_util;
 mod versions;
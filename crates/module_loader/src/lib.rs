@@ -1,2 +1,6 @@
+pub mod bundle;
+pub mod graph;
 pub mod internal;
+pub mod lockfile;
+pub mod remote;
 pub mod ts_module_loader;
@@ -0,0 +1,115 @@
+use anyhow::Context;
+use deno_core::error::AnyError;
+use deno_lockfile::Lockfile;
+use std::path::PathBuf;
+
+/// Pins the remote modules and packages imported by table scripts in a `deno.lock`
+/// compatible lockfile, which is written every time a new entry is pinned.
+#[derive(Debug)]
+pub struct ModuleLockfile {
+    lockfile: Lockfile,
+}
+
+impl ModuleLockfile {
+    pub fn read(path: PathBuf) -> Result<Self, AnyError> {
+        let lockfile = match std::fs::read_to_string(&path) {
+            Ok(text) if text.trim().is_empty() => Lockfile::new_empty(path, false),
+            Ok(text) => Lockfile::with_lockfile_content(path.clone(), &text, false)
+                .with_context(|| format!("Failed reading lockfile '{}'", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                Lockfile::new_empty(path, false)
+            }
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("Failed reading lockfile '{}'", path.display()))
+            }
+        };
+
+        Ok(Self { lockfile })
+    }
+
+    pub fn remote(&self, url: &str) -> Option<&String> {
+        self.lockfile.remote().get(url)
+    }
+
+    pub fn insert_remote(&mut self, url: String, checksum: String) -> Result<(), AnyError> {
+        self.lockfile.insert_remote(url, checksum);
+        self.write()
+    }
+
+    /// Returns the exact package a requirement such as `jsr:@std/path@^1` was pinned to.
+    pub fn package_specifier(&self, req: &str) -> Option<&String> {
+        self.lockfile.content.packages.specifiers.get(req)
+    }
+
+    pub fn insert_package_specifier(&mut self, req: String, id: String) -> Result<(), AnyError> {
+        self.lockfile.insert_package_specifier(req, id);
+        self.write()
+    }
+
+    fn write(&mut self) -> Result<(), AnyError> {
+        let Some(bytes) = self.lockfile.resolve_write_bytes() else {
+            return Ok(());
+        };
+
+        std::fs::write(&self.lockfile.filename, bytes).with_context(|| {
+            format!(
+                "Failed writing lockfile '{}'",
+                self.lockfile.filename.display()
+            )
+        })?;
+        self.lockfile.has_content_changed = false;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lockfile::ModuleLockfile;
+
+    #[test]
+    fn test_lockfile_round_trip() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("SchemaJS.lock");
+
+        let mut lockfile = ModuleLockfile::read(path.clone()).unwrap();
+        assert!(!path.exists());
+        assert_eq!(lockfile.remote("https://example.com/mod.ts"), None);
+
+        lockfile
+            .insert_remote(
+                "https://example.com/mod.ts".to_string(),
+                "abc123".to_string(),
+            )
+            .unwrap();
+        lockfile
+            .insert_package_specifier(
+                "jsr:@std/path@^1".to_string(),
+                "jsr:@std/path@1.0.2".to_string(),
+            )
+            .unwrap();
+
+        let lockfile = ModuleLockfile::read(path.clone()).unwrap();
+        assert_eq!(
+            lockfile.remote("https://example.com/mod.ts"),
+            Some(&"abc123".to_string())
+        );
+        assert_eq!(
+            lockfile.package_specifier("jsr:@std/path@^1"),
+            Some(&"jsr:@std/path@1.0.2".to_string())
+        );
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains(r#""version": "3""#));
+    }
+
+    #[test]
+    fn test_lockfile_rejects_unknown_versions() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("SchemaJS.lock");
+        std::fs::write(&path, r#"{ "version": "99", "remote": {} }"#).unwrap();
+
+        assert!(ModuleLockfile::read(path).is_err());
+    }
+}
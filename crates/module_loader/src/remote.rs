@@ -0,0 +1,444 @@
+use crate::internal::cache_env::RealDenoCacheEnv;
+use crate::internal::cache_setting::CacheSetting;
+use crate::internal::file_fetcher::{
+    FetchNoFollowOptions, FetchOptions, File, FileFetcher, FileOrRedirect, TextDecodedFile,
+};
+use crate::internal::http_util::HttpClientProvider;
+use crate::lockfile::ModuleLockfile;
+use anyhow::{anyhow, bail, Context};
+use deno_cache_dir::GlobalHttpCache;
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
+use deno_graph::source::LoaderChecksum;
+use deno_permissions::PermissionsContainer;
+use deno_semver::jsr::JsrPackageReqReference;
+use deno_semver::npm::NpmPackageReqReference;
+use deno_semver::Version;
+use deno_web::BlobStore;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const MAX_REDIRECTS: usize = 10;
+
+/// How table scripts load `https:`, `jsr:` and `npm:` modules.
+#[derive(Debug, Clone)]
+pub struct RemoteModuleOptions {
+    /// Folder downloaded modules are cached in.
+    pub cache_dir: PathBuf,
+    /// Lockfile the checksum of every downloaded module is pinned in.
+    pub lockfile: Option<PathBuf>,
+    /// Only load modules from the cache, failing for anything that was never downloaded.
+    pub cached_only: bool,
+    /// Registry `jsr:` packages are resolved against.
+    pub jsr_registry: Url,
+    /// Registry the versions of `npm:` packages are resolved against.
+    pub npm_registry: Url,
+    /// CDN `npm:` packages are served from as ES modules.
+    pub npm_cdn: Url,
+}
+
+#[derive(Deserialize)]
+struct JsrPackageMeta {
+    #[serde(default)]
+    versions: HashMap<String, JsrVersionInfo>,
+}
+
+#[derive(Deserialize, Default)]
+struct JsrVersionInfo {
+    #[serde(default)]
+    yanked: bool,
+}
+
+#[derive(Deserialize)]
+struct NpmPackageMeta {
+    #[serde(default)]
+    versions: HashMap<String, serde_json::Value>,
+    #[serde(default, rename = "dist-tags")]
+    dist_tags: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct JsrVersionMeta {
+    #[serde(default)]
+    exports: HashMap<String, String>,
+}
+
+/// Downloads, caches and pins the remote modules imported by table scripts.
+///
+/// `jsr:` and `npm:` specifiers are resolved to `https:` urls, so every module ends up
+/// cached by url under `cache_dir` and its checksum recorded in the lockfile. Once a
+/// module is in the lockfile, a download that does not match its checksum is refused.
+#[derive(Debug)]
+pub struct RemoteModules {
+    file_fetcher: FileFetcher,
+    lockfile: Option<Mutex<ModuleLockfile>>,
+    permissions: PermissionsContainer,
+    jsr_registry: Url,
+    npm_registry: Url,
+    npm_cdn: Url,
+}
+
+impl RemoteModules {
+    pub fn new(options: RemoteModuleOptions) -> Result<Self, AnyError> {
+        std::fs::create_dir_all(&options.cache_dir)?;
+        let cache_dir = options.cache_dir.canonicalize()?;
+
+        let cache_setting = if options.cached_only {
+            CacheSetting::Only
+        } else {
+            CacheSetting::Use
+        };
+
+        let file_fetcher = FileFetcher::new(
+            Arc::new(GlobalHttpCache::new(cache_dir, RealDenoCacheEnv)),
+            cache_setting,
+            true,
+            Arc::new(HttpClientProvider::new(None, None)),
+            Arc::new(BlobStore::default()),
+        );
+
+        let lockfile = match options.lockfile {
+            Some(path) => Some(Mutex::new(ModuleLockfile::read(path)?)),
+            None => None,
+        };
+
+        Ok(Self {
+            file_fetcher,
+            lockfile,
+            permissions: PermissionsContainer::allow_all(),
+            jsr_registry: options.jsr_registry,
+            npm_registry: options.npm_registry,
+            npm_cdn: options.npm_cdn,
+        })
+    }
+
+    pub fn is_remote(specifier: &ModuleSpecifier) -> bool {
+        matches!(specifier.scheme(), "http" | "https" | "jsr" | "npm")
+    }
+
+    /// Loads a remote module. The specifier of the returned file is the url the module was
+    /// finally loaded from, which relative imports inside of it must be resolved against.
+    pub async fn load(&self, specifier: &ModuleSpecifier) -> Result<TextDecodedFile, AnyError> {
+        let url = match specifier.scheme() {
+            "jsr" => self.resolve_jsr(specifier).await?,
+            "npm" => self.resolve_npm(specifier).await?,
+            _ => specifier.clone(),
+        };
+
+        self.fetch(&url, true).await?.into_text_decoded()
+    }
+
+    /// Resolves `jsr:@scope/name@req/path` to the url of the exported module in the registry.
+    /// The version a requirement resolved to is pinned in the lockfile.
+    async fn resolve_jsr(&self, specifier: &ModuleSpecifier) -> Result<Url, AnyError> {
+        let reference = JsrPackageReqReference::from_specifier(specifier)?;
+        let req = reference.req();
+        let req_key = format!("jsr:{}", req);
+
+        let version = match self.pinned_version(&req_key)? {
+            Some(version) => version,
+            None => {
+                let meta_url = self.jsr_registry.join(&format!("{}/meta.json", req.name))?;
+                let meta: JsrPackageMeta = self.fetch_json(&meta_url, false).await?;
+
+                let version = meta
+                    .versions
+                    .iter()
+                    .filter(|(_, info)| !info.yanked)
+                    .filter_map(|(version, _)| Version::parse_standard(version).ok())
+                    .filter(|version| req.version_req.matches(version))
+                    .max()
+                    .ok_or_else(|| anyhow!("No version of '{}' matches '{}'", req.name, req))?;
+
+                self.pin_version(req_key, format!("jsr:{}@{}", req.name, version))?;
+                version
+            }
+        };
+
+        let version_meta_url = self
+            .jsr_registry
+            .join(&format!("{}/{}_meta.json", req.name, version))?;
+        let version_meta: JsrVersionMeta = self.fetch_json(&version_meta_url, true).await?;
+
+        let export_name = match reference.sub_path() {
+            Some(sub_path) => format!("./{}", sub_path),
+            None => ".".to_string(),
+        };
+
+        let export = version_meta.exports.get(&export_name).ok_or_else(|| {
+            anyhow!(
+                "'{}@{}' does not export '{}'",
+                req.name,
+                version,
+                export_name
+            )
+        })?;
+
+        Ok(self.jsr_registry.join(&format!(
+            "{}/{}/{}",
+            req.name,
+            version,
+            export.trim_start_matches("./")
+        ))?)
+    }
+
+    /// Resolves `npm:name@req/path` to the url the npm CDN serves the package from.
+    /// The version a requirement resolved to is pinned in the lockfile, like for `jsr:`.
+    async fn resolve_npm(&self, specifier: &ModuleSpecifier) -> Result<Url, AnyError> {
+        let reference = NpmPackageReqReference::from_specifier(specifier)?;
+        let req = reference.req();
+        let req_key = format!("npm:{}", req);
+
+        let version = match self.pinned_version(&req_key)? {
+            Some(version) => version,
+            None => {
+                let meta_url = self.npm_registry.join(&req.name)?;
+                let meta: NpmPackageMeta = self.fetch_json(&meta_url, false).await?;
+
+                let version = match req.version_req.tag() {
+                    Some(tag) => meta
+                        .dist_tags
+                        .get(tag)
+                        .and_then(|version| Version::parse_from_npm(version).ok()),
+                    None => meta
+                        .versions
+                        .keys()
+                        .filter_map(|version| Version::parse_from_npm(version).ok())
+                        .filter(|version| req.version_req.matches(version))
+                        .max(),
+                }
+                .ok_or_else(|| anyhow!("No version of '{}' matches '{}'", req.name, req))?;
+
+                self.pin_version(req_key, format!("npm:{}@{}", req.name, version))?;
+                version
+            }
+        };
+
+        let package = format!("{}@{}", req.name, version);
+        let path = match reference.sub_path() {
+            Some(sub_path) => format!("{}/{}", package, sub_path),
+            None => package,
+        };
+
+        Ok(self.npm_cdn.join(&path)?)
+    }
+
+    /// Returns the version a package requirement such as `npm:lodash@4` is pinned to.
+    fn pinned_version(&self, req_key: &str) -> Result<Option<Version>, AnyError> {
+        let pinned = self.lockfile.as_ref().and_then(|lockfile| {
+            let lockfile = lockfile.lock();
+            lockfile.package_specifier(req_key).cloned()
+        });
+
+        let Some(pinned) = pinned else {
+            return Ok(None);
+        };
+
+        let version = pinned.rsplit_once('@').map(|(_, version)| version);
+        match version.map(Version::parse_standard) {
+            Some(Ok(version)) => Ok(Some(version)),
+            _ => bail!("Invalid lockfile entry '{}' for '{}'", pinned, req_key),
+        }
+    }
+
+    fn pin_version(&self, req_key: String, id: String) -> Result<(), AnyError> {
+        match &self.lockfile {
+            Some(lockfile) => lockfile.lock().insert_package_specifier(req_key, id),
+            None => Ok(()),
+        }
+    }
+
+    /// Fetches a JSON document. Documents that change as packages are published, such as
+    /// the list of versions of a package, are not `pinned` in the lockfile.
+    async fn fetch_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        url: &Url,
+        pinned: bool,
+    ) -> Result<T, AnyError> {
+        let file = self.fetch(url, pinned).await?;
+        serde_json::from_slice(&file.source)
+            .with_context(|| format!("Failed parsing the response of '{}'", url))
+    }
+
+    /// Fetches a remote file following redirects. Unless it is not `pinned`, every response is
+    /// checked against the checksum pinned in the lockfile, and pinned if it was not.
+    async fn fetch(&self, url: &Url, pinned: bool) -> Result<File, AnyError> {
+        let mut url = url.clone();
+        let lockfile = self.lockfile.as_ref().filter(|_| pinned);
+
+        for _ in 0..=MAX_REDIRECTS {
+            let checksum = lockfile.and_then(|lockfile| {
+                let lockfile = lockfile.lock();
+                lockfile
+                    .remote(url.as_str())
+                    .map(|checksum| LoaderChecksum::new(checksum.clone()))
+            });
+
+            let file_or_redirect = self
+                .file_fetcher
+                .fetch_no_follow_with_options(FetchNoFollowOptions {
+                    fetch_options: FetchOptions {
+                        specifier: &url,
+                        permissions: &self.permissions,
+                        maybe_accept: None,
+                        maybe_cache_setting: None,
+                    },
+                    maybe_checksum: checksum.as_ref(),
+                })
+                .await?;
+
+            match file_or_redirect {
+                FileOrRedirect::File(file) => {
+                    if let (Some(lockfile), None) = (lockfile, checksum) {
+                        lockfile
+                            .lock()
+                            .insert_remote(url.to_string(), LoaderChecksum::gen(&file.source))?;
+                    }
+
+                    return Ok(file);
+                }
+                FileOrRedirect::Redirect(redirect) => {
+                    url = redirect;
+                }
+            }
+        }
+
+        bail!("Too many redirects loading '{}'", url)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lockfile::ModuleLockfile;
+    use crate::remote::{RemoteModuleOptions, RemoteModules};
+    use deno_core::url::Url;
+    use deno_core::ModuleSpecifier;
+    use std::path::Path;
+
+    struct TestRegistries {
+        folder: tempfile::TempDir,
+    }
+
+    impl TestRegistries {
+        fn new() -> Self {
+            let folder = tempfile::tempdir().unwrap();
+            let registries = Self { folder };
+
+            registries.write(
+                "jsr/@std/path/meta.json",
+                r#"{ "versions": { "1.0.0": {}, "1.0.2": {}, "1.1.0": { "yanked": true }, "2.0.0": {} } }"#,
+            );
+            registries.write(
+                "jsr/@std/path/1.0.2_meta.json",
+                r#"{ "exports": { ".": "./mod.ts", "./posix": "./posix/mod.ts" } }"#,
+            );
+            registries.write(
+                "npm/lodash",
+                r#"{ "versions": { "4.17.20": {}, "4.17.21": {}, "5.0.0": {} }, "dist-tags": { "latest": "4.17.21" } }"#,
+            );
+
+            registries
+        }
+
+        fn write(&self, path: &str, content: &str) {
+            let path = self.folder.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        fn lockfile(&self) -> std::path::PathBuf {
+            self.folder.path().join("SchemaJS.lock")
+        }
+
+        fn modules(&self) -> RemoteModules {
+            let registry =
+                |name: &str| Url::from_directory_path(self.folder.path().join(name)).unwrap();
+
+            RemoteModules::new(RemoteModuleOptions {
+                cache_dir: self.folder.path().join("cache"),
+                lockfile: Some(self.lockfile()),
+                cached_only: false,
+                jsr_registry: registry("jsr"),
+                npm_registry: registry("npm"),
+                npm_cdn: Url::parse("https://esm.sh/").unwrap(),
+            })
+            .unwrap()
+        }
+
+        fn url(&self, path: &str) -> Url {
+            Url::from_file_path(self.folder.path().join(path)).unwrap()
+        }
+    }
+
+    fn pinned(lockfile: &Path, req: &str) -> Option<String> {
+        ModuleLockfile::read(lockfile.to_path_buf())
+            .unwrap()
+            .package_specifier(req)
+            .cloned()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_jsr() {
+        let registries = TestRegistries::new();
+        let modules = registries.modules();
+
+        let specifier = ModuleSpecifier::parse("jsr:@std/path@^1").unwrap();
+        let url = modules.resolve_jsr(&specifier).await.unwrap();
+        assert_eq!(url, registries.url("jsr/@std/path/1.0.2/mod.ts"));
+        assert_eq!(
+            pinned(&registries.lockfile(), "jsr:@std/path@^1").as_deref(),
+            Some("jsr:@std/path@1.0.2")
+        );
+
+        let specifier = ModuleSpecifier::parse("jsr:@std/path@^1/posix").unwrap();
+        let url = modules.resolve_jsr(&specifier).await.unwrap();
+        assert_eq!(url, registries.url("jsr/@std/path/1.0.2/posix/mod.ts"));
+
+        // Pinned requirements keep their version after newer ones are published.
+        registries.write(
+            "jsr/@std/path/meta.json",
+            r#"{ "versions": { "1.0.2": {}, "1.0.3": {} } }"#,
+        );
+        let specifier = ModuleSpecifier::parse("jsr:@std/path@^1").unwrap();
+        let url = registries.modules().resolve_jsr(&specifier).await.unwrap();
+        assert_eq!(url, registries.url("jsr/@std/path/1.0.2/mod.ts"));
+
+        let specifier = ModuleSpecifier::parse("jsr:@std/path@^3").unwrap();
+        assert!(modules.resolve_jsr(&specifier).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_npm() {
+        let registries = TestRegistries::new();
+        let modules = registries.modules();
+
+        let specifier = ModuleSpecifier::parse("npm:lodash@4/fp").unwrap();
+        let url = modules.resolve_npm(&specifier).await.unwrap();
+        assert_eq!(url.as_str(), "https://esm.sh/lodash@4.17.21/fp");
+        assert_eq!(
+            pinned(&registries.lockfile(), "npm:lodash@4").as_deref(),
+            Some("npm:lodash@4.17.21")
+        );
+
+        let specifier = ModuleSpecifier::parse("npm:lodash@latest").unwrap();
+        let url = modules.resolve_npm(&specifier).await.unwrap();
+        assert_eq!(url.as_str(), "https://esm.sh/lodash@4.17.21");
+
+        // Pinned requirements keep their version after newer ones are published.
+        registries.write(
+            "npm/lodash",
+            r#"{ "versions": { "4.17.21": {}, "4.18.0": {} } }"#,
+        );
+        let specifier = ModuleSpecifier::parse("npm:lodash@4").unwrap();
+        let url = registries.modules().resolve_npm(&specifier).await.unwrap();
+        assert_eq!(url.as_str(), "https://esm.sh/lodash@4.17.21");
+
+        let specifier = ModuleSpecifier::parse("npm:lodash@6").unwrap();
+        assert!(modules.resolve_npm(&specifier).await.is_err());
+    }
+}
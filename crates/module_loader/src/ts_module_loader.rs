@@ -7,6 +7,10 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::bundle::ModuleBundle;
+use crate::remote::RemoteModules;
 
 use anyhow::anyhow;
 use anyhow::bail;
@@ -18,6 +22,7 @@ use deno_ast::SourceMapOption;
 use deno_core::error::AnyError;
 use deno_core::resolve_import;
 use deno_core::resolve_path;
+use deno_core::url::Url;
 use deno_core::JsRuntime;
 use deno_core::ModuleLoadResponse;
 use deno_core::ModuleLoader;
@@ -28,11 +33,178 @@ use deno_core::ModuleType;
 use deno_core::RequestedModuleType;
 use deno_core::ResolutionKind;
 use deno_core::RuntimeOptions;
+use import_map::ImportMap;
 
 type SourceMapStore = Rc<RefCell<HashMap<String, Vec<u8>>>>;
 
 pub struct TypescriptModuleLoader {
     source_maps: SourceMapStore,
+    import_map: Option<Arc<ImportMap>>,
+    remote_modules: Option<Arc<RemoteModules>>,
+    bundle: Option<Arc<ModuleBundle>>,
+}
+
+/// A module loaded and transpiled to JavaScript.
+#[derive(Debug, Clone)]
+pub struct LoadedModule {
+    /// Url the module was finally loaded from, which differs from the requested one after a redirect.
+    pub specifier: ModuleSpecifier,
+    pub module_type: ModuleType,
+    pub code: String,
+}
+
+impl LoadedModule {
+    fn into_module_source(self, requested: &ModuleSpecifier) -> ModuleSource {
+        ModuleSource::new_with_redirect(
+            self.module_type,
+            ModuleSourceCode::String(self.code.into()),
+            requested,
+            &self.specifier,
+            None,
+        )
+    }
+}
+
+impl TypescriptModuleLoader {
+    /// Creates a loader that resolves bare specifiers through `import_map`
+    /// and loads `https:`, `jsr:` and `npm:` modules through `remote_modules`.
+    pub fn new(
+        import_map: Option<Arc<ImportMap>>,
+        remote_modules: Option<Arc<RemoteModules>>,
+    ) -> Self {
+        Self {
+            source_maps: Rc::new(RefCell::new(HashMap::new())),
+            import_map,
+            remote_modules,
+            bundle: None,
+        }
+    }
+
+    /// Creates a loader that only loads the modules in `bundle`.
+    pub fn from_bundle(bundle: Arc<ModuleBundle>) -> Self {
+        Self {
+            source_maps: Rc::new(RefCell::new(HashMap::new())),
+            import_map: None,
+            remote_modules: None,
+            bundle: Some(bundle),
+        }
+    }
+
+    /// Loads and transpiles a local or remote module, without going through V8.
+    pub async fn load_module(&self, specifier: &ModuleSpecifier) -> Result<LoadedModule, AnyError> {
+        if RemoteModules::is_remote(specifier) {
+            load_remote(&self.source_maps, self.remote_modules.clone(), specifier).await
+        } else {
+            load_local(&self.source_maps, specifier)
+        }
+    }
+}
+
+fn load_local(
+    source_maps: &SourceMapStore,
+    module_specifier: &ModuleSpecifier,
+) -> Result<LoadedModule, AnyError> {
+    let path = module_specifier
+        .to_file_path()
+        .map_err(|_| anyhow!("Only file:// URLs are supported."))?;
+
+    let code = std::fs::read_to_string(&path)?;
+    let (module_type, code) = transpile(
+        source_maps,
+        module_specifier,
+        MediaType::from_path(&path),
+        code,
+    )?;
+
+    Ok(LoadedModule {
+        specifier: module_specifier.clone(),
+        module_type,
+        code,
+    })
+}
+
+async fn load_remote(
+    source_maps: &SourceMapStore,
+    remote_modules: Option<Arc<RemoteModules>>,
+    module_specifier: &ModuleSpecifier,
+) -> Result<LoadedModule, AnyError> {
+    let Some(remote_modules) = remote_modules else {
+        bail!(
+            "Remote modules are not enabled, cannot load {}",
+            module_specifier
+        );
+    };
+
+    let file = remote_modules.load(module_specifier).await?;
+    let (module_type, code) = transpile(
+        source_maps,
+        &file.specifier,
+        file.media_type,
+        file.source.to_string(),
+    )?;
+
+    Ok(LoadedModule {
+        specifier: file.specifier,
+        module_type,
+        code,
+    })
+}
+
+fn transpile(
+    source_maps: &SourceMapStore,
+    module_specifier: &ModuleSpecifier,
+    media_type: MediaType,
+    code: String,
+) -> Result<(ModuleType, String), AnyError> {
+    let (module_type, should_transpile) = match media_type {
+        MediaType::JavaScript | MediaType::Mjs | MediaType::Cjs => (ModuleType::JavaScript, false),
+        MediaType::Jsx => (ModuleType::JavaScript, true),
+        MediaType::TypeScript
+        | MediaType::Mts
+        | MediaType::Cts
+        | MediaType::Dts
+        | MediaType::Dmts
+        | MediaType::Dcts
+        | MediaType::Tsx => (ModuleType::JavaScript, true),
+        MediaType::Json => (ModuleType::Json, false),
+        _ => bail!(
+            "Unknown media type {:?} for {}",
+            media_type,
+            module_specifier
+        ),
+    };
+
+    if !should_transpile {
+        return Ok((module_type, code));
+    }
+
+    let parsed = deno_ast::parse_module(ParseParams {
+        specifier: module_specifier.clone(),
+        text: code.into(),
+        media_type,
+        capture_tokens: false,
+        scope_analysis: false,
+        maybe_syntax: None,
+    })?;
+    let res = parsed.transpile(
+        &deno_ast::TranspileOptions {
+            imports_not_used_as_values: deno_ast::ImportsNotUsedAsValues::Remove,
+            use_decorators_proposal: true,
+            ..Default::default()
+        },
+        &deno_ast::EmitOptions {
+            source_map: SourceMapOption::Separate,
+            inline_sources: true,
+            ..Default::default()
+        },
+    )?;
+    let res = res.into_source();
+    let source_map = res.source_map.unwrap();
+    source_maps
+        .borrow_mut()
+        .insert(module_specifier.to_string(), source_map);
+
+    Ok((module_type, String::from_utf8(res.source)?))
 }
 
 impl ModuleLoader for TypescriptModuleLoader {
@@ -42,7 +214,32 @@ impl ModuleLoader for TypescriptModuleLoader {
         referrer: &str,
         _kind: ResolutionKind,
     ) -> Result<ModuleSpecifier, Error> {
-        Ok(resolve_import(specifier, referrer)?)
+        if let Some(resolved) = self
+            .bundle
+            .as_ref()
+            .and_then(|bundle| bundle.resolve(specifier, referrer))
+        {
+            return Ok(resolved);
+        }
+
+        let mut resolved = match (&self.import_map, Url::parse(referrer)) {
+            (Some(import_map), Ok(referrer)) => import_map.resolve(specifier, &referrer).ok(),
+            _ => None,
+        }
+        .map_or_else(|| resolve_import(specifier, referrer), Ok)?;
+
+        // Local modules imported by a reloaded table module get its version, so they are
+        // evaluated again rather than reused from the previous load.
+        if let Ok(referrer) = Url::parse(referrer) {
+            if referrer.scheme() == "file"
+                && resolved.scheme() == "file"
+                && resolved.query().is_none()
+            {
+                resolved.set_query(referrer.query());
+            }
+        }
+
+        Ok(resolved)
     }
 
     fn load(
@@ -52,72 +249,30 @@ impl ModuleLoader for TypescriptModuleLoader {
         _is_dyn_import: bool,
         _requested_module_type: RequestedModuleType,
     ) -> ModuleLoadResponse {
-        let source_maps = self.source_maps.clone();
-        fn load(
-            source_maps: SourceMapStore,
-            module_specifier: &ModuleSpecifier,
-        ) -> Result<ModuleSource, AnyError> {
-            let path = module_specifier
-                .to_file_path()
-                .map_err(|_| anyhow!("Only file:// URLs are supported."))?;
-
-            let media_type = MediaType::from_path(&path);
-            let (module_type, should_transpile) = match MediaType::from_path(&path) {
-                MediaType::JavaScript | MediaType::Mjs | MediaType::Cjs => {
-                    (ModuleType::JavaScript, false)
-                }
-                MediaType::Jsx => (ModuleType::JavaScript, true),
-                MediaType::TypeScript
-                | MediaType::Mts
-                | MediaType::Cts
-                | MediaType::Dts
-                | MediaType::Dmts
-                | MediaType::Dcts
-                | MediaType::Tsx => (ModuleType::JavaScript, true),
-                MediaType::Json => (ModuleType::Json, false),
-                _ => bail!("Unknown extension {:?}", path.extension()),
-            };
-
-            let code = std::fs::read_to_string(&path)?;
-            let code = if should_transpile {
-                let parsed = deno_ast::parse_module(ParseParams {
-                    specifier: module_specifier.clone(),
-                    text: code.into(),
-                    media_type,
-                    capture_tokens: false,
-                    scope_analysis: false,
-                    maybe_syntax: None,
-                })?;
-                let res = parsed.transpile(
-                    &deno_ast::TranspileOptions {
-                        imports_not_used_as_values: deno_ast::ImportsNotUsedAsValues::Remove,
-                        use_decorators_proposal: true,
-                        ..Default::default()
-                    },
-                    &deno_ast::EmitOptions {
-                        source_map: SourceMapOption::Separate,
-                        inline_sources: true,
-                        ..Default::default()
-                    },
-                )?;
-                let res = res.into_source();
-                let source_map = res.source_map.unwrap();
-                source_maps
-                    .borrow_mut()
-                    .insert(module_specifier.to_string(), source_map);
-                String::from_utf8(res.source).unwrap()
-            } else {
-                code
-            };
-            Ok(ModuleSource::new(
-                module_type,
-                ModuleSourceCode::String(code.into()),
-                module_specifier,
-                None,
-            ))
+        if let Some(bundle) = &self.bundle {
+            return ModuleLoadResponse::Sync(
+                bundle
+                    .get(module_specifier)
+                    .map(|module| module.into_module_source(module_specifier)),
+            );
         }
 
-        ModuleLoadResponse::Sync(load(source_maps, module_specifier))
+        if RemoteModules::is_remote(module_specifier) {
+            let source_maps = self.source_maps.clone();
+            let remote_modules = self.remote_modules.clone();
+            let module_specifier = module_specifier.clone();
+
+            return ModuleLoadResponse::Async(Box::pin(async move {
+                load_remote(&source_maps, remote_modules, &module_specifier)
+                    .await
+                    .map(|module| module.into_module_source(&module_specifier))
+            }));
+        }
+
+        ModuleLoadResponse::Sync(
+            load_local(&self.source_maps, module_specifier)
+                .map(|module| module.into_module_source(module_specifier)),
+        )
     }
 
     fn get_source_map(&self, specifier: &str) -> Option<Vec<u8>> {
@@ -134,12 +289,8 @@ fn main() -> Result<(), Error> {
     let main_url = &args[1];
     println!("Run {main_url}");
 
-    let source_map_store = Rc::new(RefCell::new(HashMap::new()));
-
     let mut js_runtime = JsRuntime::new(RuntimeOptions {
-        module_loader: Some(Rc::new(TypescriptModuleLoader {
-            source_maps: source_map_store,
-        })),
+        module_loader: Some(Rc::new(TypescriptModuleLoader::default())),
         ..Default::default()
     });
 
@@ -164,9 +315,6 @@ fn main() -> Result<(), Error> {
 
 impl Default for TypescriptModuleLoader {
     fn default() -> Self {
-        let source_map_store = Rc::new(RefCell::new(HashMap::new()));
-        TypescriptModuleLoader {
-            source_maps: source_map_store,
-        }
+        Self::new(None, None)
     }
 }
@@ -7,7 +7,7 @@ use std::cmp::Ordering;
 use std::str::FromStr;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, EnumAsInner)]
+#[derive(Debug, Clone, Serialize, Deserialize, EnumAsInner, PartialEq)]
 pub enum DataTypes {
     Null,
     Uuid,
@@ -1,12 +1,57 @@
 import { Column } from "ext:sjs_primitives/src/js/column.ts";
+import { DataTypes } from "ext:sjs_primitives/src/js/dataTypes.ts";
 import { Helper, HelperType } from "ext:sjs_helpers/src/js/helper.ts";
 
+type QuerySchema = Record<string, any> | Record<string, Column>;
+
+interface QuerySchemas {
+    input?: QuerySchema;
+    output?: QuerySchema;
+}
+
+const columnToJsonSchema = (col: Column) => {
+    switch (col.dataType) {
+        case DataTypes.Uuid:
+            return { type: "string", format: "uuid" };
+        case DataTypes.String:
+            return { type: "string" };
+        case DataTypes.Boolean:
+            return { type: "boolean" };
+        case DataTypes.Number:
+            return { type: "number" };
+        default:
+            return { type: "null" };
+    }
+}
+
+// Schemas are either JSON Schemas or objects of columns describing the fields of an object.
+const toJsonSchema = (schema?: QuerySchema) => {
+    if(!schema) {
+        return undefined;
+    }
+
+    const entries = Object.entries(schema);
+    if(entries.length === 0 || !entries.every(([_, val]) => val instanceof Column)) {
+        return schema;
+    }
+
+    return {
+        type: "object",
+        properties: Object.fromEntries(entries.map(([key, col]) => [key, columnToJsonSchema(col)])),
+        required: entries.filter(([_, col]) => col.required).map(([key]) => key)
+    };
+}
+
+export type Compression = "none" | "zstd" | "lz4";
+
 export class Table {
     public name: string;
     public columns: Record<string, Column> = {};
     public indexes = [];
     public primary_key = "_uid";
     public helpers: Helper[] = [];
+    public queries = [];
+    public compression: Compression = "none";
 
     constructor(name: string) {
         this.name = name;
@@ -17,8 +62,24 @@ export class Table {
         return this;
     }
 
-    addQuery(name: string, cb: any) {
+    // Compresses the rows written to new data shards, existing shards are left as they are.
+    setCompression(compression: Compression) {
+        this.compression = compression;
+        return this;
+    }
+
+    addQuery(name: string, cb: any, schemas?: QuerySchemas) {
         this.helpers.push(new Helper(name, HelperType.CustomQuery, cb));
+        this.queries.push({
+            name,
+            input: toJsonSchema(schemas?.input),
+            output: toJsonSchema(schemas?.output)
+        });
+        return this;
+    }
+
+    schedule(cron: string, cb: any) {
+        this.helpers.push(new Helper(cron, HelperType.Scheduled, cb));
         return this;
     }
 
@@ -29,6 +90,14 @@ export class Table {
                 this.helpers.push(new Helper("default", HelperType.InsertHook, cb));
             }
             break;
+            case "beforeinsert": {
+                this.helpers.push(new Helper("default", HelperType.BeforeInsertHook, cb));
+            }
+            break;
+            case "afterreconcile": {
+                this.helpers.push(new Helper("default", HelperType.AfterReconcileHook, cb));
+            }
+            break;
             default: {
                 throw new Error("Unknown hook type")
             }
@@ -0,0 +1,206 @@
+use jsonschema::JSONSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// A custom query registered with `Table.addQuery`, along with the optional JSON Schemas
+/// its arguments and its result must satisfy.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CustomQuery {
+    pub name: String,
+    #[serde(default)]
+    pub input: Option<Value>,
+    #[serde(default)]
+    pub output: Option<Value>,
+    #[serde(skip)]
+    compiled: CompiledSchemas,
+}
+
+/// Schemas of a query compiled by `CustomQuery::compile_schemas`, shared between its clones.
+#[derive(Debug, Clone, Default)]
+struct CompiledSchemas {
+    input: Option<Arc<JSONSchema>>,
+    output: Option<Arc<JSONSchema>>,
+}
+
+/// A value that does not satisfy a schema. `path` is the JSON pointer to the offending field.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SchemaViolation {
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for SchemaViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("Invalid {kind} schema for custom query '{query}': {reason}")]
+pub struct InvalidQuerySchema {
+    pub query: String,
+    pub kind: &'static str,
+    pub reason: String,
+}
+
+impl CustomQuery {
+    pub fn new(name: &str, input: Option<Value>, output: Option<Value>) -> Self {
+        Self {
+            name: name.to_string(),
+            input,
+            output,
+            compiled: CompiledSchemas::default(),
+        }
+    }
+
+    /// Compiles the schemas of the query, so they are not compiled again on every call.
+    pub fn compile_schemas(&mut self) -> Result<(), InvalidQuerySchema> {
+        self.compiled = CompiledSchemas {
+            input: self.compile(self.input.as_ref(), "input")?,
+            output: self.compile(self.output.as_ref(), "output")?,
+        };
+
+        Ok(())
+    }
+
+    fn compile(
+        &self,
+        schema: Option<&Value>,
+        kind: &'static str,
+    ) -> Result<Option<Arc<JSONSchema>>, InvalidQuerySchema> {
+        schema
+            .map(|schema| {
+                JSONSchema::compile(schema)
+                    .map(Arc::new)
+                    .map_err(|e| InvalidQuerySchema {
+                        query: self.name.clone(),
+                        kind,
+                        reason: e.to_string(),
+                    })
+            })
+            .transpose()
+    }
+
+    pub fn validate_input(&self, value: &Value) -> Result<(), Vec<SchemaViolation>> {
+        self.validate(
+            self.input.as_ref(),
+            self.compiled.input.as_ref(),
+            "input",
+            value,
+        )
+    }
+
+    pub fn validate_output(&self, value: &Value) -> Result<(), Vec<SchemaViolation>> {
+        self.validate(
+            self.output.as_ref(),
+            self.compiled.output.as_ref(),
+            "output",
+            value,
+        )
+    }
+
+    fn validate(
+        &self,
+        schema: Option<&Value>,
+        compiled: Option<&Arc<JSONSchema>>,
+        kind: &'static str,
+        value: &Value,
+    ) -> Result<(), Vec<SchemaViolation>> {
+        // Schemas of queries whose table was not loaded by the runtime are compiled as needed.
+        let compiled = match compiled {
+            Some(compiled) => compiled.clone(),
+            None => match self.compile(schema, kind) {
+                Ok(Some(compiled)) => compiled,
+                Ok(None) => return Ok(()),
+                Err(e) => {
+                    return Err(vec![SchemaViolation {
+                        path: String::new(),
+                        message: e.to_string(),
+                    }])
+                }
+            },
+        };
+
+        let result = compiled.validate(value).map_err(|errors| {
+            errors
+                .map(|e| SchemaViolation {
+                    path: e.instance_path.to_string(),
+                    message: e.to_string(),
+                })
+                .collect()
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::table::custom_query::CustomQuery;
+    use serde_json::json;
+
+    fn typed_query() -> CustomQuery {
+        let mut query = CustomQuery::new(
+            "typed",
+            Some(json!({
+                "type": "object",
+                "properties": { "username": { "type": "string" } },
+                "required": ["username"]
+            })),
+            Some(json!({ "type": "array" })),
+        );
+        query.compile_schemas().unwrap();
+        query
+    }
+
+    #[test]
+    fn test_validate_input() {
+        let query = typed_query();
+        assert!(query.validate_input(&json!({ "username": "Luis" })).is_ok());
+
+        let violations = query.validate_input(&json!({ "username": 1 })).unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, "/username");
+        assert!(violations[0].to_string().starts_with("/username: "));
+
+        let violations = query.validate_input(&json!({})).unwrap_err();
+        assert_eq!(violations[0].path, "");
+        assert!(violations[0].to_string().starts_with("/: "));
+    }
+
+    #[test]
+    fn test_validate_output() {
+        let query = typed_query();
+        assert!(query.validate_output(&json!([])).is_ok());
+        assert!(query.validate_output(&json!({})).is_err());
+    }
+
+    #[test]
+    fn test_queries_without_schemas_accept_anything() {
+        let mut query = CustomQuery::new("untyped", None, None);
+        query.compile_schemas().unwrap();
+
+        assert!(query.validate_input(&json!(1)).is_ok());
+        assert!(query.validate_output(&json!(null)).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        let mut query = CustomQuery::new("invalid", Some(json!({ "type": 1 })), None);
+
+        let err = query.compile_schemas().unwrap_err();
+        assert_eq!(err.query, "invalid");
+        assert_eq!(err.kind, "input");
+
+        // Uncompiled queries report their invalid schema when they are called.
+        let violations = query.validate_input(&json!({})).unwrap_err();
+        assert!(violations[0].message.contains("Invalid input schema"));
+    }
+}
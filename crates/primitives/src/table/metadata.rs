@@ -1,10 +1,18 @@
 use deno_core::ModuleId;
+use schemajs_config::RowFormat;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TableMetadata {
     pub module_id: Option<ModuleId>,
     pub internal: bool,
+    /// Whether rows must go through the table's `beforeInsert` hooks before being inserted.
+    pub before_insert: bool,
+    /// Format new rows of the table are stored in.
+    pub row_format: RowFormat,
+    /// Names of the columns by ordinal. Once a column has an ordinal it never changes,
+    /// so rows stored by ordinal stay readable when columns are added.
+    pub column_ordinals: Vec<String>,
 }
 
 impl TableMetadata {
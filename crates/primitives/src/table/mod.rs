@@ -1,9 +1,12 @@
+pub mod custom_query;
 pub mod metadata;
 
 use crate::column::types::DataTypes;
 use crate::column::Column;
 use crate::index::Index;
+use crate::table::custom_query::{CustomQuery, InvalidQuerySchema};
 use crate::table::metadata::TableMetadata;
+use schemajs_data::compression::Compression;
 use schemajs_index::index_type::IndexType;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -15,6 +18,11 @@ pub struct Table {
     pub columns: HashMap<String, Column>,
     pub indexes: Vec<Index>,
     pub primary_key: String,
+    #[serde(default)]
+    pub queries: Vec<CustomQuery>,
+    /// Compression of the rows written to new data shards of the table.
+    #[serde(default)]
+    pub compression: Compression,
     #[serde(skip_serializing, skip_deserializing)]
     pub metadata: TableMetadata,
 }
@@ -39,6 +47,8 @@ impl Table {
             metadata: Default::default(),
             primary_key: "_uid".to_string(),
             indexes: vec![Self::get_internal_uid_index().clone()],
+            queries: vec![],
+            compression: Compression::None,
         }
     }
 
@@ -85,6 +95,11 @@ impl Table {
         self
     }
 
+    pub fn set_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
     pub fn set_internal(mut self, internal: bool) -> Self {
         self.metadata.internal = internal;
         self
@@ -94,6 +109,42 @@ impl Table {
         self.columns.get(column_name)
     }
 
+    pub fn get_custom_query(&self, name: &str) -> Option<&CustomQuery> {
+        self.queries.iter().find(|query| query.name == name)
+    }
+
+    /// Compiles the schemas of the custom queries, failing on the first invalid one.
+    pub fn compile_query_schemas(&mut self) -> Result<(), InvalidQuerySchema> {
+        self.queries
+            .iter_mut()
+            .try_for_each(|query| query.compile_schemas())
+    }
+
+    pub fn column_ordinal(&self, column_name: &str) -> Option<usize> {
+        self.metadata
+            .column_ordinals
+            .iter()
+            .position(|name| name == column_name)
+    }
+
+    /// Keeps the ordinals in `existing` and gives the next ones to the columns without one,
+    /// in name order. Returns whether any column got a new ordinal.
+    pub fn assign_column_ordinals(&mut self, existing: Vec<String>) -> bool {
+        let mut new_columns: Vec<&String> = self
+            .columns
+            .keys()
+            .filter(|name| !existing.contains(name))
+            .collect();
+        new_columns.sort();
+
+        let changed = !new_columns.is_empty();
+        let mut ordinals = existing;
+        ordinals.extend(new_columns.into_iter().cloned());
+        self.metadata.column_ordinals = ordinals;
+
+        changed
+    }
+
     pub fn list_columns(&self) -> Vec<&String> {
         self.columns.keys().collect()
     }
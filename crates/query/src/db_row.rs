@@ -0,0 +1,156 @@
+use super::row::Row;
+use crate::row_binary::{RowBinary, BINARY_ROW_MARKER};
+use crate::row_json::{RowData, RowJson};
+use crate::RowSerializationError;
+use schemajs_config::RowFormat;
+use schemajs_primitives::column::types::DataValue;
+use schemajs_primitives::column::Column;
+use schemajs_primitives::table::Table;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// `DbRow` is the row of a database, stored in the format selected by its `row_format`
+/// setting, which the query manager records in the metadata of every table.
+///
+/// New rows are created in the format of their table, while stored rows are read in
+/// the format they were written in, so changing the setting keeps existing data readable.
+#[derive(Clone, Debug)]
+pub enum DbRow {
+    Json(RowJson),
+    Binary(RowBinary),
+}
+
+impl Row for DbRow {
+    type RowData = RowData;
+
+    fn to_data(&self) -> Self::RowData {
+        match self {
+            DbRow::Json(row) => row.to_data(),
+            DbRow::Binary(row) => row.to_data(),
+        }
+    }
+
+    fn to_vec(&self) -> Result<Vec<u8>, RowSerializationError> {
+        match self {
+            DbRow::Json(row) => row.to_vec(),
+            DbRow::Binary(row) => row.to_vec(),
+        }
+    }
+
+    fn to_map(&self) -> Result<HashMap<String, DataValue>, RowSerializationError> {
+        match self {
+            DbRow::Json(row) => row.to_map(),
+            DbRow::Binary(row) => row.to_map(),
+        }
+    }
+
+    fn from_slice(slice: &[u8], table: Arc<Table>) -> Self {
+        match slice.first() {
+            Some(&BINARY_ROW_MARKER) => DbRow::Binary(RowBinary::from_slice(slice, table)),
+            _ => DbRow::Json(RowJson::from_slice(slice, table)),
+        }
+    }
+
+    fn from_data(data: Self::RowData, table: Arc<Table>) -> Result<Self, ()> {
+        match table.metadata.row_format {
+            RowFormat::Json => RowJson::from_data(data, table).map(DbRow::Json),
+            RowFormat::Binary => RowBinary::from_data(data, table).map(DbRow::Binary),
+        }
+    }
+
+    fn from_map(table: Arc<Table>, data: HashMap<String, DataValue>) -> Result<Self, ()> {
+        match table.metadata.row_format {
+            RowFormat::Json => RowJson::from_map(table, data).map(DbRow::Json),
+            RowFormat::Binary => RowBinary::from_map(table, data).map(DbRow::Binary),
+        }
+    }
+
+    fn get_table(&self) -> Arc<Table> {
+        match self {
+            DbRow::Json(row) => row.get_table(),
+            DbRow::Binary(row) => row.get_table(),
+        }
+    }
+
+    fn get_value(&self, column: &Column) -> Option<DataValue> {
+        match self {
+            DbRow::Json(row) => row.get_value(column),
+            DbRow::Binary(row) => row.get_value(column),
+        }
+    }
+
+    fn get_value_from_slice(
+        slice: &[u8],
+        table: &Arc<Table>,
+        column: &Column,
+    ) -> Option<DataValue> {
+        match slice.first() {
+            Some(&BINARY_ROW_MARKER) => RowBinary::get_value_from_slice(slice, table, column),
+            _ => RowJson::get_value_from_slice(slice, table, column),
+        }
+    }
+
+    fn set_value(&mut self, column: &Column, value: DataValue) {
+        match self {
+            DbRow::Json(row) => row.set_value(column, value),
+            DbRow::Binary(row) => row.set_value(column, value),
+        }
+    }
+
+    fn get_table_name(&self) -> String {
+        match self {
+            DbRow::Json(row) => row.get_table_name(),
+            DbRow::Binary(row) => row.get_table_name(),
+        }
+    }
+
+    fn validate(&self) -> bool {
+        match self {
+            DbRow::Json(row) => row.validate(),
+            DbRow::Binary(row) => row.validate(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::db_row::DbRow;
+    use crate::row::Row;
+    use schemajs_config::RowFormat;
+    use schemajs_primitives::column::types::{DataTypes, DataValue};
+    use schemajs_primitives::column::Column;
+    use schemajs_primitives::table::Table;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    pub fn test_rows_are_read_in_their_format() {
+        let mut table = Table::new("users").add_column(Column::new("name", DataTypes::String));
+        table.assign_column_ordinals(vec![]);
+        let json_table = Arc::new(table.clone());
+
+        table.metadata.row_format = RowFormat::Binary;
+        let binary_table = Arc::new(table);
+
+        let json_row = DbRow::from_json(json!({ "name": "json" }), json_table).unwrap();
+        let binary_row =
+            DbRow::from_json(json!({ "name": "binary" }), binary_table.clone()).unwrap();
+        assert!(matches!(json_row, DbRow::Json(_)));
+        assert!(matches!(binary_row, DbRow::Binary(_)));
+
+        let name = binary_table.get_column("name").unwrap();
+        for (row, expected) in [(json_row, "json"), (binary_row, "binary")] {
+            let bytes = row.to_vec().unwrap();
+            assert_eq!(
+                DbRow::get_value_from_slice(&bytes, &binary_table, name),
+                Some(DataValue::String(expected.to_string()))
+            );
+
+            let row = DbRow::from_slice(&bytes, binary_table.clone());
+            assert_eq!(
+                row.get_value(name),
+                Some(DataValue::String(expected.to_string()))
+            );
+        }
+    }
+}
@@ -27,6 +27,27 @@ pub enum QueryError {
     #[error("Invalid Insertion")]
     InvalidInsertion,
 
+    #[error("Insertion rejected: {0}")]
+    InsertionRejected(String),
+
+    #[error("beforeInsert hooks cannot insert into table '{0}', which has beforeInsert hooks")]
+    NestedBeforeInsertHook(String),
+
+    #[error("Column ordinals of table '{0}' could not be read or saved: {1}")]
+    InvalidColumnOrdinals(String, String),
+
+    #[error("Unknown custom query '{0}'")]
+    UnknownCustomQuery(String),
+
+    #[error("Invalid arguments for custom query '{0}': {1}")]
+    InvalidCustomQueryArgs(String, String),
+
+    #[error("Custom query '{0}' returned an invalid result: {1}")]
+    InvalidCustomQueryResult(String, String),
+
+    #[error("Helper exceeded its budget of {0} engine ops")]
+    OpBudgetExceeded(u64),
+
     #[error("A Shard Error has occured")]
     ShardError(#[from] ShardErrors),
 
@@ -1,12 +1,15 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub mod db_row;
 pub mod errors;
 pub mod managers;
 pub mod ops;
 pub mod row;
+pub mod row_binary;
 pub mod row_json;
 mod search;
+pub mod transfer;
 
 #[derive(Debug, Error, Serialize, Deserialize, Clone)]
 pub enum RowSerializationError {
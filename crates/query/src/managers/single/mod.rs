@@ -7,10 +7,11 @@ use crate::search::search_manager::QuerySearchManager;
 use chashmap::CHashMap;
 use schemajs_config::DatabaseConfig;
 use schemajs_data::fdm::FileDescriptorManager;
-use schemajs_data::shard::shards::data_shard::config::TempDataShardConfig;
+use schemajs_data::shard::shards::data_shard::config::{DataShardConfig, TempDataShardConfig};
 use schemajs_data::shard::temp_map_shard::DataWithIndex;
 use schemajs_data::temp_offset_types::TempOffsetTypes;
-use schemajs_helpers::helper::HelperCall;
+use schemajs_dirs::create_schema_js_table;
+use schemajs_helpers::helper::{HelperCall, HelperDbContext};
 use schemajs_primitives::column::types::DataValue;
 use schemajs_primitives::table::Table;
 use serde::Serialize;
@@ -18,7 +19,7 @@ use std::collections::HashMap;
 use std::hash::Hash;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
-use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::{unbounded_channel, Sender};
 use uuid::Uuid;
 
 #[derive(Debug)]
@@ -115,32 +116,68 @@ impl<T: Row> SingleQueryManager<T> {
     /// ```
     ///
     /// Note `register_table` will panic due to `No such file or directory` due to the database must have a folder already created in system.
-    pub fn register_table(&self, table: Table) {
-        self.table_names.write().unwrap().push(table.name.clone());
-        self.tables.insert(
-            table.name.clone(),
-            TableShard::<T>::new(
-                table,
-                self.data_path.clone(),
-                self.scheme.as_str(),
-                TempDataShardConfig {
-                    max_offsets: TempOffsetTypes::Custom(Some(
-                        self.database_config.max_rows_per_temp_shard,
-                    )),
-                },
-                self.helper_tx.clone(),
-                &self.database_config,
-                self.fdm.clone(),
-            ),
-        );
+    pub fn register_table(&self, table: Table) -> Result<(), QueryError> {
+        let name = table.name.clone();
+        let table_shard = TableShard::<T>::new(
+            table,
+            self.data_path.clone(),
+            self.scheme.as_str(),
+            TempDataShardConfig {
+                max_offsets: TempOffsetTypes::Custom(Some(
+                    self.database_config.max_rows_per_temp_shard,
+                )),
+            },
+            self.helper_tx.clone(),
+            &self.database_config,
+            self.fdm.clone(),
+        )?;
+
+        self.table_names.write().unwrap().push(name.clone());
+        self.tables.insert(name, table_shard);
+        Ok(())
+    }
+
+    /// Applies a new definition of a table that is already registered, registering it otherwise.
+    /// Indexes that did not exist before are created, existing data and indexes are kept as they are.
+    /// The caller is responsible for making sure the new definition is compatible with the current one.
+    pub fn update_table(&self, mut table: Table) -> Result<(), QueryError> {
+        let Some(mut table_shard) = self.tables.get_mut(&table.name) else {
+            return self.register_table(table);
+        };
+
+        let table_path =
+            create_schema_js_table(self.data_path.clone(), self.scheme.as_str(), &table.name);
+        TableShard::<T>::init_row_format(&table_path, &mut table, &self.database_config)?;
+
+        for index in &table.indexes {
+            if table_shard.indexes.get(&index.name).is_none() {
+                let index_obj = TableShard::<T>::create_index(
+                    &table_path,
+                    index,
+                    &self.database_config,
+                    self.fdm.clone(),
+                );
+                table_shard.indexes.insert(index.name.clone(), index_obj);
+            }
+        }
+
+        table_shard.data.write().set_config(DataShardConfig {
+            max_offsets: Some(self.database_config.max_rows_per_shard),
+            compression: table.compression,
+        });
+
+        table_shard.table = Arc::new(table);
+        // The reconcile callbacks hold the table they were created with.
+        table_shard.init();
+        Ok(())
     }
 
-    pub fn insert_from_value_map(
+    pub async fn insert_from_value_map(
         &self,
         data: Vec<(String, HashMap<String, DataValue>)>,
         master_insert: bool,
     ) -> Result<Option<Uuid>, QueryError> {
-        let mut rows: Vec<T> = data
+        let rows: Vec<T> = data
             .into_iter()
             .map(|e| {
                 let table = self
@@ -152,7 +189,7 @@ impl<T: Row> SingleQueryManager<T> {
                 T::from_map(table, e.1).map_err(|_| QueryError::InvalidInsertion)
             })
             .collect::<Result<Vec<_>, _>>()?;
-        self.raw_insert(&mut rows, master_insert)
+        self.insert_with_hooks(rows, master_insert).await
     }
 
     /// Inserts a row in the first available temporary shard.
@@ -191,6 +228,74 @@ impl<T: Row> SingleQueryManager<T> {
         self.raw_insert(&mut [row], false)
     }
 
+    /// Runs the `beforeInsert` hooks of the rows' tables and inserts whatever rows they return.
+    /// The insertion fails with `QueryError::InsertionRejected` if any hook rejects its rows.
+    pub async fn insert_with_hooks(
+        &self,
+        rows: Vec<T>,
+        master_insert: bool,
+    ) -> Result<Option<Uuid>, QueryError> {
+        let mut rows = self.before_insert(rows).await?;
+        self.raw_insert(&mut rows, master_insert)
+    }
+
+    /// Sends the rows of every table that declares `beforeInsert` hooks to the helpers runtime
+    /// and waits for the rows they return. Rows of tables without such hooks are returned untouched.
+    pub async fn before_insert(&self, rows: Vec<T>) -> Result<Vec<T>, QueryError> {
+        let mut table_rows: Vec<(Arc<Table>, Vec<T>)> = vec![];
+        for row in rows {
+            let table = row.get_table();
+            match table_rows
+                .iter_mut()
+                .find(|(tbl, _)| tbl.name == table.name)
+            {
+                Some((_, rows)) => rows.push(row),
+                None => table_rows.push((table, vec![row])),
+            }
+        }
+
+        let mut hooked_rows = vec![];
+        for (table, rows) in table_rows {
+            if !table.metadata.before_insert {
+                hooked_rows.extend(rows);
+                continue;
+            }
+
+            let vals = rows
+                .iter()
+                .map(|row| row.to_json())
+                .collect::<Result<Vec<_>, _>>()?;
+            let (response_tx, mut response_rx) = unbounded_channel();
+
+            self.helper_tx
+                .send(HelperCall::BeforeInsertHook {
+                    db_ctx: HelperDbContext {
+                        db: Some(self.scheme.clone()),
+                        table: Some(table.name.clone()),
+                    },
+                    rows: vals,
+                    response: response_tx,
+                })
+                .await
+                .map_err(|_| QueryError::InvalidInsertion)?;
+
+            let vals = response_rx
+                .recv()
+                .await
+                .ok_or(QueryError::InvalidInsertion)?
+                .map_err(QueryError::InsertionRejected)?;
+
+            for val in vals {
+                hooked_rows.push(
+                    T::from_json(val, table.clone())
+                        .map_err(|_| QueryError::InvalidSerialization)?,
+                );
+            }
+        }
+
+        Ok(hooked_rows)
+    }
+
     pub fn raw_insert(
         &self,
         rows: &mut [T],
@@ -234,18 +339,36 @@ impl<T: Row> SingleQueryManager<T> {
                     table_shard.temps.insert(&vec_of_slices)?;
                 } else {
                     let mut data_lock = table_shard.data.write();
+                    let mut inserted_rows = vec![];
+
+                    // Rows of a batch get consecutive row ids, so they are stored at once and
+                    // every index is built for the whole batch.
+                    let first_row_id = data_lock.insert_rows(&vec_of_slices) as u64;
+                    let indexed_rows: Vec<(T, u64)> = vec_of_slices
+                        .iter()
+                        .enumerate()
+                        .map(|(pos, row)| {
+                            let row = T::from_slice(row, table_shard.table.clone());
+
+                            if !table_shard.table.metadata.internal {
+                                if let Ok(val) = row.to_json() {
+                                    inserted_rows.push(val);
+                                }
+                            }
+
+                            (row, first_row_id + pos as u64)
+                        })
+                        .collect();
+
+                    TableShard::<T>::insert_indexes(
+                        table_shard.table.clone(),
+                        table_shard.indexes.clone(),
+                        indexed_rows,
+                    );
 
-                    for row in vec_of_slices {
-                        let pointer = data_lock.insert_rows(&[row]);
-
-                        TableShard::<T>::insert_indexes(
-                            table_shard.table.clone(),
-                            table_shard.indexes.clone(),
-                            vec![(
-                                T::from_slice(row, table_shard.table.clone()),
-                                pointer as u64,
-                            )],
-                        );
+                    // Rows inserted into the master shard skip reconciliation, so their hooks fire here.
+                    if !inserted_rows.is_empty() {
+                        table_shard.notify_hooks(inserted_rows);
                     }
                 }
             } else {
@@ -256,7 +379,216 @@ impl<T: Row> SingleQueryManager<T> {
         Ok(id)
     }
 
+    /// Runs `f` while nothing is written to the files of any table of the database,
+    /// see `TableShard::quiesce`.
+    pub fn quiesce<R>(&self, f: impl FnOnce() -> R) -> Result<R, QueryError> {
+        let table_names = self.table_names.read().unwrap().clone();
+        self.quiesce_tables(&table_names, f)
+    }
+
+    fn quiesce_tables<R>(
+        &self,
+        table_names: &[String],
+        f: impl FnOnce() -> R,
+    ) -> Result<R, QueryError> {
+        let Some((table_name, rest)) = table_names.split_first() else {
+            return Ok(f());
+        };
+
+        match self.tables.get(table_name) {
+            Some(table) => table.quiesce(|| self.quiesce_tables(rest, f))?,
+            None => self.quiesce_tables(rest, f),
+        }
+    }
+
     pub fn get_table(&self, table_name: &str) -> Option<Arc<Table>> {
         self.tables.get(table_name).map(|e| e.table.clone())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::errors::QueryError;
+    use crate::managers::single::SingleQueryManager;
+    use crate::ops::query_ops::{QueryOps, QueryVal};
+    use crate::row::Row;
+    use crate::row_json::RowJson;
+    use schemajs_config::DatabaseConfig;
+    use schemajs_data::fdm::FileDescriptorManager;
+    use schemajs_dirs::{create_schema_js_table, create_scheme_js_db};
+    use schemajs_helpers::create_helper_channel;
+    use schemajs_helpers::helper::HelperCall;
+    use schemajs_index::index_type::IndexType;
+    use schemajs_primitives::column::types::{DataTypes, DataValue};
+    use schemajs_primitives::column::Column;
+    use schemajs_primitives::index::Index;
+    use schemajs_primitives::table::Table;
+    use serde_json::json;
+    use std::sync::Arc;
+    use tokio::sync::mpsc::Sender;
+    use uuid::Uuid;
+
+    fn create_query_manager(
+        helper_tx: Sender<HelperCall>,
+        before_insert: bool,
+    ) -> SingleQueryManager<RowJson> {
+        let test_db = Uuid::new_v4().to_string();
+        create_scheme_js_db(None, test_db.as_str());
+        let query_manager = SingleQueryManager::new(
+            test_db,
+            helper_tx,
+            Arc::new(DatabaseConfig::default()),
+            Arc::new(FileDescriptorManager::new(2500)),
+        );
+
+        let mut table = Table::new("users")
+            .add_column(Column::new("user_name", DataTypes::String))
+            .add_column(Column::new("user_age", DataTypes::Number))
+            .add_index(Index {
+                name: "user_name_indx".to_string(),
+                members: vec![String::from("user_name")],
+                index_type: IndexType::Hash,
+            });
+        table.metadata.before_insert = before_insert;
+        query_manager.register_table(table).unwrap();
+
+        query_manager
+    }
+
+    fn user(query_manager: &SingleQueryManager<RowJson>, name: &str) -> RowJson {
+        let table = query_manager.get_table("users").unwrap();
+        RowJson::from_json(json!({ "user_name": name }), table).unwrap()
+    }
+
+    fn find_user(query_manager: &SingleQueryManager<RowJson>, name: &str) -> Vec<RowJson> {
+        query_manager
+            .search_manager
+            .search(
+                "users",
+                &QueryOps::Condition(QueryVal {
+                    key: "user_name".to_string(),
+                    filter_type: "=".to_string(),
+                    value: DataValue::String(name.to_string()),
+                }),
+            )
+            .unwrap()
+    }
+
+    #[tokio::test]
+    pub async fn test_hooks_fire_once_rows_are_indexed() {
+        let (helper_tx, mut helper_rx) = create_helper_channel(10);
+        let query_manager = create_query_manager(helper_tx, false);
+
+        // Master inserts skip reconciliation, so only their `insert` hooks fire.
+        query_manager
+            .raw_insert(&mut [user(&query_manager, "Luis")], true)
+            .unwrap();
+        let (_, rows) = helper_rx.recv().await.unwrap().into_insert_hook().unwrap();
+        assert_eq!(rows[0]["user_name"], "Luis");
+
+        query_manager
+            .raw_insert(&mut [user(&query_manager, "Ana")], false)
+            .unwrap();
+        query_manager
+            .tables
+            .get("users")
+            .unwrap()
+            .temps
+            .reconcile_all()
+            .unwrap();
+
+        let (_, rows) = helper_rx.recv().await.unwrap().into_insert_hook().unwrap();
+        assert_eq!(rows[0]["user_name"], "Ana");
+        let (_, rows) = helper_rx
+            .recv()
+            .await
+            .unwrap()
+            .into_after_reconcile_hook()
+            .unwrap();
+        assert_eq!(rows[0]["user_name"], "Ana");
+        assert_eq!(find_user(&query_manager, "Ana").len(), 1);
+
+        tokio::task::yield_now().await;
+        assert!(helper_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    pub async fn test_unreadable_column_ordinals_are_an_error() {
+        let test_db = Uuid::new_v4().to_string();
+        create_scheme_js_db(None, test_db.as_str());
+        let table_path = create_schema_js_table(None, &test_db, "users");
+        std::fs::write(table_path.join("columns.json"), "not json").unwrap();
+
+        let query_manager = SingleQueryManager::<RowJson>::new(
+            test_db,
+            create_helper_channel(1).0,
+            Arc::new(DatabaseConfig::default()),
+            Arc::new(FileDescriptorManager::new(2500)),
+        );
+
+        let err = query_manager
+            .register_table(Table::new("users"))
+            .unwrap_err();
+        assert!(matches!(err, QueryError::InvalidColumnOrdinals(table, _) if table == "users"));
+        assert!(query_manager.get_table("users").is_none());
+    }
+
+    #[test]
+    pub fn test_hooks_fire_outside_of_runtime() {
+        let (helper_tx, mut helper_rx) = create_helper_channel(10);
+        let query_manager = create_query_manager(helper_tx, false);
+
+        query_manager
+            .raw_insert(&mut [user(&query_manager, "Luis")], true)
+            .unwrap();
+
+        assert!(helper_rx.blocking_recv().unwrap().is_insert_hook());
+    }
+
+    #[tokio::test]
+    pub async fn test_before_insert_hooks() {
+        let (helper_tx, mut helper_rx) = create_helper_channel(10);
+        let query_manager = create_query_manager(helper_tx, true);
+
+        // Stands in for the helpers runtime, rejecting rows named "Spam" and aging the others.
+        tokio::spawn(async move {
+            while let Some(call) = helper_rx.recv().await {
+                let HelperCall::BeforeInsertHook { rows, response, .. } = call else {
+                    continue;
+                };
+
+                if rows.iter().any(|row| row["user_name"] == "Spam") {
+                    let _ = response.send(Err("Spam is not welcome".to_string()));
+                } else {
+                    let rows = rows
+                        .into_iter()
+                        .map(|mut row| {
+                            row["user_age"] = json!(30);
+                            row
+                        })
+                        .collect();
+                    let _ = response.send(Ok(rows));
+                }
+            }
+        });
+
+        query_manager
+            .insert_with_hooks(vec![user(&query_manager, "Luis")], true)
+            .await
+            .unwrap();
+        let users = find_user(&query_manager, "Luis");
+        let age = users[0]
+            .get_value(users[0].get_table().get_column("user_age").unwrap())
+            .unwrap();
+        assert_eq!(age.as_number().unwrap().as_f64(), Some(30.0));
+
+        let rejected = query_manager
+            .insert_with_hooks(vec![user(&query_manager, "Spam")], true)
+            .await;
+        assert!(matches!(
+            rejected,
+            Err(QueryError::InsertionRejected(reason)) if reason == "Spam is not welcome"
+        ));
+        assert!(find_user(&query_manager, "Spam").is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::errors::QueryError;
 use crate::row::Row;
 use chashmap::CHashMap;
 use parking_lot::RwLock;
@@ -15,14 +16,42 @@ use schemajs_index::index_keys::IndexKeyType;
 use schemajs_index::index_type::{IndexType, IndexTypeValue};
 use schemajs_index::types::{Index, IndexKey};
 use schemajs_primitives::column::types::DataValue;
+use schemajs_primitives::column::Column;
+use schemajs_primitives::index::Index as TableIndex;
 use schemajs_primitives::table::Table;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{mpsc, Arc, LazyLock};
+use tokio::runtime::Handle;
 use tokio::sync::mpsc::Sender;
 
+/// File of the table folder with the names of its columns by ordinal.
+const COLUMN_ORDINALS_FILE: &str = "columns.json";
+
+/// Hook calls dispatched outside of the Tokio runtime, with the helpers channel they go to.
+/// They are forwarded by a single thread, in the order they were dispatched.
+static HOOK_DISPATCHER: LazyLock<mpsc::Sender<(Sender<HelperCall>, Vec<HelperCall>)>> =
+    LazyLock::new(|| {
+        let (tx, rx) = mpsc::channel::<(Sender<HelperCall>, Vec<HelperCall>)>();
+        std::thread::Builder::new()
+            .name("sjs-hook-dispatcher".to_string())
+            .spawn(move || {
+                for (helper_tx, calls) in rx {
+                    for call in calls {
+                        if helper_tx.blocking_send(call).is_err() {
+                            println!("[Hooks] Helpers are not running, hooks were not fired");
+                            break;
+                        }
+                    }
+                }
+            })
+            .expect("Failed to spawn the hook dispatcher thread");
+
+        tx
+    });
+
 /// `TableShard` is a structure that manages the sharding of a specific table's data.
 /// It is responsible for storing the table's data in a main shard, handling temporary shards
 /// for efficient insertion, and managing the indexes associated with the table.
@@ -65,24 +94,26 @@ impl<T: Row> TableShard<T> {
     /// # Returns:
     /// - A `TableShard` instance that handles data storage, sharding, and indexing for the provided table.
     pub fn new(
-        table: Table,
+        mut table: Table,
         base_path: Option<PathBuf>,
         scheme: &str,
         temp_config: TempDataShardConfig,
         helper_tx: Sender<HelperCall>,
         db_config: &Arc<DatabaseConfig>,
         fdm: Arc<FileDescriptorManager>,
-    ) -> Self {
+    ) -> Result<Self, QueryError> {
         let table_path = create_schema_js_table(base_path, scheme, table.name.as_str());
+        Self::init_row_format(&table_path, &mut table, db_config)?;
 
-        let map_shard = MapShard::new(
+        let map_shard = MapShard::open(
             table_path.clone(),
             "data_",
             DataShardConfig {
                 max_offsets: Some(db_config.max_rows_per_shard),
+                compression: table.compression,
             },
             fdm.clone(),
-        );
+        )?;
 
         let refs = Arc::new(RwLock::new(map_shard));
 
@@ -104,21 +135,7 @@ impl<T: Row> TableShard<T> {
         let mut indexes = CHashMap::new();
 
         for index in &table.indexes {
-            let path = table_path.join("indx");
-
-            if !path.exists() {
-                std::fs::create_dir(path.clone()).unwrap();
-            }
-
-            let index_obj = match index.index_type {
-                IndexType::Hash => IndexTypeValue::Hash(HashIndex::new_from_path(
-                    path,
-                    Some(format!("{}", index.name)),
-                    Some(db_config.max_records_per_hash_index_shard),
-                    fdm.clone(),
-                )),
-            };
-
+            let index_obj = Self::create_index(&table_path, index, db_config, fdm.clone());
             indexes.insert(index.name.clone(), index_obj);
         }
 
@@ -134,7 +151,65 @@ impl<T: Row> TableShard<T> {
 
         tbl_shard.init();
 
-        tbl_shard
+        Ok(tbl_shard)
+    }
+
+    /// Records the row format of the database in the table, along with the ordinals of its columns.
+    /// Ordinals are persisted in the table folder, so they never change once given.
+    ///
+    /// An unreadable ordinals file is an error rather than a table without ordinals, since
+    /// giving the columns new ordinals would misread every binary row already stored.
+    pub fn init_row_format(
+        table_path: &PathBuf,
+        table: &mut Table,
+        db_config: &DatabaseConfig,
+    ) -> Result<(), QueryError> {
+        table.metadata.row_format = db_config.row_format;
+
+        let path = table_path.join(COLUMN_ORDINALS_FILE);
+        let table_name = table.name.clone();
+        let ordinals_error =
+            |reason: String| QueryError::InvalidColumnOrdinals(table_name.clone(), reason);
+
+        let existing: Vec<String> = match std::fs::read(&path) {
+            Ok(bytes) => {
+                serde_json::from_slice(&bytes).map_err(|e| ordinals_error(e.to_string()))?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(ordinals_error(e.to_string())),
+        };
+
+        if table.assign_column_ordinals(existing) {
+            let ordinals = serde_json::to_vec(&table.metadata.column_ordinals)
+                .map_err(|e| ordinals_error(e.to_string()))?;
+            std::fs::write(&path, ordinals).map_err(|e| ordinals_error(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates (or opens, if its files exist) the index described by `index` in the table folder.
+    pub fn create_index(
+        table_path: &PathBuf,
+        index: &TableIndex,
+        db_config: &DatabaseConfig,
+        fdm: Arc<FileDescriptorManager>,
+    ) -> IndexTypeValue {
+        let path = table_path.join("indx");
+
+        if !path.exists() {
+            std::fs::create_dir(path.clone()).unwrap();
+        }
+
+        match index.index_type {
+            IndexType::Hash => IndexTypeValue::Hash(HashIndex::new_from_path(
+                path,
+                Some(format!("{}", index.name)),
+                Some(db_config.memtable_capacity),
+                Some(db_config.max_records_per_hash_index_shard),
+                fdm,
+            )),
+        }
     }
 
     /// Initializes everything related to the current table context.
@@ -156,33 +231,102 @@ impl<T: Row> TableShard<T> {
                     .map(|row| (T::from_slice(&row.data, table.clone()), row.index))
                     .collect();
 
-                {
-                    // TODO: move row->to_json inside the thread
-                    let vals: Vec<Value> = rows
-                        .iter()
-                        .filter_map(|(row, _)| row.to_json().ok())
-                        .collect();
-                    let helper_tx = helper_tx.clone();
-                    let tbl_name = table.name.clone();
-                    let scheme = scheme_name.clone();
-                    tokio::spawn(async move {
-                        let _ = helper_tx
-                            .send(HelperCall::InsertHook {
-                                rows: vals,
-                                db_ctx: HelperDbContext {
-                                    db: Some(scheme),
-                                    table: Some(tbl_name),
-                                },
-                            })
-                            .await;
-                    });
-                }
+                // TODO: move row->to_json inside the thread
+                let vals: Vec<Value> = rows
+                    .iter()
+                    .filter_map(|(row, _)| row.to_json().ok())
+                    .collect();
+
                 Self::insert_indexes(table.clone(), indexes.clone(), rows);
+                // The rows are indexed, so hooks querying them find them.
+                Self::dispatch_hooks(&helper_tx, &scheme_name, &table.name, vals, true);
                 Ok(())
             }))
         }
     }
 
+    /// Fires the `insert` hooks of the table for rows inserted straight into the master shard,
+    /// once they are indexed. They skip reconciliation, so `afterReconcile` hooks do not fire.
+    pub fn notify_hooks(&self, rows: Vec<Value>) {
+        Self::dispatch_hooks(&self.helper_tx, &self.scheme, &self.table.name, rows, false);
+    }
+
+    /// Sends the hook calls for `rows` to the helpers runtime without waiting for them to run.
+    /// `reconciled` rows also fire the `afterReconcile` hooks, after the `insert` ones.
+    fn dispatch_hooks(
+        helper_tx: &Sender<HelperCall>,
+        scheme: &str,
+        table: &str,
+        rows: Vec<Value>,
+        reconciled: bool,
+    ) {
+        let db_ctx = HelperDbContext {
+            db: Some(scheme.to_string()),
+            table: Some(table.to_string()),
+        };
+
+        let mut calls = vec![HelperCall::InsertHook {
+            rows: rows.clone(),
+            db_ctx: db_ctx.clone(),
+        }];
+        if reconciled {
+            calls.push(HelperCall::AfterReconcileHook { rows, db_ctx });
+        }
+
+        let helper_tx = helper_tx.clone();
+        match Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    for call in calls {
+                        if helper_tx.send(call).await.is_err() {
+                            println!("[Hooks] Helpers are not running, hooks were not fired");
+                            break;
+                        }
+                    }
+                });
+            }
+            // Reconciliation also runs outside of the runtime, such as on the reconciler thread.
+            Err(_) => {
+                if HOOK_DISPATCHER.send((helper_tx, calls)).is_err() {
+                    println!("[Hooks] Hook dispatcher is not running, hooks were not fired");
+                }
+            }
+        }
+    }
+
+    /// Computes the key in `index` of the row whose values are given by `get_value`,
+    /// `None` if every column of the index is null.
+    fn index_key(
+        table: &Table,
+        indexes: &CHashMap<String, IndexTypeValue>,
+        index: &TableIndex,
+        get_value: impl Fn(&Column) -> Option<DataValue>,
+    ) -> Option<IndexKeyType> {
+        let mut can_index = false;
+        let mut composite_key_vals: Vec<(String, String)> = vec![];
+
+        for index_col in &index.members {
+            let val = get_value(table.get_column(index_col)?).unwrap_or(DataValue::Null);
+
+            if !val.is_null() {
+                can_index = true;
+            }
+
+            composite_key_vals.push((index_col.clone(), val.to_string()))
+        }
+
+        if !can_index {
+            return None;
+        }
+
+        let real_indx = indexes.get(&index.name)?;
+        Some(
+            real_indx
+                .as_index()
+                .to_key(CompositeKey(composite_key_vals)),
+        )
+    }
+
     /// This method handles automatically indexing the rows that match the index in the Table.
     /// It is called during the reconciling process through `set_on_reconcile` in the TempMapShard.
     pub fn insert_indexes(
@@ -194,38 +338,13 @@ impl<T: Row> TableShard<T> {
 
         for (row_t, pos) in data.iter() {
             for index in &table.indexes {
-                let mut can_index = false;
-                let mut composite_key_vals: Vec<(String, String)> = vec![];
-
-                for index_col in &index.members {
-                    let val = row_t
-                        .get_value(table.get_column(index_col).unwrap())
-                        .unwrap_or(DataValue::Null);
-
-                    if !val.is_null() {
-                        can_index = true;
-                    }
-
-                    composite_key_vals.push((index_col.clone(), val.to_string()))
-                }
-
-                if can_index {
-                    let real_indx = indexes.get(&index.name).unwrap();
-                    let composite_key = CompositeKey(composite_key_vals);
-                    let indx = real_indx.as_index();
-                    let key = indx.to_key(composite_key);
-                    let insertion_value = (key, pos.clone());
-
-                    let index_exists = index_ordered_items.contains_key(&index.name);
-
-                    if index_exists {
-                        index_ordered_items
-                            .get_mut(&index.name)
-                            .unwrap()
-                            .push(insertion_value);
-                    } else {
-                        index_ordered_items.insert(index.name.clone(), vec![insertion_value]);
-                    }
+                if let Some(key) =
+                    Self::index_key(&table, &indexes, index, |column| row_t.get_value(column))
+                {
+                    index_ordered_items
+                        .entry(index.name.clone())
+                        .or_default()
+                        .push((key, *pos));
                 }
             }
         }
@@ -237,4 +356,128 @@ impl<T: Row> TableShard<T> {
             indx.bulk_insert(rows);
         }
     }
+
+    /// Runs `f` while nothing is written to the files of the table. Temporary shards are
+    /// reconciled first, then inserts, reconciliation, compaction and index writes wait for `f`
+    /// to return. Reads keep going. Fails without calling `f` when a temporary shard can't be
+    /// reconciled.
+    pub fn quiesce<R>(&self, f: impl FnOnce() -> R) -> Result<R, QueryError> {
+        // Locked in the same order as reconciliation does: temporary shards, data and indexes.
+        let _temps = self.temps.quiesce()?;
+        let _data = self.data.upgradable_read();
+
+        let index_shards: Vec<_> = self
+            .table
+            .indexes
+            .iter()
+            .filter_map(|index| match self.indexes.get(&index.name).as_deref() {
+                Some(IndexTypeValue::Hash(hash_index)) => Some(hash_index.index.clone()),
+                None => None,
+            })
+            .collect();
+        let _frozen: Vec<_> = index_shards.iter().map(|shard| shard.freeze()).collect();
+
+        Ok(f())
+    }
+
+    /// Calls `f` with every row of the table queries can see, in row id order, stopping at the
+    /// first error. Temporary shards are reconciled first, so rows that were just inserted are
+    /// included. Rows inserted meanwhile may or may not be.
+    pub fn scan_rows<E: From<QueryError>>(
+        &self,
+        mut f: impl FnMut(T) -> Result<(), E>,
+    ) -> Result<(), E> {
+        self.temps.reconcile_all().map_err(QueryError::from)?;
+
+        let row_count = self.data.read().locator.len();
+        for row_id in 0..row_count {
+            // Removed rows have no location anymore.
+            let Ok(bytes) = self.data.read().get_element(row_id as usize) else {
+                continue;
+            };
+
+            if Self::is_reachable(&self.table, &self.indexes, &bytes, row_id) {
+                f(T::from_slice(&bytes, self.table.clone()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the row ids of the rows queries can see whose stored bytes satisfy `predicate`.
+    /// Rows still in temporary shards are not seen, just like through indexes.
+    pub fn filter_rows(&self, predicate: impl Fn(&[u8]) -> bool) -> Vec<u64> {
+        let row_count = self.data.read().locator.len();
+
+        (0..row_count)
+            .filter(|row_id| {
+                let Ok(bytes) = self.data.read().get_element(*row_id as usize) else {
+                    return false;
+                };
+
+                predicate(&bytes) && Self::is_reachable(&self.table, &self.indexes, &bytes, *row_id)
+            })
+            .collect()
+    }
+
+    /// Compacts the past master shards of the table. See `compact_data`.
+    pub fn compact(&self) -> usize {
+        Self::compact_data(self.table.clone(), &self.data, &self.indexes)
+    }
+
+    /// Rewrites the past master shards of a table without the rows no index points to anymore,
+    /// such as rows whose reconciliation was abandoned or that were superseded by a newer row
+    /// with the same key. Rows keep their row id, so indexes are left as they are.
+    ///
+    /// Shards are rewritten while readers keep using them, the table is only locked to swap
+    /// each rewritten shard in. Returns the number of rows that were removed.
+    pub fn compact_data(
+        table: Arc<Table>,
+        data: &RwLock<MapShard<DataShard, DataShardConfig>>,
+        indexes: &CHashMap<String, IndexTypeValue>,
+    ) -> usize {
+        let shard_ids: Vec<String> = {
+            let reader = data.read();
+            let past_master_shards = reader.past_master_shards.read();
+            past_master_shards.keys().cloned().collect()
+        };
+
+        let mut removed = 0;
+
+        for shard_id in shard_ids {
+            let compacted = {
+                let reader = data.read();
+                reader.rewrite_shard(&shard_id, |row_id, bytes| {
+                    Self::is_reachable(&table, indexes, bytes, row_id)
+                })
+            };
+
+            let Ok(Some(compacted)) = compacted else {
+                continue;
+            };
+
+            if data.write().swap_shard(&compacted).is_ok() {
+                removed += compacted.removed.len();
+            }
+        }
+
+        removed
+    }
+
+    /// Whether any index of the table points to the stored row `bytes` by its row id.
+    /// Only the indexed columns are read from the row.
+    fn is_reachable(
+        table: &Arc<Table>,
+        indexes: &CHashMap<String, IndexTypeValue>,
+        bytes: &[u8],
+        row_id: u64,
+    ) -> bool {
+        table.indexes.iter().any(|index| {
+            Self::index_key(table, indexes, index, |column| {
+                T::get_value_from_slice(bytes, table, column)
+            })
+            .and_then(|key| indexes.get(&index.name)?.as_index().get(&key))
+                == Some(row_id)
+        })
+    }
 }
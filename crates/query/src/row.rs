@@ -40,7 +40,9 @@ pub trait Row {
 
     fn from_slice(slice: &[u8], table: Arc<Table>) -> Self;
 
-    fn from_data(data: Self::RowData, table: Arc<Table>) -> Self;
+    fn from_data(data: Self::RowData, table: Arc<Table>) -> Result<Self, ()>
+    where
+        Self: Sized;
 
     fn from_json(data: serde_json::Value, table: Arc<Table>) -> Result<Self, ()>
     where
@@ -70,6 +72,15 @@ pub trait Row {
     /// - `Option<DataValue>`: The value of the column, if present. If the value is not found, it returns `None`.
     fn get_value(&self, column: &Column) -> Option<DataValue>;
 
+    /// Retrieves the value of a column straight from a stored row, as given to `from_slice`.
+    /// Formats that can locate a single column override it to skip decoding the whole row.
+    fn get_value_from_slice(slice: &[u8], table: &Arc<Table>, column: &Column) -> Option<DataValue>
+    where
+        Self: Sized,
+    {
+        Self::from_slice(slice, table.clone()).get_value(column)
+    }
+
     fn set_value(&mut self, column: &Column, value: DataValue);
 
     /// Returns the name of the table to which the row belongs.
@@ -0,0 +1,320 @@
+use super::row::Row;
+use crate::row_json::RowData;
+use crate::RowSerializationError;
+use schemajs_primitives::column::types::DataValue;
+use schemajs_primitives::column::Column;
+use schemajs_primitives::table::Table;
+use serde_json::Number;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// First byte of every binary row. JSON rows always start with `{`,
+/// so both formats can be told apart when reading a shard.
+pub const BINARY_ROW_MARKER: u8 = 0xB1;
+
+const HEADER_SIZE: usize = 1 + 2;
+const OFFSET_SIZE: usize = 4;
+
+const TAG_NULL: u8 = 0;
+const TAG_UUID: u8 = 1;
+const TAG_STRING: u8 = 2;
+const TAG_BOOLEAN: u8 = 3;
+const TAG_U64: u8 = 4;
+const TAG_I64: u8 = 5;
+const TAG_F64: u8 = 6;
+
+/// `RowBinary` stores a row in a compact binary encoding keyed by the column ordinals
+/// of its table (`TableMetadata::column_ordinals`), instead of by column name:
+///
+/// - The marker byte and the number of column slots as a `u16`.
+/// - The end of every slot as a `u32`, relative to the start of the values.
+/// - The values, one slot per ordinal. An empty slot is a column without value,
+///   any other starts with a tag byte followed by the encoded value.
+///
+/// Reading a column only decodes its own slot, straight from the stored bytes.
+#[derive(Clone, Debug)]
+pub struct RowBinary {
+    pub table: Arc<Table>,
+    pub data: Vec<u8>,
+}
+
+impl RowBinary {
+    /// Returns the encoded value of the column at `ordinal`, without copying it.
+    pub fn column_bytes(data: &[u8], ordinal: usize) -> Option<&[u8]> {
+        if *data.first()? != BINARY_ROW_MARKER {
+            return None;
+        }
+
+        let slots = u16::from_le_bytes(data.get(1..HEADER_SIZE)?.try_into().ok()?) as usize;
+        if ordinal >= slots {
+            return None;
+        }
+
+        let read_offset = |slot: usize| -> Option<usize> {
+            let start = HEADER_SIZE + slot * OFFSET_SIZE;
+            let bytes = data.get(start..start + OFFSET_SIZE)?;
+            Some(u32::from_le_bytes(bytes.try_into().ok()?) as usize)
+        };
+
+        let values_start = HEADER_SIZE + slots * OFFSET_SIZE;
+        let start = if ordinal == 0 {
+            0
+        } else {
+            read_offset(ordinal - 1)?
+        };
+        let end = read_offset(ordinal)?;
+
+        if end > start {
+            data.get(values_start + start..values_start + end)
+        } else {
+            None
+        }
+    }
+
+    fn encode(table: &Table, values: &HashMap<String, DataValue>) -> Result<Vec<u8>, ()> {
+        let ordinals = &table.metadata.column_ordinals;
+        let mut slots: Vec<Option<&DataValue>> = vec![None; ordinals.len()];
+        for (name, value) in values {
+            let ordinal = table.column_ordinal(name).ok_or(())?;
+            slots[ordinal] = Some(value);
+        }
+
+        let mut offsets = Vec::with_capacity(slots.len() * OFFSET_SIZE);
+        let mut encoded = Vec::new();
+        for slot in slots {
+            if let Some(value) = slot {
+                Self::encode_value(value, &mut encoded);
+            }
+            offsets.extend((encoded.len() as u32).to_le_bytes());
+        }
+
+        let mut data = Vec::with_capacity(HEADER_SIZE + offsets.len() + encoded.len());
+        data.push(BINARY_ROW_MARKER);
+        data.extend((ordinals.len() as u16).to_le_bytes());
+        data.extend(offsets);
+        data.extend(encoded);
+
+        Ok(data)
+    }
+
+    fn encode_value(value: &DataValue, out: &mut Vec<u8>) {
+        match value {
+            DataValue::Null => out.push(TAG_NULL),
+            DataValue::Uuid(uuid) => {
+                out.push(TAG_UUID);
+                out.extend(uuid.as_bytes());
+            }
+            DataValue::String(string) => {
+                out.push(TAG_STRING);
+                out.extend(string.as_bytes());
+            }
+            DataValue::Boolean(boolean) => {
+                out.push(TAG_BOOLEAN);
+                out.push(*boolean as u8);
+            }
+            DataValue::Number(number) => {
+                if let Some(n) = number.as_u64() {
+                    out.push(TAG_U64);
+                    out.extend(n.to_le_bytes());
+                } else if let Some(n) = number.as_i64() {
+                    out.push(TAG_I64);
+                    out.extend(n.to_le_bytes());
+                } else {
+                    out.push(TAG_F64);
+                    out.extend(number.as_f64().unwrap_or_default().to_le_bytes());
+                }
+            }
+        }
+    }
+
+    pub fn decode_value(bytes: &[u8]) -> Option<DataValue> {
+        let (tag, value) = bytes.split_first()?;
+        let value = match *tag {
+            TAG_NULL => DataValue::Null,
+            TAG_UUID => DataValue::Uuid(Uuid::from_slice(value).ok()?),
+            TAG_STRING => DataValue::String(String::from_utf8(value.to_vec()).ok()?),
+            TAG_BOOLEAN => DataValue::Boolean(*value.first()? != 0),
+            TAG_U64 => DataValue::Number(u64::from_le_bytes(value.try_into().ok()?).into()),
+            TAG_I64 => DataValue::Number(i64::from_le_bytes(value.try_into().ok()?).into()),
+            TAG_F64 => DataValue::Number(Number::from_f64(f64::from_le_bytes(
+                value.try_into().ok()?,
+            ))?),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn values(&self) -> HashMap<String, DataValue> {
+        self.table
+            .metadata
+            .column_ordinals
+            .iter()
+            .enumerate()
+            .filter_map(|(ordinal, name)| {
+                let value = Self::decode_value(Self::column_bytes(&self.data, ordinal)?)?;
+                Some((name.clone(), value))
+            })
+            .collect()
+    }
+}
+
+impl Row for RowBinary {
+    type RowData = RowData;
+
+    fn to_data(&self) -> Self::RowData {
+        RowData {
+            value: self.values(),
+        }
+    }
+
+    fn to_vec(&self) -> Result<Vec<u8>, RowSerializationError> {
+        Ok(self.data.clone())
+    }
+
+    fn to_map(&self) -> Result<HashMap<String, DataValue>, RowSerializationError> {
+        Ok(self.values())
+    }
+
+    fn from_slice(slice: &[u8], table: Arc<Table>) -> Self {
+        RowBinary {
+            table,
+            data: slice.to_vec(),
+        }
+    }
+
+    fn from_data(data: Self::RowData, table: Arc<Table>) -> Result<Self, ()> {
+        Self::from_map(table, data.value)
+    }
+
+    fn from_map(table: Arc<Table>, data: HashMap<String, DataValue>) -> Result<Self, ()> {
+        let data = Self::encode(&table, &data)?;
+        Ok(RowBinary { table, data })
+    }
+
+    fn get_table(&self) -> Arc<Table> {
+        self.table.clone()
+    }
+
+    fn get_value(&self, column: &Column) -> Option<DataValue> {
+        Self::get_value_from_slice(&self.data, &self.table, column)
+    }
+
+    fn get_value_from_slice(
+        slice: &[u8],
+        table: &Arc<Table>,
+        column: &Column,
+    ) -> Option<DataValue> {
+        let ordinal = table.column_ordinal(&column.name)?;
+        Self::decode_value(Self::column_bytes(slice, ordinal)?)
+    }
+
+    fn set_value(&mut self, column: &Column, value: DataValue) {
+        let mut values = self.values();
+        values.insert(column.name.clone(), value);
+        if let Ok(data) = Self::encode(&self.table, &values) {
+            self.data = data;
+        }
+    }
+
+    fn get_table_name(&self) -> String {
+        self.table.name.clone()
+    }
+
+    fn validate(&self) -> bool {
+        self.table.columns.values().all(|column| {
+            !column.required
+                || self
+                    .table
+                    .column_ordinal(&column.name)
+                    .and_then(|ordinal| Self::column_bytes(&self.data, ordinal))
+                    .is_some()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::row::Row;
+    use crate::row_binary::RowBinary;
+    use schemajs_primitives::column::types::{DataTypes, DataValue};
+    use schemajs_primitives::column::Column;
+    use schemajs_primitives::table::Table;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn users_table() -> Table {
+        let mut table = Table::new("users")
+            .add_column(Column::new("name", DataTypes::String))
+            .add_column(Column::new("age", DataTypes::Number))
+            .add_column(Column::new("score", DataTypes::Number))
+            .add_column(Column::new("enabled", DataTypes::Boolean));
+        table.assign_column_ordinals(vec![]);
+        table
+    }
+
+    #[test]
+    pub fn test_binary_row_round_trip() {
+        let table = Arc::new(users_table());
+
+        let row = RowBinary::from_json(
+            json!({
+                "_uid": "97ad4bba-98c5-4a9e-80d8-6bf6302fb883",
+                "name": "Luis",
+                "age": -3,
+                "score": 1.5
+            }),
+            table.clone(),
+        )
+        .unwrap();
+
+        let bytes = row.to_vec().unwrap();
+        assert!(bytes.len() < serde_json::to_vec(&row.to_data()).unwrap().len());
+
+        let row = RowBinary::from_slice(&bytes, table.clone());
+        assert_eq!(
+            row.get_value(table.get_column("name").unwrap()),
+            Some(DataValue::String("Luis".to_string()))
+        );
+        assert_eq!(
+            row.get_value(table.get_column("age").unwrap()),
+            Some(DataValue::Number((-3).into()))
+        );
+        assert_eq!(
+            row.get_value(table.get_column("score").unwrap()),
+            Some(DataValue::Number(
+                serde_json::Number::from_f64(1.5).unwrap()
+            ))
+        );
+        assert_eq!(row.get_value(table.get_column("enabled").unwrap()), None);
+        assert_eq!(row.to_map().unwrap().len(), 4);
+    }
+
+    #[test]
+    pub fn test_binary_row_after_adding_columns() {
+        let table = Arc::new(users_table());
+        let row = RowBinary::from_json(json!({ "name": "Luis" }), table.clone()).unwrap();
+
+        // New columns are given the next ordinals, so existing rows are read the same.
+        let mut new_table = users_table().add_column(Column::new("email", DataTypes::String));
+        new_table.assign_column_ordinals(table.metadata.column_ordinals.clone());
+        let new_table = Arc::new(new_table);
+
+        let mut row = RowBinary::from_slice(&row.to_vec().unwrap(), new_table.clone());
+        assert_eq!(
+            row.get_value(new_table.get_column("name").unwrap()),
+            Some(DataValue::String("Luis".to_string()))
+        );
+        assert_eq!(row.get_value(new_table.get_column("email").unwrap()), None);
+
+        row.set_value(
+            new_table.get_column("email").unwrap(),
+            DataValue::String("luis@example.com".to_string()),
+        );
+        assert_eq!(
+            row.get_value(new_table.get_column("email").unwrap()),
+            Some(DataValue::String("luis@example.com".to_string()))
+        );
+    }
+}
@@ -55,11 +55,11 @@ impl Row for RowJson {
         }
     }
 
-    fn from_data(data: Self::RowData, table: Arc<Table>) -> Self {
-        RowJson {
+    fn from_data(data: Self::RowData, table: Arc<Table>) -> Result<Self, ()> {
+        Ok(RowJson {
             table,
             values: data,
-        }
+        })
     }
 
     fn from_map(table: Arc<Table>, data: HashMap<String, DataValue>) -> Result<Self, ()> {
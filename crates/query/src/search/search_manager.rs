@@ -105,9 +105,19 @@ impl<T: Row> QuerySearchManager<T> {
             if let Some(pointer) = indx.get(&key) {
                 return vec![pointer];
             }
+
+            return vec![];
         }
 
-        vec![]
+        // Without an index every row is checked, decoding only the column of the condition.
+        let Some(column) = shard.table.get_column(&cond.key) else {
+            return vec![];
+        };
+        let expected = cond.value.to_string();
+        shard.filter_rows(|bytes| {
+            T::get_value_from_slice(bytes, &shard.table, column)
+                .is_some_and(|value| value.to_string() == expected)
+        })
     }
 
     fn find_index_for_query(
@@ -183,7 +193,7 @@ impl<T: Row> QuerySearchManager<T> {
 
         for pointer in pointers {
             let tbl_data = get_table_shard.data.read();
-            let data = tbl_data.get_element(pointer as usize).unwrap();
+            let data = tbl_data.get_element(pointer as usize)?;
             results.push(T::from_slice(&data, get_table_shard.table.clone()));
         }
 
@@ -193,12 +203,13 @@ impl<T: Row> QuerySearchManager<T> {
 
 #[cfg(test)]
 mod test {
+    use crate::db_row::DbRow;
     use crate::managers::single::SingleQueryManager;
     use crate::ops::query_ops::{QueryOps, QueryVal};
     use crate::row::Row;
     use crate::row_json::{RowData, RowJson};
     use crate::search::search_manager::QuerySearchManager;
-    use schemajs_config::DatabaseConfig;
+    use schemajs_config::{DatabaseConfig, RowFormat};
     use schemajs_data::fdm::FileDescriptorManager;
     use schemajs_dirs::create_scheme_js_db;
     use schemajs_helpers::create_helper_channel;
@@ -263,7 +274,7 @@ mod test {
                 index_type: IndexType::Hash,
             });
 
-        query_manager.register_table(tbl);
+        query_manager.register_table(tbl).unwrap();
 
         let table = query_manager.get_table("users").unwrap();
 
@@ -375,7 +386,7 @@ mod test {
 
         let tbl = tables.get("users").unwrap();
 
-        tbl.temps.reconcile_all();
+        tbl.temps.reconcile_all().unwrap();
 
         let results = search_manager.search("users", &ops).unwrap();
         let row_0 = &results[0];
@@ -392,6 +403,70 @@ mod test {
         assert_eq!(vals[1], "Luis");
     }
 
+    #[tokio::test]
+    pub async fn test_search_without_index() {
+        let test_db = Uuid::new_v4().to_string();
+        create_scheme_js_db(None, test_db.as_str());
+        let query_manager = SingleQueryManager::<DbRow>::new(
+            test_db,
+            create_helper_channel(1).0,
+            Arc::new(DatabaseConfig {
+                row_format: RowFormat::Binary,
+                ..Default::default()
+            }),
+            Arc::new(FileDescriptorManager::new(2500)),
+        );
+
+        query_manager
+            .register_table(
+                Table::new("users")
+                    .add_column(Column::new("user_id", DataTypes::String))
+                    .add_column(Column::new("user_name", DataTypes::String))
+                    .add_index(Index {
+                        name: "user_id_indx".to_string(),
+                        members: vec![String::from("user_id")],
+                        index_type: IndexType::Hash,
+                    }),
+            )
+            .unwrap();
+
+        let table = query_manager.get_table("users").unwrap();
+        for (id, name) in [("1", "Luis"), ("2", "Veronica"), ("3", "Luis")] {
+            query_manager
+                .insert(
+                    DbRow::from_json(
+                        serde_json::json!({ "user_id": id, "user_name": name }),
+                        table.clone(),
+                    )
+                    .unwrap(),
+                )
+                .unwrap();
+        }
+
+        let tables = query_manager.tables.clone();
+        tables.get("users").unwrap().temps.reconcile_all().unwrap();
+
+        let search_manager = QuerySearchManager::new(tables.clone());
+        let results = search_manager
+            .search(
+                "users",
+                &QueryOps::Condition(QueryVal {
+                    key: "user_name".to_string(),
+                    filter_type: "=".to_string(),
+                    value: DataValue::String("Luis".to_string()),
+                }),
+            )
+            .unwrap();
+
+        let user_id = table.get_column("user_id").unwrap();
+        let mut ids: Vec<String> = results
+            .iter()
+            .map(|row| row.get_value(user_id).unwrap().to_string())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["1", "3"]);
+    }
+
     fn get_user_table_for_drop_test() -> Table {
         Table::new("users")
             .add_column(Column::new("user_id", DataTypes::String).set_default_index(true))
@@ -418,7 +493,7 @@ mod test {
 
             let tbl = get_user_table_for_drop_test();
 
-            query_manager.register_table(tbl);
+            query_manager.register_table(tbl).unwrap();
 
             let table = query_manager.get_table("users").unwrap();
             let row_1 = query_manager
@@ -440,7 +515,7 @@ mod test {
 
             let tbl = tables.get("users").unwrap();
 
-            tbl.temps.reconcile_all();
+            tbl.temps.reconcile_all().unwrap();
 
             let results = search_manager.search("users", &ops).unwrap();
             let row_0 = &results[0];
@@ -464,7 +539,7 @@ mod test {
                 Arc::new(FileDescriptorManager::new(2500)),
             );
             let tbl = get_user_table_for_drop_test();
-            query_manager.register_table(tbl);
+            query_manager.register_table(tbl).unwrap();
             let tables = query_manager.tables.clone();
             let search_manager = QuerySearchManager::new(tables.clone());
             let ops = QueryOps::Or(vec![QueryOps::And(vec![QueryOps::Condition(QueryVal {
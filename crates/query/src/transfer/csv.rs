@@ -0,0 +1,86 @@
+use crate::transfer::{value_from_text, value_to_text, RowEncoder, TransferError};
+use csv::{Reader, StringRecordsIntoIter, Writer};
+use schemajs_primitives::column::types::DataValue;
+use schemajs_primitives::column::Column;
+use schemajs_primitives::table::Table;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+/// Writes a header with the names of the columns, followed by one record per row.
+/// Null values are written as empty fields.
+pub struct CsvEncoder<W: Write> {
+    writer: Writer<W>,
+}
+
+impl<W: Write> CsvEncoder<W> {
+    pub fn new(writer: W, columns: &[Column]) -> Result<Self, TransferError> {
+        let mut writer = Writer::from_writer(writer);
+        writer.write_record(columns.iter().map(|column| column.name.as_str()))?;
+
+        Ok(Self { writer })
+    }
+}
+
+impl<W: Write> RowEncoder for CsvEncoder<W> {
+    fn encode(&mut self, values: Vec<DataValue>) -> Result<(), TransferError> {
+        self.writer.write_record(values.iter().map(value_to_text))?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), TransferError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Reads records whose header names columns of the table, in any order and possibly only
+/// some of them. Empty fields are null.
+pub struct CsvDecoder<R: Read> {
+    records: StringRecordsIntoIter<R>,
+    columns: Vec<Column>,
+}
+
+impl<R: Read> CsvDecoder<R> {
+    pub fn new(reader: R, table: Arc<Table>) -> Result<Self, TransferError> {
+        let mut reader = Reader::from_reader(reader);
+
+        let columns = reader
+            .headers()?
+            .iter()
+            .map(|name| {
+                table
+                    .get_column(name)
+                    .cloned()
+                    .ok_or_else(|| TransferError::UnknownColumn(name.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            records: reader.into_records(),
+            columns,
+        })
+    }
+}
+
+impl<R: Read> Iterator for CsvDecoder<R> {
+    type Item = Result<HashMap<String, DataValue>, TransferError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.records.next()? {
+            Ok(record) => record,
+            Err(err) => return Some(Err(err.into())),
+        };
+        let line = record.position().map_or(0, |pos| pos.line());
+
+        let values = self
+            .columns
+            .iter()
+            .zip(record.iter())
+            .map(|(column, field)| Ok((column.name.clone(), value_from_text(column, field)?)))
+            .collect::<Result<HashMap<_, _>, TransferError>>()
+            .map_err(|err| err.at_line(line));
+
+        Some(values)
+    }
+}
@@ -0,0 +1,454 @@
+pub mod csv;
+pub mod ndjson;
+pub mod parquet;
+
+use crate::errors::QueryError;
+use crate::managers::single::table_shard::TableShard;
+use crate::managers::single::SingleQueryManager;
+use crate::row::Row;
+use schemajs_primitives::column::types::{DataTypes, DataValue};
+use schemajs_primitives::column::Column;
+use schemajs_primitives::table::Table;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Number of rows inserted into the master shard at once by `import_table`.
+pub const IMPORT_BATCH_SIZE: usize = 1000;
+
+/// File formats tables can be exported to and imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferFormat {
+    /// One JSON object per line, keyed by column name.
+    Ndjson,
+    /// A header with the column names followed by one record per row.
+    Csv,
+    /// Apache Parquet, with a flat, optional column per column of the table.
+    Parquet,
+}
+
+impl FromStr for TransferFormat {
+    type Err = TransferError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ndjson" | "jsonl" => Ok(TransferFormat::Ndjson),
+            "csv" => Ok(TransferFormat::Csv),
+            "parquet" => Ok(TransferFormat::Parquet),
+            _ => Err(TransferError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TransferError {
+    #[error("Unknown transfer format '{0}'")]
+    UnknownFormat(String),
+
+    #[error("Unknown column '{0}'")]
+    UnknownColumn(String),
+
+    #[error("Invalid value for column '{column}': {value}")]
+    InvalidValue { column: String, value: String },
+
+    #[error("Invalid record at line {line}: {reason}")]
+    InvalidRecord { line: u64, reason: String },
+
+    #[error("CSV error: {0}")]
+    Csv(#[from] ::csv::Error),
+
+    #[error("Parquet error: {0}")]
+    Parquet(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Query(#[from] QueryError),
+}
+
+impl TransferError {
+    /// Ties an error to the line of the file the record it was found in starts at.
+    pub(crate) fn at_line(self, line: u64) -> TransferError {
+        match self {
+            TransferError::InvalidRecord { .. } | TransferError::Io(_) => self,
+            err => TransferError::InvalidRecord {
+                line,
+                reason: err.to_string(),
+            },
+        }
+    }
+}
+
+/// Writes rows, given as their values in the order of the columns of the table.
+pub(crate) trait RowEncoder {
+    fn encode(&mut self, values: Vec<DataValue>) -> Result<(), TransferError>;
+
+    /// Writes whatever is buffered, once every row was encoded.
+    fn finish(self) -> Result<(), TransferError>;
+}
+
+/// Columns of a table in the order they are exported in: by ordinal, or by name for tables
+/// whose ordinals were not assigned.
+pub fn export_columns(table: &Table) -> Vec<Column> {
+    let mut names: Vec<&String> = table
+        .metadata
+        .column_ordinals
+        .iter()
+        .filter(|name| table.columns.contains_key(*name))
+        .collect();
+
+    if names.is_empty() {
+        names = table.list_columns();
+        names.sort();
+    }
+
+    names
+        .into_iter()
+        .filter_map(|name| table.get_column(name).cloned())
+        .collect()
+}
+
+/// Writes every row of `table_name` queries can see to `writer`, returning the number of rows.
+pub fn export_table<T: Row, W: Write>(
+    query_manager: &SingleQueryManager<T>,
+    table_name: &str,
+    format: TransferFormat,
+    writer: W,
+) -> Result<u64, TransferError> {
+    let table_shard = query_manager
+        .tables
+        .get(table_name)
+        .ok_or_else(|| QueryError::InvalidTable(table_name.to_string()))?;
+    let columns = export_columns(&table_shard.table);
+
+    match format {
+        TransferFormat::Ndjson => export_rows(
+            &table_shard,
+            &columns,
+            ndjson::NdjsonEncoder::new(writer, columns.clone()),
+        ),
+        TransferFormat::Csv => export_rows(
+            &table_shard,
+            &columns,
+            csv::CsvEncoder::new(writer, &columns)?,
+        ),
+        TransferFormat::Parquet => export_rows(
+            &table_shard,
+            &columns,
+            parquet::ParquetEncoder::new(writer, columns.clone())?,
+        ),
+    }
+}
+
+fn export_rows<T: Row, E: RowEncoder>(
+    table_shard: &TableShard<T>,
+    columns: &[Column],
+    mut encoder: E,
+) -> Result<u64, TransferError> {
+    let mut exported = 0;
+
+    table_shard.scan_rows(|row| {
+        let values = columns
+            .iter()
+            .map(|column| row.get_value(column).unwrap_or(DataValue::Null))
+            .collect();
+
+        encoder.encode(values)?;
+        exported += 1;
+        Ok::<(), TransferError>(())
+    })?;
+
+    encoder.finish()?;
+    Ok(exported)
+}
+
+/// Reads rows of `table_name` from `reader` and inserts them straight into its master shard,
+/// `IMPORT_BATCH_SIZE` rows at a time, returning the number of rows.
+///
+/// Rows are inserted as they are read, so the rows of the batches before an invalid record
+/// stay in the table. `beforeInsert` hooks are not run.
+pub fn import_table<T: Row, R: Read>(
+    query_manager: &SingleQueryManager<T>,
+    table_name: &str,
+    format: TransferFormat,
+    reader: R,
+) -> Result<u64, TransferError> {
+    let table = query_manager
+        .get_table(table_name)
+        .ok_or_else(|| QueryError::InvalidTable(table_name.to_string()))?;
+
+    match format {
+        TransferFormat::Ndjson => insert_records(
+            query_manager,
+            table.clone(),
+            ndjson::NdjsonDecoder::new(reader, table),
+        ),
+        TransferFormat::Csv => insert_records(
+            query_manager,
+            table.clone(),
+            csv::CsvDecoder::new(reader, table)?,
+        ),
+        TransferFormat::Parquet => insert_records(
+            query_manager,
+            table.clone(),
+            parquet::ParquetDecoder::new(reader, table)?,
+        ),
+    }
+}
+
+fn insert_records<T: Row>(
+    query_manager: &SingleQueryManager<T>,
+    table: Arc<Table>,
+    records: impl Iterator<Item = Result<HashMap<String, DataValue>, TransferError>>,
+) -> Result<u64, TransferError> {
+    let mut imported = 0;
+    let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+
+    for values in records {
+        let row = T::from_map(table.clone(), values?).map_err(|_| QueryError::InvalidInsertion)?;
+        batch.push(row);
+
+        if batch.len() == IMPORT_BATCH_SIZE {
+            query_manager.raw_insert(&mut batch, true)?;
+            imported += batch.len() as u64;
+            batch.clear();
+        }
+    }
+
+    if !batch.is_empty() {
+        query_manager.raw_insert(&mut batch, true)?;
+        imported += batch.len() as u64;
+    }
+
+    Ok(imported)
+}
+
+fn invalid_value(column: &Column, value: impl ToString) -> TransferError {
+    TransferError::InvalidValue {
+        column: column.name.clone(),
+        value: value.to_string(),
+    }
+}
+
+/// Converts a JSON value to the type of `column`. Uuids are read from strings.
+pub(crate) fn value_from_json(column: &Column, value: &Value) -> Result<DataValue, TransferError> {
+    if value.is_null() {
+        return Ok(DataValue::Null);
+    }
+
+    match (&column.data_type, value) {
+        (DataTypes::Null, _) => Ok(DataValue::Null),
+        (DataTypes::Uuid, Value::String(val)) => Uuid::from_str(val)
+            .map(DataValue::Uuid)
+            .map_err(|_| invalid_value(column, value)),
+        (DataTypes::String, Value::String(val)) => Ok(DataValue::String(val.clone())),
+        (DataTypes::Boolean, Value::Bool(val)) => Ok(DataValue::Boolean(*val)),
+        (DataTypes::Number, Value::Number(val)) => Ok(DataValue::Number(val.clone())),
+        _ => Err(invalid_value(column, value)),
+    }
+}
+
+/// Converts the text of a field to the type of `column`. Empty fields are null.
+pub(crate) fn value_from_text(column: &Column, text: &str) -> Result<DataValue, TransferError> {
+    if text.is_empty() {
+        return Ok(DataValue::Null);
+    }
+
+    match column.data_type {
+        DataTypes::Null => Ok(DataValue::Null),
+        DataTypes::Uuid => Uuid::from_str(text)
+            .map(DataValue::Uuid)
+            .map_err(|_| invalid_value(column, text)),
+        DataTypes::String => Ok(DataValue::String(text.to_string())),
+        DataTypes::Boolean => bool::from_str(text)
+            .map(DataValue::Boolean)
+            .map_err(|_| invalid_value(column, text)),
+        DataTypes::Number => serde_json::Number::from_str(text)
+            .map(DataValue::Number)
+            .map_err(|_| invalid_value(column, text)),
+    }
+}
+
+/// Text of a field, the reverse of `value_from_text`.
+pub(crate) fn value_to_text(value: &DataValue) -> String {
+    match value {
+        DataValue::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::managers::single::SingleQueryManager;
+    use crate::ops::query_ops::{QueryOps, QueryVal};
+    use crate::row::Row;
+    use crate::row_json::RowJson;
+    use crate::transfer::parquet::ParquetEncoder;
+    use crate::transfer::{export_table, import_table, RowEncoder, TransferError, TransferFormat};
+    use schemajs_config::DatabaseConfig;
+    use schemajs_data::fdm::FileDescriptorManager;
+    use schemajs_dirs::create_scheme_js_db;
+    use schemajs_helpers::create_helper_channel;
+    use schemajs_index::index_type::IndexType;
+    use schemajs_primitives::column::types::{DataTypes, DataValue};
+    use schemajs_primitives::column::Column;
+    use schemajs_primitives::index::Index;
+    use schemajs_primitives::table::Table;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn create_query_manager() -> SingleQueryManager<RowJson> {
+        let test_db = Uuid::new_v4().to_string();
+        create_scheme_js_db(None, test_db.as_str());
+        let query_manager = SingleQueryManager::new(
+            test_db,
+            create_helper_channel(1).0,
+            Arc::new(DatabaseConfig::default()),
+            Arc::new(FileDescriptorManager::new(2500)),
+        );
+
+        query_manager
+            .register_table(
+                Table::new("users")
+                    .add_column(Column::new("user_name", DataTypes::String))
+                    .add_column(Column::new("user_age", DataTypes::Number))
+                    .add_column(Column::new("verified", DataTypes::Boolean))
+                    .add_index(Index {
+                        name: "user_name_indx".to_string(),
+                        members: vec![String::from("user_name")],
+                        index_type: IndexType::Hash,
+                    }),
+            )
+            .unwrap();
+
+        query_manager
+    }
+
+    fn find_user(query_manager: &SingleQueryManager<RowJson>, name: &str) -> Vec<RowJson> {
+        query_manager
+            .search_manager
+            .search(
+                "users",
+                &QueryOps::Condition(QueryVal {
+                    key: "user_name".to_string(),
+                    filter_type: "=".to_string(),
+                    value: DataValue::String(name.to_string()),
+                }),
+            )
+            .unwrap()
+    }
+
+    #[tokio::test]
+    pub async fn test_export_and_import() {
+        let source = create_query_manager();
+        let table = source.get_table("users").unwrap();
+
+        let mut rows: Vec<RowJson> = (0..2500)
+            .map(|i| {
+                RowJson::from_json(
+                    serde_json::json!({
+                        "user_name": format!("user{}", i),
+                        "user_age": i,
+                        "verified": i % 2 == 0
+                    }),
+                    table.clone(),
+                )
+                .unwrap()
+            })
+            .collect();
+        source.raw_insert(&mut rows, false).unwrap();
+
+        for format in [
+            TransferFormat::Ndjson,
+            TransferFormat::Csv,
+            TransferFormat::Parquet,
+        ] {
+            let mut exported = vec![];
+            assert_eq!(
+                export_table(&source, "users", format, &mut exported).unwrap(),
+                2500
+            );
+
+            let target = create_query_manager();
+            assert_eq!(
+                import_table(&target, "users", format, exported.as_slice()).unwrap(),
+                2500
+            );
+
+            let user = find_user(&target, "user1201");
+            assert_eq!(user.len(), 1);
+            assert_eq!(
+                user[0].get_value(
+                    target
+                        .get_table("users")
+                        .unwrap()
+                        .get_column("user_age")
+                        .unwrap()
+                ),
+                Some(DataValue::Number(1201.into()))
+            );
+            assert_eq!(
+                user[0].get_value(Table::get_internal_uid()),
+                find_user(&source, "user1201")[0].get_value(Table::get_internal_uid())
+            );
+        }
+    }
+
+    #[tokio::test]
+    pub async fn test_import_invalid_records() {
+        let query_manager = create_query_manager();
+
+        let csv = "user_name,user_age\nuser0,20\nuser1,twenty\n";
+        let err =
+            import_table(&query_manager, "users", TransferFormat::Csv, csv.as_bytes()).unwrap_err();
+        assert!(matches!(err, TransferError::InvalidRecord { line: 3, .. }));
+
+        let csv = "user_name,country\nuser0,US\n";
+        let err =
+            import_table(&query_manager, "users", TransferFormat::Csv, csv.as_bytes()).unwrap_err();
+        assert!(matches!(err, TransferError::UnknownColumn(col) if col == "country"));
+
+        let ndjson = "{\"user_name\": \"user0\"}\n\n{\"user_name\": 1}\n";
+        let err = import_table(
+            &query_manager,
+            "users",
+            TransferFormat::Ndjson,
+            ndjson.as_bytes(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, TransferError::InvalidRecord { line: 3, .. }));
+
+        let mut parquet = vec![];
+        let mut encoder = ParquetEncoder::new(
+            &mut parquet,
+            vec![Column::new("country", DataTypes::String)],
+        )
+        .unwrap();
+        encoder
+            .encode(vec![DataValue::String("US".to_string())])
+            .unwrap();
+        encoder.finish().unwrap();
+        let err = import_table(
+            &query_manager,
+            "users",
+            TransferFormat::Parquet,
+            parquet.as_slice(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, TransferError::UnknownColumn(col) if col == "country"));
+
+        let err = import_table(
+            &query_manager,
+            "users",
+            TransferFormat::Parquet,
+            csv.as_bytes(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, TransferError::Parquet(_)));
+    }
+}
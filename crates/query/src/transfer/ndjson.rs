@@ -0,0 +1,107 @@
+use crate::transfer::{value_from_json, RowEncoder, TransferError};
+use schemajs_primitives::column::types::DataValue;
+use schemajs_primitives::column::Column;
+use schemajs_primitives::table::Table;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Lines, Read, Write};
+use std::sync::Arc;
+
+/// Writes every row as a JSON object on its own line, with the keys in column order.
+pub struct NdjsonEncoder<W: Write> {
+    writer: W,
+    columns: Vec<Column>,
+}
+
+impl<W: Write> NdjsonEncoder<W> {
+    pub fn new(writer: W, columns: Vec<Column>) -> Self {
+        Self { writer, columns }
+    }
+}
+
+impl<W: Write> RowEncoder for NdjsonEncoder<W> {
+    fn encode(&mut self, values: Vec<DataValue>) -> Result<(), TransferError> {
+        self.writer.write_all(b"{")?;
+
+        for (pos, (column, value)) in self.columns.iter().zip(values).enumerate() {
+            if pos > 0 {
+                self.writer.write_all(b",")?;
+            }
+
+            serde_json::to_writer(&mut self.writer, &column.name).map_err(std::io::Error::from)?;
+            self.writer.write_all(b":")?;
+            serde_json::to_writer(&mut self.writer, &value.to_value())
+                .map_err(std::io::Error::from)?;
+        }
+
+        self.writer.write_all(b"}\n")?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), TransferError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Reads one JSON object per line. Blank lines are skipped and missing keys are left unset.
+pub struct NdjsonDecoder<R: Read> {
+    lines: Lines<BufReader<R>>,
+    line: u64,
+    table: Arc<Table>,
+}
+
+impl<R: Read> NdjsonDecoder<R> {
+    pub fn new(reader: R, table: Arc<Table>) -> Self {
+        Self {
+            lines: BufReader::new(reader).lines(),
+            line: 0,
+            table,
+        }
+    }
+
+    fn decode(&self, line: &str) -> Result<HashMap<String, DataValue>, TransferError> {
+        let value: Value =
+            serde_json::from_str(line).map_err(|err| TransferError::InvalidRecord {
+                line: self.line,
+                reason: err.to_string(),
+            })?;
+        let object = value
+            .as_object()
+            .ok_or_else(|| TransferError::InvalidRecord {
+                line: self.line,
+                reason: "Expected a JSON object".to_string(),
+            })?;
+
+        let mut values = HashMap::new();
+        for (col_name, val) in object {
+            let column = self
+                .table
+                .get_column(col_name)
+                .ok_or_else(|| TransferError::UnknownColumn(col_name.clone()))?;
+            values.insert(col_name.clone(), value_from_json(column, val)?);
+        }
+
+        Ok(values)
+    }
+}
+
+impl<R: Read> Iterator for NdjsonDecoder<R> {
+    type Item = Result<HashMap<String, DataValue>, TransferError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line += 1;
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into())),
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            return Some(self.decode(&line).map_err(|err| err.at_line(self.line)));
+        }
+    }
+}
@@ -0,0 +1,1266 @@
+use crate::transfer::{invalid_value, RowEncoder, TransferError};
+use parquet_format_safe::thrift;
+use parquet_format_safe::thrift::protocol::{TCompactInputProtocol, TCompactOutputProtocol};
+use parquet_format_safe::{
+    ColumnChunk, ColumnMetaData, CompressionCodec, ConvertedType, DataPageHeader, Encoding,
+    FieldRepetitionType, FileMetaData, LogicalType, PageHeader, PageType, RowGroup, SchemaElement,
+    Type,
+};
+use schemajs_primitives::column::types::{DataTypes, DataValue};
+use schemajs_primitives::column::Column;
+use schemajs_primitives::table::Table;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::vec::IntoIter;
+use uuid::Uuid;
+
+/// Bytes a Parquet file starts and ends with.
+const MAGIC: &[u8; 4] = b"PAR1";
+
+/// Number of rows `ParquetEncoder` buffers before writing them as a row group.
+pub const ROW_GROUP_SIZE: usize = 65536;
+
+/// Level pages are compressed at by `ParquetEncoder`.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Whole doubles below this are exact, so they are imported as integers.
+const MAX_SAFE_INTEGER: f64 = 9007199254740992.0;
+
+/// Thrift charges 8 bytes for every element of a list, which takes at least a byte in the file.
+const THRIFT_ALLOCATION_FACTOR: usize = 8;
+
+impl From<thrift::Error> for TransferError {
+    fn from(err: thrift::Error) -> Self {
+        TransferError::Parquet(err.to_string())
+    }
+}
+
+fn corrupted(reason: impl ToString) -> TransferError {
+    TransferError::Parquet(reason.to_string())
+}
+
+/// Physical type values of a column are written as. The type of numbers depends on the values
+/// of the column, see `ColumnBuffer::number_type`.
+fn physical_type(data_type: &DataTypes) -> Option<Type> {
+    match data_type {
+        DataTypes::Null => Some(Type::INT32),
+        DataTypes::Uuid => Some(Type::FIXED_LEN_BYTE_ARRAY),
+        DataTypes::String => Some(Type::BYTE_ARRAY),
+        DataTypes::Boolean => Some(Type::BOOLEAN),
+        DataTypes::Number => None,
+    }
+}
+
+fn schema_element(column: &Column, type_: Type) -> SchemaElement {
+    let (type_length, converted_type, logical_type) = match column.data_type {
+        DataTypes::Null => (None, None, Some(LogicalType::UNKNOWN(Default::default()))),
+        DataTypes::Uuid => (Some(16), None, Some(LogicalType::UUID(Default::default()))),
+        DataTypes::String => (
+            None,
+            Some(ConvertedType::UTF8),
+            Some(LogicalType::STRING(Default::default())),
+        ),
+        DataTypes::Boolean | DataTypes::Number => (None, None, None),
+    };
+
+    SchemaElement {
+        type_: Some(type_),
+        type_length,
+        repetition_type: Some(FieldRepetitionType::OPTIONAL),
+        name: column.name.clone(),
+        num_children: None,
+        converted_type,
+        scale: None,
+        precision: None,
+        field_id: None,
+        logical_type,
+    }
+}
+
+/// Bits packed from the least significant bit of every byte on, the way Parquet stores
+/// booleans and bit-packed runs.
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bits {
+    fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+
+        if bit {
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= 1 << (self.len % 8);
+        }
+
+        self.len += 1;
+    }
+}
+
+/// Values of a column in the row group being buffered.
+#[derive(Default)]
+struct ColumnBuffer {
+    defined: Bits,
+    values: Vec<u8>,
+    booleans: Bits,
+    numbers: Vec<serde_json::Number>,
+}
+
+impl ColumnBuffer {
+    fn push(&mut self, column: &Column, value: DataValue) -> Result<(), TransferError> {
+        match (&column.data_type, value) {
+            (_, DataValue::Null) | (DataTypes::Null, _) => {
+                self.defined.push(false);
+                return Ok(());
+            }
+            (DataTypes::Uuid, DataValue::Uuid(uuid)) => {
+                self.values.extend_from_slice(uuid.as_bytes());
+            }
+            (DataTypes::String, DataValue::String(text)) => {
+                self.values
+                    .extend_from_slice(&(text.len() as u32).to_le_bytes());
+                self.values.extend_from_slice(text.as_bytes());
+            }
+            (DataTypes::Boolean, DataValue::Boolean(val)) => self.booleans.push(val),
+            (DataTypes::Number, DataValue::Number(number)) => self.numbers.push(number),
+            (_, value) => return Err(invalid_value(column, value)),
+        }
+
+        self.defined.push(true);
+        Ok(())
+    }
+
+    /// Type the numbers of a column are written as, decided from the ones of its first row
+    /// group: 64-bit integers if they all are, doubles otherwise.
+    fn number_type(&self) -> Type {
+        let integers =
+            !self.numbers.is_empty() && self.numbers.iter().all(|number| number.as_i64().is_some());
+
+        if integers {
+            Type::INT64
+        } else {
+            Type::DOUBLE
+        }
+    }
+
+    /// Writes the buffered numbers as `type_`, failing on those it cannot hold exactly.
+    fn write_numbers(&mut self, column: &Column, type_: Type) -> Result<(), TransferError> {
+        for number in std::mem::take(&mut self.numbers) {
+            if type_ == Type::INT64 {
+                let val = number
+                    .as_i64()
+                    .ok_or_else(|| inexact_number(column, &number, "64-bit integers"))?;
+                self.values.extend_from_slice(&val.to_le_bytes());
+            } else {
+                let val = number
+                    .as_f64()
+                    .filter(|val| is_exact(&number, *val))
+                    .ok_or_else(|| inexact_number(column, &number, "doubles"))?;
+                self.values.extend_from_slice(&val.to_le_bytes());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Encodes the buffered values as a single data page, with the definition levels as one
+    /// bit-packed run, and returns it along with its size before compression.
+    fn into_page(self, num_rows: usize) -> Result<(Vec<u8>, usize), TransferError> {
+        let mut levels = vec![];
+        write_uleb128(&mut levels, ((self.defined.bytes.len() as u64) << 1) | 1);
+        levels.extend_from_slice(&self.defined.bytes);
+
+        let mut body = Vec::with_capacity(4 + levels.len() + self.values.len());
+        body.extend_from_slice(&(levels.len() as u32).to_le_bytes());
+        body.extend_from_slice(&levels);
+        body.extend_from_slice(&self.values);
+        body.extend_from_slice(&self.booleans.bytes);
+
+        let compressed = zstd::bulk::compress(&body, ZSTD_LEVEL)?;
+        let header = PageHeader {
+            type_: PageType::DATA_PAGE,
+            uncompressed_page_size: page_size(body.len())?,
+            compressed_page_size: page_size(compressed.len())?,
+            crc: None,
+            data_page_header: Some(DataPageHeader {
+                num_values: num_rows as i32,
+                encoding: Encoding::PLAIN,
+                definition_level_encoding: Encoding::RLE,
+                repetition_level_encoding: Encoding::RLE,
+                statistics: None,
+            }),
+            index_page_header: None,
+            dictionary_page_header: None,
+            data_page_header_v2: None,
+        };
+
+        let mut page = vec![];
+        let header_len =
+            header.write_to_out_protocol(&mut TCompactOutputProtocol::new(&mut page))?;
+        page.extend_from_slice(&compressed);
+
+        Ok((page, header_len + body.len()))
+    }
+}
+
+/// Whether `value` is `number`, which integers above 2^53 are not once converted to doubles.
+fn is_exact(number: &serde_json::Number, value: f64) -> bool {
+    match (number.as_i64(), number.as_u64()) {
+        (Some(int), _) => value as i128 == int as i128,
+        (_, Some(int)) => value as i128 == int as i128,
+        _ => true,
+    }
+}
+
+fn inexact_number(column: &Column, number: &serde_json::Number, type_name: &str) -> TransferError {
+    TransferError::Parquet(format!(
+        "Column '{}' is written as {}, which cannot hold {}",
+        column.name, type_name, number
+    ))
+}
+
+fn page_size(len: usize) -> Result<i32, TransferError> {
+    i32::try_from(len).map_err(|_| corrupted("Page is larger than 2GB"))
+}
+
+fn write_uleb128(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Writes every column as an optional, flat column, `ROW_GROUP_SIZE` rows per row group.
+/// Every column chunk is a single plain encoded page compressed with zstd.
+///
+/// Number columns are written as 64-bit integers when the numbers of their first row group are
+/// all integers, and as doubles otherwise. The export fails on a number the type of its column
+/// cannot hold, such as a fraction after the first row group of an integer column, rather than
+/// rounding it.
+pub struct ParquetEncoder<W: Write> {
+    writer: W,
+    offset: u64,
+    columns: Vec<Column>,
+    /// Physical type of every column, which numbers only get once their first row group is written.
+    types: Vec<Option<Type>>,
+    buffers: Vec<ColumnBuffer>,
+    buffered_rows: usize,
+    row_groups: Vec<RowGroup>,
+    num_rows: i64,
+}
+
+impl<W: Write> ParquetEncoder<W> {
+    pub fn new(mut writer: W, columns: Vec<Column>) -> Result<Self, TransferError> {
+        writer.write_all(MAGIC)?;
+
+        Ok(Self {
+            writer,
+            offset: MAGIC.len() as u64,
+            buffers: columns.iter().map(|_| ColumnBuffer::default()).collect(),
+            types: columns
+                .iter()
+                .map(|column| physical_type(&column.data_type))
+                .collect(),
+            columns,
+            buffered_rows: 0,
+            row_groups: vec![],
+            num_rows: 0,
+        })
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), TransferError> {
+        self.writer.write_all(bytes)?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn flush_row_group(&mut self) -> Result<(), TransferError> {
+        let num_rows = std::mem::take(&mut self.buffered_rows);
+        let file_offset = self.offset as i64;
+        let mut total_byte_size = 0;
+        let mut chunks = Vec::with_capacity(self.columns.len());
+
+        for index in 0..self.columns.len() {
+            let mut buffer = std::mem::take(&mut self.buffers[index]);
+            let type_ = *self.types[index].get_or_insert_with(|| buffer.number_type());
+            buffer.write_numbers(&self.columns[index], type_)?;
+
+            let (page, uncompressed_size) = buffer.into_page(num_rows)?;
+            let data_page_offset = self.offset as i64;
+            self.write(&page)?;
+
+            let column = &self.columns[index];
+            total_byte_size += uncompressed_size as i64;
+            chunks.push(ColumnChunk {
+                file_path: None,
+                file_offset: data_page_offset,
+                meta_data: Some(ColumnMetaData {
+                    type_,
+                    encodings: vec![Encoding::PLAIN, Encoding::RLE],
+                    path_in_schema: vec![column.name.clone()],
+                    codec: CompressionCodec::ZSTD,
+                    num_values: num_rows as i64,
+                    total_uncompressed_size: uncompressed_size as i64,
+                    total_compressed_size: page.len() as i64,
+                    key_value_metadata: None,
+                    data_page_offset,
+                    index_page_offset: None,
+                    dictionary_page_offset: None,
+                    statistics: None,
+                    encoding_stats: None,
+                    bloom_filter_offset: None,
+                }),
+                offset_index_offset: None,
+                offset_index_length: None,
+                column_index_offset: None,
+                column_index_length: None,
+                crypto_metadata: None,
+                encrypted_column_metadata: None,
+            });
+        }
+
+        self.row_groups.push(RowGroup {
+            columns: chunks,
+            total_byte_size,
+            num_rows: num_rows as i64,
+            sorting_columns: None,
+            file_offset: Some(file_offset),
+            total_compressed_size: Some(self.offset as i64 - file_offset),
+            ordinal: i16::try_from(self.row_groups.len()).ok(),
+        });
+        self.num_rows += num_rows as i64;
+
+        Ok(())
+    }
+}
+
+impl<W: Write> RowEncoder for ParquetEncoder<W> {
+    fn encode(&mut self, values: Vec<DataValue>) -> Result<(), TransferError> {
+        for ((column, buffer), value) in self.columns.iter().zip(&mut self.buffers).zip(values) {
+            buffer.push(column, value)?;
+        }
+
+        self.buffered_rows += 1;
+        if self.buffered_rows == ROW_GROUP_SIZE {
+            self.flush_row_group()?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), TransferError> {
+        if self.buffered_rows > 0 {
+            self.flush_row_group()?;
+        }
+
+        let root = SchemaElement {
+            type_: None,
+            type_length: None,
+            repetition_type: None,
+            name: String::from("schema"),
+            num_children: Some(self.columns.len() as i32),
+            converted_type: None,
+            scale: None,
+            precision: None,
+            field_id: None,
+            logical_type: None,
+        };
+        let metadata = FileMetaData {
+            version: 1,
+            schema: std::iter::once(root)
+                .chain(self.columns.iter().zip(&self.types).map(|(column, type_)| {
+                    // Number columns without rows.
+                    schema_element(column, type_.unwrap_or(Type::DOUBLE))
+                }))
+                .collect(),
+            num_rows: self.num_rows,
+            row_groups: std::mem::take(&mut self.row_groups),
+            key_value_metadata: None,
+            created_by: Some(format!("schemajs version {}", env!("CARGO_PKG_VERSION"))),
+            column_orders: None,
+            encryption_algorithm: None,
+            footer_signing_key_metadata: None,
+        };
+
+        let mut footer = vec![];
+        let metadata_len =
+            metadata.write_to_out_protocol(&mut TCompactOutputProtocol::new(&mut footer))?;
+        footer.extend_from_slice(&(metadata_len as u32).to_le_bytes());
+        footer.extend_from_slice(MAGIC);
+        self.write(&footer)?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// A value as it is stored in a Parquet file.
+#[derive(Debug, Clone)]
+enum PlainValue {
+    Boolean(bool),
+    Int(i64),
+    Float(f64),
+    Bytes(Vec<u8>),
+}
+
+impl Display for PlainValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlainValue::Boolean(val) => write!(f, "{}", val),
+            PlainValue::Int(val) => write!(f, "{}", val),
+            PlainValue::Float(val) => write!(f, "{}", val),
+            PlainValue::Bytes(val) => write!(f, "{}", String::from_utf8_lossy(val)),
+        }
+    }
+}
+
+impl PlainValue {
+    /// Converts a value to the type of `column`. Uuids are read from 16 bytes or from text.
+    fn into_data_value(self, column: &Column) -> Result<DataValue, TransferError> {
+        match (&column.data_type, self) {
+            (DataTypes::Null, _) => Ok(DataValue::Null),
+            (DataTypes::Uuid, PlainValue::Bytes(bytes)) => {
+                let uuid = match bytes.len() {
+                    16 => Uuid::from_slice(&bytes).ok(),
+                    _ => std::str::from_utf8(&bytes)
+                        .ok()
+                        .and_then(|text| Uuid::from_str(text).ok()),
+                };
+
+                uuid.map(DataValue::Uuid)
+                    .ok_or_else(|| invalid_value(column, PlainValue::Bytes(bytes)))
+            }
+            (DataTypes::String, PlainValue::Bytes(bytes)) => String::from_utf8(bytes)
+                .map(DataValue::String)
+                .map_err(|err| invalid_value(column, String::from_utf8_lossy(err.as_bytes()))),
+            (DataTypes::Boolean, PlainValue::Boolean(val)) => Ok(DataValue::Boolean(val)),
+            (DataTypes::Number, PlainValue::Int(val)) => Ok(DataValue::Number(val.into())),
+            (DataTypes::Number, PlainValue::Float(val)) => number_from_f64(val)
+                .map(DataValue::Number)
+                .ok_or_else(|| invalid_value(column, val)),
+            (_, value) => Err(invalid_value(column, value)),
+        }
+    }
+}
+
+/// Whole doubles, such as those of number columns that also hold fractions, are read back as
+/// integers while they are exact.
+fn number_from_f64(value: f64) -> Option<serde_json::Number> {
+    if value.fract() == 0.0 && value.abs() < MAX_SAFE_INTEGER {
+        Some((value as i64).into())
+    } else {
+        serde_json::Number::from_f64(value)
+    }
+}
+
+/// Reads rows from a Parquet file whose columns are columns of the table, in any order and
+/// possibly only some of them. The file is copied to a temporary file first, as its metadata
+/// is at its end.
+///
+/// Only flat schemas are read. Pages can be in any encoding but the deprecated `BIT_PACKED`,
+/// and compressed with snappy, gzip, zstd or lz4. Values that do not fit their column are
+/// reported with the number of their row, counted from 1, as their line.
+pub struct ParquetDecoder {
+    file: File,
+    columns: Vec<Column>,
+    leaves: Vec<SchemaElement>,
+    row_groups: IntoIter<RowGroup>,
+    values: Vec<IntoIter<Option<PlainValue>>>,
+    remaining: usize,
+    row: u64,
+    failed: bool,
+}
+
+impl ParquetDecoder {
+    pub fn new(mut reader: impl Read, table: Arc<Table>) -> Result<Self, TransferError> {
+        let mut file = tempfile::tempfile()?;
+        let len = std::io::copy(&mut reader, &mut file)?;
+        if len < (MAGIC.len() * 2 + 4) as u64 {
+            return Err(corrupted("File is too short to be a Parquet file"));
+        }
+
+        let mut magic = [0; 4];
+        file.seek(SeekFrom::Start(0))?;
+        file.read_exact(&mut magic)?;
+
+        let mut tail = [0; 8];
+        file.seek(SeekFrom::End(-8))?;
+        file.read_exact(&mut tail)?;
+
+        if &magic != MAGIC || &tail[4..] != MAGIC {
+            return Err(corrupted("File is not a Parquet file"));
+        }
+
+        let metadata_len = u32::from_le_bytes([tail[0], tail[1], tail[2], tail[3]]) as u64;
+        if metadata_len + 12 > len {
+            return Err(corrupted("Metadata is larger than the file"));
+        }
+
+        let mut bytes = vec![0; metadata_len as usize];
+        file.seek(SeekFrom::End(-8 - metadata_len as i64))?;
+        file.read_exact(&mut bytes)?;
+        let metadata = FileMetaData::read_from_in_protocol(&mut TCompactInputProtocol::new(
+            bytes.as_slice(),
+            bytes.len() * THRIFT_ALLOCATION_FACTOR,
+        ))?;
+
+        let (root, leaves) = metadata
+            .schema
+            .split_first()
+            .ok_or_else(|| corrupted("File has no schema"))?;
+        let flat = root.num_children.unwrap_or(0) as usize == leaves.len()
+            && leaves.iter().all(|leaf| {
+                leaf.type_.is_some() && leaf.repetition_type != Some(FieldRepetitionType::REPEATED)
+            });
+        if !flat {
+            return Err(corrupted("Only files with flat schemas can be imported"));
+        }
+
+        let columns = leaves
+            .iter()
+            .map(|leaf| {
+                table
+                    .get_column(&leaf.name)
+                    .cloned()
+                    .ok_or_else(|| TransferError::UnknownColumn(leaf.name.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            file,
+            columns,
+            leaves: leaves.to_vec(),
+            row_groups: metadata.row_groups.into_iter(),
+            values: vec![],
+            remaining: 0,
+            row: 0,
+            failed: false,
+        })
+    }
+
+    fn read_row_group(&mut self, row_group: RowGroup) -> Result<(), TransferError> {
+        if row_group.columns.len() != self.leaves.len() {
+            return Err(corrupted("Row group does not match the schema"));
+        }
+
+        let num_rows = usize::try_from(row_group.num_rows)
+            .map_err(|_| corrupted("Row group has a negative number of rows"))?;
+
+        let mut values = Vec::with_capacity(self.leaves.len());
+        for (chunk, leaf) in row_group.columns.iter().zip(&self.leaves) {
+            let column = read_column_chunk(&mut self.file, chunk, leaf)?;
+            if column.len() != num_rows {
+                return Err(corrupted(format!(
+                    "Column '{}' does not have a value for every row",
+                    leaf.name
+                )));
+            }
+
+            values.push(column.into_iter());
+        }
+
+        self.values = values;
+        self.remaining = num_rows;
+        Ok(())
+    }
+}
+
+impl Iterator for ParquetDecoder {
+    type Item = Result<HashMap<String, DataValue>, TransferError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        while self.remaining == 0 {
+            let row_group = self.row_groups.next()?;
+            if let Err(err) = self.read_row_group(row_group) {
+                self.failed = true;
+                return Some(Err(err));
+            }
+        }
+
+        self.remaining -= 1;
+        self.row += 1;
+        let row = self.row;
+
+        let values = self
+            .columns
+            .iter()
+            .zip(&mut self.values)
+            .map(|(column, values)| {
+                let value = match values.next().flatten() {
+                    Some(value) => value.into_data_value(column)?,
+                    None => DataValue::Null,
+                };
+                Ok((column.name.clone(), value))
+            })
+            .collect::<Result<HashMap<_, _>, TransferError>>()
+            .map_err(|err| err.at_line(row));
+
+        Some(values)
+    }
+}
+
+/// Reads every value of a column chunk, `None` standing for null.
+fn read_column_chunk(
+    file: &mut File,
+    chunk: &ColumnChunk,
+    leaf: &SchemaElement,
+) -> Result<Vec<Option<PlainValue>>, TransferError> {
+    let meta = chunk
+        .meta_data
+        .as_ref()
+        .filter(|_| chunk.file_path.is_none())
+        .ok_or_else(|| corrupted(format!("Column '{}' is not in the file", leaf.name)))?;
+    let type_ = leaf.type_.unwrap_or(meta.type_);
+    let optional = leaf.repetition_type == Some(FieldRepetitionType::OPTIONAL);
+
+    let start = match meta.dictionary_page_offset {
+        Some(offset) if offset > 0 && offset < meta.data_page_offset => offset,
+        _ => meta.data_page_offset,
+    };
+    let len = u64::try_from(meta.total_compressed_size)
+        .map_err(|_| corrupted("Column chunk has a negative size"))?;
+    let num_values = usize::try_from(meta.num_values)
+        .map_err(|_| corrupted("Column chunk has a negative number of values"))?;
+
+    let mut bytes = vec![];
+    file.seek(SeekFrom::Start(
+        u64::try_from(start).map_err(|_| corrupted("Column chunk has a negative offset"))?,
+    ))?;
+    file.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(corrupted("Column chunk is larger than the file"));
+    }
+
+    let mut values = vec![];
+    let mut dictionary = None;
+    let mut input = bytes.as_slice();
+
+    while values.len() < num_values {
+        if input.is_empty() {
+            return Err(corrupted("Column chunk ends before its last value"));
+        }
+
+        let header = PageHeader::read_from_in_protocol(&mut TCompactInputProtocol::new(
+            &mut input,
+            bytes.len() * THRIFT_ALLOCATION_FACTOR,
+        ))?;
+        let page = take(&mut input, header.compressed_page_size)?;
+
+        match header.type_ {
+            PageType::DICTIONARY_PAGE => {
+                let dictionary_header = header
+                    .dictionary_page_header
+                    .ok_or_else(|| corrupted("Dictionary page without a header"))?;
+                let data = decompress(meta.codec, page, header.uncompressed_page_size)?;
+                let count = usize::try_from(dictionary_header.num_values)
+                    .map_err(|_| corrupted("Dictionary has a negative number of values"))?;
+
+                dictionary = Some(read_plain(&data, type_, leaf.type_length, count)?);
+            }
+            PageType::DATA_PAGE => {
+                let data_header = header
+                    .data_page_header
+                    .ok_or_else(|| corrupted("Data page without a header"))?;
+                let count = page_values(data_header.num_values, num_values - values.len())?;
+                let data = decompress(meta.codec, page, header.uncompressed_page_size)?;
+
+                let mut data = data.as_slice();
+                let defined = if optional {
+                    let levels_len = take(&mut data, 4)?;
+                    let levels_len = i32::from_le_bytes([
+                        levels_len[0],
+                        levels_len[1],
+                        levels_len[2],
+                        levels_len[3],
+                    ]);
+                    read_levels(take(&mut data, levels_len)?, count)?
+                } else {
+                    vec![true; count]
+                };
+
+                read_values(
+                    &mut values,
+                    defined,
+                    data,
+                    data_header.encoding,
+                    type_,
+                    leaf.type_length,
+                    dictionary.as_deref(),
+                )?;
+            }
+            PageType::DATA_PAGE_V2 => {
+                let data_header = header
+                    .data_page_header_v2
+                    .ok_or_else(|| corrupted("Data page without a header"))?;
+                let count = page_values(data_header.num_values, num_values - values.len())?;
+
+                let mut page = page;
+                take(&mut page, data_header.repetition_levels_byte_length)?;
+                let levels = take(&mut page, data_header.definition_levels_byte_length)?;
+                let defined = if optional {
+                    read_levels(levels, count)?
+                } else {
+                    vec![true; count]
+                };
+
+                let data = if data_header.is_compressed.unwrap_or(true) {
+                    let levels_len = data_header.repetition_levels_byte_length
+                        + data_header.definition_levels_byte_length;
+                    let size = header.uncompressed_page_size.saturating_sub(levels_len);
+                    decompress(meta.codec, page, size)?
+                } else {
+                    page.to_vec()
+                };
+
+                read_values(
+                    &mut values,
+                    defined,
+                    &data,
+                    data_header.encoding,
+                    type_,
+                    leaf.type_length,
+                    dictionary.as_deref(),
+                )?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(values)
+}
+
+/// Number of values of a data page, which can not be more than the values left in its chunk.
+fn page_values(num_values: i32, left: usize) -> Result<usize, TransferError> {
+    usize::try_from(num_values)
+        .ok()
+        .filter(|count| *count <= left)
+        .ok_or_else(|| corrupted("Data page has more values than its column chunk"))
+}
+
+fn take<'a>(input: &mut &'a [u8], len: impl TryInto<usize>) -> Result<&'a [u8], TransferError> {
+    let len = len
+        .try_into()
+        .ok()
+        .filter(|len| *len <= input.len())
+        .ok_or_else(|| corrupted("Page is truncated"))?;
+
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+fn decompress(
+    codec: CompressionCodec,
+    data: &[u8],
+    uncompressed_size: i32,
+) -> Result<Vec<u8>, TransferError> {
+    let size =
+        usize::try_from(uncompressed_size).map_err(|_| corrupted("Page has a negative size"))?;
+
+    let decompressed = match codec {
+        CompressionCodec::UNCOMPRESSED => data.to_vec(),
+        CompressionCodec::SNAPPY => snap::raw::Decoder::new()
+            .decompress_vec(data)
+            .map_err(corrupted)?,
+        CompressionCodec::GZIP => {
+            let mut decompressed = vec![];
+            flate2::read::MultiGzDecoder::new(data)
+                .take(size as u64)
+                .read_to_end(&mut decompressed)
+                .map_err(corrupted)?;
+            decompressed
+        }
+        CompressionCodec::ZSTD => zstd::stream::decode_all(data).map_err(corrupted)?,
+        CompressionCodec::LZ4_RAW => lz4_flex::block::decompress(data, size).map_err(corrupted)?,
+        codec => {
+            return Err(corrupted(format!(
+                "Pages compressed with codec {} can not be imported",
+                codec.0
+            )))
+        }
+    };
+
+    if decompressed.len() != size {
+        return Err(corrupted("Page does not decompress to its size"));
+    }
+
+    Ok(decompressed)
+}
+
+/// Reads the definition levels of a flat, optional column: whether each value is not null.
+fn read_levels(data: &[u8], count: usize) -> Result<Vec<bool>, TransferError> {
+    Ok(read_hybrid(data, 1, count)?
+        .into_iter()
+        .map(|level| level == 1)
+        .collect())
+}
+
+/// Appends the values of a data page, reading a value for every level that is defined.
+fn read_values(
+    values: &mut Vec<Option<PlainValue>>,
+    defined: Vec<bool>,
+    data: &[u8],
+    encoding: Encoding,
+    type_: Type,
+    type_length: Option<i32>,
+    dictionary: Option<&[PlainValue]>,
+) -> Result<(), TransferError> {
+    let count = defined.iter().filter(|defined| **defined).count();
+    let present = match count {
+        0 => vec![],
+        _ => read_encoded(data, encoding, type_, type_length, count, dictionary)?,
+    };
+
+    if present.len() != count {
+        return Err(corrupted("Data page does not have a value for every level"));
+    }
+
+    let mut present = present.into_iter();
+    values.extend(
+        defined
+            .into_iter()
+            .map(|defined| if defined { present.next() } else { None }),
+    );
+    Ok(())
+}
+
+/// Reads `count` values of a physical type, in any encoding but the deprecated `BIT_PACKED`.
+fn read_encoded(
+    data: &[u8],
+    encoding: Encoding,
+    type_: Type,
+    type_length: Option<i32>,
+    count: usize,
+    dictionary: Option<&[PlainValue]>,
+) -> Result<Vec<PlainValue>, TransferError> {
+    let mut input = data;
+
+    match (encoding, type_) {
+        (Encoding::PLAIN, _) => read_plain(data, type_, type_length, count),
+        (Encoding::PLAIN_DICTIONARY | Encoding::RLE_DICTIONARY, _) => {
+            let dictionary = dictionary
+                .ok_or_else(|| corrupted("Dictionary encoded page without a dictionary"))?;
+            let bit_width = take(&mut input, 1)?[0];
+
+            read_hybrid(input, bit_width, count)?
+                .into_iter()
+                .map(|index| {
+                    dictionary
+                        .get(index as usize)
+                        .cloned()
+                        .ok_or_else(|| corrupted("Dictionary index is out of range"))
+                })
+                .collect()
+        }
+        (Encoding::RLE, Type::BOOLEAN) => {
+            let len = take(&mut input, 4)?;
+            let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]);
+
+            Ok(read_hybrid(take(&mut input, len)?, 1, count)?
+                .into_iter()
+                .map(|value| PlainValue::Boolean(value == 1))
+                .collect())
+        }
+        (Encoding::DELTA_BINARY_PACKED, Type::INT32) => Ok(read_delta(&mut input, count)?
+            .into_iter()
+            .map(|value| PlainValue::Int(value as i32 as i64))
+            .collect()),
+        (Encoding::DELTA_BINARY_PACKED, Type::INT64) => Ok(read_delta(&mut input, count)?
+            .into_iter()
+            .map(PlainValue::Int)
+            .collect()),
+        (Encoding::DELTA_LENGTH_BYTE_ARRAY, Type::BYTE_ARRAY) => {
+            Ok(read_delta_length(&mut input, count)?
+                .into_iter()
+                .map(|bytes| PlainValue::Bytes(bytes.to_vec()))
+                .collect())
+        }
+        (Encoding::DELTA_BYTE_ARRAY, Type::BYTE_ARRAY | Type::FIXED_LEN_BYTE_ARRAY) => {
+            let prefixes = read_delta(&mut input, count)?;
+            let suffixes = read_delta_length(&mut input, count)?;
+            let mut previous: Vec<u8> = vec![];
+
+            prefixes
+                .into_iter()
+                .zip(suffixes)
+                .map(|(prefix, suffix)| {
+                    let prefix = usize::try_from(prefix)
+                        .ok()
+                        .and_then(|prefix| previous.get(..prefix))
+                        .ok_or_else(|| corrupted("Prefix is longer than the previous value"))?;
+
+                    previous = [prefix, suffix].concat();
+                    Ok(PlainValue::Bytes(previous.clone()))
+                })
+                .collect()
+        }
+        (Encoding::BYTE_STREAM_SPLIT, Type::FLOAT | Type::DOUBLE) => {
+            let width = if type_ == Type::FLOAT { 4 } else { 8 };
+            let streams = take(&mut input, count.saturating_mul(width))?;
+
+            Ok((0..count)
+                .map(|index| {
+                    let mut bytes = [0; 8];
+                    for (byte, value) in bytes.iter_mut().enumerate().take(width) {
+                        *value = streams[byte * count + index];
+                    }
+
+                    match width {
+                        4 => PlainValue::Float(f32::from_le_bytes([
+                            bytes[0], bytes[1], bytes[2], bytes[3],
+                        ]) as f64),
+                        _ => PlainValue::Float(f64::from_le_bytes(bytes)),
+                    }
+                })
+                .collect())
+        }
+        (encoding, _) => Err(corrupted(format!(
+            "Pages with encoding {} can not be imported",
+            encoding.0
+        ))),
+    }
+}
+
+/// Reads the `DELTA_BINARY_PACKED` encoded integers at the start of `input`, of which there can
+/// not be more than `max_count`.
+fn read_delta(input: &mut &[u8], max_count: usize) -> Result<Vec<i64>, TransferError> {
+    let block_size = read_uleb128(input)? as usize;
+    let miniblocks = read_uleb128(input)? as usize;
+    let count = read_uleb128(input)? as usize;
+    let mut value = read_zigzag(input)?;
+
+    if count > max_count {
+        return Err(corrupted("Data page has more values than levels"));
+    }
+
+    if miniblocks == 0
+        || !block_size.is_multiple_of(miniblocks)
+        || !(block_size / miniblocks).is_multiple_of(8)
+    {
+        return Err(corrupted("Invalid delta encoding block size"));
+    }
+
+    let miniblock_size = block_size / miniblocks;
+    let mut values = vec![];
+    if count > 0 {
+        values.push(value);
+    }
+
+    while values.len() < count {
+        let min_delta = read_zigzag(input)?;
+        let bit_widths = take(input, miniblocks)?;
+
+        for bit_width in bit_widths {
+            if values.len() == count {
+                break;
+            }
+
+            if *bit_width > 64 {
+                return Err(corrupted("Bit width is larger than 64"));
+            }
+
+            let len = miniblock_size.saturating_mul(*bit_width as usize) / 8;
+            let packed = take(input, len)?;
+            for index in 0..miniblock_size.min(count - values.len()) {
+                let delta = unpack(packed, index, *bit_width as usize)?;
+                value = value.wrapping_add(min_delta).wrapping_add(delta as i64);
+                values.push(value);
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+/// Reads `DELTA_LENGTH_BYTE_ARRAY` encoded byte arrays: their lengths, followed by their bytes.
+fn read_delta_length<'a>(
+    input: &mut &'a [u8],
+    max_count: usize,
+) -> Result<Vec<&'a [u8]>, TransferError> {
+    read_delta(input, max_count)?
+        .into_iter()
+        .map(|len| take(input, usize::try_from(len).unwrap_or(usize::MAX)))
+        .collect()
+}
+
+fn read_zigzag(input: &mut &[u8]) -> Result<i64, TransferError> {
+    let value = read_uleb128(input)?;
+    Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+}
+
+/// Value `index` of values of `bit_width` bits, packed from the least significant bit on.
+fn unpack(packed: &[u8], index: usize, bit_width: usize) -> Result<u64, TransferError> {
+    let mut value = 0;
+
+    for bit in 0..bit_width {
+        let pos = index * bit_width + bit;
+        let byte = packed
+            .get(pos / 8)
+            .ok_or_else(|| corrupted("Bit-packed values are truncated"))?;
+        value |= ((*byte as u64 >> (pos % 8)) & 1) << bit;
+    }
+
+    Ok(value)
+}
+
+/// Reads `count` plain encoded values of a physical type.
+fn read_plain(
+    data: &[u8],
+    type_: Type,
+    type_length: Option<i32>,
+    count: usize,
+) -> Result<Vec<PlainValue>, TransferError> {
+    let mut input = data;
+    let mut values = vec![];
+
+    if type_ == Type::BOOLEAN {
+        let bits = take(&mut input, count.div_ceil(8))?;
+        return Ok((0..count)
+            .map(|pos| PlainValue::Boolean((bits[pos / 8] >> (pos % 8)) & 1 == 1))
+            .collect());
+    }
+
+    for _ in 0..count {
+        let value = match type_ {
+            Type::INT32 => {
+                let bytes = take(&mut input, 4)?;
+                PlainValue::Int(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as i64)
+            }
+            Type::INT64 => PlainValue::Int(i64::from_le_bytes(
+                take(&mut input, 8)?.try_into().unwrap_or_default(),
+            )),
+            Type::FLOAT => {
+                let bytes = take(&mut input, 4)?;
+                PlainValue::Float(
+                    f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+                )
+            }
+            Type::DOUBLE => PlainValue::Float(f64::from_le_bytes(
+                take(&mut input, 8)?.try_into().unwrap_or_default(),
+            )),
+            Type::BYTE_ARRAY => {
+                let len = take(&mut input, 4)?;
+                let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]);
+                PlainValue::Bytes(take(&mut input, len)?.to_vec())
+            }
+            Type::FIXED_LEN_BYTE_ARRAY => {
+                PlainValue::Bytes(take(&mut input, type_length.unwrap_or_default())?.to_vec())
+            }
+            _ => return Err(corrupted("INT96 columns can not be imported")),
+        };
+
+        values.push(value);
+    }
+
+    Ok(values)
+}
+
+/// Reads `count` values of the RLE / bit-packing hybrid encoding Parquet uses for levels and
+/// dictionary indices.
+fn read_hybrid(data: &[u8], bit_width: u8, count: usize) -> Result<Vec<u32>, TransferError> {
+    if bit_width > 32 {
+        return Err(corrupted("Bit width is larger than 32"));
+    }
+
+    let bit_width = bit_width as usize;
+    let mut input = data;
+    let mut values = vec![];
+
+    while values.len() < count {
+        let header = read_uleb128(&mut input)?;
+        let left = count - values.len();
+
+        if header & 1 == 1 {
+            // Writers may leave out the padding of the last group of 8 values.
+            let groups = (header >> 1) as usize;
+            let len = groups.saturating_mul(bit_width).min(input.len());
+            let packed = take(&mut input, len)?;
+
+            for index in 0..groups.saturating_mul(8).min(left) {
+                values.push(unpack(packed, index, bit_width)? as u32);
+            }
+        } else {
+            let run = (header >> 1) as usize;
+            let value = take(&mut input, bit_width.div_ceil(8))?
+                .iter()
+                .rev()
+                .fold(0u32, |value, byte| (value << 8) | *byte as u32);
+            values.extend(std::iter::repeat_n(value, run.min(left)));
+        }
+    }
+
+    Ok(values)
+}
+
+fn read_uleb128(input: &mut &[u8]) -> Result<u64, TransferError> {
+    let mut value = 0;
+
+    for shift in (0..64).step_by(7) {
+        let byte = take(input, 1)?[0];
+        value |= ((byte & 0x7f) as u64) << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(corrupted("Varint is longer than 64 bits"))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::transfer::parquet::{ParquetDecoder, ParquetEncoder, ROW_GROUP_SIZE};
+    use crate::transfer::{RowEncoder, TransferError};
+    use schemajs_primitives::column::types::{DataTypes, DataValue};
+    use schemajs_primitives::column::Column;
+    use schemajs_primitives::table::Table;
+    use serde_json::Number;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn users_table() -> Arc<Table> {
+        Arc::new(
+            Table::new("users")
+                .add_column(Column::new("id", DataTypes::Uuid))
+                .add_column(Column::new("name", DataTypes::String))
+                .add_column(Column::new("age", DataTypes::Number))
+                .add_column(Column::new("score", DataTypes::Number))
+                .add_column(Column::new("visits", DataTypes::Number))
+                .add_column(Column::new("verified", DataTypes::Boolean)),
+        )
+    }
+
+    fn value_or_null(is_null: bool, value: impl FnOnce() -> DataValue) -> DataValue {
+        if is_null {
+            DataValue::Null
+        } else {
+            value()
+        }
+    }
+
+    /// Row `i` of the files in `test_cases/parquet`, which were written by parquet-rs 53.4.1.
+    fn expected_row(i: u64) -> HashMap<String, DataValue> {
+        HashMap::from([
+            (
+                "id".to_string(),
+                value_or_null(i % 10 == 9, || DataValue::Uuid(Uuid::from_u128(i as u128))),
+            ),
+            (
+                "name".to_string(),
+                value_or_null(i.is_multiple_of(7), || {
+                    DataValue::String(format!("user{}", i % 50))
+                }),
+            ),
+            (
+                "age".to_string(),
+                value_or_null(i.is_multiple_of(5), || match i % 3 {
+                    0 => DataValue::Number((i64::MAX - i as i64).into()),
+                    _ => DataValue::Number((i as i64 - 150).into()),
+                }),
+            ),
+            (
+                "score".to_string(),
+                value_or_null(i.is_multiple_of(11), || match i % 4 {
+                    0 => DataValue::Number((i / 4).into()),
+                    _ => DataValue::Number(Number::from_f64(i as f64 / 4.0).unwrap()),
+                }),
+            ),
+            (
+                "visits".to_string(),
+                value_or_null(i.is_multiple_of(13), || {
+                    DataValue::Number((i as i64 * 7 - 500).into())
+                }),
+            ),
+            ("verified".to_string(), DataValue::Boolean(i % 3 == 1)),
+        ])
+    }
+
+    #[test]
+    fn test_import_files_of_other_writers() {
+        for file in [
+            "plain_snappy",
+            "dictionary_gzip",
+            "delta_zstd",
+            "dictionary_fallback_lz4",
+        ] {
+            let path = format!("./test_cases/parquet/{}.parquet", file);
+            let reader = std::fs::File::open(path).unwrap();
+            let rows = ParquetDecoder::new(reader, users_table())
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(rows.len(), 300, "{}", file);
+            for (i, row) in rows.into_iter().enumerate() {
+                assert_eq!(
+                    serde_json::to_value(row).unwrap(),
+                    serde_json::to_value(expected_row(i as u64)).unwrap(),
+                    "{} row {}",
+                    file,
+                    i
+                );
+            }
+        }
+    }
+
+    fn encode_numbers(numbers: Vec<DataValue>) -> Result<Vec<u8>, TransferError> {
+        let mut file = vec![];
+        let mut encoder =
+            ParquetEncoder::new(&mut file, vec![Column::new("age", DataTypes::Number)])?;
+        for number in numbers {
+            encoder.encode(vec![number])?;
+        }
+        encoder.finish()?;
+
+        Ok(file)
+    }
+
+    #[test]
+    fn test_integers_are_exact() {
+        let table = Arc::new(Table::new("users").add_column(Column::new("age", DataTypes::Number)));
+        let numbers: Vec<DataValue> = (0..ROW_GROUP_SIZE as i64 + 10)
+            .map(|i| match i % 3 {
+                0 => DataValue::Number((i64::MAX - i).into()),
+                1 => DataValue::Number((i64::MIN + i).into()),
+                _ => DataValue::Null,
+            })
+            .collect();
+
+        let file = encode_numbers(numbers.clone()).unwrap();
+        let imported: Vec<DataValue> = ParquetDecoder::new(file.as_slice(), table)
+            .unwrap()
+            .map(|row| row.unwrap().remove("age").unwrap())
+            .collect();
+        assert_eq!(
+            serde_json::to_value(imported).unwrap(),
+            serde_json::to_value(numbers).unwrap()
+        );
+
+        // Numbers are not rounded to the type of their column.
+        let mut numbers = vec![DataValue::Number(1.into()); ROW_GROUP_SIZE];
+        numbers.push(DataValue::Number(Number::from_f64(1.5).unwrap()));
+        let err = encode_numbers(numbers).unwrap_err();
+        assert!(matches!(err, TransferError::Parquet(reason) if reason.contains("1.5")));
+
+        let numbers = vec![
+            DataValue::Number(Number::from_f64(0.5).unwrap()),
+            DataValue::Number(((1_i64 << 53) + 1).into()),
+        ];
+        assert!(encode_numbers(numbers).is_err());
+        assert!(encode_numbers(vec![DataValue::Number(u64::MAX.into())]).is_err());
+        assert!(encode_numbers(vec![DataValue::Number((1_i64 << 53).into())]).is_ok());
+    }
+}